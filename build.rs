@@ -1,8 +1,25 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     tonic_build::configure()
         .build_server(true)
-        .build_client(false)
+        .build_client(true)
         .compile(&["proto/game.proto"], &["proto"])?;
-    
+
+    // Commit the daemon was built from; SACAS_GIT_HASH overrides it for
+    // builds outside a git checkout
+    println!("cargo:rerun-if-env-changed=SACAS_GIT_HASH");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/refs/heads");
+    let git_hash = std::env::var("SACAS_GIT_HASH").ok().or_else(git_head).unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=SACAS_GIT_HASH={}", git_hash);
+
     Ok(())
 }
+
+fn git_head() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short=12", "HEAD"])
+        .output()
+        .ok()?;
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (output.status.success() && !hash.is_empty()).then_some(hash)
+}
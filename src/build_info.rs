@@ -0,0 +1,31 @@
+// sacas-daemon/src/build_info.rs
+// Daemon version and source commit, reported for rollout tracking
+
+use std::fmt;
+
+use serde::Serialize;
+
+/// Version and commit baked in at compile time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Short git hash, or "unknown" when built outside a checkout
+    pub git_hash: &'static str,
+}
+
+/// Server API revision this build speaks, sent as `X-API-Version`
+///
+/// Bumped with any release that depends on a breaking server change, so the
+/// server can route or refuse older daemons.
+pub const API_VERSION: &str = "1";
+
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    git_hash: env!("SACAS_GIT_HASH"),
+};
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{} ({})", self.version, self.git_hash)
+    }
+}
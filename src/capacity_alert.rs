@@ -0,0 +1,163 @@
+// sacas-daemon/src/capacity_alert.rs
+// Reminders that entropy is nearing or past capacity, at a configurable cadence
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CapacityAlertConfig {
+    /// Entropy as a percentage of capacity that starts warnings (0 disables alerts)
+    pub warn_percent: f64,
+    /// Seconds between warning reminders
+    pub warn_every_secs: u64,
+    /// Percentage treated as critical, e.g. at capacity where decay sets in
+    pub critical_percent: f64,
+    /// Seconds between critical reminders
+    pub critical_every_secs: u64,
+}
+
+impl Default for CapacityAlertConfig {
+    fn default() -> Self {
+        Self {
+            warn_percent: 90.0,
+            warn_every_secs: 3600,
+            critical_percent: 100.0,
+            critical_every_secs: 600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CapacityLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl CapacityAlertConfig {
+    pub fn level(&self, entropy: u64, capacity: u64) -> CapacityLevel {
+        if self.warn_percent <= 0.0 || capacity == 0 {
+            return CapacityLevel::Normal;
+        }
+
+        let percent = entropy as f64 / capacity as f64 * 100.0;
+        if percent >= self.critical_percent {
+            CapacityLevel::Critical
+        } else if percent >= self.warn_percent {
+            CapacityLevel::Warning
+        } else {
+            CapacityLevel::Normal
+        }
+    }
+
+    fn interval(&self, level: CapacityLevel) -> Duration {
+        match level {
+            CapacityLevel::Critical => Duration::from_secs(self.critical_every_secs),
+            _ => Duration::from_secs(self.warn_every_secs),
+        }
+    }
+}
+
+/// When the last reminder went out, and at which level
+#[derive(Debug, Default)]
+struct Cadence {
+    last: Option<(Instant, CapacityLevel)>,
+}
+
+/// Decides when a capacity reminder is due
+///
+/// Cadence state is kept apart from the config, which is passed to each
+/// check, so a reloaded config takes effect without resetting reminders.
+/// Clones share the cadence.
+#[derive(Clone, Default)]
+pub struct CapacityAlerter {
+    cadence: Arc<Mutex<Cadence>>,
+}
+
+impl CapacityAlerter {
+    /// The level to notify about now, if a reminder is due
+    ///
+    /// Reaching a higher level notifies at once; otherwise reminders repeat
+    /// at the current level's interval. Dropping back to normal resets the
+    /// cadence.
+    pub fn due(&self, config: &CapacityAlertConfig, entropy: u64, capacity: u64, now: Instant) -> Option<CapacityLevel> {
+        let level = config.level(entropy, capacity);
+        let mut cadence = self.cadence.lock().unwrap();
+
+        if level == CapacityLevel::Normal {
+            cadence.last = None;
+            return None;
+        }
+
+        let due = match cadence.last {
+            None => true,
+            Some((_, last_level)) if level > last_level => true,
+            Some((at, _)) => now.saturating_duration_since(at) >= config.interval(level),
+        };
+
+        if due {
+            cadence.last = Some((now, level));
+            Some(level)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> CapacityAlertConfig {
+        CapacityAlertConfig {
+            warn_percent: 80.0,
+            warn_every_secs: 3600,
+            critical_percent: 100.0,
+            critical_every_secs: 300,
+        }
+    }
+
+    #[test]
+    fn test_two_threshold_cadence() {
+        let alerter = CapacityAlerter::default();
+        let config = config();
+        let t0 = Instant::now();
+        let at = |secs| t0 + Duration::from_secs(secs);
+
+        assert_eq!(alerter.due(&config, 700, 1000, at(0)), None);
+        assert_eq!(alerter.due(&config, 850, 1000, at(0)), Some(CapacityLevel::Warning));
+        assert_eq!(alerter.due(&config, 900, 1000, at(600)), None);
+
+        // Escalating notifies immediately, then repeats on the faster cadence
+        assert_eq!(alerter.due(&config, 1100, 1000, at(601)), Some(CapacityLevel::Critical));
+        assert_eq!(alerter.due(&config, 1100, 1000, at(800)), None);
+        assert_eq!(alerter.due(&config, 1100, 1000, at(901)), Some(CapacityLevel::Critical));
+
+        // Back under critical: warnings wait out the warning interval
+        assert_eq!(alerter.due(&config, 900, 1000, at(1000)), None);
+        assert_eq!(alerter.due(&config, 900, 1000, at(4501)), Some(CapacityLevel::Warning));
+
+        // Normal resets, so the next crossing notifies at once
+        assert_eq!(alerter.due(&config, 100, 1000, at(4502)), None);
+        assert_eq!(alerter.due(&config, 900, 1000, at(4503)), Some(CapacityLevel::Warning));
+    }
+
+    #[test]
+    fn test_cadence_survives_config_change() {
+        let alerter = CapacityAlerter::default();
+        let t0 = Instant::now();
+        assert!(alerter.due(&config(), 850, 1000, t0).is_some());
+
+        let reloaded = CapacityAlertConfig { warn_every_secs: 7200, ..config() };
+        assert_eq!(alerter.due(&reloaded, 850, 1000, t0 + Duration::from_secs(3601)), None);
+        assert!(alerter.due(&reloaded, 850, 1000, t0 + Duration::from_secs(7200)).is_some());
+    }
+
+    #[test]
+    fn test_zero_warn_percent_disables() {
+        let config = CapacityAlertConfig { warn_percent: 0.0, ..config() };
+        assert_eq!(config.level(5000, 1000), CapacityLevel::Normal);
+    }
+}
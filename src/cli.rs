@@ -0,0 +1,274 @@
+// sacas-daemon/src/cli.rs
+// Command-line interface: no subcommand runs the daemon
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+
+use crate::config::{Config, Service};
+use crate::device::heal::{apply_rotation, detect_reinstall, SignedFingerprintRotation};
+use crate::device::{AttestationBundle, DeviceIdentity, MacHardwareInfo, Stamper};
+use crate::grpc::proto::game_service_client::GameServiceClient;
+use crate::grpc::proto::TailEventsRequest;
+use crate::http::{HttpClient, ServerPool};
+use crate::mining::bench::{simulate, BenchParams};
+use crate::mining::strategy_by_name;
+use crate::types::capacity_from_karma;
+use crate::sync::signed_sync::SYNC_SCHEMA_LATEST;
+use crate::sync::verify::verify_sync;
+
+#[derive(Debug, Parser)]
+#[command(name = "sacas-daemon", version, about = "SACAS: The Entropy Protocol daemon")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// Use synthetic hardware and skip device validation, for runs against a test server
+    #[cfg(feature = "fleet-sim")]
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+    /// Run as simulated fleet device <N>, with its own data dir, key and fingerprint
+    #[cfg(feature = "fleet-sim")]
+    #[arg(long, global = true, value_name = "N", requires = "dry_run")]
+    pub fleet_instance: Option<u32>,
+}
+
+impl Cli {
+    /// Whether `--dry-run` was given; always false without the `fleet-sim` feature
+    pub fn dry_run(&self) -> bool {
+        #[cfg(feature = "fleet-sim")]
+        return self.dry_run;
+        #[cfg(not(feature = "fleet-sim"))]
+        return false;
+    }
+
+    /// The `--fleet-instance` number; always `None` without the `fleet-sim` feature
+    pub fn fleet_instance(&self) -> Option<u32> {
+        #[cfg(feature = "fleet-sim")]
+        return self.fleet_instance;
+        #[cfg(not(feature = "fleet-sim"))]
+        return None;
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Build a signed sync request for sample inputs and verify it offline
+    VerifySync {
+        #[arg(long, default_value_t = 100, allow_negative_numbers = true)]
+        entropy_delta: i64,
+        #[arg(long, default_value_t = 1.0)]
+        network_quality: f64,
+        #[arg(long, default_value_t = 3600)]
+        uptime_seconds: u64,
+        #[arg(long, default_value_t = SYNC_SCHEMA_LATEST)]
+        schema_version: u32,
+        /// Device ID to sign for (defaults to the configured one)
+        #[arg(long)]
+        device_id: Option<String>,
+        /// Device key file (defaults to the data directory's device.key)
+        #[arg(long)]
+        key: Option<PathBuf>,
+    },
+    /// Export a signed bundle proving this device's identity and hardware binding
+    Attest {
+        /// Write the bundle here instead of stdout
+        #[arg(long)]
+        output: Option<PathBuf>,
+        /// Device key file (defaults to the data directory's device.key)
+        #[arg(long)]
+        key: Option<PathBuf>,
+    },
+    /// Re-bind the config after a macOS reinstall changed the hardware UUID
+    ///
+    /// Only applies when the serial number and model still match. The
+    /// server is told first, with a request signed by the device key.
+    HealFingerprint {
+        /// Don't ask for confirmation
+        #[arg(long)]
+        yes: bool,
+        /// Device key file (defaults to the data directory's device.key)
+        #[arg(long)]
+        key: Option<PathBuf>,
+    },
+    /// Simulate mining yield and decay over time, without waiting for real ticks
+    ///
+    /// Settings not given on the command line come from the mining config,
+    /// or its defaults when there is no config yet.
+    BenchMining {
+        #[arg(long, default_value_t = 1000)]
+        karma: u64,
+        #[arg(long, default_value_t = 1.0)]
+        network_quality: f64,
+        /// Passive income in Ω per second
+        #[arg(long, default_value_t = 0.0)]
+        passive_income: f64,
+        #[arg(long)]
+        multiplier: Option<f64>,
+        /// Yield formula: "sqrt" or "linear"
+        #[arg(long)]
+        strategy: Option<String>,
+        /// Simulated duration in hours
+        #[arg(long, default_value_t = 24)]
+        hours: u64,
+    },
+    /// Stream log events from the running daemon as they happen
+    Tail {
+        /// Least severe level to show: error, warn, info or debug
+        #[arg(long, default_value = "warn")]
+        level: String,
+    },
+}
+
+/// Run a one-shot subcommand
+pub async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::VerifySync {
+            entropy_delta,
+            network_quality,
+            uptime_seconds,
+            schema_version,
+            device_id,
+            key,
+        } => {
+            let identity = load_identity(key)?;
+
+            let device_id = match device_id {
+                Some(id) => id,
+                None => Config::load()
+                    .ok()
+                    .and_then(|c| c.device_id)
+                    .context("No device ID configured; pass --device-id")?,
+            };
+
+            let report = verify_sync(
+                &identity,
+                &device_id,
+                entropy_delta,
+                network_quality,
+                uptime_seconds,
+                schema_version,
+            );
+            println!("{}", report);
+
+            if !report.verified {
+                anyhow::bail!("Signature failed self-verification");
+            }
+            Ok(())
+        }
+        Command::Attest { output, key } => {
+            let identity = load_identity(key)?;
+            let config = Config::load()?;
+
+            let bundle = AttestationBundle::create(&config, &identity)?;
+            bundle.verify().context("Attestation failed self-verification")?;
+            let json = serde_json::to_string_pretty(&bundle)?;
+
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, json)
+                        .with_context(|| format!("Failed to write attestation to {:?}", path))?;
+                    eprintln!("Attestation written to {:?}", path);
+                }
+                None => println!("{}", json),
+            }
+            Ok(())
+        }
+        Command::HealFingerprint { yes, key } => heal_fingerprint(yes, key).await,
+        Command::BenchMining {
+            karma,
+            network_quality,
+            passive_income,
+            multiplier,
+            strategy,
+            hours,
+        } => {
+            let mining = Config::load().map(|c| c.mining).unwrap_or_default();
+            let strategy_name = strategy.unwrap_or(mining.yield_strategy);
+            let strategy = strategy_by_name(&strategy_name)
+                .with_context(|| format!("Unknown yield strategy {:?}", strategy_name))?;
+
+            let params = BenchParams {
+                karma,
+                network_quality,
+                passive_income,
+                multiplier: multiplier.unwrap_or(mining.base_multiplier),
+                capacity: capacity_from_karma(karma, mining.capacity_formula),
+                tick_interval_secs: mining.tick_interval_secs,
+                decay_grace_secs: mining.decay_grace_secs,
+                duration_secs: hours.saturating_mul(3600),
+            };
+            println!("{} yield, karma {}, quality {:.2}, capacity {} Ω", strategy_name, karma, network_quality, params.capacity);
+            println!("{}", simulate(strategy.as_ref(), &params));
+            Ok(())
+        }
+        Command::Tail { level } => tail(level).await,
+    }
+}
+
+async fn tail(level: String) -> Result<()> {
+    let port = Config::load().context("No configuration; has the daemon been set up?")?.grpc_port;
+    let url = format!("http://127.0.0.1:{}", port);
+    let mut client = GameServiceClient::connect(url.clone())
+        .await
+        .with_context(|| format!("No daemon listening on {}", url))?;
+
+    let mut events = client.tail_events(TailEventsRequest { level: level.clone() }).await?.into_inner();
+    eprintln!("Tailing {}+ events from {} (Ctrl-C to stop)", level, url);
+    while let Some(event) = events.message().await? {
+        let at = chrono::DateTime::from_timestamp_millis(event.timestamp_ms).unwrap_or_default();
+        println!("{} {:>5} {}: {}", at.format("%H:%M:%S%.3f"), event.level, event.target, event.message);
+    }
+    eprintln!("Daemon closed the stream");
+    Ok(())
+}
+
+async fn heal_fingerprint(yes: bool, key: Option<PathBuf>) -> Result<()> {
+    let identity = load_identity(key)?;
+    let config_path = Config::config_path()?;
+    let mut config = Config::load_from(&config_path)?;
+    let device_id = config.device_id.clone().context("Device not registered; nothing to heal")?;
+    let hw = MacHardwareInfo::collect()?;
+
+    let Some(rotation) = detect_reinstall(&config, &hw) else {
+        anyhow::bail!(
+            "Binding doesn't look like a reinstall of this Mac (serial {} / model {} must match); nothing changed",
+            hw.serial_number,
+            hw.model_identifier
+        );
+    };
+
+    println!("Same Mac ({} / {}), new hardware UUID:", hw.model_identifier, hw.serial_number);
+    println!("  {} -> {}", rotation.old_uuid, rotation.new_uuid);
+    if !yes && !confirm("Notify the server and update the stored fingerprint?")? {
+        anyhow::bail!("Aborted; nothing changed");
+    }
+
+    SignedFingerprintRotation::create_and_sign(&device_id, &rotation, &hw.serial_number, identity.signer(), &Stamper::default())
+        .send(&HttpClient::new(&config.http), &ServerPool::new(&config.service_urls(Service::Sync)))
+        .await?;
+
+    apply_rotation(&mut config, &hw, &rotation)?;
+    config.save(&config_path)?;
+    println!("Fingerprint updated in {:?}", config_path);
+    Ok(())
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    use std::io::Write;
+
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+fn load_identity(key: Option<PathBuf>) -> Result<DeviceIdentity> {
+    let key_path = match key {
+        Some(path) => path,
+        None => Config::identity_path()?,
+    };
+    DeviceIdentity::load(&key_path)
+        .with_context(|| format!("Failed to load device key from {:?}", key_path))
+}
@@ -0,0 +1,97 @@
+// sacas-daemon/src/combat/allocator.rs
+// Defense split recommendations based on the active network climate
+
+use crate::types::Climate;
+
+/// Recommended L1/L2/L3 split for a given entropy budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefenseRecommendation {
+    pub l1: u64,
+    pub l2: u64,
+    pub l3: u64,
+    pub reason: String,
+}
+
+pub struct DefenseAllocator;
+
+impl DefenseAllocator {
+    /// Split `budget` across the three layers for the current climate
+    pub fn recommend(budget: u64, climate: &Climate) -> DefenseRecommendation {
+        let (weights, rationale) = Self::weights_for(&climate.code);
+
+        let l1 = (budget as f64 * weights[0]) as u64;
+        let l2 = (budget as f64 * weights[1]) as u64;
+        // Rounding remainder goes to the core layer; f64 rounding near u64::MAX
+        // can push l1 + l2 past the budget
+        let l3 = budget.saturating_sub(l1).saturating_sub(l2);
+
+        DefenseRecommendation {
+            l1,
+            l2,
+            l3,
+            reason: format!("{} climate: {}", climate.code, rationale),
+        }
+    }
+
+    /// Layer weights (L1, L2, L3) per climate code, see the Gameplay Guide
+    fn weights_for(code: &str) -> ([f64; 3], &'static str) {
+        match code {
+            "STORM" => ([0.15, 0.25, 0.60], "attacks succeed more easily, reinforce L3 against parasitism"),
+            "DROUGHT" => ([0.05, 0.35, 0.60], "L1 is ineffective, shift points to L2/L3"),
+            "FOG" => ([0.30, 0.20, 0.50], "long-range scans fail, expect close combat at L1"),
+            "GLITCH" => ([0.30, 0.20, 0.50], "cooldowns reset, hold a tank build until things settle"),
+            "VOLATILE" => ([0.20, 0.30, 0.50], "fluctuating conditions, keep a balanced build"),
+            _ => ([0.20, 0.30, 0.50], "balanced build"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn climate(code: &str) -> Climate {
+        Climate {
+            code: code.to_string(),
+            description: String::new(),
+            modifiers: serde_json::json!({}),
+            start_time: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_normal_is_balanced() {
+        let rec = DefenseAllocator::recommend(1000, &climate("NORMAL"));
+        assert_eq!((rec.l1, rec.l2, rec.l3), (200, 300, 500));
+        assert!(rec.reason.starts_with("NORMAL climate"));
+    }
+
+    #[test]
+    fn test_climates_change_allocation() {
+        let normal = DefenseAllocator::recommend(1000, &climate("NORMAL"));
+        let storm = DefenseAllocator::recommend(1000, &climate("STORM"));
+        let drought = DefenseAllocator::recommend(1000, &climate("DROUGHT"));
+
+        assert!(storm.l3 > normal.l3);
+        assert!(drought.l1 < normal.l1);
+        assert_ne!(storm, drought);
+    }
+
+    #[test]
+    fn test_split_uses_whole_budget() {
+        for code in ["NORMAL", "STORM", "DROUGHT", "FOG", "GLITCH", "VOLATILE"] {
+            let rec = DefenseAllocator::recommend(997, &climate(code));
+            assert_eq!(rec.l1 + rec.l2 + rec.l3, 997);
+        }
+    }
+
+    #[test]
+    fn test_huge_budget_does_not_overflow() {
+        for code in ["NORMAL", "STORM", "DROUGHT", "FOG", "GLITCH", "VOLATILE"] {
+            let rec = DefenseAllocator::recommend(u64::MAX, &climate(code));
+            assert!(rec.l3 >= rec.l1 && rec.l3 >= rec.l2, "{:?}", rec);
+            assert!(rec.l1.checked_add(rec.l2).and_then(|sum| sum.checked_add(rec.l3)).is_some());
+        }
+    }
+}
@@ -0,0 +1,151 @@
+// sacas-daemon/src/combat/auto_attack.rs
+// Opt-in loop that scans, picks a target with the configured strategy and attacks it
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{debug, info, warn};
+
+use super::client::CombatClient;
+use super::target::{Candidate, TargetSelector};
+use crate::radar::client::RadarTarget;
+use crate::radar::RadarClient;
+use crate::state::StateManager;
+
+/// The LOCKED contact among `targets` that `selector` prefers
+///
+/// Contacts are only simulated for selectors that rank by simulated
+/// outcome; a failed simulation leaves that contact without one.
+pub async fn pick_target(client: &CombatClient, selector: &dyn TargetSelector, targets: &[RadarTarget]) -> Option<String> {
+    let locked = RadarClient::get_locked_targets(targets);
+
+    let mut simulations = Vec::with_capacity(locked.len());
+    for target in &locked {
+        let simulation = if selector.uses_simulation() {
+            match client.simulate_battle(&target.device_id).await {
+                Ok(simulation) => Some(simulation),
+                Err(e) => {
+                    debug!("Simulation against {} failed: {}", target.device_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        simulations.push(simulation);
+    }
+
+    let candidates: Vec<Candidate> = locked
+        .iter()
+        .zip(&simulations)
+        .map(|(target, simulation)| Candidate { target, simulation: simulation.as_ref() })
+        .collect();
+    selector.select(&candidates).map(|target| target.device_id.clone())
+}
+
+/// Scan and attack the target `selector` picks, every `period`
+///
+/// Each round scans `bands` nearest first, or runs one unbounded scan when
+/// there are none. A round is skipped while a human account manages the
+/// device, when the scan finds nothing to attack, or when the client
+/// refuses the attack (karma gate, budget, cooldown).
+pub async fn run_auto_attack(
+    client: Arc<CombatClient>,
+    radar: Arc<RadarClient>,
+    state: StateManager,
+    selector: Arc<dyn TargetSelector>,
+    bands: Vec<u64>,
+    period: Duration,
+) {
+    let mut ticker = crate::wake::interval(period);
+    info!("⚔️  Auto-attack every {}s", period.as_secs());
+
+    loop {
+        ticker.tick().await;
+        if state.is_binding_lost() {
+            debug!("Auto-attack paused: device binding lost");
+            continue;
+        }
+        if state.is_managed().await {
+            debug!("Auto-attack paused: device is managed by a human account");
+            continue;
+        }
+
+        let scanned = match bands.is_empty() {
+            true => radar.scan(None).await.map(|scan| scan.targets),
+            false => radar.scan_bands(&bands).await.map(|scan| scan.targets),
+        };
+        let targets = match scanned {
+            Ok(targets) => targets,
+            Err(e) => {
+                warn!("⚠️  Auto-attack scan failed: {}", e);
+                continue;
+            }
+        };
+        let Some(target_id) = pick_target(&client, selector.as_ref(), &targets).await else {
+            debug!("No attackable target among {} contacts", targets.len());
+            continue;
+        };
+
+        match client.attack(&target_id).await {
+            Ok(result) => info!("⚔️  Auto-attack on {}: {}", target_id, result.outcome),
+            Err(e) => warn!("⚠️  Auto-attack on {} failed: {}", target_id, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::selector_by_name;
+    use crate::radar::client::DefenseInfo;
+    use crate::test_support::{combat_client, MockServer};
+
+    fn client(url: &str) -> CombatClient {
+        combat_client(url, StateManager::in_memory("dev".to_string(), 100))
+    }
+
+    fn target(id: &str, visibility: &str, defense: u64) -> RadarTarget {
+        RadarTarget {
+            device_id: id.to_string(),
+            visibility: visibility.to_string(),
+            distance: 100.0,
+            karma: Some(1000),
+            karma_range: None,
+            defense: Some(DefenseInfo { l1: defense, l2: 0, l3: 0, total: defense }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_only_locked_contacts_are_picked() {
+        let server = MockServer::respond_with(500, "should not be called").await;
+        let targets = [target("fuzzy", "FUZZY", 1), target("locked", "LOCKED", 500)];
+
+        let weakest = selector_by_name("weakest").unwrap();
+        let picked = pick_target(&client(&server.url), weakest.as_ref(), &targets).await;
+
+        assert_eq!(picked.as_deref(), Some("locked"));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_best_roi_simulates_each_contact() {
+        let server = MockServer::start(|request| {
+            let loot = if request.body.contains("rich") { 900 } else { 10 };
+            let body = format!(
+                r#"{{"probabilities":{{"l1_win":0.5,"l2_success":0.5,"l3_parasitize":0.5}},"expected_loot":{}}}"#,
+                loot
+            );
+            (200, body)
+        })
+        .await;
+        let targets = [target("poor", "LOCKED", 100), target("rich", "LOCKED", 300)];
+
+        let best_roi = selector_by_name("best_roi").unwrap();
+        let picked = pick_target(&client(&server.url), best_roi.as_ref(), &targets).await;
+
+        assert_eq!(picked.as_deref(), Some("rich"));
+        assert_eq!(server.requests().len(), 2);
+        assert!(server.requests().iter().all(|r| r.path == "/api/game/battle/simulate"));
+    }
+}
@@ -0,0 +1,219 @@
+// sacas-daemon/src/combat/auto_defense.rs
+// Opt-in starter defense for a freshly registered, undefended device
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use tracing::{info, warn};
+
+use super::client::{CombatClient, DefenseConfig};
+use crate::config::CombatConfig;
+use crate::state::StateManager;
+use crate::types::DefenseArray;
+
+/// How often an undefended device is checked for enough entropy
+pub const AUTO_DEFENSE_CHECK_SECS: u64 = 60;
+
+/// Sets a conservative defense once, so a new device isn't parasitized
+/// before its owner gets around to configuring one
+///
+/// Only applies while all three layers are still zero; any defense already
+/// in place, set by hand or adopted from the server, is left alone.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoDefense {
+    points: u64,
+    min_entropy: u64,
+}
+
+impl AutoDefense {
+    /// `None` unless `combat.auto_defense` is on
+    pub fn from_config(config: &CombatConfig) -> Option<Self> {
+        config.auto_defense.then_some(Self {
+            points: config.auto_defense_points,
+            min_entropy: config.auto_defense_min_entropy,
+        })
+    }
+
+    /// The "Newbie" 1:1:2 split from the Gameplay Guide
+    pub fn split(&self) -> DefenseConfig {
+        let l1 = self.points / 4;
+        DefenseConfig { l1, l2: l1, l3: self.points - 2 * l1 }
+    }
+
+    /// Whether the starter defense should be configured now
+    ///
+    /// Local checks come first; only an undefended device with enough
+    /// entropy above the floor asks the server, whose defense must be all
+    /// zero too. A device a human account manages is left to its owner.
+    pub async fn due(&self, client: &CombatClient, state: &StateManager) -> Result<StarterDefense> {
+        let defense = state.get_snapshot().await.player.defense;
+        if defense.l1 + defense.l2 + defense.l3 > 0 {
+            return Ok(StarterDefense::NotNeeded);
+        }
+        if state.is_managed().await || state.spendable_entropy().await < self.min_entropy {
+            return Ok(StarterDefense::Waiting);
+        }
+
+        let server = client.get_defense_status().await?.defense;
+        if server.l1 + server.l2 + server.l3 > 0 {
+            return Ok(StarterDefense::NotNeeded);
+        }
+        Ok(StarterDefense::Due(self.split()))
+    }
+
+    /// Configure the starter defense if it is due; returns the check it acted on
+    ///
+    /// `Due` means the split was just configured.
+    pub async fn apply(&self, client: &CombatClient, state: &StateManager) -> Result<StarterDefense> {
+        let check = self.due(client, state).await?;
+        let StarterDefense::Due(split) = &check else {
+            return Ok(check);
+        };
+
+        client.configure_defense(split.clone()).await?;
+        let cooldown_ends = state.get_snapshot().await.player.defense.cooldown_ends;
+        state
+            .replace_defense(DefenseArray {
+                l1: split.l1,
+                l2: split.l2,
+                l3: split.l3,
+                last_update: Utc::now(),
+                cooldown_ends,
+            })
+            .await;
+        Ok(check)
+    }
+}
+
+/// What a starter defense check found
+#[derive(Debug, Clone, PartialEq)]
+pub enum StarterDefense {
+    /// A defense is already set, locally or on the server
+    NotNeeded,
+    /// Undefended, but managed by a human or short of entropy above the floor
+    Waiting,
+    /// Undefended with enough entropy: configure this split
+    Due(DefenseConfig),
+}
+
+/// Check every `period` until the starter defense is set or no longer needed
+pub async fn run_auto_defense(client: Arc<CombatClient>, state: StateManager, auto: AutoDefense, period: Duration) {
+    let mut ticker = crate::wake::interval(period);
+    info!("🛡️  Starter defense armed: {} points once {} Ω is spendable", auto.points, auto.min_entropy);
+
+    loop {
+        ticker.tick().await;
+
+        match auto.apply(&client, &state).await {
+            Ok(StarterDefense::Due(split)) => {
+                info!("🛡️  Starter defense set: L1={}, L2={}, L3={}", split.l1, split.l2, split.l3);
+                return;
+            }
+            Ok(StarterDefense::NotNeeded) => {
+                info!("🛡️  Defense already configured; starter defense not needed");
+                return;
+            }
+            Ok(StarterDefense::Waiting) => {}
+            Err(e) => warn!("⚠️  Starter defense failed: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{combat_client, MockServer};
+
+    fn client(url: &str, state: &StateManager) -> CombatClient {
+        combat_client(url, state.clone())
+    }
+
+    fn enabled() -> CombatConfig {
+        CombatConfig { auto_defense: true, auto_defense_points: 200, auto_defense_min_entropy: 1000, ..Default::default() }
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        assert_eq!(AutoDefense::from_config(&CombatConfig::default()), None);
+
+        let split = AutoDefense::from_config(&enabled()).unwrap().split();
+        assert_eq!((split.l1, split.l2, split.l3), (50, 50, 100));
+    }
+
+    /// Answers defense status with `server_defense` and accepts any configure
+    async fn server(server_defense: [u64; 3]) -> MockServer {
+        let [l1, l2, l3] = server_defense;
+        MockServer::start(move |request| {
+            if request.path == "/api/game/defense/status" {
+                let status = serde_json::json!({
+                    "defense": {"l1": l1, "l2": l2, "l3": l3},
+                    "total_combat_points": l1 + l2 + l3,
+                    "last_configured": null,
+                    "cooldown": {"active": false, "ends_at": null, "remaining_seconds": 0},
+                });
+                (200, status.to_string())
+            } else {
+                (200, r#"{"success":true}"#.to_string())
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_fires_only_with_enough_entropy_above_floor() {
+        let server = server([0, 0, 0]).await;
+        let auto = AutoDefense::from_config(&enabled()).unwrap();
+
+        // 1200 Ω, but a 500 Ω floor leaves only 700 spendable
+        let state = StateManager::in_memory("dev".to_string(), 100).with_entropy_floor(500);
+        state.update_entropy(1200).await;
+        let client = client(&server.url, &state);
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::Waiting);
+        assert!(server.requests().is_empty());
+
+        state.update_entropy(300).await;
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::Due(auto.split()));
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/api/game/defense/status", "/api/game/defense/configure"]);
+        let body: serde_json::Value = serde_json::from_str(&server.requests()[1].body).unwrap();
+        assert_eq!(body, serde_json::json!({"l1": 50, "l2": 50, "l3": 100}));
+
+        // Defended now, so it never fires again
+        assert_eq!(state.get_snapshot().await.player.defense.l3, 100);
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::NotNeeded);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_waits_while_managed() {
+        let server = server([0, 0, 0]).await;
+        let auto = AutoDefense::from_config(&enabled()).unwrap();
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        state.update_entropy(5000).await;
+        let client = client(&server.url, &state);
+
+        state.update_managed(true).await;
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::Waiting);
+        assert!(server.requests().is_empty());
+
+        // Unlinked: autonomous again
+        state.update_managed(false).await;
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::Due(auto.split()));
+    }
+
+    #[tokio::test]
+    async fn test_server_defense_wins_over_local_zero() {
+        // Local state hasn't caught up with a defense set elsewhere
+        let server = server([10, 10, 20]).await;
+        let auto = AutoDefense::from_config(&enabled()).unwrap();
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        state.update_entropy(5000).await;
+        let client = client(&server.url, &state);
+
+        assert_eq!(auto.apply(&client, &state).await.unwrap(), StarterDefense::NotNeeded);
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/api/game/defense/status"]);
+    }
+}
@@ -0,0 +1,187 @@
+// sacas-daemon/src/combat/budget.rs
+// Shared entropy budget so attack spending can't starve defense
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::state::StateManager;
+use crate::types::SpendReason;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BudgetConfig {
+    /// Share of entropy capacity (0-100%) that attacks may never spend
+    pub defense_reserve_percent: u8,
+    /// Entropy a device must hold to launch an attack at all (Ω)
+    #[serde(default)]
+    pub min_entropy_to_attack: u64,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            defense_reserve_percent: 30,
+            min_entropy_to_attack: 0,
+        }
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BudgetError {
+    #[error("Attack spend of {requested} Ω would dip into the defense reserve ({reserved} Ω reserved, {spendable} Ω spendable)")]
+    ReserveProtected { requested: u64, spendable: u64, reserved: u64 },
+
+    #[error("Insufficient entropy: requested {requested} Ω, have {available} Ω")]
+    Insufficient { requested: u64, available: u64 },
+
+    #[error("Refusing to attack with {spendable} Ω spendable: at least {reserve} Ω must stay in reserve")]
+    BelowReserve { spendable: u64, reserve: u64 },
+}
+
+/// Gatekeeper every spending loop goes through
+///
+/// Attacks may only spend entropy above the defense reserve; defense may
+/// spend everything above the entropy floor. The reserve counts from the
+/// floor up. The check and the deduction happen under one state lock, so
+/// concurrent loops can't jointly overdraw the reserve.
+#[derive(Clone)]
+pub struct EntropyBudget {
+    state: StateManager,
+    reserve_percent: u64,
+    min_attack_entropy: u64,
+}
+
+impl EntropyBudget {
+    pub fn new(state: StateManager, config: &BudgetConfig) -> Self {
+        Self {
+            state,
+            reserve_percent: u64::from(config.defense_reserve_percent.min(100)),
+            min_attack_entropy: config.min_entropy_to_attack,
+        }
+    }
+
+    /// Entropy held back for defense given the current capacity
+    pub fn reserve_for(&self, capacity: u64) -> u64 {
+        capacity.saturating_mul(self.reserve_percent) / 100
+    }
+
+    /// How much an attack could spend right now
+    pub async fn attack_allowance(&self) -> u64 {
+        let capacity = self.state.get_snapshot().await.player.capacity;
+        self.state.spendable_entropy().await.saturating_sub(self.reserve_for(capacity))
+    }
+
+    /// Refuse to start an attack unless spendable entropy is above the reserve
+    ///
+    /// The reserve is the larger of `min_entropy_to_attack` and the defense
+    /// reserve. The server doesn't quote an attack's price up front, so the
+    /// check is against the balance above the entropy floor.
+    pub async fn check_attack(&self) -> Result<(), BudgetError> {
+        let capacity = self.state.get_snapshot().await.player.capacity;
+        let reserve = self.reserve_for(capacity).max(self.min_attack_entropy);
+        let spendable = self.state.spendable_entropy().await;
+
+        if spendable <= reserve {
+            return Err(BudgetError::BelowReserve { spendable, reserve });
+        }
+        Ok(())
+    }
+
+    /// Deduct an attack cost, refusing anything that would touch the reserve
+    pub async fn spend_for_attack(&self, amount: u64) -> Result<u64, BudgetError> {
+        let reserve_percent = self.reserve_percent;
+        self.state
+            .try_spend_entropy(amount, SpendReason::Combat, |capacity| capacity.saturating_mul(reserve_percent) / 100)
+            .await
+            .map_err(|(available, floor)| BudgetError::ReserveProtected {
+                requested: amount,
+                spendable: available.saturating_sub(floor),
+                reserved: floor,
+            })
+    }
+
+    /// Deduct a defense cost; defense may draw on the reserve
+    pub async fn spend_for_defense(&self, amount: u64) -> Result<u64, BudgetError> {
+        self.state
+            .try_spend_entropy(amount, SpendReason::Defense, |_| 0)
+            .await
+            .map_err(|(available, _)| BudgetError::Insufficient { requested: amount, available })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn budget_with(entropy: u64) -> (EntropyBudget, StateManager) {
+        // karma 10 → capacity 1000, so a 30% reserve is 300 Ω
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(entropy as i64).await;
+        let budget = EntropyBudget::new(state.clone(), &BudgetConfig::default());
+        (budget, state)
+    }
+
+    #[tokio::test]
+    async fn test_attack_cannot_touch_reserve() {
+        let (budget, state) = budget_with(1000).await;
+        assert_eq!(budget.attack_allowance().await, 700);
+
+        assert_eq!(budget.spend_for_attack(500).await, Ok(500));
+        assert_eq!(
+            budget.spend_for_attack(201).await,
+            Err(BudgetError::ReserveProtected { requested: 201, spendable: 200, reserved: 300 })
+        );
+        assert_eq!(state.get_snapshot().await.player.entropy, 500);
+
+        // Defense may still use everything
+        assert_eq!(budget.spend_for_defense(500).await, Ok(0));
+    }
+
+    #[tokio::test]
+    async fn test_attack_blocked_at_or_below_reserve() {
+        let (budget, _) = budget_with(300).await;
+        assert_eq!(budget.check_attack().await, Err(BudgetError::BelowReserve { spendable: 300, reserve: 300 }));
+
+        let (budget, _) = budget_with(301).await;
+        assert_eq!(budget.check_attack().await, Ok(()));
+
+        // An explicit minimum above the percentage reserve wins
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(400).await;
+        let config = BudgetConfig { min_entropy_to_attack: 500, ..Default::default() };
+        let budget = EntropyBudget::new(state, &config);
+        assert_eq!(budget.check_attack().await, Err(BudgetError::BelowReserve { spendable: 400, reserve: 500 }));
+    }
+
+    #[tokio::test]
+    async fn test_attack_refused_at_entropy_floor() {
+        // 500 Ω is above the 300 Ω reserve, but all of it is floor
+        let state = StateManager::in_memory("dev".to_string(), 10).with_entropy_floor(500);
+        state.update_entropy(500).await;
+        let budget = EntropyBudget::new(state.clone(), &BudgetConfig::default());
+
+        assert_eq!(budget.check_attack().await, Err(BudgetError::BelowReserve { spendable: 0, reserve: 300 }));
+        assert_eq!(budget.attack_allowance().await, 0);
+
+        // The reserve sits on top of the floor
+        state.update_entropy(300).await;
+        assert_eq!(budget.check_attack().await, Err(BudgetError::BelowReserve { spendable: 300, reserve: 300 }));
+        state.update_entropy(1).await;
+        assert_eq!(budget.check_attack().await, Ok(()));
+        assert_eq!(budget.attack_allowance().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_attacks_respect_reserve() {
+        let (budget, state) = budget_with(1000).await;
+
+        let spends = (0..20).map(|_| {
+            let budget = budget.clone();
+            tokio::spawn(async move { budget.spend_for_attack(50).await })
+        });
+        let results = futures_util::future::join_all(spends).await;
+
+        let succeeded = results.into_iter().filter(|r| r.as_ref().unwrap().is_ok()).count();
+        assert_eq!(succeeded, 14);
+        assert_eq!(state.get_snapshot().await.player.entropy, 300);
+    }
+}
@@ -1,18 +1,34 @@
 // sacas-daemon/src/combat/client.rs
 // Combat HTTP client for battle and defense configuration
 
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use anyhow::{Result, Context};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use crate::config::{Config, Service};
+use crate::device::{Signer, SigningContext, Stamp, Stamper};
+use crate::http::{outcome_unknown, EndpointCategory, HttpClient, ServerPool};
+use crate::karma_gate::KarmaGate;
+use crate::state::StateManager;
+use crate::types::{EntropyAmount, Parasite, SpendReason};
+use super::cooldown::{ensure_no_cooldown, CooldownCheck};
+use super::reconcile::server_cooldown_end;
+use super::EntropyBudget;
 use tracing::{info, debug, warn};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DefenseConfig {
     pub l1: u64,
     pub l2: u64,
     pub l3: u64,
 }
 
+/// Parasites the server counts for this device
+#[derive(Debug, Deserialize)]
+struct ParasiteList {
+    parasites: Vec<Parasite>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DefenseStatus {
     pub defense: DefenseConfig,
@@ -35,6 +51,9 @@ pub struct BattleResult {
     pub outcome: String, // "PARASITIZED" or "REPELLED"
     pub layers: BattleLayers,
     pub loot: LootInfo,
+    /// Entropy the server charged the attacker, when it reports it
+    #[serde(default)]
+    pub cost: Option<EntropyAmount>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,18 +76,18 @@ pub struct LayerResult {
 
 #[derive(Debug, Deserialize)]
 pub struct LootInfo {
-    pub entropy_looted: String,
-    pub attacker_entropy_after: String,
-    pub defender_entropy_after: String,
+    pub entropy_looted: EntropyAmount,
+    pub attacker_entropy_after: EntropyAmount,
+    pub defender_entropy_after: EntropyAmount,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct BattleSimulation {
     pub probabilities: Probabilities,
-    pub expected_loot: String,
+    pub expected_loot: EntropyAmount,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
 pub struct Probabilities {
     pub l1_win: f64,
     pub l2_success: f64,
@@ -76,29 +95,79 @@ pub struct Probabilities {
 }
 
 pub struct CombatClient {
-    client: Client,
-    api_base: String,
+    client: HttpClient,
+    servers: ServerPool,
     device_id: String,
-    private_key: ed25519_dalek::SigningKey,
+    signer: Box<dyn Signer>,
+    state: StateManager,
+    budget: Option<EntropyBudget>,
+    karma_gate: Option<KarmaGate>,
+    cooldown_check: Option<CooldownCheck>,
+    /// Last attack cost the server reported, 0 until one has
+    last_attack_cost: AtomicU64,
+    stamper: Stamper,
 }
 
 impl CombatClient {
     pub fn new(
-        api_base: String,
+        client: HttpClient,
+        servers: ServerPool,
         device_id: String,
-        private_key: ed25519_dalek::SigningKey,
+        signer: impl Signer + 'static,
+        state: StateManager,
     ) -> Self {
         Self {
-            client: Client::new(),
-            api_base,
+            client,
+            servers,
             device_id,
-            private_key,
+            signer: Box::new(signer),
+            state,
+            budget: None,
+            karma_gate: None,
+            cooldown_check: None,
+            last_attack_cost: AtomicU64::new(0),
+            stamper: Stamper::default(),
         }
     }
 
+    /// The client the daemon runs with: karma gate, entropy budget and
+    /// cooldown check all attached from `config`
+    pub fn from_config(config: &Config, device_id: String, signer: impl Signer + 'static, state: StateManager) -> Self {
+        Self::new(
+            HttpClient::new(&config.http),
+            ServerPool::new(&config.service_urls(Service::Combat)),
+            device_id,
+            signer,
+            state.clone(),
+        )
+        .with_karma_gate(KarmaGate::new("attack", config.karma_gates.attack, state.clone()))
+        .with_budget(EntropyBudget::new(state, &config.budget))
+        .with_cooldown_check(std::time::Duration::from_secs(config.combat.cooldown_check_secs))
+    }
+
+    /// Refuse attacks that would leave entropy at or below the budget's reserve
+    pub fn with_budget(mut self, budget: EntropyBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Skip attacks while karma is below the gate's threshold
+    pub fn with_karma_gate(mut self, gate: KarmaGate) -> Self {
+        self.karma_gate = Some(gate);
+        self
+    }
+
+    /// Ask the server for the defense cooldown before each attack, reusing
+    /// the answer for `ttl`
+    pub fn with_cooldown_check(mut self, ttl: std::time::Duration) -> Self {
+        self.cooldown_check = Some(CooldownCheck::new(ttl));
+        self
+    }
+
     /// Configure defense allocation (L1/L2/L3)
     pub async fn configure_defense(&self, config: DefenseConfig) -> Result<serde_json::Value> {
-        let url = format!("{}/api/game/defense/configure", self.api_base);
+        self.state.ensure_binding()?;
+        let path = "/api/game/defense/configure";
         
         let body = serde_json::json!({
             "l1": config.l1,
@@ -106,145 +175,475 @@ impl CombatClient {
             "l3": config.l3
         });
 
-        let response = self.signed_post(&url, &body).await?;
+        let response = self.signed_post(path, &body, self.stamper.stamp()).await?;
         
         if !response.status().is_success() {
             let status = response.status();
-            let text = response.text().await?;
+            let text = self.client.read_text(response).await?;
             anyhow::bail!("Defense configuration failed ({}): {}", status, text);
         }
 
-        let result: serde_json::Value = response.json().await?;
+        let result: serde_json::Value = self.client.read_json(response).await?;
         info!("Defense configured: L1={}, L2={}, L3={}", config.l1, config.l2, config.l3);
+        let cost = result.get("cost").and_then(|cost| serde_json::from_value::<EntropyAmount>(cost.clone()).ok());
+        if let Some(cost) = cost {
+            self.follow_charge(cost.value(), SpendReason::Defense).await;
+        }
         
         Ok(result)
     }
 
     /// Get current defense status
     pub async fn get_defense_status(&self) -> Result<DefenseStatus> {
-        let url = format!("{}/api/game/defense/status", self.api_base);
+        let path = "/api/game/defense/status";
         
-        let response = self.signed_get(&url).await?;
+        let response = self.signed_get(path).await?;
         
         if !response.status().is_success() {
             let status = response.status();
-            let text = response.text().await?;
+            let text = self.client.read_text(response).await?;
             anyhow::bail!("Failed to get defense status ({}): {}", status, text);
         }
 
-        let status: DefenseStatus = response.json().await?;
+        let status: DefenseStatus = self.client.read_json(response).await?;
         Ok(status)
     }
 
+    /// Parasites the server still counts for this device
+    ///
+    /// The server is authoritative: hosts can reclaim a parasite without
+    /// this device hearing about it.
+    pub async fn get_parasites(&self) -> Result<Vec<Parasite>> {
+        let path = "/api/game/parasites";
+
+        let response = self.signed_get(path).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = self.client.read_text(response).await?;
+            anyhow::bail!("Failed to get parasites ({}): {}", status, text);
+        }
+
+        let list: ParasiteList = self.client.read_json(response).await?;
+        Ok(list.parasites)
+    }
+
     /// Attack a target device
+    ///
+    /// Fails with `BindingLost`, `KarmaTooLow` or `BudgetError::BelowReserve` before
+    /// contacting the server when a gate or budget is attached and rules the
+    /// attack out, and with `CooldownActive` when the cooldown check finds
+    /// a defense change still pending. The cost the server reports is
+    /// deducted locally. When the server may have charged an attack whose
+    /// response never arrived, the spend is left pending until a sync
+    /// confirms it.
     pub async fn attack(&self, target_id: &str) -> Result<BattleResult> {
-        let url = format!("{}/api/game/battle/attack", self.api_base);
+        self.state.ensure_binding()?;
+        if let Some(gate) = &self.karma_gate {
+            gate.check().await?;
+        }
+        if let Some(budget) = &self.budget {
+            budget.check_attack().await?;
+        }
+        if let Some(check) = &self.cooldown_check {
+            self.check_cooldown(check).await?;
+        }
+
+        let path = "/api/game/battle/attack";
         
         let body = serde_json::json!({
             "target_id": target_id
         });
 
-        let response = self.signed_post(&url, &body).await?;
+        let stamp = self.stamper.stamp();
+        let nonce = stamp.nonce.clone();
+        let response = match self.signed_post(path, &body, stamp).await {
+            Ok(response) => response,
+            Err(e) => {
+                if outcome_unknown(&e) {
+                    self.leave_pending(nonce).await;
+                }
+                return Err(e);
+            }
+        };
         
         if !response.status().is_success() {
             let status = response.status();
-            let text = response.text().await?;
+            let text = self.client.read_text(response).await?;
             anyhow::bail!("Attack failed ({}): {}", status, text);
         }
 
-        let result: BattleResult = response.json().await?;
+        // Past a success status the server has charged, even if the body is unreadable
+        let result: BattleResult = match self.client.read_json(response).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.leave_pending(nonce).await;
+                return Err(e);
+            }
+        };
         info!("Battle {} - Outcome: {}", result.battle_id, result.outcome);
+        if let Some(cost) = result.cost {
+            self.last_attack_cost.store(cost.value(), Ordering::Relaxed);
+            self.follow_charge(cost.value(), SpendReason::Combat).await;
+        }
         
         Ok(result)
     }
 
+    /// Record an attack under `nonce` for the next sync to confirm
+    ///
+    /// The server doesn't quote attack prices, so the last cost it reported
+    /// stands in until it confirms the real one.
+    async fn leave_pending(&self, nonce: String) {
+        let cost = self.last_attack_cost.load(Ordering::Relaxed);
+        warn!("⚠️  Attack outcome unknown; ~{} Ω pending until the next sync", cost);
+        self.state.record_pending_spend(cost, SpendReason::Combat, nonce).await;
+    }
+
+    /// Deduct a charge the server already made through the spend ledger
+    ///
+    /// With a budget attached the charge goes through it, so attacks are
+    /// held to the defense reserve; otherwise it is floor-checked like any
+    /// other spend. A charge that would break either is logged and recorded
+    /// anyway, since the server has already taken it.
+    async fn follow_charge(&self, amount: u64, reason: SpendReason) {
+        let refused = match (&self.budget, &reason) {
+            (Some(budget), SpendReason::Defense) => budget.spend_for_defense(amount).await.err().map(|e| e.to_string()),
+            (Some(budget), _) => budget.spend_for_attack(amount).await.err().map(|e| e.to_string()),
+            (None, _) => self.state.spend_entropy(amount, reason.clone()).await.err().map(|e| e.to_string()),
+        };
+        if let Some(e) = refused {
+            warn!("⚠️  Server charged past the local budget: {}", e);
+            self.state.record_server_charge(amount, reason).await;
+        }
+    }
+
+    /// Refresh the server's cooldown if the cached one is stale and fail
+    /// while it's active
+    ///
+    /// A failed status request doesn't block the attack; the server has the
+    /// final say either way.
+    async fn check_cooldown(&self, check: &CooldownCheck) -> Result<()> {
+        let now = std::time::Instant::now();
+        let ends = match check.cached(now) {
+            Some(ends) => ends,
+            None => match self.get_defense_status().await {
+                Ok(status) => {
+                    let ends = server_cooldown_end(&status, chrono::Utc::now());
+                    check.store(now, ends);
+                    ends
+                }
+                Err(e) => {
+                    warn!("⚠️  Pre-attack defense check failed, attacking anyway: {}", e);
+                    None
+                }
+            },
+        };
+
+        ensure_no_cooldown(ends, chrono::Utc::now())?;
+        Ok(())
+    }
+
     /// Simulate battle without executing
     pub async fn simulate_battle(&self, target_id: &str) -> Result<BattleSimulation> {
-        let url = format!("{}/api/game/battle/simulate", self.api_base);
+        let path = "/api/game/battle/simulate";
         
         let body = serde_json::json!({
             "target_id": target_id
         });
 
-        let response = self.signed_post(&url, &body).await?;
+        let response = self.signed_post(path, &body, self.stamper.stamp()).await?;
         
         if !response.status().is_success() {
             let status = response.status();
-            let text = response.text().await?;
+            let text = self.client.read_text(response).await?;
             anyhow::bail!("Simulation failed ({}): {}", status, text);
         }
 
-        let result: BattleSimulation = response.json().await?;
+        let result: BattleSimulation = self.client.read_json(response).await?;
         Ok(result)
     }
 
-    /// Sign and send POST request with Ed25519 signature
+    /// Send a POST request signed by the device's `Signer`
     async fn signed_post(
         &self,
-        url: &str,
+        path: &str,
         body: &serde_json::Value,
+        stamp: Stamp,
     ) -> Result<reqwest::Response> {
-        use ed25519_dalek::Signer;
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
-            .as_secs();
-        let nonce = uuid::Uuid::new_v4().to_string();
+        self.client.acquire(EndpointCategory::Combat)?;
 
         let body_str = body.to_string();
-        let message = format!("POST|{}|{}|{}|{}", 
-            url.split("/api/").nth(1).unwrap_or(""),
-            body_str,
-            timestamp,
-            nonce
-        );
+        let signing = SigningContext::for_api("POST", path, &body_str, &stamp);
+        let Stamp { timestamp, nonce } = stamp;
+
+        let sig_hex = self.signer.sign_hex(signing.canonical.as_bytes());
+
+        let response = self.servers
+            .send(path, |url| {
+                self.client
+                    .post(url)
+                    .header("X-Device-ID", &self.device_id)
+                    .header("X-Signature", &sig_hex)
+                    .header("X-Signature-Alg", self.signer.algorithm())
+                    .header("X-Timestamp", timestamp.to_string())
+                    .header("X-Nonce", &nonce)
+                    .header("Content-Type", "application/json")
+                    .body(body_str.clone())
+            })
+            .await?;
 
-        let signature = self.private_key.sign(message.as_bytes());
-        let sig_hex = hex::encode(signature.to_bytes());
-
-        let response = self.client
-            .post(url)
-            .header("X-Device-ID", &self.device_id)
-            .header("X-Signature", sig_hex)
-            .header("X-Timestamp", timestamp.to_string())
-            .header("X-Nonce", nonce)
-            .header("Content-Type", "application/json")
-            .body(body_str)
-            .send()
+        signing.log_if_rejected(response.status());
+        Ok(response)
+    }
+
+    /// Send a GET request signed by the device's `Signer`
+    async fn signed_get(&self, path: &str) -> Result<reqwest::Response> {
+        self.client.acquire(EndpointCategory::Combat)?;
+
+        let stamp = self.stamper.stamp();
+        let signing = SigningContext::for_api("GET", path, "", &stamp);
+        let Stamp { timestamp, nonce } = stamp;
+
+        let sig_hex = self.signer.sign_hex(signing.canonical.as_bytes());
+
+        let response = self.servers
+            .send(path, |url| {
+                self.client
+                    .get(url)
+                    .header("X-Device-ID", &self.device_id)
+                    .header("X-Signature", &sig_hex)
+                    .header("X-Signature-Alg", self.signer.algorithm())
+                    .header("X-Timestamp", timestamp.to_string())
+                    .header("X-Nonce", &nonce)
+            })
             .await?;
 
+        signing.log_if_rejected(response.status());
         Ok(response)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::cooldown::CooldownActive;
+    use crate::combat::{BudgetConfig, BudgetError};
+    use crate::test_support::{combat_client, sample_config, test_signer, MockServer};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    const BATTLE: &str = r#"{"success":true,"battle_id":"b-1","outcome":"REPELLED",
+        "layers":{"l1":{"success":false,"attack":10,"defense":20},
+                  "l2":{"success":false,"attack":0,"defense":0},
+                  "l3":{"success":false,"attack":0,"defense":0}},
+        "loot":{"entropy_looted":"0","attacker_entropy_after":"400","defender_entropy_after":"900"}}"#;
+
+    async fn guarded_client(url: &str, entropy: u64) -> CombatClient {
+        // karma 10 → capacity 1000, so the default 30% reserve is 300 Ω
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(entropy as i64).await;
+
+        combat_client(url, state.clone()).with_budget(EntropyBudget::new(state, &BudgetConfig::default()))
+    }
+
+    fn costed_battle(cost: u64) -> String {
+        BATTLE.replace(r#""success":true,"#, &format!(r#""success":true,"cost":"{}","#, cost))
+    }
 
-    /// Sign and send GET request with Ed25519 signature
-    async fn signed_get(&self, url: &str) -> Result<reqwest::Response> {
-        use ed25519_dalek::Signer;
-        use std::time::{SystemTime, UNIX_EPOCH};
+    #[tokio::test]
+    async fn test_attack_blocked_below_reserve() {
+        let server = MockServer::respond_with(200, BATTLE).await;
+        let client = guarded_client(&server.url, 250).await;
 
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
-            .as_secs();
-        let nonce = uuid::Uuid::new_v4().to_string();
+        let err = client.attack("target").await.unwrap_err();
 
-        let message = format!("GET|{}||{}|{}", 
-            url.split("/api/").nth(1).unwrap_or(""),
-            timestamp,
-            nonce
+        assert_eq!(
+            err.downcast_ref::<BudgetError>(),
+            Some(&BudgetError::BelowReserve { spendable: 250, reserve: 300 })
         );
+        assert!(server.requests().is_empty());
+    }
 
-        let signature = self.private_key.sign(message.as_bytes());
-        let sig_hex = hex::encode(signature.to_bytes());
+    #[tokio::test]
+    async fn test_attack_blocked_at_entropy_floor() {
+        let server = MockServer::respond_with(200, BATTLE).await;
+        // 700 Ω clears the 300 Ω reserve, but only 200 Ω sits above the floor
+        let state = StateManager::in_memory("dev".to_string(), 10).with_entropy_floor(500);
+        state.update_entropy(700).await;
+        let client = combat_client(&server.url, state.clone()).with_budget(EntropyBudget::new(state, &BudgetConfig::default()));
 
-        let response = self.client
-            .get(url)
-            .header("X-Device-ID", &self.device_id)
-            .header("X-Signature", sig_hex)
-            .header("X-Timestamp", timestamp.to_string())
-            .header("X-Nonce", nonce)
-            .send()
-            .await?;
+        let err = client.attack("target").await.unwrap_err();
 
-        Ok(response)
+        assert_eq!(
+            err.downcast_ref::<BudgetError>(),
+            Some(&BudgetError::BelowReserve { spendable: 200, reserve: 300 })
+        );
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_attack_permitted_above_reserve() {
+        let server = MockServer::respond_with(200, BATTLE).await;
+        let client = guarded_client(&server.url, 800).await;
+
+        let result = client.attack("target").await.unwrap();
+
+        assert_eq!(result.battle_id, "b-1");
+        assert_eq!(result.loot.defender_entropy_after, EntropyAmount(900));
+        assert_eq!(server.requests()[0].path, "/api/game/battle/attack");
+    }
+
+    #[tokio::test]
+    async fn test_attack_deducts_reported_cost() {
+        let server = MockServer::respond_with(200, &costed_battle(50)).await;
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(800).await;
+        let client = combat_client(&server.url, state.clone());
+
+        client.attack("target").await.unwrap();
+
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 750);
+        assert_eq!(snapshot.ledger[0].reason, SpendReason::Combat);
+    }
+
+    #[tokio::test]
+    async fn test_daemon_client_spends_through_budget() {
+        let server = MockServer::respond_with(200, &costed_battle(150)).await;
+        // karma 10 → capacity 1000, so the default 30% reserve is 300 Ω
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(400).await;
+        let config = Config { server_url: server.url.clone().into(), ..sample_config() };
+        let client = CombatClient::from_config(&config, "dev".to_string(), test_signer(), state.clone());
+
+        // Only 100 Ω was spendable, but the server's charge is followed
+        client.attack("target").await.unwrap();
+        assert_eq!(state.get_snapshot().await.player.entropy, 250);
+
+        // The budget now rules out the next attack before it's sent
+        let sent = server.requests().len();
+        let err = client.attack("target").await.unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<BudgetError>(),
+            Some(&BudgetError::BelowReserve { spendable: 250, reserve: 300 })
+        );
+        assert_eq!(server.requests().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_outcome_leaves_cost_pending() {
+        // Answers the first attack in full and cuts the second one short
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let body = costed_battle(50);
+            for truncate in [false, true] {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf).await;
+                let sent = if truncate { &body[..10] } else { &body[..] };
+                let head = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len());
+                stream.write_all(head.as_bytes()).await.unwrap();
+                stream.write_all(sent.as_bytes()).await.unwrap();
+            }
+        });
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(800).await;
+        let client = combat_client(&url, state.clone());
+
+        client.attack("target").await.unwrap();
+        assert!(client.attack("target").await.is_err());
+
+        // The last reported cost stands in until a sync confirms the nonce
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 750);
+        assert_eq!(snapshot.ledger.len(), 2);
+        let pending = snapshot.ledger.back().unwrap();
+        assert!(pending.pending && pending.amount == 50 && pending.nonce.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_defense_cost_recorded_in_ledger() {
+        let server = MockServer::respond_with(200, r#"{"success":true,"cost":"40"}"#).await;
+        let state = StateManager::in_memory("dev".to_string(), 10).with_entropy_floor(100);
+        state.update_entropy(120).await;
+        let client = combat_client(&server.url, state.clone());
+
+        client.configure_defense(DefenseConfig { l1: 10, l2: 10, l3: 20 }).await.unwrap();
+
+        // The server charged past the floor; the ledger still follows it
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 80);
+        assert_eq!(snapshot.ledger.len(), 1);
+        assert_eq!(snapshot.ledger[0].reason, SpendReason::Defense);
+        assert_eq!(snapshot.ledger[0].amount, 40);
+    }
+
+    fn defense_status(cooldown_secs: u64) -> String {
+        format!(r#"{{"defense":{{"l1":10,"l2":10,"l3":10}},"total_combat_points":30,"last_configured":null,
+            "cooldown":{{"active":{},"ends_at":null,"remaining_seconds":{}}}}}"#, cooldown_secs > 0, cooldown_secs)
+    }
+
+    async fn cooldown_server(cooldown_secs: u64) -> MockServer {
+        let status = defense_status(cooldown_secs);
+        MockServer::start(move |req| match req.path.as_str() {
+            "/api/game/defense/status" => (200, status.clone()),
+            _ => (200, BATTLE.to_string()),
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_active_cooldown_skips_attack() {
+        let server = cooldown_server(120).await;
+        let client = guarded_client(&server.url, 800).await.with_cooldown_check(std::time::Duration::from_secs(10));
+
+        let err = client.attack("target").await.unwrap_err();
+        assert!(err.downcast_ref::<CooldownActive>().is_some_and(|e| e.remaining_secs > 100));
+
+        // The cached status answers the second decision
+        assert!(client.attack("target").await.is_err());
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/api/game/defense/status"]);
+    }
+
+    #[tokio::test]
+    async fn test_expired_cooldown_permits_attack() {
+        let server = cooldown_server(0).await;
+        let client = guarded_client(&server.url, 800).await.with_cooldown_check(std::time::Duration::from_secs(10));
+
+        assert_eq!(client.attack("target").await.unwrap().battle_id, "b-1");
+        let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(paths, ["/api/game/defense/status", "/api/game/battle/attack"]);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_request_logs_signing_context() {
+        use crate::device::stamp::{FixedClock, FixedNonce};
+        use crate::event_log::EventLog;
+        use std::sync::Arc;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let events = EventLog::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(events.layer()));
+        let mut logged = events.subscribe(tracing::Level::DEBUG);
+
+        let server = MockServer::respond_with(401, r#"{"error":"bad signature"}"#).await;
+        let mut client = guarded_client(&server.url, 800).await;
+        client.stamper = Stamper::new(Arc::new(FixedClock(1_700_000_000)), Arc::new(FixedNonce("n-1")));
+        assert!(client.simulate_battle("t-1").await.is_err());
+
+        let diagnostic = loop {
+            let event = logged.next().await.unwrap();
+            if event.message.contains("rejected") {
+                break event.message;
+            }
+        };
+        let canonical = r#"POST|game/battle/simulate|{"target_id":"t-1"}|1700000000|n-1"#;
+        assert!(diagnostic.contains(&format!("{:?}", canonical)), "{}", diagnostic);
+        assert!(diagnostic.contains("401"));
+
+        let signature = server.requests()[0].header("x-signature").unwrap().to_string();
+        assert!(!diagnostic.contains(&signature));
     }
 }
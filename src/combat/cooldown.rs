@@ -0,0 +1,64 @@
+// sacas-daemon/src/combat/cooldown.rs
+// Just-in-time check of the server's defense cooldown before attacking
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq)]
+#[error("Defense cooldown active for another {remaining_secs}s; skipping attack")]
+pub struct CooldownActive {
+    pub remaining_secs: i64,
+}
+
+/// Remembers the server's cooldown end for `ttl`, so back-to-back attack
+/// decisions share one status request
+pub struct CooldownCheck {
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, Option<DateTime<Utc>>)>>,
+}
+
+impl CooldownCheck {
+    pub fn new(ttl: Duration) -> Self {
+        Self { ttl, cached: Mutex::new(None) }
+    }
+
+    /// The cooldown end fetched within the last `ttl`, if any was
+    pub fn cached(&self, now: Instant) -> Option<Option<DateTime<Utc>>> {
+        self.cached
+            .lock()
+            .unwrap()
+            .filter(|(at, _)| now.saturating_duration_since(*at) < self.ttl)
+            .map(|(_, ends)| ends)
+    }
+
+    pub fn store(&self, now: Instant, ends: Option<DateTime<Utc>>) {
+        *self.cached.lock().unwrap() = Some((now, ends));
+    }
+}
+
+/// Fails while a cooldown ending at `ends` is still running
+pub fn ensure_no_cooldown(ends: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Result<(), CooldownActive> {
+    match ends {
+        Some(ends) if ends > now => Err(CooldownActive { remaining_secs: (ends - now).num_seconds() }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let check = CooldownCheck::new(Duration::from_secs(10));
+        let t0 = Instant::now();
+        assert_eq!(check.cached(t0), None);
+
+        check.store(t0, None);
+        assert_eq!(check.cached(t0 + Duration::from_secs(9)), Some(None));
+        assert_eq!(check.cached(t0 + Duration::from_secs(10)), None);
+    }
+}
@@ -0,0 +1,105 @@
+// sacas-daemon/src/combat/estimate.rs
+// Offline estimate of battle odds, from the battle model in the Gameplay Guide
+
+use crate::combat::client::Probabilities;
+use crate::types::DefenseArray;
+
+/// L1 breaks when the attack exceeds the defense by this factor
+pub const L1_BREAK_RATIO: f64 = 1.2;
+/// A broken L1 takes this share off the defender's L2 and L3
+pub const L1_BREAK_PENALTY: f64 = 0.3;
+/// L3 is parasitized when the attack exceeds the defense by this factor
+pub const L3_PARASITIZE_RATIO: f64 = 1.5;
+/// Chance an L3 attack misses outright after a failed L2
+pub const L3_MISS_CHANCE: f64 = 0.2;
+
+/// Points committed to each layer, by an attacker or a defender
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayerPoints {
+    pub l1: u64,
+    pub l2: u64,
+    pub l3: u64,
+}
+
+impl From<&DefenseArray> for LayerPoints {
+    fn from(defense: &DefenseArray) -> Self {
+        Self { l1: defense.l1, l2: defense.l2, l3: defense.l3 }
+    }
+}
+
+/// Odds of `attack` against `defense`, in the shape of the server's `BattleSimulation`
+///
+/// This is an estimate: it follows the published model (L1 and L3
+/// thresholds, L2 as a weighted coin, the L3 miss after a failed L2) but
+/// does not apply climate modifiers or anything else the server may weigh.
+pub fn estimate_battle(attack: LayerPoints, defense: LayerPoints) -> Probabilities {
+    let l1_broken = attack.l1 as f64 > defense.l1 as f64 * L1_BREAK_RATIO;
+    let weaken = if l1_broken { 1.0 - L1_BREAK_PENALTY } else { 1.0 };
+    let l2_defense = defense.l2 as f64 * weaken;
+    let l3_defense = defense.l3 as f64 * weaken;
+
+    let l2_success = if attack.l2 == 0 {
+        0.0
+    } else {
+        attack.l2 as f64 / (attack.l2 as f64 + l2_defense)
+    };
+
+    // Past the threshold, only a miss after a failed L2 stops the parasite
+    let l3_parasitize = if attack.l3 as f64 > l3_defense * L3_PARASITIZE_RATIO {
+        l2_success + (1.0 - l2_success) * (1.0 - L3_MISS_CHANCE)
+    } else {
+        0.0
+    };
+
+    Probabilities {
+        l1_win: if l1_broken { 1.0 } else { 0.0 },
+        l2_success,
+        l3_parasitize,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(l1: u64, l2: u64, l3: u64) -> LayerPoints {
+        LayerPoints { l1, l2, l3 }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn test_guide_combat_example() {
+        // "Combat Example" in the Gameplay Guide: just enough on L1 and L3
+        let odds = estimate_battle(points(181, 150, 316), points(150, 200, 300));
+        assert_eq!(odds.l1_win, 1.0);
+        assert!(close(odds.l2_success, 150.0 / 290.0));
+        assert!(close(odds.l3_parasitize, 0.517 + 0.483 * 0.8));
+    }
+
+    #[test]
+    fn test_held_l1_keeps_full_defense() {
+        // 180 is exactly 1.2x, which does not break
+        let odds = estimate_battle(points(180, 200, 451), points(150, 200, 300));
+        assert_eq!(odds.l1_win, 0.0);
+        assert!(close(odds.l2_success, 0.5));
+        assert!(close(odds.l3_parasitize, 0.5 + 0.5 * 0.8));
+
+        // Without the L1 discount, 316 no longer clears 300 x 1.5
+        let odds = estimate_battle(points(0, 200, 316), points(150, 200, 300));
+        assert_eq!(odds.l3_parasitize, 0.0);
+    }
+
+    #[test]
+    fn test_no_l2_investment() {
+        let odds = estimate_battle(points(0, 0, 1000), points(100, 200, 300));
+        assert_eq!(odds.l2_success, 0.0);
+        assert!(close(odds.l3_parasitize, 1.0 - L3_MISS_CHANCE));
+
+        // An undefended L2 always falls to any attack
+        let odds = estimate_battle(points(0, 1, 0), points(0, 0, 0));
+        assert_eq!(odds.l2_success, 1.0);
+    }
+}
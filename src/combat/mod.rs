@@ -3,5 +3,20 @@
 // Provides HTTP client for combat-related API endpoints
 
 pub mod client;
+pub mod allocator;
+pub mod auto_attack;
+pub mod auto_defense;
+pub mod budget;
+pub mod cooldown;
+pub mod estimate;
+pub mod reconcile;
+pub mod target;
 
 pub use client::CombatClient;
+pub use allocator::DefenseAllocator;
+pub use auto_attack::run_auto_attack;
+pub use auto_defense::{run_auto_defense, AutoDefense, AUTO_DEFENSE_CHECK_SECS};
+pub use budget::{BudgetConfig, BudgetError, EntropyBudget};
+pub use estimate::{estimate_battle, LayerPoints};
+pub use reconcile::{run_defense_reconciliation, run_parasite_reconciliation};
+pub use target::selector_by_name;
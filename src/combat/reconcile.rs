@@ -0,0 +1,258 @@
+// sacas-daemon/src/combat/reconcile.rs
+// Periodically pull the server's defense status and parasite list into local state
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tracing::{debug, info, warn};
+
+use super::client::{CombatClient, DefenseStatus};
+use crate::state::StateManager;
+use crate::types::{DefenseArray, Parasite};
+
+/// Cooldown ends closer together than this are treated as equal
+const COOLDOWN_TOLERANCE_SECS: i64 = 5;
+
+/// One defense field where local state disagreed with the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefenseDrift {
+    pub field: &'static str,
+    pub local: String,
+    pub server: String,
+}
+
+/// When the server says the cooldown ends, if one is active
+pub(crate) fn server_cooldown_end(status: &DefenseStatus, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    if !status.cooldown.active {
+        return None;
+    }
+
+    status
+        .cooldown
+        .ends_at
+        .as_deref()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|| Some(now + chrono::Duration::seconds(status.cooldown.remaining_seconds as i64)))
+}
+
+fn cooldown_drifted(local: Option<DateTime<Utc>>, server: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    // An expired local cooldown is the same as none
+    let local = local.filter(|t| *t > now);
+    match (local, server) {
+        (None, None) => false,
+        (Some(a), Some(b)) => (a - b).num_seconds().abs() > COOLDOWN_TOLERANCE_SECS,
+        _ => true,
+    }
+}
+
+/// Bring local defense in line with `status`, returning what differed
+///
+/// Local state is only written when something drifted.
+pub async fn reconcile_defense(state: &StateManager, status: &DefenseStatus) -> Vec<DefenseDrift> {
+    let now = Utc::now();
+    let local = state.get_snapshot().await.player.defense;
+    let server_cooldown = server_cooldown_end(status, now);
+
+    let mut drift: Vec<DefenseDrift> = [
+        ("l1", local.l1, status.defense.l1),
+        ("l2", local.l2, status.defense.l2),
+        ("l3", local.l3, status.defense.l3),
+    ]
+    .into_iter()
+    .filter(|(_, local, server)| local != server)
+    .map(|(field, local, server)| DefenseDrift {
+        field,
+        local: local.to_string(),
+        server: server.to_string(),
+    })
+    .collect();
+
+    if cooldown_drifted(local.cooldown_ends, server_cooldown, now) {
+        let show = |t: Option<DateTime<Utc>>| t.map_or("none".to_string(), |t| t.to_rfc3339());
+        drift.push(DefenseDrift {
+            field: "cooldown_ends",
+            local: show(local.cooldown_ends),
+            server: show(server_cooldown),
+        });
+    }
+
+    if !drift.is_empty() {
+        state.replace_defense(DefenseArray {
+            l1: status.defense.l1,
+            l2: status.defense.l2,
+            l3: status.defense.l3,
+            last_update: now,
+            cooldown_ends: server_cooldown,
+        }).await;
+    }
+
+    drift
+}
+
+/// Parasites gained and lost by adopting the server's list, by host node id
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParasiteChanges {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Replace local parasites with the server's list, returning what changed
+///
+/// Passive income is recomputed from the new list.
+pub async fn reconcile_parasites(state: &StateManager, server: Vec<Parasite>) -> ParasiteChanges {
+    let local = state.get_snapshot().await.parasites;
+    let missing_from = |list: &[Parasite], p: &&Parasite| !list.iter().any(|q| q.node_id == p.node_id);
+
+    let changes = ParasiteChanges {
+        added: server.iter().filter(|p| missing_from(&local, p)).map(|p| p.node_id.clone()).collect(),
+        removed: local.iter().filter(|p| missing_from(&server, p)).map(|p| p.node_id.clone()).collect(),
+    };
+    state.replace_parasites(server).await;
+    changes
+}
+
+/// Reconcile defense against the server every `period`
+pub async fn run_defense_reconciliation(client: Arc<CombatClient>, state: StateManager, period: Duration) {
+    let mut ticker = crate::wake::interval(period);
+
+    info!("🛡️  Defense reconciliation every {}s", period.as_secs());
+
+    loop {
+        ticker.tick().await;
+        if state.is_binding_lost() {
+            debug!("Defense reconciliation paused: device binding lost");
+            continue;
+        }
+
+        let status = match client.get_defense_status().await {
+            Ok(status) => status,
+            Err(e) => {
+                warn!("⚠️  Defense status check failed: {}", e);
+                continue;
+            }
+        };
+
+        let drift = reconcile_defense(&state, &status).await;
+        if drift.is_empty() {
+            debug!("Defense matches server");
+            continue;
+        }
+
+        warn!("🛡️  Local defense diverged from server; adopting server view");
+        for d in &drift {
+            warn!("   {}: local {}, server {}", d.field, d.local, d.server);
+        }
+    }
+}
+
+/// Reconcile parasites against the server every `period`
+pub async fn run_parasite_reconciliation(client: Arc<CombatClient>, state: StateManager, period: Duration) {
+    let mut ticker = crate::wake::interval(period);
+
+    info!("🦠 Parasite reconciliation every {}s", period.as_secs());
+
+    loop {
+        ticker.tick().await;
+        if state.is_binding_lost() {
+            debug!("Parasite reconciliation paused: device binding lost");
+            continue;
+        }
+
+        let parasites = match client.get_parasites().await {
+            Ok(parasites) => parasites,
+            Err(e) => {
+                warn!("⚠️  Parasite list check failed: {}", e);
+                continue;
+            }
+        };
+
+        let changes = reconcile_parasites(&state, parasites).await;
+        if changes == ParasiteChanges::default() {
+            debug!("Parasites match server");
+            continue;
+        }
+
+        for host in &changes.added {
+            info!("🦠 Parasite on {} added from server", host);
+        }
+        for host in &changes.removed {
+            warn!("🦠 Parasite on {} reclaimed by its host", host);
+        }
+        info!("🦠 Passive income now {:.2}/tick", state.get_snapshot().await.player.passive_income);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::client::{CooldownInfo, DefenseConfig};
+
+    fn status(l1: u64, l2: u64, l3: u64, cooldown: Option<u64>) -> DefenseStatus {
+        DefenseStatus {
+            defense: DefenseConfig { l1, l2, l3 },
+            total_combat_points: l1 + l2 + l3,
+            last_configured: None,
+            cooldown: CooldownInfo {
+                active: cooldown.is_some(),
+                ends_at: None,
+                remaining_seconds: cooldown.unwrap_or(0),
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn test_divergent_status_updates_local_defense() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+
+        let drift = reconcile_defense(&state, &status(40, 30, 20, Some(600))).await;
+
+        let fields: Vec<_> = drift.iter().map(|d| d.field).collect();
+        assert_eq!(fields, ["l1", "l2", "l3", "cooldown_ends"]);
+
+        let defense = state.get_snapshot().await.player.defense;
+        assert_eq!((defense.l1, defense.l2, defense.l3), (40, 30, 20));
+        let remaining = (defense.cooldown_ends.unwrap() - Utc::now()).num_seconds();
+        assert!((595..=600).contains(&remaining));
+    }
+
+    fn parasite(node_id: &str, yield_per_tick: f64) -> Parasite {
+        Parasite {
+            node_id: node_id.to_string(),
+            tax_rate: 0.1,
+            yield_per_tick,
+            total_collected: 0,
+            established_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parasites_follow_server_list() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        state.add_parasite(parasite("kept", 1.5)).await;
+        state.add_parasite(parasite("reclaimed", 2.0)).await;
+
+        let changes = reconcile_parasites(&state, vec![parasite("kept", 1.5), parasite("new", 0.5)]).await;
+
+        assert_eq!(changes.added, ["new"]);
+        assert_eq!(changes.removed, ["reclaimed"]);
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.parasites.len(), 2);
+        assert_eq!(snapshot.player.passive_income, 2.0);
+
+        // Agreeing with the server again changes nothing
+        state.checkpoint().await.unwrap();
+        let changes = reconcile_parasites(&state, vec![parasite("kept", 1.5), parasite("new", 0.5)]).await;
+        assert_eq!(changes, ParasiteChanges::default());
+        assert!(!state.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_matching_status_leaves_state_clean() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+
+        assert!(reconcile_defense(&state, &status(0, 0, 0, None)).await.is_empty());
+        assert!(!state.is_dirty());
+    }
+}
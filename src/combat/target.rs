@@ -0,0 +1,145 @@
+// sacas-daemon/src/combat/target.rs
+// Swappable rules for picking which radar contact to attack
+
+use super::client::BattleSimulation;
+use crate::radar::client::RadarTarget;
+use crate::radar::RadarClient;
+
+/// A lockable radar contact, with a battle simulation when one was run
+pub struct Candidate<'a> {
+    pub target: &'a RadarTarget,
+    pub simulation: Option<&'a BattleSimulation>,
+}
+
+/// Chooses an attack target from the current candidates
+pub trait TargetSelector: Send + Sync {
+    fn select<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a RadarTarget>;
+
+    /// Whether candidates need a battle simulation to be ranked
+    fn uses_simulation(&self) -> bool {
+        false
+    }
+}
+
+/// Lowest total defense (the long-standing default)
+pub struct Weakest;
+
+impl TargetSelector for Weakest {
+    fn select<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a RadarTarget> {
+        let targets: Vec<&'a RadarTarget> = candidates.iter().map(|c| c.target).collect();
+        RadarClient::find_weakest_target(&targets)
+    }
+}
+
+/// Most karma, hence the most entropy to loot; fuzzy contacts use the top
+/// of their karma range
+pub struct HighestKarma;
+
+impl TargetSelector for HighestKarma {
+    fn select<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a RadarTarget> {
+        candidates
+            .iter()
+            .filter_map(|c| {
+                let karma = c.target.karma.or(c.target.karma_range.map(|[_, high]| high))?;
+                Some((c.target, karma))
+            })
+            .max_by_key(|(_, karma)| *karma)
+            .map(|(target, _)| target)
+    }
+}
+
+/// Best simulated expected loot per point of defense to break through
+///
+/// Candidates without a simulation or known defense are skipped.
+pub struct BestRoi;
+
+impl BestRoi {
+    fn score(candidate: &Candidate) -> Option<f64> {
+        let loot = candidate.simulation?.expected_loot.value() as f64;
+        let defense = candidate.target.defense.as_ref()?.total;
+        Some(loot / defense.max(1) as f64)
+    }
+}
+
+impl TargetSelector for BestRoi {
+    fn select<'a>(&self, candidates: &[Candidate<'a>]) -> Option<&'a RadarTarget> {
+        candidates
+            .iter()
+            .filter_map(|c| Some((c.target, Self::score(c)?)))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(target, _)| target)
+    }
+
+    fn uses_simulation(&self) -> bool {
+        true
+    }
+}
+
+/// Look up a selector by its `combat.target_strategy` config name
+pub fn selector_by_name(name: &str) -> Option<Box<dyn TargetSelector>> {
+    match name {
+        "weakest" => Some(Box::new(Weakest)),
+        "highest_karma" => Some(Box::new(HighestKarma)),
+        "best_roi" => Some(Box::new(BestRoi)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::combat::client::Probabilities;
+    use crate::radar::client::DefenseInfo;
+
+    fn target(id: &str, karma: Option<u64>, defense: u64) -> RadarTarget {
+        RadarTarget {
+            device_id: id.to_string(),
+            visibility: "LOCKED".to_string(),
+            distance: 100.0,
+            karma,
+            karma_range: None,
+            defense: Some(DefenseInfo { l1: defense, l2: 0, l3: 0, total: defense }),
+        }
+    }
+
+    fn simulation(expected_loot: &str) -> BattleSimulation {
+        BattleSimulation {
+            probabilities: Probabilities { l1_win: 0.5, l2_success: 0.5, l3_parasitize: 0.5 },
+            expected_loot: expected_loot.parse().unwrap(),
+        }
+    }
+
+    /// weak: little defense, little loot; rich: most karma, heavy defense;
+    /// value: best loot per defense point; fuzzy: karma only as a range
+    fn pick(name: &str) -> String {
+        let weak = target("weak", Some(200), 50);
+        let rich = target("rich", Some(9000), 4000);
+        let value = target("value", Some(3000), 400);
+        let fuzzy = RadarTarget { karma: None, karma_range: Some([1000, 5000]), ..target("fuzzy", None, 900) };
+        let (weak_sim, rich_sim, value_sim) = (simulation("20"), simulation("1200"), simulation("600"));
+
+        let candidates = [
+            Candidate { target: &weak, simulation: Some(&weak_sim) },
+            Candidate { target: &rich, simulation: Some(&rich_sim) },
+            Candidate { target: &value, simulation: Some(&value_sim) },
+            Candidate { target: &fuzzy, simulation: None },
+        ];
+
+        selector_by_name(name).unwrap().select(&candidates).unwrap().device_id.clone()
+    }
+
+    #[test]
+    fn test_each_strategy_picks_expected_target() {
+        assert_eq!(pick("weakest"), "weak");
+        assert_eq!(pick("highest_karma"), "rich");
+        assert_eq!(pick("best_roi"), "value");
+        assert!(selector_by_name("random").is_none());
+    }
+
+    #[test]
+    fn test_no_candidates_selects_nothing() {
+        for name in ["weakest", "highest_karma", "best_roi"] {
+            assert!(selector_by_name(name).unwrap().select(&[]).is_none());
+        }
+    }
+}
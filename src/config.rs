@@ -2,8 +2,9 @@ use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use chrono::{DateTime, Utc};
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
@@ -12,7 +13,8 @@ pub struct Config {
     pub display_name: Option<String>,
     
     pub karma: u64,
-    pub server_url: String,
+    /// SACAS server base URL, or a list of mirrors tried in order
+    pub server_url: ServerUrls,
     pub grpc_port: u16,
     
     // New: Moltbook configuration (optional)
@@ -23,6 +25,172 @@ pub struct Config {
     
     pub network: NetworkConfig,
     pub mining: MiningConfig,
+
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    #[serde(default)]
+    pub persistence: PersistenceConfig,
+
+    #[serde(default)]
+    pub grpc: GrpcConfig,
+
+    #[serde(default)]
+    pub sync: SyncConfig,
+
+    #[serde(default)]
+    pub tls: TlsConfig,
+
+    #[serde(default)]
+    pub budget: crate::combat::BudgetConfig,
+
+    #[serde(default)]
+    pub combat: CombatConfig,
+
+    #[serde(default)]
+    pub shutdown: ShutdownConfig,
+
+    #[serde(default)]
+    pub websocket: WebSocketConfig,
+
+    #[serde(default)]
+    pub enforcement: crate::device::EnforcementConfig,
+
+    #[serde(default)]
+    pub notifications: crate::notifier::NotificationConfig,
+
+    #[serde(default)]
+    pub karma_gates: crate::karma_gate::KarmaGateConfig,
+
+    #[serde(default)]
+    pub endpoints: EndpointOverrides,
+
+    #[serde(default)]
+    pub capacity_alerts: crate::capacity_alert::CapacityAlertConfig,
+
+    #[serde(default)]
+    pub entropy_floor: EntropyFloorConfig,
+
+    /// Random offsets for periodic loops, so a fleet doesn't tick in step
+    #[serde(default)]
+    pub jitter: crate::jitter::JitterConfig,
+
+    /// How much ledger and anomaly history is kept in state
+    #[serde(default)]
+    pub history: crate::history::HistoryConfig,
+
+    /// What happens when a background task panics
+    #[serde(default)]
+    pub panic: crate::panic_hook::PanicConfig,
+
+    /// Optional background services; all run by default
+    #[serde(default)]
+    pub subsystems: crate::subsystem::SubsystemConfig,
+
+    /// Prometheus scrape endpoint; off by default
+    #[serde(default)]
+    pub metrics: crate::metrics::MetricsConfig,
+}
+
+/// One or more SACAS server base URLs
+///
+/// Accepts either `server_url = "https://..."` or a list; a single URL is
+/// written back as a plain string so existing configs round-trip unchanged.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerUrls(Vec<String>);
+
+impl ServerUrls {
+    pub fn new(urls: Vec<String>) -> Result<Self> {
+        if urls.is_empty() {
+            anyhow::bail!("server_url must name at least one server");
+        }
+        Ok(Self(urls))
+    }
+
+    /// The first configured server
+    pub fn primary(&self) -> &str {
+        &self.0[0]
+    }
+
+    pub fn urls(&self) -> &[String] {
+        &self.0
+    }
+}
+
+impl From<String> for ServerUrls {
+    fn from(url: String) -> Self {
+        Self(vec![url])
+    }
+}
+
+impl Serialize for ServerUrls {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self.0.as_slice() {
+            [single] => serializer.serialize_str(single),
+            urls => urls.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for ServerUrls {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum OneOrMany {
+            One(String),
+            Many(Vec<String>),
+        }
+
+        let urls = match OneOrMany::deserialize(deserializer)? {
+            OneOrMany::One(url) => vec![url],
+            OneOrMany::Many(urls) => urls,
+        };
+        Self::new(urls).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Server-backed services whose base URL can be overridden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    Combat,
+    Radar,
+    Sync,
+}
+
+/// Per-service base URLs used instead of `server_url`, e.g. to route one
+/// service to a staging host
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EndpointOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "base_url")]
+    pub combat: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "base_url")]
+    pub radar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "base_url")]
+    pub sync: Option<String>,
+}
+
+impl EndpointOverrides {
+    pub fn get(&self, service: Service) -> Option<&str> {
+        match service {
+            Service::Combat => self.combat.as_deref(),
+            Service::Radar => self.radar.as_deref(),
+            Service::Sync => self.sync.as_deref(),
+        }
+    }
+}
+
+/// Reject override URLs that aren't absolute http(s) URLs
+fn base_url<'de, D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Option<String>, D::Error> {
+    let url = String::deserialize(deserializer)?;
+    match reqwest::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => Ok(Some(url)),
+        Ok(parsed) => Err(serde::de::Error::custom(format!(
+            "endpoint override {:?} must be an http(s) URL with a host (got scheme {:?})",
+            url,
+            parsed.scheme()
+        ))),
+        Err(e) => Err(serde::de::Error::custom(format!("invalid endpoint override {:?}: {}", url, e))),
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -48,7 +216,19 @@ pub struct DeviceConfig {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct NetworkConfig {
     pub probe_interval_secs: u64,
+    /// Longest gap between probes while every anchor is unreachable
+    #[serde(default = "default_probe_backoff_max_secs")]
+    pub probe_backoff_max_secs: u64,
+    /// Probe right at startup (default) instead of one `probe_interval_secs` later
+    #[serde(default = "default_first_probe_immediately")]
+    pub first_probe_immediately: bool,
     pub anchors: Vec<Anchor>,
+    /// Opt-in upload of signed latency vectors
+    #[serde(default)]
+    pub telemetry: crate::network::telemetry::TelemetrySamplingConfig,
+    /// With no anchors: run at neutral quality (default) or refuse the config
+    #[serde(default)]
+    pub empty_anchors: crate::network::EmptyAnchors,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -62,12 +242,540 @@ pub struct Anchor {
 pub struct MiningConfig {
     pub tick_interval_secs: u64,
     pub base_multiplier: f64,
+    /// Yield formula name: "sqrt" (default) or "linear"
+    #[serde(default = "default_yield_strategy")]
+    pub yield_strategy: String,
+    /// Seconds entropy may stay over capacity before decay applies
+    #[serde(default = "default_decay_grace_secs")]
+    pub decay_grace_secs: u64,
+    /// How karma converts to entropy capacity (default: karma × 100)
+    #[serde(default)]
+    pub capacity_formula: crate::types::CapacityFormula,
+    /// Ticks mining may miss before the watchdog flags it stalled (0 disables)
+    #[serde(default = "default_watchdog_ticks")]
+    pub watchdog_ticks: u32,
+    /// Abort and respawn a stalled mining loop
+    #[serde(default = "default_restart_stalled")]
+    pub restart_stalled: bool,
+    /// Seconds mining waits for the first network probe before crediting yield (0 disables)
+    ///
+    /// With `network.first_probe_immediately` off, that probe comes a full
+    /// probe interval after startup, so keep this longer than the interval.
+    #[serde(default = "default_warmup_timeout_secs")]
+    pub warmup_timeout_secs: u64,
+}
+
+impl Default for MiningConfig {
+    fn default() -> Self {
+        Self {
+            tick_interval_secs: 5,
+            base_multiplier: 0.5,
+            yield_strategy: default_yield_strategy(),
+            decay_grace_secs: default_decay_grace_secs(),
+            capacity_formula: Default::default(),
+            watchdog_ticks: default_watchdog_ticks(),
+            restart_stalled: default_restart_stalled(),
+            warmup_timeout_secs: default_warmup_timeout_secs(),
+        }
+    }
+}
+
+fn default_warmup_timeout_secs() -> u64 {
+    60
+}
+
+fn default_watchdog_ticks() -> u32 {
+    5
+}
+
+fn default_restart_stalled() -> bool {
+    true
+}
+
+fn default_probe_backoff_max_secs() -> u64 {
+    600
+}
+
+fn default_first_probe_immediately() -> bool {
+    true
+}
+
+fn default_yield_strategy() -> String {
+    "sqrt".to_string()
+}
+
+fn default_decay_grace_secs() -> u64 {
+    60
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HttpConfig {
+    /// Largest response body accepted from any server (bytes)
+    pub max_response_bytes: usize,
+    /// Client-side request budgets per endpoint category
+    #[serde(default)]
+    pub rate_limit: crate::http::RateLimitConfig,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            max_response_bytes: 1024 * 1024, // 1 MiB
+            rate_limit: Default::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PersistenceConfig {
+    /// How often state is checkpointed to disk (0 disables checkpoints)
+    pub checkpoint_interval_secs: u64,
+    /// Extra attempts for a failed checkpoint before it is reported as failing
+    #[serde(default = "default_checkpoint_retries")]
+    pub checkpoint_retries: u32,
+    /// Delay before the first retry, doubled for each one after
+    #[serde(default = "default_checkpoint_retry_backoff_ms")]
+    pub checkpoint_retry_backoff_ms: u64,
+}
+
+fn default_checkpoint_retries() -> u32 {
+    3
+}
+
+fn default_checkpoint_retry_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for PersistenceConfig {
+    fn default() -> Self {
+        Self {
+            checkpoint_interval_secs: 60,
+            checkpoint_retries: default_checkpoint_retries(),
+            checkpoint_retry_backoff_ms: default_checkpoint_retry_backoff_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GrpcConfig {
+    /// HTTP/2 streams allowed per connection
+    pub max_concurrent_streams: u32,
+    /// Requests processed concurrently per connection
+    pub concurrency_limit_per_connection: usize,
+    /// Largest accepted request message (bytes)
+    pub max_decoding_message_size: usize,
+    /// Serve admin RPCs that change state directly, such as SpendEntropy
+    #[serde(default)]
+    pub allow_admin_rpcs: bool,
+    /// State changes buffered per WatchStatus stream; a stream that falls
+    /// further behind skips ahead to the latest status
+    #[serde(default = "default_status_channel_capacity")]
+    pub status_channel_capacity: usize,
+}
+
+fn default_status_channel_capacity() -> usize {
+    crate::status_feed::DEFAULT_STATUS_CHANNEL_CAPACITY
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_streams: 32,
+            concurrency_limit_per_connection: 16,
+            max_decoding_message_size: 64 * 1024, // 64 KiB
+            allow_admin_rpcs: false,
+            status_channel_capacity: default_status_channel_capacity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SyncConfig {
+    /// Seconds between device syncs
+    pub interval_secs: u64,
+    /// Run the first sync shortly after startup instead of a full interval later
+    pub first_sync_immediately: bool,
+    /// Delay before that first sync, giving mining a chance to produce a delta
+    pub warmup_secs: u64,
+    /// Queued offline deltas replayed at once
+    #[serde(default = "default_replay_concurrency")]
+    pub replay_concurrency: usize,
+    /// Minimum gap between starting two replayed deltas
+    #[serde(default = "default_replay_spacing_ms")]
+    pub replay_spacing_ms: u64,
+    /// Deltas smaller than this are held back and added to the next sync (0 = off)
+    #[serde(default)]
+    pub min_sync_delta: u64,
+    /// Same as `min_sync_delta`, as a percentage of capacity; the larger wins
+    #[serde(default)]
+    pub min_sync_delta_percent: f64,
+    /// Longest a small delta is held back before it is synced anyway
+    #[serde(default = "default_max_deferral_secs")]
+    pub max_deferral_secs: u64,
+    /// Server Ed25519 public key (base64) that signs mining parameters;
+    /// without it, parameters in sync responses are ignored
+    #[serde(default)]
+    pub server_params_key: Option<String>,
+    /// Re-check the device binding against live hardware every this many
+    /// syncs (0 disables)
+    #[serde(default = "default_binding_check_every")]
+    pub binding_check_every: u32,
+    /// Stop syncing once the server answers 426 Upgrade Required; mining
+    /// carries on locally until the daemon is updated
+    #[serde(default)]
+    pub observe_on_upgrade_required: bool,
+    /// Cap on the uptime_seconds sent with each sync; uptime is per-process
+    /// and reported as this value once the daemon has run longer
+    #[serde(default = "default_max_uptime_secs")]
+    pub max_uptime_secs: u64,
+    /// Consecutive 401 sync responses before the device key is reported as
+    /// rejected by the server (0 disables)
+    #[serde(default = "default_key_rejected_after")]
+    pub key_rejected_after: u32,
+}
+
+fn default_binding_check_every() -> u32 {
+    12
+}
+
+fn default_key_rejected_after() -> u32 {
+    3
+}
+
+fn default_max_uptime_secs() -> u64 {
+    30 * 24 * 3600
+}
+
+fn default_replay_concurrency() -> usize {
+    4
+}
+
+fn default_replay_spacing_ms() -> u64 {
+    200
+}
+
+fn default_max_deferral_secs() -> u64 {
+    1800
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            interval_secs: 300,
+            first_sync_immediately: true,
+            warmup_secs: 10,
+            replay_concurrency: default_replay_concurrency(),
+            replay_spacing_ms: default_replay_spacing_ms(),
+            min_sync_delta: 0,
+            min_sync_delta_percent: 0.0,
+            max_deferral_secs: default_max_deferral_secs(),
+            server_params_key: None,
+            binding_check_every: default_binding_check_every(),
+            observe_on_upgrade_required: false,
+            max_uptime_secs: default_max_uptime_secs(),
+            key_rejected_after: default_key_rejected_after(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TlsConfig {
+    /// Refuse plaintext connections (disable only for local development)
+    pub require_tls: bool,
+    /// SHA-256 of the server certificate (DER, hex); connections to any
+    /// other certificate are rejected
+    #[serde(default)]
+    pub server_cert_sha256: Option<String>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self {
+            require_tls: true,
+            server_cert_sha256: None,
+        }
+    }
+}
+
+/// Karma given to a new device when nothing else sets it
+pub const DEFAULT_KARMA: u64 = 1000;
+
+/// First-run karma: `SACAS_DEFAULT_KARMA` if set, otherwise `DEFAULT_KARMA`
+///
+/// Karma fixes the starting entropy capacity (100 Ω per karma point), so a
+/// zero or unparseable value is rejected rather than silently replaced.
+fn cold_start_karma(configured: Option<String>) -> Result<u64> {
+    let Some(raw) = configured else {
+        return Ok(DEFAULT_KARMA);
+    };
+
+    match raw.trim().parse::<u64>() {
+        Ok(karma) if karma > 0 => Ok(karma),
+        _ => anyhow::bail!("SACAS_DEFAULT_KARMA must be a positive integer, got {:?}", raw),
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CombatConfig {
+    /// How often local defense is checked against the server (0 disables)
+    pub defense_reconcile_secs: u64,
+    /// Attack target rule: "weakest" (default), "highest_karma" or "best_roi"
+    #[serde(default = "default_target_strategy")]
+    pub target_strategy: String,
+    /// Seconds a pre-attack defense status check is reused for
+    #[serde(default = "default_cooldown_check_secs")]
+    pub cooldown_check_secs: u64,
+    /// How often local parasites are replaced by the server's list (0 disables)
+    #[serde(default = "default_parasite_reconcile_secs")]
+    pub parasite_reconcile_secs: u64,
+    /// How often to scan and attack the target `target_strategy` picks (0 disables)
+    #[serde(default)]
+    pub auto_attack_secs: u64,
+    /// Distance bands each auto-attack round scans, nearest first (empty
+    /// runs one unbounded scan)
+    #[serde(default)]
+    pub auto_attack_bands: Vec<u64>,
+    /// Configure a starter defense on an undefended device once it can afford one
+    #[serde(default)]
+    pub auto_defense: bool,
+    /// Defense points the starter defense spreads 1:1:2 over L1/L2/L3
+    #[serde(default = "default_auto_defense_points")]
+    pub auto_defense_points: u64,
+    /// Entropy above the floor needed before the starter defense is set
+    #[serde(default = "default_auto_defense_min_entropy")]
+    pub auto_defense_min_entropy: u64,
+}
+
+fn default_auto_defense_points() -> u64 {
+    200
+}
+
+fn default_auto_defense_min_entropy() -> u64 {
+    1000
+}
+
+fn default_parasite_reconcile_secs() -> u64 {
+    600
+}
+
+fn default_cooldown_check_secs() -> u64 {
+    10
+}
+
+fn default_target_strategy() -> String {
+    "weakest".to_string()
+}
+
+impl Default for CombatConfig {
+    fn default() -> Self {
+        Self {
+            defense_reconcile_secs: 300,
+            target_strategy: default_target_strategy(),
+            cooldown_check_secs: default_cooldown_check_secs(),
+            parasite_reconcile_secs: default_parasite_reconcile_secs(),
+            auto_attack_secs: 0,
+            auto_attack_bands: Vec::new(),
+            auto_defense: false,
+            auto_defense_points: default_auto_defense_points(),
+            auto_defense_min_entropy: default_auto_defense_min_entropy(),
+        }
+    }
+}
+
+/// Entropy that automatic spending (radar, attacks, defense) never touches
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EntropyFloorConfig {
+    /// Minimum balance kept in reserve (0 disables the floor)
+    pub amount: u64,
+    /// Warn once entropy is within this percentage above the floor
+    pub warn_margin_percent: f64,
+}
+
+impl Default for EntropyFloorConfig {
+    fn default() -> Self {
+        Self {
+            amount: 0,
+            warn_margin_percent: 10.0,
+        }
+    }
+}
+
+impl EntropyFloorConfig {
+    /// Whether `entropy` is at the floor or within the warning margin above it
+    pub fn is_near(&self, entropy: u64) -> bool {
+        self.amount > 0 && (entropy as f64) <= self.amount as f64 * (1.0 + self.warn_margin_percent / 100.0)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ShutdownConfig {
+    /// How long final work may run after a stop signal before the process
+    /// exits anyway
+    pub grace_secs: u64,
+}
+
+impl Default for ShutdownConfig {
+    fn default() -> Self {
+        Self { grace_secs: 10 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebSocketConfig {
+    /// Recent battle ids remembered so replayed events are applied once
+    pub battle_dedup_window: usize,
+    /// Path the server mounts the WebSocket at, below `server_url`
+    #[serde(default = "default_ws_path")]
+    pub path: String,
+    /// Seconds to wait for AUTH_SUCCESS before giving up and reconnecting
+    #[serde(default = "default_ws_auth_timeout_secs")]
+    pub auth_timeout_secs: u64,
+}
+
+fn default_ws_path() -> String {
+    "/ws".to_string()
+}
+
+fn default_ws_auth_timeout_secs() -> u64 {
+    15
+}
+
+impl Default for WebSocketConfig {
+    fn default() -> Self {
+        Self {
+            battle_dedup_window: 1024,
+            path: default_ws_path(),
+            auth_timeout_secs: default_ws_auth_timeout_secs(),
+        }
+    }
+}
+
+/// Device ID in a state file left by an earlier registration
+fn previous_device_id(state_path: &Path) -> Option<String> {
+    let raw = fs::read_to_string(state_path).ok()?;
+    let state: crate::types::GameState = serde_json::from_str(&raw).ok()?;
+    Some(state.player.id).filter(|id| !id.is_empty())
+}
+
+/// Data dir fixed at startup, ahead of `SACAS_HOME`
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+fn resolve_data_dir(sacas_home: Option<std::ffi::OsString>, home: Option<PathBuf>) -> Result<PathBuf> {
+    match (sacas_home, home) {
+        (Some(dir), _) if !dir.is_empty() => Ok(PathBuf::from(dir)),
+        (_, Some(home)) => Ok(home.join(".sacas")),
+        _ => anyhow::bail!(
+            "Cannot determine the SACAS data directory: no home directory is available. \
+             Set SACAS_HOME to a private directory."
+        ),
+    }
+}
+
+/// A local server and the port it binds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    pub name: &'static str,
+    pub port: u16,
+}
+
+/// Ports claimed by more than one listener, with every claimant
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("Port collision between enabled listeners: {}", describe_collisions(.0))]
+pub struct PortCollisions(pub Vec<(u16, Vec<&'static str>)>);
+
+fn describe_collisions(collisions: &[(u16, Vec<&'static str>)]) -> String {
+    collisions
+        .iter()
+        .map(|(port, names)| format!("port {} used by {}", port, names.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Fail with every shared port at once, before anything tries to bind
+///
+/// Port 0 asks the OS for a free port, so it never collides.
+pub fn check_port_collisions(listeners: &[Listener]) -> std::result::Result<(), PortCollisions> {
+    let mut by_port: std::collections::BTreeMap<u16, Vec<&'static str>> = Default::default();
+    for listener in listeners.iter().filter(|l| l.port != 0) {
+        by_port.entry(listener.port).or_default().push(listener.name);
+    }
+
+    let collisions: Vec<_> = by_port.into_iter().filter(|(_, names)| names.len() > 1).collect();
+    if collisions.is_empty() {
+        Ok(())
+    } else {
+        Err(PortCollisions(collisions))
+    }
+}
+
+/// One bound device field that no longer matches the hardware
+#[derive(Debug, Clone, PartialEq)]
+pub struct BindingMismatch {
+    pub field: &'static str,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Outcome of checking the stored device binding against live hardware
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceBinding {
+    Verified,
+    /// Every core field differs: this is a different machine
+    Replaced(Vec<BindingMismatch>),
+    /// Only some core fields differ: the config was edited or copied
+    Tampered(Vec<BindingMismatch>),
+    /// Core fields match but a volatile one (model, board id) changed
+    Drifted(Vec<BindingMismatch>),
+}
+
+impl DeviceBinding {
+    /// Whether this outcome must stop the daemon
+    ///
+    /// A core mismatch always does; volatile-field drift only under
+    /// `strict_binding`.
+    pub fn is_fatal(&self, enforcement: &crate::device::EnforcementConfig) -> bool {
+        match self {
+            DeviceBinding::Verified => false,
+            DeviceBinding::Drifted(_) => enforcement.strict_binding,
+            DeviceBinding::Replaced(_) | DeviceBinding::Tampered(_) => true,
+        }
+    }
 }
 
 impl Config {
+    /// Every enabled server that binds a port: gRPC and metrics, each when on
+    ///
+    /// New servers that bind a port register here.
+    pub fn listeners(&self) -> Vec<Listener> {
+        let mut listeners = Vec::new();
+        if self.subsystems.grpc {
+            listeners.push(Listener { name: "grpc", port: self.grpc_port });
+        }
+        if self.metrics.enabled {
+            listeners.push(Listener { name: "metrics", port: self.metrics.port });
+        }
+        listeners
+    }
+
+    /// Enforcement policy from the saved config, for checks that run before
+    /// it is loaded; strict when there is no readable config yet
+    pub fn enforcement_policy() -> crate::device::EnforcementConfig {
+        Self::load().map(|c| c.enforcement).unwrap_or_default()
+    }
+
+    /// Servers `service` talks to: its override if set, otherwise `server_url`
+    pub fn service_urls(&self, service: Service) -> Vec<String> {
+        match self.endpoints.get(service) {
+            Some(url) => vec![url.to_string()],
+            None => self.server_url.urls().to_vec(),
+        }
+    }
+
     pub fn load() -> Result<Self> {
-        let config_path = Self::config_path();
-        
+        Self::load_from(&Self::config_path()?)
+    }
+
+    pub fn load_from(config_path: &Path) -> Result<Self> {
         if !config_path.exists() {
             anyhow::bail!(
                 "Configuration file not found at: {:?}\n\n\
@@ -76,22 +784,108 @@ impl Config {
             );
         }
 
-        let config_str = fs::read_to_string(&config_path)
+        let config_str = fs::read_to_string(config_path)
             .context("Failed to read config file")?;
         
         let config: Config = toml::from_str(&config_str)
             .context("Failed to parse config file")?;
 
+        check_port_collisions(&config.listeners())?;
+        if config.network.anchors.is_empty() && config.network.empty_anchors == crate::network::EmptyAnchors::Refuse {
+            anyhow::bail!("No network anchors configured and network.empty_anchors is Refuse; add at least one anchor");
+        }
         Ok(config)
     }
 
-    pub fn config_path() -> PathBuf {
-        dirs::home_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(".sacas")
-            .join("config.toml")
+    /// Root directory for all daemon data: the dir set with
+    /// `override_data_dir`, `$SACAS_HOME`, or `~/.sacas`
+    ///
+    /// Fails rather than falling back to the working directory, which could
+    /// leave the private key somewhere world-readable.
+    pub fn data_dir() -> Result<PathBuf> {
+        if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+            return Ok(dir.clone());
+        }
+        resolve_data_dir(std::env::var_os("SACAS_HOME"), dirs::home_dir())
+    }
+
+    /// Use `dir` as the data dir for the rest of the process
+    ///
+    /// Must run before any data path is read. Fails if a dir was already set.
+    #[cfg(feature = "fleet-sim")]
+    pub fn override_data_dir(dir: PathBuf) -> Result<()> {
+        DATA_DIR_OVERRIDE
+            .set(dir)
+            .map_err(|dir| anyhow::anyhow!("Data dir already set; cannot switch to {}", dir.display()))
+    }
+
+    pub fn config_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("config.toml"))
+    }
+
+    /// Ed25519 device key
+    pub fn identity_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("device.key"))
+    }
+
+    /// Persisted game state snapshot
+    pub fn state_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("state.json"))
+    }
+
+    /// Append-only journal of settled entropy spends
+    pub fn ledger_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("ledger.jsonl"))
+    }
+
+    /// Last mining parameters accepted from the server
+    pub fn mining_params_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("mining_params.json"))
     }
     
+    /// Compare every bound `DeviceConfig` field against the current hardware
+    ///
+    /// The model identifier is reported but not used to tell replacement from
+    /// tampering, since a replacement machine is often the same model.
+    pub fn verify_device_binding(&self, hw_info: &crate::device::MacHardwareInfo) -> DeviceBinding {
+        const CORE_FIELDS: [&str; 2] = ["hardware_uuid", "serial_number"];
+
+        let bound = &self.device;
+        let fields = [
+            ("hardware_uuid", &bound.hardware_uuid, hw_info.hardware_uuid.clone()),
+            ("serial_number", &bound.serial_number, hw_info.serial_number.clone()),
+            ("device_fingerprint", &bound.device_fingerprint, hw_info.generate_fingerprint()),
+            ("model_identifier", &bound.model_identifier, hw_info.model_identifier.clone()),
+        ];
+
+        let mismatches: Vec<BindingMismatch> = fields
+            .into_iter()
+            .filter(|(_, expected, actual)| *expected != actual)
+            .map(|(field, expected, actual)| BindingMismatch {
+                field,
+                expected: expected.clone(),
+                actual,
+            })
+            .collect();
+
+        if mismatches.is_empty() {
+            return DeviceBinding::Verified;
+        }
+
+        // The binding decision rests on the core fields alone
+        let core_bound = crate::device::fingerprint::core_fingerprint(&bound.hardware_uuid, &bound.serial_number);
+        if core_bound == hw_info.core_fingerprint() {
+            return DeviceBinding::Drifted(mismatches);
+        }
+
+        let core_mismatches = mismatches.iter().filter(|m| CORE_FIELDS.contains(&m.field)).count();
+        if core_mismatches == CORE_FIELDS.len() {
+            DeviceBinding::Replaced(mismatches)
+        } else {
+            DeviceBinding::Tampered(mismatches)
+        }
+    }
+
     pub fn save(&self, path: &Path) -> Result<()> {
         let config_str = toml::to_string_pretty(self)
             .context("Failed to serialize config")?;
@@ -108,31 +902,48 @@ impl Config {
         identity: crate::device::DeviceIdentity,
     ) -> Result<Self> {
         use crate::moltbook::MoltbookClient;
-        use crate::device::register_device;
+        use crate::device::{check_registration, register_device};
         
         info!("Creating new configuration for autonomous device...");
         
         // 1. Register device with SACAS backend
-        let server_url = std::env::var("SACAS_API_URL")
-            .unwrap_or_else(|_| "https://sacas.ai".to_string());
+        // SACAS_API_URL may list comma-separated mirrors
+        let server_url = match std::env::var("SACAS_API_URL") {
+            Ok(urls) => ServerUrls::new(
+                urls.split(',').map(str::trim).filter(|u| !u.is_empty()).map(String::from).collect(),
+            ).context("SACAS_API_URL is empty")?,
+            Err(_) => ServerUrls::from("https://sacas.ai".to_string()),
+        };
         
-        let registration = register_device(&hw_info, &identity, &server_url).await?;
+        let http = crate::http::HttpClient::default();
+        let servers = crate::http::ServerPool::new(server_url.urls());
+        let registration = register_device(&http, &hw_info, &identity, &servers).await?;
+
+        // A state file left behind means this machine registered before
+        let state_path = Self::state_path()?;
+        if let Err(e) = check_registration(previous_device_id(&state_path).as_deref(), &registration.device_id) {
+            error!("🚨 {}", e);
+            error!("   Progress recorded for {} is kept in {:?}", e.stored, state_path);
+            error!("   Restore the matching config and device key, or move that file aside to start over as {}", e.returned);
+            return Err(e.into());
+        }
         
         info!("✅ Device registered successfully");
         info!("   Device ID: {}", registration.device_id);
         info!("   Display Name: {}", registration.display_name);
         
         // Moltbook integration is available but abstracted from user interaction
-        // Default karma is 1000 for all new installations
+        let cold_start = cold_start_karma(std::env::var("SACAS_DEFAULT_KARMA").ok())?;
         let bot_token = String::new(); // No user prompt
         
         if bot_token.is_empty() {
-            info!("Using default Karma: 1000");
+            info!("Using default Karma: {}", cold_start);
         }
         
         // 3. Fetch initial Karma from Moltbook (if provided)
         let karma = if !bot_token.is_empty() {
             let moltbook_client = MoltbookClient::new(
+                http.clone(),
                 "https://api.moltbook.com".to_string(),
                 bot_token.clone(),
                 "sacas-agent".to_string(),
@@ -145,16 +956,54 @@ impl Config {
                 }
                 Err(e) => {
                     warn!("⚠️  Failed to fetch Karma from Moltbook: {}", e);
-                    warn!("Using default Karma: 1000");
-                    1000
+                    warn!("Using default Karma: {}", cold_start);
+                    cold_start
                 }
             }
         } else {
-            1000
+            cold_start
         };
         
         // 4. Create configuration
-        let config = Config {
+        let mut config = Self::first_run(&hw_info, &registration, server_url, karma);
+        config.moltbook = if !bot_token.is_empty() {
+            let agent_name = bot_token.split('@').next().unwrap_or("sacas-agent").to_string();
+
+            Some(MoltbookConfig {
+                api_url: "https://www.moltbook.com".to_string(),
+                api_key: bot_token.clone(),
+                agent_name,
+                last_karma_sync: Utc::now(),
+                sync_interval_hours: 1,
+            })
+        } else {
+            None
+        };
+        
+        // 5. Save configuration
+        let config_path = Self::config_path()?;
+        let config_dir = config_path.parent().unwrap();
+        fs::create_dir_all(config_dir)?;
+        
+        config.save(&config_path)?;
+        
+        info!("✅ Configuration saved to: {:?}", config_path);
+        info!("  Device ID: {}", registration.device_id);
+        info!("  Display Name: {}", registration.display_name);
+        info!("  Karma: {}", karma);
+        info!("  Device: {}", hw_info.model_identifier);
+        
+        Ok(config)
+    }
+
+    /// Configuration for a freshly registered device, before Moltbook is attached
+    fn first_run(
+        hw_info: &crate::device::MacHardwareInfo,
+        registration: &crate::device::DeviceRegistration,
+        server_url: ServerUrls,
+        karma: u64,
+    ) -> Self {
+        Config {
             // Device-centric fields
             device_id: Some(registration.device_id.clone()),
             display_name: Some(registration.display_name.clone()),
@@ -163,20 +1012,8 @@ impl Config {
             server_url,
             grpc_port: 50051,
             
-            // Moltbook is optional
-            moltbook: if !bot_token.is_empty() {
-               let agent_name = bot_token.split('@').next().unwrap_or("sacas-agent").to_string();
-            
-                Some(MoltbookConfig {
-                    api_url: "https://www.moltbook.com".to_string(),
-                    api_key: bot_token.clone(),
-                    agent_name,
-                    last_karma_sync: Utc::now(),
-                    sync_interval_hours: 1,
-                })
-            } else {
-                None
-            },
+            // Moltbook is attached by the caller when configured
+            moltbook: None,
             
             device: DeviceConfig {
                 hardware_uuid: hw_info.hardware_uuid.clone(),
@@ -189,29 +1026,36 @@ impl Config {
             
             network: NetworkConfig {
                 probe_interval_secs: 60,
+                probe_backoff_max_secs: default_probe_backoff_max_secs(),
+                first_probe_immediately: default_first_probe_immediately(),
                 anchors: Self::default_anchors(),
+                telemetry: Default::default(),
+                empty_anchors: Default::default(),
             },
             
-            mining: MiningConfig {
-                tick_interval_secs: 5,
-                base_multiplier: 0.5,
-            },
-        };
-        
-        // 5. Save configuration
-        let config_path = Self::config_path();
-        let config_dir = config_path.parent().unwrap();
-        fs::create_dir_all(config_dir)?;
-        
-        config.save(&config_path)?;
-        
-        info!("✅ Configuration saved to: {:?}", Self::config_path());
-        info!("  Device ID: {}", registration.device_id);
-        info!("  Display Name: {}", registration.display_name);
-        info!("  Karma: {}", karma);
-        info!("  Device: {}", hw_info.model_identifier);
-        
-        Ok(config)
+            mining: MiningConfig::default(),
+
+            http: HttpConfig::default(),
+            persistence: PersistenceConfig::default(),
+            grpc: GrpcConfig::default(),
+            sync: SyncConfig::default(),
+            tls: TlsConfig::default(),
+            budget: Default::default(),
+            combat: CombatConfig::default(),
+            shutdown: ShutdownConfig::default(),
+            websocket: WebSocketConfig::default(),
+            enforcement: Default::default(),
+            notifications: Default::default(),
+            karma_gates: Default::default(),
+            endpoints: Default::default(),
+            capacity_alerts: Default::default(),
+            entropy_floor: Default::default(),
+            jitter: Default::default(),
+            history: Default::default(),
+            panic: Default::default(),
+            subsystems: Default::default(),
+            metrics: Default::default(),
+        }
     }
 
     fn default_anchors() -> Vec<Anchor> {
@@ -244,3 +1088,261 @@ impl Config {
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::MacHardwareInfo;
+    use crate::test_support::sample_config;
+
+    fn hardware() -> MacHardwareInfo {
+        MacHardwareInfo {
+            hardware_uuid: "A1B2C3D4-0000-1111-2222-333344445555".to_string(),
+            serial_number: "C02XK1ZZJGH5".to_string(),
+            model_identifier: "Mac14,3".to_string(),
+            board_id: "Mac-7BA5B2D9E42DDD94".to_string(),
+            rom_version: "8419.80.7".to_string(),
+            cpu_brand: "Apple M2".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_previous_device_id_from_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        assert_eq!(previous_device_id(&path), None);
+
+        let store = crate::store::FileStore::new(path.clone(), dir.path().join("ledger.jsonl"));
+        crate::state::StateManager::new("dev-123".to_string(), 100, std::sync::Arc::new(store))
+            .save_snapshot()
+            .await
+            .unwrap();
+        assert_eq!(previous_device_id(&path).as_deref(), Some("dev-123"));
+    }
+
+    #[test]
+    fn test_empty_anchors_refused_only_when_configured() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut config = sample_config();
+        config.save(&path).unwrap();
+        assert!(Config::load_from(&path).is_ok());
+
+        config.network.empty_anchors = crate::network::EmptyAnchors::Refuse;
+        config.save(&path).unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn test_port_collisions_reported_together() {
+        let listener = |name, port| Listener { name, port };
+        let mut config = sample_config();
+        assert!(check_port_collisions(&config.listeners()).is_ok());
+
+        // Metrics only claims its port while enabled
+        config.metrics.port = config.grpc_port;
+        assert!(check_port_collisions(&config.listeners()).is_ok());
+        config.metrics.enabled = true;
+        assert_eq!(
+            check_port_collisions(&config.listeners()),
+            Err(PortCollisions(vec![(config.grpc_port, vec!["grpc", "metrics"])]))
+        );
+
+        // A disabled gRPC server binds nothing
+        config.subsystems.grpc = false;
+        assert!(check_port_collisions(&config.listeners()).is_ok());
+
+        assert!(check_port_collisions(&[listener("grpc", 0), listener("metrics", 0)]).is_ok());
+
+        let err = check_port_collisions(&[
+            listener("grpc", 50051),
+            listener("metrics", 9090),
+            listener("health", 50051),
+            listener("admin", 9090),
+            listener("debug", 8080),
+        ])
+        .unwrap_err();
+        assert_eq!(err, PortCollisions(vec![(9090, vec!["metrics", "admin"]), (50051, vec!["grpc", "health"])]));
+        assert_eq!(
+            err.to_string(),
+            "Port collision between enabled listeners: port 9090 used by metrics, admin; port 50051 used by grpc, health"
+        );
+    }
+
+    fn bound_config(hw: &MacHardwareInfo) -> Config {
+        let mut config = sample_config();
+        config.device = DeviceConfig {
+            hardware_uuid: hw.hardware_uuid.clone(),
+            serial_number: hw.serial_number.clone(),
+            model_identifier: hw.model_identifier.clone(),
+            device_fingerprint: hw.generate_fingerprint(),
+            is_verified: true,
+            first_seen: Utc::now(),
+        };
+        config
+    }
+
+    #[test]
+    fn test_device_binding_full_match() {
+        let hw = hardware();
+        assert_eq!(bound_config(&hw).verify_device_binding(&hw), DeviceBinding::Verified);
+    }
+
+    #[test]
+    fn test_device_binding_single_field_tamper() {
+        let hw = hardware();
+        let mut config = bound_config(&hw);
+        config.device.serial_number = "FAKESERIAL01".to_string();
+
+        match config.verify_device_binding(&hw) {
+            DeviceBinding::Tampered(mismatches) => {
+                assert_eq!(mismatches.len(), 1);
+                assert_eq!(mismatches[0].field, "serial_number");
+                assert_eq!(mismatches[0].expected, "FAKESERIAL01");
+                assert_eq!(mismatches[0].actual, hw.serial_number);
+            }
+            other => panic!("expected tamper, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_device_binding_full_replacement() {
+        let hw = hardware();
+        let config = bound_config(&hw);
+        let replacement = MacHardwareInfo {
+            hardware_uuid: "FFFFFFFF-0000-1111-2222-333344445555".to_string(),
+            serial_number: "H4TXQ0ZZQ6NY".to_string(),
+            board_id: "Mac-0000000000000000".to_string(),
+            ..hw
+        };
+
+        match config.verify_device_binding(&replacement) {
+            DeviceBinding::Replaced(mismatches) => assert_eq!(mismatches.len(), 3),
+            other => panic!("expected replacement, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_device_binding_volatile_change_only_warns() {
+        let hw = hardware();
+        let config = bound_config(&hw);
+        let updated = MacHardwareInfo { board_id: "Mac-0000000000000000".to_string(), ..hw.clone() };
+
+        let binding = config.verify_device_binding(&updated);
+        match &binding {
+            DeviceBinding::Drifted(mismatches) => {
+                let fields: Vec<_> = mismatches.iter().map(|m| m.field).collect();
+                assert_eq!(fields, ["device_fingerprint"]);
+            }
+            other => panic!("expected drift, got {:?}", other),
+        }
+        assert!(!binding.is_fatal(&config.enforcement));
+
+        let strict = crate::device::EnforcementConfig { strict_binding: true, ..Default::default() };
+        assert!(binding.is_fatal(&strict));
+    }
+
+    #[test]
+    fn test_device_binding_core_mismatch_exits() {
+        let hw = hardware();
+        let config = bound_config(&hw);
+        let other_mac = MacHardwareInfo { serial_number: "H4TXQ0ZZQ6NY".to_string(), ..hw.clone() };
+
+        let binding = config.verify_device_binding(&other_mac);
+        assert!(matches!(binding, DeviceBinding::Tampered(_)));
+        assert!(binding.is_fatal(&config.enforcement));
+    }
+
+    #[test]
+    fn test_sacas_home_redirects_paths() {
+        let home = Some(PathBuf::from("/Users/mini"));
+
+        let dir = resolve_data_dir(Some("/srv/sacas".into()), home.clone()).unwrap();
+        assert_eq!(dir, PathBuf::from("/srv/sacas"));
+
+        // An empty SACAS_HOME counts as unset
+        assert_eq!(resolve_data_dir(Some("".into()), home).unwrap(), PathBuf::from("/Users/mini/.sacas"));
+    }
+
+    #[test]
+    fn test_missing_home_is_an_error() {
+        let err = resolve_data_dir(None, None).unwrap_err();
+        assert!(err.to_string().contains("Set SACAS_HOME"));
+
+        let err = resolve_data_dir(Some("".into()), None).unwrap_err();
+        assert!(err.to_string().contains("no home directory"));
+
+        let home = resolve_data_dir(None, Some(PathBuf::from("/Users/mini"))).unwrap();
+        assert_eq!(home, PathBuf::from("/Users/mini/.sacas"));
+    }
+
+    #[test]
+    fn test_cold_start_karma() {
+        assert_eq!(cold_start_karma(None).unwrap(), DEFAULT_KARMA);
+        assert_eq!(cold_start_karma(Some("250".to_string())).unwrap(), 250);
+        assert!(cold_start_karma(Some("0".to_string())).is_err());
+        assert!(cold_start_karma(Some("-5".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_first_run_uses_configured_karma() {
+        let hw = hardware();
+        let registration = crate::device::DeviceRegistration {
+            device_id: "dev-123".to_string(),
+            display_name: "device_dev-123".to_string(),
+        };
+        let karma = cold_start_karma(Some("250".to_string())).unwrap();
+
+        let config = Config::first_run(&hw, &registration, "https://sacas.ai".to_string().into(), karma);
+        let player = crate::types::Player::new(registration.device_id, config.karma);
+
+        assert_eq!(config.karma, 250);
+        assert_eq!(player.capacity, 25_000);
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct ServerOnly {
+        server_url: ServerUrls,
+    }
+
+    #[test]
+    fn test_server_url_accepts_string_or_list() {
+        let single: ServerOnly = toml::from_str(r#"server_url = "https://sacas.ai""#).unwrap();
+        assert_eq!(single.server_url.urls(), ["https://sacas.ai"]);
+        assert_eq!(toml::to_string(&single).unwrap().trim(), r#"server_url = "https://sacas.ai""#);
+
+        let list: ServerOnly =
+            toml::from_str(r#"server_url = ["https://sacas.ai", "https://eu.sacas.ai"]"#).unwrap();
+        assert_eq!(list.server_url.primary(), "https://sacas.ai");
+        assert_eq!(list.server_url.urls().len(), 2);
+
+        assert!(toml::from_str::<ServerOnly>("server_url = []").is_err());
+    }
+
+    #[test]
+    fn test_endpoint_override_only_for_its_service() {
+        let mut config = sample_config();
+        config.server_url = ServerUrls::new(vec!["https://sacas.ai".to_string(), "https://eu.sacas.ai".to_string()]).unwrap();
+        config.endpoints = toml::from_str(r#"combat = "https://combat.staging.sacas.ai""#).unwrap();
+
+        assert_eq!(config.service_urls(Service::Combat), ["https://combat.staging.sacas.ai"]);
+        assert_eq!(config.service_urls(Service::Radar), ["https://sacas.ai", "https://eu.sacas.ai"]);
+        assert_eq!(config.service_urls(Service::Sync), config.server_url.urls());
+    }
+
+    #[test]
+    fn test_endpoint_override_must_be_http_url() {
+        assert!(toml::from_str::<EndpointOverrides>(r#"radar = "not a url""#).is_err());
+        assert!(toml::from_str::<EndpointOverrides>(r#"sync = "ftp://sacas.ai""#).is_err());
+        assert!(toml::from_str::<EndpointOverrides>(r#"sync = "http://localhost:8080""#).is_ok());
+    }
+
+    #[test]
+    fn test_entropy_floor_warning_margin() {
+        let floor = EntropyFloorConfig { amount: 1000, warn_margin_percent: 10.0 };
+        assert!(floor.is_near(900));
+        assert!(floor.is_near(1100));
+        assert!(!floor.is_near(1101));
+        assert!(!EntropyFloorConfig::default().is_near(0));
+    }
+}
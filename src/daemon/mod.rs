@@ -1,44 +1,137 @@
-use crate::config::Config;
+mod shutdown;
+
+pub use shutdown::{shutdown_signal, ShutdownTasks};
+
+use crate::capacity_alert::{CapacityAlertConfig, CapacityAlerter, CapacityLevel};
+use crate::combat::{selector_by_name, CombatClient};
+use crate::config::{Config, EntropyFloorConfig, Service};
+use crate::device::DeviceIdentity;
+use crate::http::{HttpClient, ServerPool};
+use crate::karma_gate::KarmaGate;
+use crate::radar::RadarClient;
 use crate::state::StateManager;
-use crate::network::NetworkProbe;
-use crate::mining::MiningEngine;
+use crate::store::FileStore;
+use crate::jitter::JitterConfig;
+use crate::network::telemetry::LatencySampler;
+use crate::network::{AnchorRegistry, NetworkProbe, ProbeSchedule};
+use crate::mining::params::ServerParamsTrust;
+use crate::mining::{strategy_by_name, MiningEngine, MiningWatchdog};
 use crate::grpc::start_grpc_server;
-use anyhow::Result;
+use crate::metrics::serve_metrics;
+use crate::notifier::{notifier_for, Notifier};
+use crate::panic_hook::respawn_on_panic;
+use crate::subsystem::{Subsystem, SubsystemSpawner};
+use crate::sync::OfflineQueue;
+use anyhow::{Context, Result};
 use std::sync::Arc;
-use tracing::info;
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+/// How a failed checkpoint is retried within one checkpoint tick
+#[derive(Debug, Clone, Copy)]
+struct CheckpointRetry {
+    retries: u32,
+    backoff: Duration,
+}
 
 pub struct OmniDaemon {
     config: Config,
     state_manager: Arc<StateManager>,
+    anchors: AnchorRegistry,
+    sync_backlog: OfflineQueue,
     network_probe: NetworkProbe,
     mining_engine: MiningEngine,
+    /// Serves on-demand scans over gRPC once the device is registered
+    radar: Option<Arc<RadarClient>>,
+    /// Runs attacks for ExecuteAttack and the combat loops once the device is registered
+    combat: Option<Arc<CombatClient>>,
 }
 
 impl OmniDaemon {
     pub async fn new(config: Config) -> Result<Self> {
         // Initialize state manager  
-        let state_manager = Arc::new(StateManager::new(
-            config.device_id.clone().unwrap_or_default(),
-            config.karma,
-        ));
+        let store = Arc::new(FileStore::new(Config::state_path()?, Config::ledger_path()?));
+        let state_manager = Arc::new(
+            StateManager::new(config.device_id.clone().unwrap_or_default(), config.karma, store)
+                .with_decay_grace(config.mining.decay_grace_secs)
+                .with_capacity_formula(config.mining.capacity_formula)
+                .with_entropy_floor(config.entropy_floor.amount)
+                .with_history_retention(config.history)
+                .with_status_channel_capacity(config.grpc.status_channel_capacity),
+        );
+
+        // Pick up where the last run left off
+        match state_manager.restore().await {
+            Ok(true) => info!("💾 Restored state from {:?}", Config::state_path()?),
+            Ok(false) => {}
+            Err(e) => warn!("⚠️  Could not restore saved state, starting fresh: {:#}", e),
+        }
+
+        // Verified server mining parameters from an earlier run win over local config
+        if let Some(trust) = ServerParamsTrust::from_config(&config.sync)? {
+            if let Some(params) = trust.load() {
+                info!("⛏️  Using server mining parameters (tick {}s, multiplier {})", params.tick_interval_secs, params.base_multiplier);
+                state_manager.set_server_mining_params(params);
+            }
+        }
 
-        // Initialize network probe
-        let network_probe = NetworkProbe::new(config.network.anchors.clone())?;
+        // Initialize network probe over the live (gRPC-managed) anchor set
+        let anchors = AnchorRegistry::new(config.network.anchors.clone())
+            .with_empty_policy(config.network.empty_anchors)
+            .persisted_to(Config::config_path()?);
+        let network_probe = NetworkProbe::new(anchors.clone())?;
 
         // Initialize mining engine (use SAME state_manager instance!)
-        let mining_state = StateManager {
-            state: state_manager.get_handle(),
-        };
+        let mining_state = (*state_manager).clone();
+        let yield_strategy = strategy_by_name(&config.mining.yield_strategy)
+            .ok_or_else(|| anyhow::anyhow!(
+                "Unknown mining.yield_strategy '{}' (expected \"sqrt\" or \"linear\")",
+                config.mining.yield_strategy
+            ))?;
         let mining_engine = MiningEngine::new(
             mining_state,
             config.mining.tick_interval_secs,
-        );
+            yield_strategy,
+            config.mining.base_multiplier,
+        )
+        .with_warmup(Duration::from_secs(config.mining.warmup_timeout_secs))
+        .with_jitter(config.jitter);
+
+        // Fail at startup rather than on the first attack
+        if selector_by_name(&config.combat.target_strategy).is_none() {
+            anyhow::bail!(
+                "Unknown combat.target_strategy '{}' (expected \"weakest\", \"highest_karma\" or \"best_roi\")",
+                config.combat.target_strategy
+            );
+        }
+
+        let clients = match &config.device_id {
+            Some(device_id) => {
+                let identity = DeviceIdentity::load_or_generate(&Config::identity_path()?)?;
+                let radar = RadarClient::new(
+                    HttpClient::new(&config.http),
+                    ServerPool::new(&config.service_urls(Service::Radar)),
+                    device_id.clone(),
+                    identity.signer().clone(),
+                    (*state_manager).clone(),
+                )
+                .with_karma_gate(KarmaGate::new("radar", config.karma_gates.radar, (*state_manager).clone()));
+                let combat = CombatClient::from_config(&config, device_id.clone(), identity.signer().clone(), (*state_manager).clone());
+                Some((Arc::new(radar), Arc::new(combat)))
+            }
+            None => None,
+        };
+        let (radar, combat) = clients.unzip();
 
         Ok(Self {
             config,
             state_manager,
+            anchors,
+            sync_backlog: OfflineQueue::default(),
             network_probe,
             mining_engine,
+            radar,
+            combat,
         })
     }
 
@@ -46,7 +139,25 @@ impl OmniDaemon {
         self.state_manager.clone()
     }
 
-    pub async fn run(self) -> Result<()> {
+    /// The radar client, once the device is registered
+    pub fn get_radar(&self) -> Option<Arc<RadarClient>> {
+        self.radar.clone()
+    }
+
+    /// The combat client, once the device is registered
+    pub fn get_combat(&self) -> Option<Arc<CombatClient>> {
+        self.combat.clone()
+    }
+
+    /// Unsynced deltas, filled by the sync loop and reported over gRPC
+    pub fn get_sync_backlog(&self) -> OfflineQueue {
+        self.sync_backlog.clone()
+    }
+
+    /// Run until a stop signal, then give final work the shutdown grace
+    ///
+    /// Returns the names of final tasks abandoned when the grace expired.
+    pub async fn run(self) -> Result<Vec<&'static str>> {
         let state_manager = self.state_manager.clone();
         let network_probe = Arc::new(self.network_probe);
         let config = Arc::new(self.config);
@@ -54,55 +165,471 @@ impl OmniDaemon {
         // Spawn gRPC server
         let grpc_addr = format!("127.0.0.1:{}", config.grpc_port);
         let grpc_state = state_manager.clone();
-        tokio::spawn(async move {
-            if let Err(e) = start_grpc_server(grpc_addr, grpc_state).await {
+        let grpc_anchors = self.anchors.clone();
+        let grpc_backlog = self.sync_backlog.clone();
+        let grpc_radar = self.radar.clone();
+        let grpc_combat = self.combat.clone();
+        let grpc_limits = config.grpc.clone();
+        let mut subsystems = SubsystemSpawner::new(config.subsystems);
+        subsystems.spawn(Subsystem::Grpc, async move {
+            if let Err(e) = start_grpc_server(grpc_addr, grpc_state, grpc_anchors, grpc_backlog, grpc_radar, grpc_combat, grpc_limits).await {
                 tracing::error!("gRPC server error: {}", e);
             }
         });
 
+        if config.metrics.enabled {
+            let metrics_addr = format!("127.0.0.1:{}", config.metrics.port);
+            let listener = tokio::net::TcpListener::bind(&metrics_addr).await
+                .with_context(|| format!("Failed to bind metrics endpoint on {}", metrics_addr))?;
+            let (metrics_state, metrics_backlog) = (state_manager.clone(), self.sync_backlog.clone());
+            tokio::spawn(async move {
+                if let Err(e) = serve_metrics(listener, metrics_state, metrics_backlog).await {
+                    error!("Metrics endpoint error: {:#}", e);
+                }
+            });
+        }
+
+        // Background loops are restarted if they panic
+        let respawn_delay = Duration::from_secs(config.panic.respawn_delay_secs);
+
         // Spawn network probe loop
         let probe_state = state_manager.clone();
         let probe = network_probe.clone();
-        let probe_interval = config.network.probe_interval_secs;
-        tokio::spawn(async move {
-            Self::probe_network_loop(probe, probe_state, probe_interval).await;
-        });
+        let probe_config = config.clone();
+        subsystems.spawn(Subsystem::NetworkProbe, respawn_on_panic("Network probe", respawn_delay, move || {
+            let probe_schedule = ProbeSchedule::new(
+                Duration::from_secs(probe_config.network.probe_interval_secs),
+                Duration::from_secs(probe_config.network.probe_backoff_max_secs),
+            )
+            .with_first_probe_immediately(probe_config.network.first_probe_immediately);
+            let sampler = LatencySampler::new(probe_config.network.telemetry.clone());
+            Self::probe_network_loop(probe.clone(), probe_state.clone(), probe_schedule, sampler, probe_config.jitter)
+        }));
+
+        // Spawn state checkpoint loop
+        let checkpoint_interval = config.persistence.checkpoint_interval_secs;
+        if checkpoint_interval > 0 {
+            info!("💾 State checkpoints every {}s to {:?}", checkpoint_interval, Config::state_path()?);
+            let checkpoint_state = state_manager.clone();
+            let retry = CheckpointRetry {
+                retries: config.persistence.checkpoint_retries,
+                backoff: Duration::from_millis(config.persistence.checkpoint_retry_backoff_ms),
+            };
+            let notifier = notifier_for(config.notifications.backend);
+            tokio::spawn(respawn_on_panic("State checkpoints", respawn_delay, move || {
+                Self::checkpoint_loop(
+                    checkpoint_state.clone(),
+                    Duration::from_secs(checkpoint_interval),
+                    retry,
+                    notifier.clone(),
+                )
+            }));
+        }
+
+        // Spawn capacity reminders
+        let alert_state = state_manager.clone();
+        let alert_config = config.capacity_alerts.clone();
+        let floor_config = config.entropy_floor.clone();
+        let alert_period = Duration::from_secs(config.mining.tick_interval_secs.max(1));
+        let alert_notifier = notifier_for(config.notifications.backend);
+        tokio::spawn(respawn_on_panic("Capacity alerts", respawn_delay, move || {
+            Self::capacity_alert_loop(
+                alert_state.clone(),
+                alert_config.clone(),
+                floor_config.clone(),
+                CapacityAlerter::default(),
+                alert_period,
+                alert_notifier.clone(),
+            )
+        }));
+
+        // Spawn history sweep for entries that age out between inserts
+        if config.history.sweep_interval_secs > 0 {
+            let sweep_state = state_manager.clone();
+            let sweep_period = Duration::from_secs(config.history.sweep_interval_secs);
+            tokio::spawn(respawn_on_panic("History sweep", respawn_delay, move || {
+                Self::history_sweep_loop(sweep_state.clone(), sweep_period)
+            }));
+        }
+
+        // Mine under the watchdog until asked to stop
+        let tick = Duration::from_secs(config.mining.tick_interval_secs.max(1));
+        let watchdog = (config.mining.watchdog_ticks > 0).then(|| MiningWatchdog::new(
+            self.mining_engine.heartbeat(),
+            tick,
+            config.mining.watchdog_ticks,
+            state_manager.clone(),
+        ));
+        tokio::select! {
+            _ = Self::supervise_mining(
+                Arc::new(self.mining_engine),
+                watchdog,
+                tick,
+                config.mining.restart_stalled,
+                notifier_for(config.notifications.backend),
+            ) => {}
+            _ = shutdown_signal() => info!("🛑 Shutdown requested"),
+        }
+
+        let mut final_tasks = ShutdownTasks::default();
+        let journal_state = state_manager.clone();
+        final_tasks.spawn("ledger journal", async move { journal_state.flush_journal().await });
+        if checkpoint_interval > 0 {
+            final_tasks.spawn("final checkpoint", async move {
+                if let Err(e) = state_manager.checkpoint().await {
+                    warn!("⚠️  Final state checkpoint failed: {:#}", e);
+                }
+            });
+        }
+
+        Ok(final_tasks
+            .finish_within(Duration::from_secs(config.shutdown.grace_secs))
+            .await)
+    }
+
+    /// Run mining in its own task, checking the watchdog every `period`
+    ///
+    /// A stall is reported once; with `restart` the stuck task is aborted and
+    /// a fresh one spawned. Mining is aborted when this future is dropped.
+    async fn supervise_mining(
+        engine: Arc<MiningEngine>,
+        watchdog: Option<MiningWatchdog>,
+        period: Duration,
+        restart: bool,
+        notifier: Arc<dyn Notifier>,
+    ) {
+        // Aborts the current mining task when replaced or when the
+        // supervisor is dropped
+        struct MiningTask(tokio::task::AbortHandle);
+        impl Drop for MiningTask {
+            fn drop(&mut self) {
+                self.0.abort();
+            }
+        }
+        let spawn = |engine: &Arc<MiningEngine>| {
+            let engine = engine.clone();
+            MiningTask(tokio::spawn(async move { engine.run().await }).abort_handle())
+        };
+
+        engine.heartbeat().beat();
+        let mut _mining = spawn(&engine);
+        let Some(watchdog) = watchdog else {
+            return std::future::pending().await;
+        };
+
+        let mut ticker = crate::wake::interval(period);
+        let mut alerted = false;
+        loop {
+            ticker.tick().await;
+
+            if !watchdog.check(tokio::time::Instant::now()) {
+                alerted = false;
+                continue;
+            }
+
+            if !alerted {
+                notifier.notify("⛏️ Mining Stalled", "Entropy is not accruing");
+                alerted = true;
+            }
+            if restart {
+                warn!("⛏️  Restarting mining loop");
+                engine.heartbeat().beat();
+                _mining = spawn(&engine);
+            }
+        }
+    }
+
+    /// Remind about entropy near capacity, and warn once each time it
+    /// sinks toward the entropy floor
+    async fn capacity_alert_loop(
+        state_manager: Arc<StateManager>,
+        config: CapacityAlertConfig,
+        floor: EntropyFloorConfig,
+        alerter: CapacityAlerter,
+        period: Duration,
+        notifier: Arc<dyn Notifier>,
+    ) {
+        let mut ticker = crate::wake::interval(period);
+        let mut near_floor = false;
+
+        loop {
+            ticker.tick().await;
+
+            let player = state_manager.get_snapshot().await.player;
+            let percent = player.entropy as f64 / player.capacity.max(1) as f64 * 100.0;
+            match alerter.due(&config, player.entropy, player.capacity, std::time::Instant::now()) {
+                Some(CapacityLevel::Critical) => {
+                    warn!("🔥 Entropy at {:.0}% of capacity: excess is decaying", percent);
+                    notifier.notify("🔥 Entropy Decaying", &format!("At {:.0}% of capacity. Spend or defend now.", percent));
+                }
+                Some(_) => {
+                    warn!("⚠️  Entropy at {:.0}% of capacity", percent);
+                    notifier.notify("⚠️ Nearing Capacity", &format!("Entropy at {:.0}% of capacity.", percent));
+                }
+                None => {}
+            }
 
-        // Spawn mining loop (runs in current task)
-        self.mining_engine.run().await;
+            let was_near_floor = std::mem::replace(&mut near_floor, floor.is_near(player.entropy));
+            if near_floor && !was_near_floor {
+                warn!("🪫 Entropy {} is close to the {} floor; automatic spending stops there", player.entropy, floor.amount);
+                notifier.notify(
+                    "🪫 Entropy Running Low",
+                    &format!("{} Ω left; radar, attacks and defense stop at {} Ω.", player.entropy, floor.amount),
+                );
+            }
+        }
+    }
+
+    async fn history_sweep_loop(state_manager: Arc<StateManager>, period: Duration) {
+        let mut ticker = crate::wake::interval(period);
+        loop {
+            ticker.tick().await;
+            let pruned = state_manager.prune_history().await;
+            if pruned > 0 {
+                info!("🧹 Pruned {} expired history entries", pruned);
+            }
+        }
+    }
 
-        Ok(())
+    async fn checkpoint_loop(
+        state_manager: Arc<StateManager>,
+        period: Duration,
+        retry: CheckpointRetry,
+        notifier: Arc<dyn Notifier>,
+    ) {
+        let mut ticker = crate::wake::interval(period);
+
+        loop {
+            ticker.tick().await;
+            Self::checkpoint_once(&state_manager, retry, notifier.as_ref()).await;
+        }
+    }
+
+    /// One checkpoint tick, retried with backoff
+    ///
+    /// Once the retries run out the state is flagged `checkpoint_failing`
+    /// and the user is alerted, once per failing streak. The daemon keeps
+    /// running, and the flag clears on the next successful write.
+    async fn checkpoint_once(
+        state_manager: &StateManager,
+        retry: CheckpointRetry,
+        notifier: &dyn Notifier,
+    ) {
+        let mut delay = retry.backoff;
+        let mut attempt = 0;
+
+        let result = loop {
+            match state_manager.checkpoint().await {
+                Err(e) if attempt < retry.retries => {
+                    warn!("⚠️  State checkpoint failed (retry in {}ms): {:#}", delay.as_millis(), e);
+                    tokio::time::sleep(delay).await;
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => break result,
+            }
+        };
+
+        match result {
+            Ok(written) => {
+                if written {
+                    tracing::debug!("State checkpoint written");
+                }
+                if state_manager.set_checkpoint_failing(false) {
+                    info!("💾 State checkpoints recovered");
+                }
+            }
+            Err(e) => {
+                if state_manager.set_checkpoint_failing(true) {
+                    warn!("⚠️  State checkpoint still failing: {:#}", e);
+                    return;
+                }
+                error!("🚨 State checkpoints failing after {} retries: {:#}", retry.retries, e);
+                error!("   Progress is not being saved; check disk space and permissions");
+                notifier.notify("💾 State Not Saved", &format!("Checkpoints are failing: {:#}", e));
+            }
+        }
     }
 
     async fn probe_network_loop(
         probe: Arc<NetworkProbe>,
         state_manager: Arc<StateManager>,
-        interval_secs: u64,
+        mut schedule: ProbeSchedule,
+        mut sampler: LatencySampler,
+        jitter: JitterConfig,
     ) {
-        use tokio::time::{interval, Duration};
-        
-        let mut ticker = interval(Duration::from_secs(interval_secs));
-        
-        info!("🌐 Network probe started (interval: {}s)", interval_secs);
+        info!(
+            "🌐 Network probe started (interval: {}s, first probe in {}s)",
+            schedule.base().as_secs(),
+            schedule.first_delay().as_secs()
+        );
+        // An immediate first probe stays immediate; mining warm-up waits on it
+        let first_delay = match schedule.first_delay() {
+            Duration::ZERO => Duration::ZERO,
+            delay => jitter.first(delay, schedule.base()),
+        };
+        tokio::time::sleep(first_delay).await;
 
         loop {
-            ticker.tick().await;
-            
-            match probe.build_latency_vector().await {
+            let quality = match probe.build_latency_vector().await {
                 Ok(vector) => {
                     let quality = probe.calculate_network_quality(&vector.data);
                     state_manager.update_network_quality(quality).await;
                     
-                    info!(
-                        "Network probe complete: avg_latency={:.1}ms, quality={:.2}",
-                        vector.data.iter().sum::<f64>() / vector.data.len() as f64,
-                        quality
-                    );
+                    if vector.data.is_empty() {
+                        warn!("🌐 No network anchors configured; using neutral quality {:.2}", quality);
+                    } else {
+                        info!(
+                            "Network probe complete: avg_latency={:.1}ms, quality={:.2}",
+                            vector.data.iter().sum::<f64>() / vector.data.len() as f64,
+                            quality
+                        );
+                    }
+
+                    // Local quality above is always current; only the upload is sampled
+                    if sampler.should_upload(quality) {
+                        state_manager.queue_latency_sample(vector);
+                    }
+                    Some(quality)
                 }
                 Err(e) => {
                     tracing::error!("Network probe failed: {}", e);
+                    None
                 }
+            };
+
+            let delay = schedule.next_delay(quality);
+            if delay > schedule.base() {
+                warn!("🌐 Network unreachable; next probe in {}s", delay.as_secs());
             }
+            tokio::time::sleep(jitter.next(delay)).await;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mining::params::MiningParams;
+    use crate::store::{MemoryStore, StateStore};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_checkpoint_loop_fires() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(42).await;
+
+        let task = tokio::spawn(OmniDaemon::checkpoint_loop(
+            state_manager.clone(),
+            Duration::from_millis(20),
+            CheckpointRetry { retries: 0, backoff: Duration::ZERO },
+            notifier_for(crate::notifier::NotificationBackend::None),
+        ));
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        task.abort();
+
+        assert!(state_manager.store().load_snapshot().unwrap().is_some());
+        assert!(!state_manager.is_dirty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_first_probe_timing() {
+        for immediately in [true, false] {
+            let probe = Arc::new(NetworkProbe::new(AnchorRegistry::new(vec![])).unwrap());
+            let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+            let schedule = ProbeSchedule::new(Duration::from_millis(300), Duration::from_millis(300))
+                .with_first_probe_immediately(immediately);
+            let sampler = LatencySampler::new(Default::default());
+
+            let task = tokio::spawn(OmniDaemon::probe_network_loop(
+                probe,
+                state_manager.clone(),
+                schedule,
+                sampler,
+                JitterConfig::NONE,
+            ));
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            assert_eq!(state_manager.has_network_quality(), immediately);
+            tokio::time::sleep(Duration::from_millis(400)).await;
+            assert!(state_manager.has_network_quality());
+            task.abort();
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_server_tick_not_restarted() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let engine = Arc::new(MiningEngine::new((*state_manager).clone(), 10, strategy_by_name("sqrt").unwrap(), 1.0));
+        // Local limit is 5 × 10s; the server tick is far longer
+        let watchdog = MiningWatchdog::new(engine.heartbeat(), Duration::from_secs(10), 5, state_manager.clone());
+
+        let task = tokio::spawn(OmniDaemon::supervise_mining(
+            engine,
+            Some(watchdog),
+            Duration::from_secs(10),
+            true,
+            notifier_for(crate::notifier::NotificationBackend::None),
+        ));
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        assert_eq!(state_manager.get_snapshot().await.player.entropy, 10);
+
+        state_manager.set_server_mining_params(MiningParams { tick_interval_secs: 3600, base_multiplier: 1.0, issued_at: 1 });
+        tokio::time::sleep(Duration::from_secs(1000)).await;
+
+        // A restart would have mined a fresh first tick
+        assert_eq!(state_manager.get_snapshot().await.player.entropy, 10);
+        assert!(state_manager.is_mining_healthy());
+        task.abort();
+    }
+
+    /// Memory store whose snapshot writes fail while `failing` is set
+    #[derive(Default)]
+    struct FlakyStore {
+        inner: MemoryStore,
+        failing: AtomicBool,
+    }
+
+    impl StateStore for FlakyStore {
+        fn save_snapshot(&self, state: &crate::types::GameState) -> Result<()> {
+            if self.failing.load(Ordering::SeqCst) {
+                anyhow::bail!("disk full");
+            }
+            self.inner.save_snapshot(state)
+        }
+
+        fn load_snapshot(&self) -> Result<Option<crate::types::GameState>> {
+            self.inner.load_snapshot()
+        }
+
+        fn append_ledger(&self, entry: &crate::types::LedgerEntry) -> Result<()> {
+            self.inner.append_ledger(entry)
+        }
+
+        fn read_ledger(&self) -> Result<Vec<crate::types::LedgerEntry>> {
+            self.inner.read_ledger()
+        }
+
+        fn prune_ledger(&self, retention: &crate::history::RetentionConfig, now: chrono::DateTime<chrono::Utc>) -> Result<usize> {
+            self.inner.prune_ledger(retention, now)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_failing_flag_set_and_cleared() {
+        let store = Arc::new(FlakyStore::default());
+        store.failing.store(true, Ordering::SeqCst);
+        let state_manager = StateManager::new("dev".to_string(), 100, store.clone());
+        let retry = CheckpointRetry { retries: 2, backoff: Duration::from_millis(1) };
+        let notifier = crate::notifier::NoopNotifier;
+
+        state_manager.update_entropy(42).await;
+        OmniDaemon::checkpoint_once(&state_manager, retry, &notifier).await;
+        assert!(state_manager.is_checkpoint_failing());
+        assert!(state_manager.is_dirty());
+
+        store.failing.store(false, Ordering::SeqCst);
+        OmniDaemon::checkpoint_once(&state_manager, retry, &notifier).await;
+        assert!(!state_manager.is_checkpoint_failing());
+        assert!(store.load_snapshot().unwrap().is_some());
+    }
+}
@@ -0,0 +1,92 @@
+// sacas-daemon/src/daemon/shutdown.rs
+// Bounded wait for final work once the daemon is asked to stop
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Resolves on Ctrl-C, or SIGTERM where the platform has it
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+                return;
+            }
+            Err(e) => warn!("⚠️  Cannot listen for SIGTERM: {}", e),
+        }
+    }
+
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        warn!("⚠️  Cannot listen for Ctrl-C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Final work that must finish, or be given up on, before the process exits
+#[derive(Default)]
+pub struct ShutdownTasks {
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+}
+
+impl ShutdownTasks {
+    pub fn spawn<F>(&mut self, name: &'static str, task: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.tasks.push((name, tokio::spawn(task)));
+    }
+
+    /// Wait up to `grace` for every task, aborting whatever is left
+    ///
+    /// Returns the names of the tasks that were abandoned.
+    pub async fn finish_within(self, grace: Duration) -> Vec<&'static str> {
+        let deadline = tokio::time::Instant::now() + grace;
+        let mut abandoned = Vec::new();
+
+        for (name, mut handle) in self.tasks {
+            if tokio::time::timeout_at(deadline, &mut handle).await.is_err() {
+                handle.abort();
+                abandoned.push(name);
+            }
+        }
+
+        if abandoned.is_empty() {
+            info!("🛑 Shutdown tasks finished");
+        } else {
+            warn!(
+                "🛑 Shutdown grace of {}s expired; abandoning: {}",
+                grace.as_secs(),
+                abandoned.join(", ")
+            );
+        }
+
+        abandoned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_slow_task_abandoned_after_grace() {
+        let mut tasks = ShutdownTasks::default();
+        tasks.spawn("quick", async {});
+        tasks.spawn("wedged", tokio::time::sleep(Duration::from_secs(60)));
+
+        let started = std::time::Instant::now();
+        let abandoned = tasks.finish_within(Duration::from_millis(50)).await;
+
+        assert_eq!(abandoned, ["wedged"]);
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
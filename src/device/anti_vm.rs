@@ -1,5 +1,6 @@
-use std::process::Command;
-use anyhow::Result;
+use super::{run_command, DeviceError};
+
+type Result<T> = std::result::Result<T, DeviceError>;
 
 pub struct VMDetector;
 
@@ -38,9 +39,7 @@ impl VMDetector {
     }
     
     fn check_serial_is_zero() -> Result<bool> {
-        let output = Command::new("ioreg")
-            .args(&["-l"])
-            .output()?;
+        let output = run_command("ioreg", &["-l"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
         
@@ -54,9 +53,7 @@ impl VMDetector {
     }
     
     fn check_hypervisor_in_model() -> Result<Option<String>> {
-        let output = Command::new("sysctl")
-            .args(&["-n", "hw.model"])
-            .output()?;
+        let output = run_command("sysctl", &["-n", "hw.model"])?;
         
         let model = String::from_utf8_lossy(&output.stdout).to_lowercase();
         
@@ -72,9 +69,7 @@ impl VMDetector {
     }
     
     fn check_suspicious_memory() -> Result<bool> {
-        let output = Command::new("sysctl")
-            .args(&["-n", "hw.memsize"])
-            .output()?;
+        let output = run_command("sysctl", &["-n", "hw.memsize"])?;
         
         let memsize: u64 = String::from_utf8_lossy(&output.stdout)
             .trim()
@@ -87,9 +82,7 @@ impl VMDetector {
     }
     
     fn check_usb_devices() -> Result<bool> {
-        let output = Command::new("ioreg")
-            .args(&["-l", "-p", "IOUSB"])
-            .output()?;
+        let output = run_command("ioreg", &["-l", "-p", "IOUSB"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
         
@@ -0,0 +1,130 @@
+// sacas-daemon/src/device/attestation.rs
+// Signed, self-contained proof of a device's identity and hardware binding
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+
+use super::fingerprint::serial_hash;
+use super::DeviceIdentity;
+use crate::config::Config;
+
+/// Format tag so verifiers can reject bundles they don't understand
+pub const ATTESTATION_FORMAT: &str = "sacas-attestation-v1";
+
+/// Everything the attestation signature covers
+///
+/// The signed message is this struct serialized as compact JSON, fields in
+/// declaration order. The serial number only appears as its SHA-256.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttestationClaims {
+    pub format: String,
+    pub device_id: Option<String>,
+    pub device_fingerprint: String,
+    pub model_identifier: String,
+    pub serial_hash: String,
+    pub public_key: String,
+    pub first_seen: DateTime<Utc>,
+    pub issued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestationBundle {
+    pub claims: AttestationClaims,
+    /// Ed25519 signature over the claims (base64)
+    pub signature: String,
+}
+
+impl AttestationBundle {
+    /// Attest to the binding recorded in `config`, signed with `identity`
+    pub fn create(config: &Config, identity: &DeviceIdentity) -> Result<Self> {
+        let claims = AttestationClaims {
+            format: ATTESTATION_FORMAT.to_string(),
+            device_id: config.device_id.clone(),
+            device_fingerprint: config.device.device_fingerprint.clone(),
+            model_identifier: config.device.model_identifier.clone(),
+            serial_hash: serial_hash(&config.device.serial_number),
+            public_key: identity.public_key_base64(),
+            first_seen: config.device.first_seen,
+            issued_at: Utc::now(),
+        };
+        let message = serde_json::to_vec(&claims).context("Failed to serialize attestation")?;
+
+        Ok(Self {
+            signature: identity.sign_base64(&message),
+            claims,
+        })
+    }
+
+    /// Check the signature against the public key carried in the claims
+    ///
+    /// This proves the bundle is intact; callers still need to confirm the
+    /// public key is the one registered for the device.
+    pub fn verify(&self) -> Result<()> {
+        if self.claims.format != ATTESTATION_FORMAT {
+            anyhow::bail!("Unsupported attestation format {:?}", self.claims.format);
+        }
+
+        let key_bytes: [u8; 32] = base64::decode(&self.claims.public_key)
+            .context("Public key is not valid base64")?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Public key must be 32 bytes"))?;
+        let key = VerifyingKey::from_bytes(&key_bytes).context("Invalid public key")?;
+
+        let signature = base64::decode(&self.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .context("Signature is not a valid Ed25519 signature")?;
+
+        let message = serde_json::to_vec(&self.claims).context("Failed to serialize attestation")?;
+        key.verify(&message, &signature)
+            .map_err(|_| anyhow::anyhow!("Attestation signature does not match its contents"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_config;
+
+    fn bundle() -> AttestationBundle {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = DeviceIdentity::load_or_generate(&dir.path().join("device.key")).unwrap();
+        AttestationBundle::create(&sample_config(), &identity).unwrap()
+    }
+
+    #[test]
+    fn test_bundle_verifies_after_round_trip() {
+        let json = serde_json::to_string(&bundle()).unwrap();
+        let parsed: AttestationBundle = serde_json::from_str(&json).unwrap();
+
+        assert!(parsed.verify().is_ok());
+        assert!(!json.contains(&sample_config().device.serial_number));
+    }
+
+    #[test]
+    fn test_tampered_fields_fail_verification() {
+        let original = bundle();
+        let other_key = {
+            let dir = tempfile::tempdir().unwrap();
+            DeviceIdentity::load_or_generate(&dir.path().join("device.key")).unwrap().public_key_base64()
+        };
+
+        let tampers: [&dyn Fn(&mut AttestationClaims); 7] = [
+            &|c| c.device_id = Some("dev-999".to_string()),
+            &|c| c.device_fingerprint.push('0'),
+            &|c| c.model_identifier = "Mac15,3".to_string(),
+            &|c| c.serial_hash = serial_hash("OTHERSERIAL"),
+            &|c| c.public_key = other_key.clone(),
+            &|c| c.first_seen -= chrono::Duration::days(1),
+            &|c| c.issued_at += chrono::Duration::seconds(1),
+        ];
+
+        for tamper in tampers {
+            let mut bundle = original.clone();
+            tamper(&mut bundle.claims);
+            assert!(bundle.verify().is_err(), "tampered bundle verified: {:?}", bundle.claims);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+// sacas-daemon/src/device/binding_guard.rs
+// Periodic re-check of the device binding while the daemon runs
+
+use std::sync::Arc;
+
+use super::{DeviceError, MacHardwareInfo};
+use crate::config::{Config, DeviceBinding};
+
+type Collector = Arc<dyn Fn() -> Result<MacHardwareInfo, DeviceError> + Send + Sync>;
+
+/// Re-collects hardware every few syncs and compares it to the bound config
+///
+/// Startup only checks the binding once, so a VM snapshot restored on other
+/// hardware would keep mining under the old binding. Collecting hardware info
+/// shells out, so it runs only every `every` syncs, and the full field
+/// comparison only when the fingerprint differs from the one last seen.
+pub struct BindingGuard {
+    config: Config,
+    every: u32,
+    syncs_since_check: u32,
+    /// Fingerprint the hardware had at the last check that verified
+    cached_fingerprint: String,
+    collect: Collector,
+}
+
+impl BindingGuard {
+    /// Guard `config`'s binding, checking every `every` syncs (0 never checks)
+    pub fn new(config: Config, every: u32) -> Self {
+        Self::with_collector(config, every, Arc::new(MacHardwareInfo::collect))
+    }
+
+    fn with_collector(config: Config, every: u32, collect: Collector) -> Self {
+        let cached_fingerprint = config.device.device_fingerprint.clone();
+        Self {
+            config,
+            every,
+            syncs_since_check: 0,
+            cached_fingerprint,
+            collect,
+        }
+    }
+
+    /// Count one sync; returns the mismatch if this one's check found the
+    /// hardware no longer matches the binding
+    ///
+    /// Collection runs on the blocking pool. A failed collection is logged
+    /// and treated as unchanged, so a flaky `ioreg` can't halt the daemon.
+    pub async fn on_sync(&mut self) -> Option<DeviceBinding> {
+        if self.every == 0 {
+            return None;
+        }
+        self.syncs_since_check += 1;
+        if self.syncs_since_check < self.every {
+            return None;
+        }
+        self.syncs_since_check = 0;
+
+        let collect = self.collect.clone();
+        let hw = match tokio::task::spawn_blocking(move || collect()).await {
+            Ok(Ok(hw)) => hw,
+            Ok(Err(e)) => {
+                tracing::warn!("⚠️  Could not re-check device binding: {}", e);
+                return None;
+            }
+            Err(e) => {
+                tracing::warn!("⚠️  Device binding check did not finish: {}", e);
+                return None;
+            }
+        };
+        let fingerprint = hw.generate_fingerprint();
+        if fingerprint == self.cached_fingerprint {
+            return None;
+        }
+
+        match self.config.verify_device_binding(&hw) {
+            DeviceBinding::Verified => {
+                self.cached_fingerprint = fingerprint;
+                None
+            }
+            DeviceBinding::Drifted(mismatches) if !self.config.enforcement.strict_binding => {
+                for m in &mismatches {
+                    tracing::warn!("⚠️  Device detail changed: {}: expected {}, found {}", m.field, m.expected, m.actual);
+                }
+                // Warn once per change, not on every later check
+                self.cached_fingerprint = fingerprint;
+                None
+            }
+            changed => Some(changed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn hardware(hardware_uuid: &str) -> MacHardwareInfo {
+        MacHardwareInfo {
+            hardware_uuid: hardware_uuid.to_string(),
+            serial_number: "C02XK1ZZJGH5".to_string(),
+            model_identifier: "Mac14,3".to_string(),
+            board_id: "Mac-7BA5B2D9E42DDD94".to_string(),
+            rom_version: "8419.80.7".to_string(),
+            cpu_brand: "Apple M2".to_string(),
+        }
+    }
+
+    /// A guard bound to the original hardware, and a handle to swap what it collects
+    fn guard(every: u32) -> (BindingGuard, Arc<Mutex<MacHardwareInfo>>, Arc<Mutex<u32>>) {
+        let original = hardware("A1B2C3D4-0000-1111-2222-333344445555");
+        let mut config = crate::test_support::sample_config();
+        config.device.device_fingerprint = original.generate_fingerprint();
+
+        let current = Arc::new(Mutex::new(original));
+        let collections = Arc::new(Mutex::new(0));
+        let (hw, count) = (current.clone(), collections.clone());
+        let guard = BindingGuard::with_collector(config, every, Arc::new(move || {
+            *count.lock().unwrap() += 1;
+            Ok(hw.lock().unwrap().clone())
+        }));
+        (guard, current, collections)
+    }
+
+    #[tokio::test]
+    async fn test_swapped_hardware_trips_guard() {
+        let (mut guard, current, collections) = guard(3);
+        for _ in 0..6 {
+            assert!(guard.on_sync().await.is_none());
+        }
+        assert_eq!(*collections.lock().unwrap(), 2);
+
+        // Snapshot restored on another Mac mid-run: caught within `every` syncs
+        *current.lock().unwrap() = hardware("FFFFFFFF-0000-1111-2222-333344445555");
+        assert!(guard.on_sync().await.is_none());
+        assert!(guard.on_sync().await.is_none());
+        match guard.on_sync().await {
+            Some(DeviceBinding::Tampered(mismatches)) => {
+                let fields: Vec<_> = mismatches.iter().map(|m| m.field).collect();
+                assert_eq!(fields, ["hardware_uuid", "device_fingerprint"]);
+            }
+            other => panic!("expected a tampered binding, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_volatile_change_does_not_trip_guard() {
+        let (mut guard, current, collections) = guard(1);
+        current.lock().unwrap().board_id = "Mac-0000000000000000".to_string();
+        assert!(guard.on_sync().await.is_none());
+        assert!(guard.on_sync().await.is_none());
+        assert_eq!(*collections.lock().unwrap(), 2);
+
+        // Different serial: a core mismatch still trips it
+        current.lock().unwrap().serial_number = "H4TXQ0ZZQ6NY".to_string();
+        assert!(matches!(guard.on_sync().await, Some(DeviceBinding::Tampered(_))));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_guard_never_collects() {
+        let (mut guard, current, collections) = guard(0);
+        *current.lock().unwrap() = hardware("FFFFFFFF-0000-1111-2222-333344445555");
+        for _ in 0..20 {
+            assert!(guard.on_sync().await.is_none());
+        }
+        assert_eq!(*collections.lock().unwrap(), 0);
+    }
+}
@@ -0,0 +1,116 @@
+// sacas-daemon/src/device/enforcement.rs
+// Which startup validation failures stop the daemon and which only warn
+
+use serde::{Deserialize, Serialize};
+
+use super::DeviceError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EnforcementLevel {
+    /// Every validation failure exits (production)
+    #[default]
+    Strict,
+    /// Non-security-critical failures are logged and startup continues
+    WarnOnly,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EnforcementConfig {
+    pub level: EnforcementLevel,
+    /// Under `WarnOnly`, this many VM signals still stop the daemon
+    pub vm_signal_threshold: usize,
+    /// Also exit when only volatile binding fields (model, board id) changed;
+    /// by default that is a warning and only a core mismatch exits
+    #[serde(default)]
+    pub strict_binding: bool,
+}
+
+impl Default for EnforcementConfig {
+    fn default() -> Self {
+        Self {
+            level: EnforcementLevel::Strict,
+            vm_signal_threshold: 2,
+            strict_binding: false,
+        }
+    }
+}
+
+/// A startup check whose failure the policy has to rule on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationCheck {
+    /// Best-effort hardware field: model whitelist, CPU brand, board id
+    HardwareField,
+    /// Serial number missing or a VM placeholder
+    SerialNumber,
+    /// Number of virtual machine signals detected
+    VmSignals(usize),
+    /// x86_64 build running under Rosetta
+    Translation,
+}
+
+impl ValidationCheck {
+    /// Which check a `MacValidator` failure belongs to
+    pub fn for_model_error(error: &DeviceError) -> Self {
+        match error {
+            DeviceError::SerialInvalid(_) => Self::SerialNumber,
+            _ => Self::HardwareField,
+        }
+    }
+}
+
+impl EnforcementConfig {
+    /// Whether a failed `check` must stop the daemon
+    ///
+    /// Serial number failures are fatal at every level. Device binding
+    /// mismatches are ruled on by `DeviceBinding::is_fatal` instead.
+    pub fn is_fatal(&self, check: ValidationCheck) -> bool {
+        match (self.level, check) {
+            (EnforcementLevel::Strict, _) => true,
+            (_, ValidationCheck::SerialNumber) => true,
+            (_, ValidationCheck::VmSignals(count)) => count >= self.vm_signal_threshold,
+            (_, ValidationCheck::HardwareField | ValidationCheck::Translation) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_makes_every_check_fatal() {
+        let policy = EnforcementConfig::default();
+        for check in [
+            ValidationCheck::HardwareField,
+            ValidationCheck::SerialNumber,
+            ValidationCheck::VmSignals(1),
+            ValidationCheck::Translation,
+        ] {
+            assert!(policy.is_fatal(check), "{:?}", check);
+        }
+    }
+
+    #[test]
+    fn test_warn_only_downgrades_non_critical_checks() {
+        let policy = EnforcementConfig {
+            level: EnforcementLevel::WarnOnly,
+            vm_signal_threshold: 2,
+            ..Default::default()
+        };
+
+        assert!(!policy.is_fatal(ValidationCheck::HardwareField));
+        assert!(!policy.is_fatal(ValidationCheck::Translation));
+        assert!(!policy.is_fatal(ValidationCheck::VmSignals(1)));
+        assert!(policy.is_fatal(ValidationCheck::VmSignals(2)));
+        assert!(policy.is_fatal(ValidationCheck::SerialNumber));
+    }
+
+    #[test]
+    fn test_model_errors_map_to_checks() {
+        let serial = DeviceError::SerialInvalid("Virtual machines are not allowed.".to_string());
+        let model = DeviceError::InvalidModel("VirtualMac2,1".to_string());
+
+        assert_eq!(ValidationCheck::for_model_error(&serial), ValidationCheck::SerialNumber);
+        assert_eq!(ValidationCheck::for_model_error(&model), ValidationCheck::HardwareField);
+    }
+}
@@ -0,0 +1,38 @@
+use thiserror::Error;
+
+/// Errors from hardware collection and validation
+#[derive(Debug, Error)]
+pub enum DeviceError {
+    #[error(
+        "❌ Invalid Mac model: '{0}'\n\n\
+         Only genuine Mac computers are allowed to run SACAS.\n\
+         Supported models: Mac mini, MacBook Pro, MacBook Air, iMac, Mac Studio, Mac Pro (Apple Silicon only)"
+    )]
+    InvalidModel(String),
+
+    #[error(
+        "❌ Invalid CPU: '{0}'\n\n\
+         SACAS only supports Apple Silicon Macs.\n\
+         Intel-based Macs are not supported."
+    )]
+    UnsupportedCpu(String),
+
+    #[error("❌ Virtual machine detected: {0}")]
+    VirtualMachine(String),
+
+    #[error("Failed to run {command}: {reason}")]
+    CommandFailed { command: String, reason: String },
+
+    #[error("Failed to parse {0}")]
+    ParseFailed(String),
+
+    #[error("❌ Invalid serial number: {0}")]
+    SerialInvalid(String),
+
+    #[error(
+        "❌ Running under Rosetta translation\n\n\
+         This is an x86_64 build of SACAS running on Apple Silicon.\n\
+         Install the native arm64 build instead."
+    )]
+    Translated,
+}
@@ -1,8 +1,10 @@
-use std::process::Command;
-use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use sha2::{Sha256, Digest};
 
+use super::{run_command, DeviceError};
+
+type Result<T> = std::result::Result<T, DeviceError>;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MacHardwareInfo {
     pub hardware_uuid: String,      // IOPlatformUUID
@@ -27,18 +29,20 @@ impl MacHardwareInfo {
     
     /// Get Hardware UUID (most reliable unique identifier)
     fn get_hardware_uuid() -> Result<String> {
-        let output = Command::new("ioreg")
-            .args(&["-d2", "-c", "IOPlatformExpertDevice"])
-            .output()
-            .context("Failed to run ioreg command")?;
+        let output = run_command("ioreg", &["-d2", "-c", "IOPlatformExpertDevice"])?;
         
         if !output.status.success() {
-            anyhow::bail!("ioreg command failed");
+            return Err(DeviceError::CommandFailed {
+                command: "ioreg".to_string(),
+                reason: format!("exited with {}", output.status),
+            });
         }
         
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        
-        // Parse: "IOPlatformUUID" = "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"
+        Self::parse_hardware_uuid(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Parse: "IOPlatformUUID" = "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"
+    fn parse_hardware_uuid(stdout: &str) -> Result<String> {
         for line in stdout.lines() {
             if line.contains("IOPlatformUUID") {
                 if let Some(uuid) = line.split('"').nth(3) {
@@ -47,25 +51,24 @@ impl MacHardwareInfo {
             }
         }
         
-        anyhow::bail!("IOPlatformUUID not found in ioreg output")
+        Err(DeviceError::ParseFailed("IOPlatformUUID from ioreg output".to_string()))
     }
     
     /// Get Serial Number
     fn get_serial_number() -> Result<String> {
-        let output = Command::new("ioreg")
-            .args(&["-l"])
-            .output()
-            .context("Failed to run ioreg command")?;
+        let output = run_command("ioreg", &["-l"])?;
         
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        
-        // 解析: "IOPlatformSerialNumber" = "XXXXXXXXXX"
+        Self::parse_serial_number(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// 解析: "IOPlatformSerialNumber" = "XXXXXXXXXX"
+    fn parse_serial_number(stdout: &str) -> Result<String> {
         for line in stdout.lines() {
             if line.contains("IOPlatformSerialNumber") {
                 if let Some(serial) = line.split('"').nth(3) {
                     // "0" 表示虚拟机
                     if serial == "0" {
-                        anyhow::bail!("Serial number is '0' - Virtual machine detected");
+                        return Err(DeviceError::VirtualMachine("serial number is '0'".to_string()));
                     }
                     if !serial.is_empty() {
                         return Ok(serial.to_string());
@@ -74,20 +77,17 @@ impl MacHardwareInfo {
             }
         }
         
-        anyhow::bail!("Serial number not found or invalid")
+        Err(DeviceError::SerialInvalid("not found in ioreg output".to_string()))
     }
     
     /// Get Model Identifier (e.g., MacBookPro18,1)
     fn get_model_identifier() -> Result<String> {
-        let output = Command::new("sysctl")
-            .args(&["-n", "hw.model"])
-            .output()
-            .context("Failed to run sysctl command")?;
+        let output = run_command("sysctl", &["-n", "hw.model"])?;
         
         let model = String::from_utf8_lossy(&output.stdout).trim().to_string();
         
         if model.is_empty() {
-            anyhow::bail!("hw.model is empty");
+            return Err(DeviceError::ParseFailed("hw.model (empty)".to_string()));
         }
         
         Ok(model)
@@ -95,10 +95,7 @@ impl MacHardwareInfo {
     
     /// Get Board ID
     fn get_board_id() -> Result<String> {
-        let output = Command::new("ioreg")
-            .args(&["-l"])
-            .output()
-            .context("Failed to run ioreg command")?;
+        let output = run_command("ioreg", &["-l"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
         
@@ -117,9 +114,7 @@ impl MacHardwareInfo {
         }
         
         // Fallback: use system_profiler
-        let output = Command::new("system_profiler")
-            .args(&["SPHardwareDataType"])
-            .output()?;
+        let output = run_command("system_profiler", &["SPHardwareDataType"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
         for line in stdout.lines() {
@@ -135,10 +130,7 @@ impl MacHardwareInfo {
     
     /// Get Boot ROM Version
     fn get_rom_version() -> Result<String> {
-        let output = Command::new("system_profiler")
-            .args(&["SPHardwareDataType"])
-            .output()
-            .context("Failed to run system_profiler")?;
+        let output = run_command("system_profiler", &["SPHardwareDataType"])?;
         
         let stdout = String::from_utf8_lossy(&output.stdout);
         
@@ -155,15 +147,12 @@ impl MacHardwareInfo {
     
     /// Get CPU Brand
     fn get_cpu_brand() -> Result<String> {
-        let output = Command::new("sysctl")
-            .args(&["-n", "machdep.cpu.brand_string"])
-            .output()
-            .context("Failed to run sysctl command")?;
+        let output = run_command("sysctl", &["-n", "machdep.cpu.brand_string"])?;
         
         let cpu = String::from_utf8_lossy(&output.stdout).trim().to_string();
         
         if cpu.is_empty() {
-            anyhow::bail!("CPU brand string is empty");
+            return Err(DeviceError::ParseFailed("CPU brand string (empty)".to_string()));
         }
         
         Ok(cpu)
@@ -179,4 +168,61 @@ impl MacHardwareInfo {
         
         format!("{:x}", hasher.finalize())
     }
+
+    /// Fingerprint of the stable core fields only, which decides the binding
+    ///
+    /// Model and board id are best-effort reads that can change on the same
+    /// machine; `generate_fingerprint` covers them too.
+    pub fn core_fingerprint(&self) -> String {
+        core_fingerprint(&self.hardware_uuid, &self.serial_number)
+    }
+}
+
+/// SHA-256 over a platform UUID and serial number
+pub fn core_fingerprint(hardware_uuid: &str, serial_number: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(hardware_uuid.as_bytes());
+    hasher.update(serial_number.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// SHA-256 of the serial number, shared with the server instead of the serial
+pub fn serial_hash(serial_number: &str) -> String {
+    format!("{:x}", Sha256::digest(serial_number.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_failures() {
+        let uuid = MacHardwareInfo::parse_hardware_uuid(r#"  "IOPlatformUUID" = "ABCD-1234""#).unwrap();
+        assert_eq!(uuid, "ABCD-1234");
+
+        assert!(matches!(
+            MacHardwareInfo::parse_hardware_uuid("no uuid here"),
+            Err(DeviceError::ParseFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_serial_errors() {
+        assert!(matches!(
+            MacHardwareInfo::parse_serial_number(r#""IOPlatformSerialNumber" = "0""#),
+            Err(DeviceError::VirtualMachine(_))
+        ));
+        assert!(matches!(
+            MacHardwareInfo::parse_serial_number(r#""IOPlatformSerialNumber" = """#),
+            Err(DeviceError::SerialInvalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_command() {
+        assert!(matches!(
+            run_command("sacas-no-such-command", &[]),
+            Err(DeviceError::CommandFailed { .. })
+        ));
+    }
 }
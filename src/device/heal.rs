@@ -0,0 +1,222 @@
+// sacas-daemon/src/device/heal.rs
+// Re-binding the config to a Mac whose platform UUID changed after a reinstall
+
+use anyhow::{Context, Result};
+
+use super::fingerprint::serial_hash;
+use super::{MacHardwareInfo, Signer, Stamper, SIGNATURE_ALG_HEADER};
+use crate::config::{Config, DeviceBinding};
+use crate::http::{EndpointCategory, HttpClient, ServerPool};
+
+/// A binding that differs from the hardware only in its UUID and fingerprint
+///
+/// Serial and model still match, which is what a macOS reinstall looks like
+/// on the same physical machine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FingerprintRotation {
+    pub old_uuid: String,
+    pub new_uuid: String,
+    pub old_fingerprint: String,
+    pub new_fingerprint: String,
+}
+
+/// The rotation that would re-bind `config` to `hw`, if this looks like a reinstall
+///
+/// Anything else, including a verified binding or a different machine,
+/// returns `None`.
+pub fn detect_reinstall(config: &Config, hw: &MacHardwareInfo) -> Option<FingerprintRotation> {
+    let DeviceBinding::Tampered(mismatches) = config.verify_device_binding(hw) else {
+        return None;
+    };
+
+    let only_uuid = mismatches
+        .iter()
+        .all(|m| m.field == "hardware_uuid" || m.field == "device_fingerprint");
+    if !only_uuid || !mismatches.iter().any(|m| m.field == "hardware_uuid") {
+        return None;
+    }
+
+    Some(FingerprintRotation {
+        old_uuid: config.device.hardware_uuid.clone(),
+        new_uuid: hw.hardware_uuid.clone(),
+        old_fingerprint: config.device.device_fingerprint.clone(),
+        new_fingerprint: hw.generate_fingerprint(),
+    })
+}
+
+/// Write `rotation` into the config's binding
+///
+/// Refuses unless the binding still differs from `hw` exactly as `rotation`
+/// describes, so a stale or hand-built rotation can't rebind the config.
+pub fn apply_rotation(config: &mut Config, hw: &MacHardwareInfo, rotation: &FingerprintRotation) -> Result<()> {
+    if detect_reinstall(config, hw).as_ref() != Some(rotation) {
+        anyhow::bail!("Device binding no longer matches the detected reinstall; run heal-fingerprint again");
+    }
+
+    config.device.hardware_uuid = rotation.new_uuid.clone();
+    config.device.device_fingerprint = rotation.new_fingerprint.clone();
+    Ok(())
+}
+
+/// Signed notice to the server that this device's fingerprint changed
+///
+/// Signing with the registered device key proves the same device is asking.
+pub struct SignedFingerprintRotation {
+    pub device_id: String,
+    pub timestamp: i64,
+    pub nonce: String,
+    pub signature: String,
+    pub algorithm: &'static str,
+    body_json: String,
+}
+
+impl SignedFingerprintRotation {
+    pub fn create_and_sign(
+        device_id: &str,
+        rotation: &FingerprintRotation,
+        serial_number: &str,
+        signer: &dyn Signer,
+        stamper: &Stamper,
+    ) -> Self {
+        let body_json = serde_json::json!({
+            "old_fingerprint": rotation.old_fingerprint,
+            "new_fingerprint": rotation.new_fingerprint,
+            "serial_hash": serial_hash(serial_number),
+        })
+        .to_string();
+
+        let stamp = stamper.stamp();
+        let mut request = Self {
+            device_id: device_id.to_string(),
+            timestamp: stamp.timestamp,
+            nonce: stamp.nonce,
+            signature: String::new(),
+            algorithm: signer.algorithm(),
+            body_json,
+        };
+        request.signature = signer.sign_base64(request.canonical_message().as_bytes());
+        request
+    }
+
+    /// Format: POST|/api/devices/{id}/fingerprint|BODY_JSON|timestamp|nonce
+    fn canonical_message(&self) -> String {
+        format!(
+            "POST|{}|{}|{}|{}",
+            self.path(),
+            self.body_json,
+            self.timestamp,
+            self.nonce
+        )
+    }
+
+    fn path(&self) -> String {
+        format!("/api/devices/{}/fingerprint", self.device_id)
+    }
+
+    /// Send the notice; the server must accept it before the config changes
+    pub async fn send(&self, http: &HttpClient, servers: &ServerPool) -> Result<()> {
+        http.acquire(EndpointCategory::Sync)?;
+
+        let response = servers
+            .send(&self.path(), |url| {
+                http.post(url)
+                    .header("content-type", "application/json")
+                    .header("x-device-id", &self.device_id)
+                    .header("x-signature", &self.signature)
+                    .header(SIGNATURE_ALG_HEADER, self.algorithm)
+                    .header("x-timestamp", self.timestamp.to_string())
+                    .header("x-nonce", &self.nonce)
+                    .body(self.body_json.clone())
+            })
+            .await
+            .context("Failed to send fingerprint rotation")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = http.read_text(response).await.unwrap_or_default();
+            anyhow::bail!("Server refused fingerprint rotation ({}): {}", status, error_text);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::DeviceIdentity;
+    use crate::test_support::{sample_config, MockServer};
+
+    fn bound_hardware(config: &Config) -> MacHardwareInfo {
+        MacHardwareInfo {
+            hardware_uuid: config.device.hardware_uuid.clone(),
+            serial_number: config.device.serial_number.clone(),
+            model_identifier: config.device.model_identifier.clone(),
+            board_id: "Mac-0000".to_string(),
+            rom_version: "1.0".to_string(),
+            cpu_brand: "Apple M1".to_string(),
+        }
+    }
+
+    fn bound_config() -> (Config, MacHardwareInfo) {
+        let mut config = sample_config();
+        let hw = bound_hardware(&config);
+        config.device.device_fingerprint = hw.generate_fingerprint();
+        (config, hw)
+    }
+
+    #[test]
+    fn test_detects_same_serial_new_uuid() {
+        let (config, mut hw) = bound_config();
+        assert_eq!(detect_reinstall(&config, &hw), None);
+
+        hw.hardware_uuid = "FFFFFFFF-0000-1111-2222-333344445555".to_string();
+        let rotation = detect_reinstall(&config, &hw).unwrap();
+        assert_eq!(rotation.old_uuid, config.device.hardware_uuid);
+        assert_eq!(rotation.new_fingerprint, hw.generate_fingerprint());
+
+        // A different serial is a different machine, not a reinstall
+        hw.serial_number = "C02OTHER".to_string();
+        assert_eq!(detect_reinstall(&config, &hw), None);
+    }
+
+    #[test]
+    fn test_rotation_applies_only_to_matching_binding() {
+        let (mut config, mut hw) = bound_config();
+        hw.hardware_uuid = "FFFFFFFF-0000-1111-2222-333344445555".to_string();
+        let rotation = detect_reinstall(&config, &hw).unwrap();
+
+        let forged = FingerprintRotation { new_uuid: "EEEEEEEE".to_string(), ..rotation.clone() };
+        assert!(apply_rotation(&mut config, &hw, &forged).is_err());
+        assert_ne!(config.device.hardware_uuid, hw.hardware_uuid);
+
+        apply_rotation(&mut config, &hw, &rotation).unwrap();
+        assert_eq!(config.verify_device_binding(&hw), DeviceBinding::Verified);
+    }
+
+    #[tokio::test]
+    async fn test_rotation_notice_is_signed() {
+        use ed25519_dalek::{Signature, Verifier};
+
+        let server = MockServer::respond_with(200, r#"{"success":true}"#).await;
+        let dir = tempfile::tempdir().unwrap();
+        let identity = DeviceIdentity::load_or_generate(&dir.path().join("device.key")).unwrap();
+        let rotation = FingerprintRotation {
+            old_uuid: "A".to_string(),
+            new_uuid: "B".to_string(),
+            old_fingerprint: "old".to_string(),
+            new_fingerprint: "new".to_string(),
+        };
+
+        let notice = SignedFingerprintRotation::create_and_sign("dev-123", &rotation, "C02XYZ", identity.signer(), &Stamper::default());
+        notice
+            .send(&HttpClient::default(), &ServerPool::new(std::slice::from_ref(&server.url)))
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/api/devices/dev-123/fingerprint");
+        assert_eq!(request.header("x-signature-alg"), Some("ed25519"));
+        let signature = Signature::from_slice(&base64::decode(request.header("x-signature").unwrap()).unwrap()).unwrap();
+        assert!(identity.verifying_key.verify(notice.canonical_message().as_bytes(), &signature).is_ok());
+    }
+}
@@ -4,10 +4,14 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use tracing::{info, warn};
 
+use super::Ed25519Signer;
+
 /// Device cryptographic identity manager
+#[derive(Clone)]
 pub struct DeviceIdentity {
     pub signing_key: SigningKey,
     pub verifying_key: VerifyingKey,
+    signer: Ed25519Signer,
 }
 
 impl DeviceIdentity {
@@ -28,16 +32,20 @@ impl DeviceIdentity {
     fn generate() -> Result<Self> {
         let mut rng = rand::rngs::OsRng;
         let signing_key = SigningKey::from_bytes(&rand::random());
-        let verifying_key = signing_key.verifying_key();
-        
-        Ok(Self {
+
+        Ok(Self::from_signing_key(signing_key))
+    }
+
+    pub fn from_signing_key(signing_key: SigningKey) -> Self {
+        Self {
+            verifying_key: signing_key.verifying_key(),
+            signer: Ed25519Signer::new(signing_key.clone()),
             signing_key,
-            verifying_key,
-        })
+        }
     }
 
     /// Load existing key from file
-    fn load(path: &Path) -> Result<Self> {
+    pub fn load(path: &Path) -> Result<Self> {
         let key_bytes = fs::read(path)
             .context("Failed to read device key file")?;
         
@@ -48,13 +56,7 @@ impl DeviceIdentity {
         let key_array: [u8; 32] = key_bytes.try_into()
             .map_err(|_| anyhow::anyhow!("Failed to convert key bytes"))?;
         
-        let signing_key = SigningKey::from_bytes(&key_array);
-        let verifying_key = signing_key.verifying_key();
-        
-        Ok(Self {
-            signing_key,
-            verifying_key,
-        })
+        Ok(Self::from_signing_key(SigningKey::from_bytes(&key_array)))
     }
 
     /// Save private key to file (with restricted permissions)
@@ -94,6 +96,11 @@ impl DeviceIdentity {
         let signature = self.sign(message);
         base64::encode(signature.to_bytes())
     }
+
+    /// Signer for outgoing requests, using the device key
+    pub fn signer(&self) -> &Ed25519Signer {
+        &self.signer
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,53 @@
+pub mod error;
 pub mod fingerprint;
 pub mod validator;
 pub mod anti_vm;
 pub mod registration;
 pub mod identity;
+pub mod attestation;
+pub mod enforcement;
+pub mod translation;
+pub mod stamp;
+pub mod heal;
+pub mod binding_guard;
+pub mod signer;
 
+pub use error::DeviceError;
 pub use fingerprint::MacHardwareInfo;
 pub use validator::MacValidator;
 pub use anti_vm::VMDetector;
-pub use registration::{DeviceRegistration, register_device};
+pub use registration::{check_registration, DeviceRegistration, DuplicateRegistration, register_device};
 pub use identity::DeviceIdentity;
+pub use attestation::AttestationBundle;
+pub use enforcement::{EnforcementConfig, EnforcementLevel, ValidationCheck};
+pub use translation::check_native;
+pub use stamp::{Stamp, Stamper};
+pub use binding_guard::BindingGuard;
+pub use signer::{Ed25519Signer, Signer, SigningContext, SIGNATURE_ALG_HEADER};
+
+use std::process::{Command, Output};
+
+/// Run a system command, mapping spawn failures to `DeviceError::CommandFailed`
+pub(crate) fn run_command(program: &str, args: &[&str]) -> Result<Output, DeviceError> {
+    Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| DeviceError::CommandFailed {
+            command: program.to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Runs system commands; tests substitute canned output
+pub trait CommandRunner {
+    fn run(&self, program: &str, args: &[&str]) -> Result<Output, DeviceError>;
+}
+
+/// Runs commands on the host
+pub struct SystemCommands;
+
+impl CommandRunner for SystemCommands {
+    fn run(&self, program: &str, args: &[&str]) -> Result<Output, DeviceError> {
+        run_command(program, args)
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
-use reqwest;
 use tracing::{info, warn, error};
 
+use crate::build_info::{BuildInfo, BUILD_INFO};
 use crate::device::{MacHardwareInfo, DeviceIdentity};
+use crate::http::{HttpClient, ServerPool};
+use crate::types::short_id;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceRegistration {
@@ -17,6 +19,7 @@ struct RegisterRequest {
     model: String,
     serial_hash: String,
     public_key: String,
+    build_info: BuildInfo,
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,51 +29,69 @@ struct RegisterResponse {
     message: Option<String>,
 }
 
+/// The server answered with a different device ID than the one on record
+///
+/// Usually the same machine registering again after a config or key was
+/// lost or restored out of step. Adopting the new ID would orphan the
+/// progress recorded under the old one.
+#[derive(Debug, Clone, thiserror::Error, PartialEq)]
+#[error("Server returned device ID {returned}, but this device is registered as {stored}")]
+pub struct DuplicateRegistration {
+    pub stored: String,
+    pub returned: String,
+}
+
+/// Fails when the server's device ID differs from the `stored` one, if any
+pub fn check_registration(stored: Option<&str>, returned: &str) -> Result<(), DuplicateRegistration> {
+    match stored {
+        Some(stored) if stored != returned => Err(DuplicateRegistration {
+            stored: stored.to_string(),
+            returned: returned.to_string(),
+        }),
+        _ => Ok(()),
+    }
+}
+
 /// Register device with SACAS backend (device-centric)
 pub async fn register_device(
+    http: &HttpClient,
     hw_info: &MacHardwareInfo,
     identity: &DeviceIdentity,
-    server_url: &str,
+    servers: &ServerPool,
 ) -> Result<DeviceRegistration> {
     info!("🤖 Registering autonomous device with server...");
     
     let fingerprint = hw_info.generate_fingerprint();
     let public_key = identity.public_key_base64();
     
-    // Generate serial_hash (SHA256 of serial number)
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    hasher.update(hw_info.serial_number.as_bytes());
-    let serial_hash = format!("{:x}", hasher.finalize());
+    let serial_hash = super::fingerprint::serial_hash(&hw_info.serial_number);
     
     let request = RegisterRequest {
         fingerprint: fingerprint.clone(),
         model: hw_info.model_identifier.clone(),
         serial_hash,
         public_key,
+        build_info: BUILD_INFO,
     };
     
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&format!("{}/api/devices/register", server_url))
-        .json(&request)
-        .send()
+    let response = servers
+        .send("/api/devices/register", |url| http.post(url).json(&request))
         .await
         .context("Failed to send registration request")?;
     
     if !response.status().is_success() {
         let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
+        let error_text = http.read_text(response).await.unwrap_or_default();
         anyhow::bail!("Registration failed with status {}: {}", status, error_text);
     }
     
-    let reg_response: RegisterResponse = response
-        .json()
+    let reg_response: RegisterResponse = http
+        .read_json(response)
         .await
         .context("Failed to parse registration response")?;
     
     let display_name = reg_response.display_name
-        .unwrap_or_else(|| format!("device_{}", &reg_response.device_id[..8]));
+        .unwrap_or_else(|| format!("device_{}", short_id(&reg_response.device_id)));
     
     info!("✅ Device registered successfully");
     info!("   Device ID: {}", reg_response.device_id);
@@ -100,4 +121,14 @@ mod tests {
         assert_eq!(reg.device_id, "test-id");
         assert_eq!(reg.display_name, "test-device");
     }
+
+    #[test]
+    fn test_registration_mismatch_detected() {
+        assert!(check_registration(None, "dev-new").is_ok());
+        assert!(check_registration(Some("dev-123"), "dev-123").is_ok());
+        assert_eq!(
+            check_registration(Some("dev-123"), "dev-new"),
+            Err(DuplicateRegistration { stored: "dev-123".to_string(), returned: "dev-new".to_string() })
+        );
+    }
 }
@@ -0,0 +1,118 @@
+// sacas-daemon/src/device/signer.rs
+// Signature schemes behind one trait, named on the wire by X-Signature-Alg
+
+use std::sync::Arc;
+
+use ed25519_dalek::SigningKey;
+use tracing::debug;
+
+use super::Stamp;
+
+/// Header naming the algorithm that produced X-Signature
+pub const SIGNATURE_ALG_HEADER: &str = "x-signature-alg";
+
+/// Signs canonical request messages
+///
+/// Requests carry `algorithm()` in X-Signature-Alg, so the server can accept
+/// a new scheme alongside Ed25519 while devices migrate.
+pub trait Signer: Send + Sync {
+    /// Identifier sent in X-Signature-Alg
+    fn algorithm(&self) -> &'static str;
+
+    /// Raw signature bytes over `message`
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+
+    fn sign_base64(&self, message: &[u8]) -> String {
+        base64::encode(self.sign(message))
+    }
+
+    fn sign_hex(&self, message: &[u8]) -> String {
+        hex::encode(self.sign(message))
+    }
+}
+
+/// Ed25519 with the device key; the only scheme the server accepts today
+///
+/// Clones share one copy of the key.
+#[derive(Clone)]
+pub struct Ed25519Signer {
+    key: Arc<SigningKey>,
+}
+
+impl Ed25519Signer {
+    pub const ALGORITHM: &'static str = "ed25519";
+
+    pub fn new(key: SigningKey) -> Self {
+        Self { key: Arc::new(key) }
+    }
+}
+
+impl Signer for Ed25519Signer {
+    fn algorithm(&self) -> &'static str {
+        Self::ALGORITHM
+    }
+
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        ed25519_dalek::Signer::sign(self.key.as_ref(), message).to_bytes().to_vec()
+    }
+}
+
+/// What went into one request's signature, for diagnosing a rejection
+///
+/// Holds only what is already on the wire: the request path, the canonical
+/// message, timestamp and nonce. Never the key, and not the signature either.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningContext {
+    /// Where the request went, to tell rejections apart in the log; the
+    /// canonical message is what was actually signed
+    pub path: String,
+    pub canonical: String,
+    pub timestamp: i64,
+    pub nonce: String,
+}
+
+impl SigningContext {
+    /// `METHOD|path after /api/|body|timestamp|nonce`, as combat and radar requests sign it
+    pub fn for_api(method: &str, path: &str, body: &str, stamp: &Stamp) -> Self {
+        let path = path.split("/api/").nth(1).unwrap_or("").to_string();
+        let canonical = format!("{}|{}|{}|{}|{}", method, path, body, stamp.timestamp, stamp.nonce);
+        Self { path, canonical, timestamp: stamp.timestamp, nonce: stamp.nonce.clone() }
+    }
+
+    /// Log the signing inputs at debug when `status` is a 4xx
+    pub fn log_if_rejected(&self, status: reqwest::StatusCode) {
+        if !status.is_client_error() {
+            return;
+        }
+        debug!(
+            "🔏 Signed request to {} rejected ({}): canonical={:?} timestamp={} nonce={}",
+            self.path, status, self.canonical, self.timestamp, self.nonce
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signature, Verifier};
+
+    #[test]
+    fn test_ed25519_signature_verifies() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let signer = Ed25519Signer::new(key.clone());
+
+        let bytes = signer.sign(b"POST|sync|{}|1|n");
+        let signature = Signature::from_slice(&bytes).unwrap();
+        assert!(key.verifying_key().verify(b"POST|sync|{}|1|n", &signature).is_ok());
+        assert_eq!(signer.sign_hex(b"x"), hex::encode(signer.sign(b"x")));
+        assert_eq!(signer.algorithm(), "ed25519");
+    }
+
+    #[test]
+    fn test_api_signing_context() {
+        let stamp = Stamp { timestamp: 1_700_000_000, nonce: "n-1".to_string() };
+        let context = SigningContext::for_api("GET", "/api/game/defense", "", &stamp);
+        assert_eq!(context.path, "game/defense");
+        assert_eq!(context.canonical, "GET|game/defense||1700000000|n-1");
+    }
+}
@@ -0,0 +1,93 @@
+// sacas-daemon/src/device/stamp.rs
+// Timestamp and nonce sources for signed requests, replaceable in tests
+
+use std::sync::Arc;
+
+/// Current Unix time in seconds
+pub trait Clock: Send + Sync {
+    fn now(&self) -> i64;
+}
+
+/// A fresh nonce per signed request
+pub trait NonceSource: Send + Sync {
+    fn nonce(&self) -> String;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        chrono::Utc::now().timestamp()
+    }
+}
+
+/// UUID v4 nonces
+pub struct UuidNonces;
+
+impl NonceSource for UuidNonces {
+    fn nonce(&self) -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+}
+
+/// The timestamp and nonce signed into one request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stamp {
+    pub timestamp: i64,
+    pub nonce: String,
+}
+
+/// Stamps signed requests; the default uses the system clock and UUIDs
+#[derive(Clone)]
+pub struct Stamper {
+    clock: Arc<dyn Clock>,
+    nonces: Arc<dyn NonceSource>,
+}
+
+impl Default for Stamper {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemClock), Arc::new(UuidNonces))
+    }
+}
+
+impl Stamper {
+    pub fn new(clock: Arc<dyn Clock>, nonces: Arc<dyn NonceSource>) -> Self {
+        Self { clock, nonces }
+    }
+
+    pub fn stamp(&self) -> Stamp {
+        Stamp {
+            timestamp: self.clock.now(),
+            nonce: self.nonces.nonce(),
+        }
+    }
+}
+
+/// Always the same time
+#[cfg(test)]
+pub struct FixedClock(pub i64);
+
+#[cfg(test)]
+impl Clock for FixedClock {
+    fn now(&self) -> i64 {
+        self.0
+    }
+}
+
+/// Always the same nonce
+#[cfg(test)]
+pub struct FixedNonce(pub &'static str);
+
+#[cfg(test)]
+impl NonceSource for FixedNonce {
+    fn nonce(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[cfg(test)]
+impl Stamper {
+    pub fn fixed(timestamp: i64, nonce: &'static str) -> Self {
+        Self::new(Arc::new(FixedClock(timestamp)), Arc::new(FixedNonce(nonce)))
+    }
+}
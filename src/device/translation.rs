@@ -0,0 +1,64 @@
+// sacas-daemon/src/device/translation.rs
+// Detects an x86_64 build running under Rosetta on Apple Silicon
+
+use super::{CommandRunner, DeviceError};
+
+/// Whether this process is being translated by Rosetta
+///
+/// `sysctl.proc_translated` is 1 for a translated process and 0 for a native
+/// one. It doesn't exist on Intel Macs, where nothing can be translated, so a
+/// failed or empty lookup counts as native.
+pub fn is_translated(runner: &dyn CommandRunner) -> Result<bool, DeviceError> {
+    let output = runner.run("sysctl", &["-n", "sysctl.proc_translated"])?;
+    if !output.status.success() {
+        return Ok(false);
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+/// Fails with `DeviceError::Translated` when running under Rosetta
+pub fn check_native(runner: &dyn CommandRunner) -> Result<(), DeviceError> {
+    if is_translated(runner)? {
+        return Err(DeviceError::Translated);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::{ExitStatus, Output};
+
+    struct Canned {
+        code: i32,
+        stdout: &'static str,
+    }
+
+    impl CommandRunner for Canned {
+        fn run(&self, program: &str, args: &[&str]) -> Result<Output, DeviceError> {
+            assert_eq!((program, args), ("sysctl", &["-n", "sysctl.proc_translated"][..]));
+            Ok(Output {
+                status: ExitStatus::from_raw(self.code << 8),
+                stdout: self.stdout.as_bytes().to_vec(),
+                stderr: Vec::new(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_translated_process_rejected() {
+        let rosetta = Canned { code: 0, stdout: "1\n" };
+        assert!(is_translated(&rosetta).unwrap());
+        assert!(matches!(check_native(&rosetta), Err(DeviceError::Translated)));
+    }
+
+    #[test]
+    fn test_native_process_accepted() {
+        assert!(check_native(&Canned { code: 0, stdout: "0\n" }).is_ok());
+
+        // Intel Macs have no such sysctl
+        assert!(check_native(&Canned { code: 1, stdout: "" }).is_ok());
+    }
+}
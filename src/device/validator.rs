@@ -1,109 +1,107 @@
-use anyhow::Result;
 use super::fingerprint::MacHardwareInfo;
+use super::DeviceError;
 
-// Whitelist of allowed Mac models (only genuine Macs allowed)
-const ALLOWED_MAC_MODELS: &[&str] = &[
+// Whitelist of allowed Mac models (only genuine Macs allowed), with the
+// marketing name shown for each
+const ALLOWED_MAC_MODELS: &[(&str, &str)] = &[
     // Mac mini
-    "Macmini9,1",  // Mac mini (M1, 2020)
-    "Mac14,3",     // Mac mini (M2, 2023)
-    "Mac14,12",    // Mac mini (M2 Pro, 2023)
-    "Mac16,10",    // Mac mini (M4, 2024)
+    ("Macmini9,1", "Mac mini (M1, 2020)"),
+    ("Mac14,3", "Mac mini (M2, 2023)"),
+    ("Mac14,12", "Mac mini (M2 Pro, 2023)"),
+    ("Mac16,10", "Mac mini (M4, 2024)"),
     
     // MacBook Pro - M1 Series (2021)
-    "MacBookPro18,1",  // MacBook Pro (14-inch, M1 Pro, 2021)
-    "MacBookPro18,2",  // MacBook Pro (14-inch, M1 Max, 2021)
-    "MacBookPro18,3",  // MacBook Pro (16-inch, M1 Pro, 2021)
-    "MacBookPro18,4",  // MacBook Pro (16-inch, M1 Max, 2021)
+    ("MacBookPro18,1", "MacBook Pro (14-inch, M1 Pro, 2021)"),
+    ("MacBookPro18,2", "MacBook Pro (14-inch, M1 Max, 2021)"),
+    ("MacBookPro18,3", "MacBook Pro (16-inch, M1 Pro, 2021)"),
+    ("MacBookPro18,4", "MacBook Pro (16-inch, M1 Max, 2021)"),
     
     // MacBook Pro - M2 Series (2022-2023)
-    "Mac14,7",    // MacBook Pro (13-inch, M2, 2022)
-    "Mac14,5",    // MacBook Pro (14-inch, M2 Pro, 2023)
-    "Mac14,9",    // MacBook Pro (14-inch, M2 Max, 2023)
-    "Mac14,6",    // MacBook Pro (16-inch, M2 Pro, 2023)
-    "Mac14,10",   // MacBook Pro (16-inch, M2 Max, 2023)
+    ("Mac14,7", "MacBook Pro (13-inch, M2, 2022)"),
+    ("Mac14,5", "MacBook Pro (14-inch, M2 Pro, 2023)"),
+    ("Mac14,9", "MacBook Pro (14-inch, M2 Max, 2023)"),
+    ("Mac14,6", "MacBook Pro (16-inch, M2 Pro, 2023)"),
+    ("Mac14,10", "MacBook Pro (16-inch, M2 Max, 2023)"),
     
     // MacBook Pro - M3 Series (2023)
-    "Mac15,3",    // MacBook Pro (14-inch, M3, 2023)
-    "Mac15,6",    // MacBook Pro (14-inch, M3 Pro, 2023)
-    "Mac15,7",    // MacBook Pro (14-inch, M3 Max, 2023)
-    "Mac15,8",    // MacBook Pro (16-inch, M3 Pro, 2023)
-    "Mac15,9",    // MacBook Pro (16-inch, M3 Pro, 2023)
-    "Mac15,10",   // MacBook Pro (16-inch, M3 Max, 2023)
-    "Mac15,11",   // MacBook Pro (16-inch, M3 Max, 2023)
+    ("Mac15,3", "MacBook Pro (14-inch, M3, 2023)"),
+    ("Mac15,6", "MacBook Pro (14-inch, M3 Pro, 2023)"),
+    ("Mac15,7", "MacBook Pro (14-inch, M3 Max, 2023)"),
+    ("Mac15,8", "MacBook Pro (16-inch, M3 Pro, 2023)"),
+    ("Mac15,9", "MacBook Pro (16-inch, M3 Pro, 2023)"),
+    ("Mac15,10", "MacBook Pro (16-inch, M3 Max, 2023)"),
+    ("Mac15,11", "MacBook Pro (16-inch, M3 Max, 2023)"),
     
     // MacBook Pro - M4 Series (2024)
-    "Mac16,1",    // MacBook Pro (14-inch, M4, 2024)
-    "Mac16,2",    // MacBook Pro (14-inch, M4 Pro, 2024)
-    "Mac16,3",    // MacBook Pro (14-inch, M4 Max, 2024)
-    "Mac16,4",    // MacBook Pro (14-inch, M4 Max, 2024) - High config
-    "Mac16,5",    // MacBook Pro (16-inch, M4 Pro, 2024)
-    "Mac16,6",    // MacBook Pro (16-inch, M4 Max, 2024)
-    "Mac16,7",    // MacBook Pro (16-inch, M4 Pro, 2024) - High config
+    ("Mac16,1", "MacBook Pro (14-inch, M4, 2024)"),
+    ("Mac16,2", "MacBook Pro (14-inch, M4 Pro, 2024)"),
+    ("Mac16,3", "MacBook Pro (14-inch, M4 Max, 2024)"),
+    ("Mac16,4", "MacBook Pro (14-inch, M4 Max, 2024) - High config"),
+    ("Mac16,5", "MacBook Pro (16-inch, M4 Pro, 2024)"),
+    ("Mac16,6", "MacBook Pro (16-inch, M4 Max, 2024)"),
+    ("Mac16,7", "MacBook Pro (16-inch, M4 Pro, 2024) - High config"),
     
     // MacBook Air - M1 Series (2020)
-    "MacBookAir10,1",  // MacBook Air (M1, 2020)
+    ("MacBookAir10,1", "MacBook Air (M1, 2020)"),
     
     // MacBook Air - M2 Series (2022-2023)
-    "Mac14,2",     // MacBook Air (13-inch, M2, 2022)
-    "Mac14,15",    // MacBook Air (15-inch, M2, 2023)
+    ("Mac14,2", "MacBook Air (13-inch, M2, 2022)"),
+    ("Mac14,15", "MacBook Air (15-inch, M2, 2023)"),
     
     // MacBook Air - M3 Series (2024)
-    "Mac15,12",    // MacBook Air (13-inch, M3, 2024)
-    "Mac15,13",    // MacBook Air (15-inch, M3, 2024)
+    ("Mac15,12", "MacBook Air (13-inch, M3, 2024)"),
+    ("Mac15,13", "MacBook Air (15-inch, M3, 2024)"),
     
     // MacBook Air - M4 Series (2025, predicted)
-    "Mac16,12",    // MacBook Air (13-inch, M4, 2025)
-    "Mac16,13",    // MacBook Air (15-inch, M4, 2025)
+    ("Mac16,12", "MacBook Air (13-inch, M4, 2025)"),
+    ("Mac16,13", "MacBook Air (15-inch, M4, 2025)"),
     
     // iMac - M1 Series (2021)
-    "iMac21,1",    // iMac (24-inch, M1, 2021, 2 ports)
-    "iMac21,2",    // iMac (24-inch, M1, 2021, 4 ports)
+    ("iMac21,1", "iMac (24-inch, M1, 2021, 2 ports)"),
+    ("iMac21,2", "iMac (24-inch, M1, 2021, 4 ports)"),
     
     // iMac - M3 Series (2023)
-    "Mac15,4",     // iMac (24-inch, M3, 2023, 2 ports)
-    "Mac15,5",     // iMac (24-inch, M3, 2023, 4 ports)
+    ("Mac15,4", "iMac (24-inch, M3, 2023, 2 ports)"),
+    ("Mac15,5", "iMac (24-inch, M3, 2023, 4 ports)"),
     
     // iMac - M4 Series (2024)
-    "Mac16,8",     // iMac (24-inch, M4, 2024)
-    "Mac16,9",     // iMac (24-inch, M4, 2024)
+    ("Mac16,8", "iMac (24-inch, M4, 2024)"),
+    ("Mac16,9", "iMac (24-inch, M4, 2024)"),
     
     // Mac Studio - M1 Series (2022)
-    "Mac13,1",     // Mac Studio (M1 Max, 2022)
-    "Mac13,2",     // Mac Studio (M1 Ultra, 2022)
+    ("Mac13,1", "Mac Studio (M1 Max, 2022)"),
+    ("Mac13,2", "Mac Studio (M1 Ultra, 2022)"),
     
     // Mac Studio - M2 Series (2023)
-    "Mac14,13",    // Mac Studio (M2 Max, 2023)
-    "Mac14,14",    // Mac Studio (M2 Ultra, 2023)
+    ("Mac14,13", "Mac Studio (M2 Max, 2023)"),
+    ("Mac14,14", "Mac Studio (M2 Ultra, 2023)"),
     
     // Mac Studio - M3/M4 Series (2025, predicted)
-    "Mac16,14",    // Mac Studio (M3/M4 Max, 2025)
-    "Mac16,15",    // Mac Studio (M3/M4 Ultra, 2025)
+    ("Mac16,14", "Mac Studio (M3/M4 Max, 2025)"),
+    ("Mac16,15", "Mac Studio (M3/M4 Ultra, 2025)"),
     
     // Mac Pro - M2 Series (2023)
-    "Mac14,8",     // Mac Pro (M2 Ultra, 2023)
+    ("Mac14,8", "Mac Pro (M2 Ultra, 2023)"),
     
     // Mac Pro - M4 Series (2025, predicted)
-    "Mac16,16",    // Mac Pro (M4 Ultra, 2025)
+    ("Mac16,16", "Mac Pro (M4 Ultra, 2025)"),
 ];
 
 pub struct MacValidator;
 
 impl MacValidator {
     /// Validate if this is a genuine Mac computer
-    pub fn validate(hw_info: &MacHardwareInfo) -> Result<()> {
+    pub fn validate(hw_info: &MacHardwareInfo) -> Result<(), DeviceError> {
         // 1. Check if model is in whitelist
-        if !ALLOWED_MAC_MODELS.contains(&hw_info.model_identifier.as_str()) {
-            anyhow::bail!(
-                "❌ Invalid Mac model: '{}'\n\n\
-                 Only genuine Mac computers are allowed to run SACAS.\n\
-                 Supported models: Mac mini, MacBook Pro, MacBook Air, iMac, Mac Studio, Mac Pro (Apple Silicon only)",
-                hw_info.model_identifier
-            );
+        if Self::known_model_name(&hw_info.model_identifier).is_none() {
+            return Err(DeviceError::InvalidModel(hw_info.model_identifier.clone()));
         }
         
         // 2. Check Serial Number is not "0" (VM signature)
         if hw_info.serial_number == "0" || hw_info.serial_number.is_empty() {
-            anyhow::bail!("❌ Invalid serial number. Virtual machines are not allowed.");
+            return Err(DeviceError::SerialInvalid(
+                "Virtual machines are not allowed.".to_string()
+            ));
         }
         
         
@@ -114,36 +112,107 @@ impl MacValidator {
         if hw_info.board_id == "0" 
             || hw_info.board_id.is_empty() 
             || hw_info.board_id == "unknown" {
-            anyhow::bail!("❌ Invalid board ID: Virtual machines not allowed");
+            return Err(DeviceError::VirtualMachine(
+                format!("invalid board ID '{}'", hw_info.board_id)
+            ));
         }
         // Accept any other board ID format (real Macs have diverse formats)
         
         // 4. Check CPU Brand (must be Apple Silicon)
         if !hw_info.cpu_brand.contains("Apple") {
-            anyhow::bail!(
-                "❌ Invalid CPU: '{}'\n\n\
-                 SACAS only supports Apple Silicon Macs.\n\
-                 Intel-based Macs are not supported.",
-                hw_info.cpu_brand
-            );
+            return Err(DeviceError::UnsupportedCpu(hw_info.cpu_brand.clone()));
         }
         
         Ok(())
     }
     
+    /// Marketing name for a whitelisted model id
+    fn known_model_name(model: &str) -> Option<&'static str> {
+        ALLOWED_MAC_MODELS
+            .iter()
+            .find(|(id, _)| *id == model)
+            .map(|(_, name)| *name)
+    }
+
     /// Get friendly name for Mac model
+    ///
+    /// Whitelisted ids get their full marketing name; anything else falls
+    /// back to a family name.
     pub fn get_friendly_name(model: &str) -> &'static str {
+        if let Some(name) = Self::known_model_name(model) {
+            return name;
+        }
+
         match model {
-            "Macmini9,1" => "Mac mini (M1, 2020)",
-            "Mac14,3" => "Mac mini (M2, 2023)",
-            "Mac14,12" => "Mac mini (M2 Pro, 2023)",
-            "Mac16,10" => "Mac mini (M4, 2024)",
             _ if model.starts_with("MacBookPro") => "MacBook Pro (Apple Silicon)",
             _ if model.starts_with("MacBookAir") => "MacBook Air (Apple Silicon)",
             _ if model.starts_with("iMac") => "iMac (Apple Silicon)",
             _ if model.starts_with("Mac13") || model.starts_with("Mac14,13") || model.starts_with("Mac14,14") || model.starts_with("Mac16,14") || model.starts_with("Mac16,15") => "Mac Studio",
             _ if model == "Mac14,8" || model == "Mac16,16" => "Mac Pro (Apple Silicon)",
+            _ if model.starts_with("Macmini") => "Mac mini (Apple Silicon)",
             _ => "Mac (Apple Silicon)",
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn genuine() -> MacHardwareInfo {
+        MacHardwareInfo {
+            hardware_uuid: "UUID".to_string(),
+            serial_number: "C02XYZ".to_string(),
+            model_identifier: "Mac16,10".to_string(),
+            board_id: "Mac-1234".to_string(),
+            rom_version: "unknown".to_string(),
+            cpu_brand: "Apple M4".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_genuine_mac_passes() {
+        assert!(MacValidator::validate(&genuine()).is_ok());
+    }
+
+    #[test]
+    fn test_error_variants() {
+        let hw = MacHardwareInfo { model_identifier: "VMware7,1".to_string(), ..genuine() };
+        assert!(matches!(MacValidator::validate(&hw), Err(DeviceError::InvalidModel(_))));
+
+        let hw = MacHardwareInfo { serial_number: "0".to_string(), ..genuine() };
+        assert!(matches!(MacValidator::validate(&hw), Err(DeviceError::SerialInvalid(_))));
+
+        let hw = MacHardwareInfo { board_id: "unknown".to_string(), ..genuine() };
+        assert!(matches!(MacValidator::validate(&hw), Err(DeviceError::VirtualMachine(_))));
+
+        let hw = MacHardwareInfo { cpu_brand: "Intel(R) Core(TM) i9".to_string(), ..genuine() };
+        assert!(matches!(MacValidator::validate(&hw), Err(DeviceError::UnsupportedCpu(_))));
+    }
+
+    #[test]
+    fn test_friendly_names_are_precise() {
+        assert_eq!(MacValidator::get_friendly_name("Mac16,10"), "Mac mini (M4, 2024)");
+        assert_eq!(MacValidator::get_friendly_name("MacBookPro18,3"), "MacBook Pro (16-inch, M1 Pro, 2021)");
+        assert_eq!(MacValidator::get_friendly_name("Mac15,13"), "MacBook Air (15-inch, M3, 2024)");
+        assert_eq!(MacValidator::get_friendly_name("Mac14,14"), "Mac Studio (M2 Ultra, 2023)");
+        assert_eq!(MacValidator::get_friendly_name("Mac16,3"), "MacBook Pro (14-inch, M4 Max, 2024)");
+        assert_eq!(MacValidator::get_friendly_name("Mac16,4"), "MacBook Pro (14-inch, M4 Max, 2024) - High config");
+        assert_eq!(MacValidator::get_friendly_name("Mac16,7"), "MacBook Pro (16-inch, M4 Pro, 2024) - High config");
+    }
+
+    #[test]
+    fn test_unknown_ids_fall_back_to_family() {
+        assert_eq!(MacValidator::get_friendly_name("MacBookPro99,1"), "MacBook Pro (Apple Silicon)");
+        assert_eq!(MacValidator::get_friendly_name("Mac13,3"), "Mac Studio");
+        assert_eq!(MacValidator::get_friendly_name("Mac99,1"), "Mac (Apple Silicon)");
+    }
+
+    #[test]
+    fn test_every_whitelisted_model_has_a_name() {
+        for (id, name) in ALLOWED_MAC_MODELS {
+            assert!(!name.is_empty(), "{}", id);
+            assert_eq!(MacValidator::get_friendly_name(id), *name);
+        }
+    }
+}
@@ -0,0 +1,146 @@
+// sacas-daemon/src/event_log.rs
+// In-memory feed of the daemon's log events, for `sacas-daemon tail`
+
+use std::fmt::Write as _;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Events buffered per tail subscriber before it starts dropping them
+pub const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+/// One log event as a tail subscriber sees it
+#[derive(Debug, Clone)]
+pub struct LogEvent {
+    pub at: DateTime<Utc>,
+    pub level: Level,
+    pub target: String,
+    /// The message followed by any other fields as `key=value`
+    pub message: String,
+}
+
+/// Fans log events out to live subscribers; nothing is kept once sent
+///
+/// Clones share the same feed. Recording with no subscriber attached is
+/// a no-op, so the layer costs next to nothing when nobody is tailing.
+#[derive(Clone)]
+pub struct EventLog {
+    tx: broadcast::Sender<LogEvent>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self { tx: broadcast::channel(EVENT_CHANNEL_CAPACITY).0 }
+    }
+}
+
+impl EventLog {
+    /// The feed the daemon's tracing subscriber writes to
+    pub fn global() -> &'static EventLog {
+        static GLOBAL: OnceLock<EventLog> = OnceLock::new();
+        GLOBAL.get_or_init(EventLog::default)
+    }
+
+    /// Tracing layer that records every event it sees into this feed
+    pub fn layer(&self) -> EventLayer {
+        EventLayer { log: self.clone() }
+    }
+
+    /// Events at `max_level` or more severe, from now on
+    pub fn subscribe(&self, max_level: Level) -> EventSubscriber {
+        EventSubscriber { rx: self.tx.subscribe(), max_level }
+    }
+
+    pub fn record(&self, event: LogEvent) {
+        // Only fails when nobody is subscribed
+        let _ = self.tx.send(event);
+    }
+}
+
+/// One tail session's view of the feed
+pub struct EventSubscriber {
+    rx: broadcast::Receiver<LogEvent>,
+    max_level: Level,
+}
+
+impl EventSubscriber {
+    /// Wait for the next event that passes the level filter; `None` once the log is gone
+    ///
+    /// A subscriber that falls behind skips the events it missed.
+    pub async fn next(&mut self) -> Option<LogEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) if event.level <= self.max_level => return Some(event),
+                Ok(_) | Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+pub struct EventLayer {
+    log: EventLog,
+}
+
+impl<S: Subscriber> Layer<S> for EventLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.log.tx.receiver_count() == 0 {
+            return;
+        }
+
+        let mut fields = MessageVisitor::default();
+        event.record(&mut fields);
+        self.log.record(LogEvent {
+            at: Utc::now(),
+            level: *event.metadata().level(),
+            target: event.metadata().target().to_string(),
+            message: fields.message.trim().to_string(),
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let rest = std::mem::take(&mut self.message);
+            let _ = write!(self.message, "{:?}{}", value, rest);
+        } else {
+            let _ = write!(self.message, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[tokio::test]
+    async fn test_events_after_subscribe_are_streamed() {
+        let log = EventLog::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(log.layer()));
+
+        tracing::warn!("before anyone listens");
+        let mut subscriber = log.subscribe(Level::WARN);
+
+        tracing::info!("too quiet for a warn filter");
+        tracing::warn!(anchor = "tokyo", "probe failed");
+        tracing::error!("sync halted");
+
+        let event = subscriber.next().await.unwrap();
+        assert_eq!(event.level, Level::WARN);
+        assert_eq!(event.message, "probe failed anchor=\"tokyo\"");
+        assert!(event.target.starts_with("sacas_daemon"));
+        assert_eq!(subscriber.next().await.unwrap().message, "sync halted");
+        assert!(subscriber.rx.is_empty());
+    }
+}
@@ -0,0 +1,86 @@
+// sacas-daemon/src/fleet.rs
+// Simulated devices for fleet testing: per-instance data dirs and synthetic hardware
+
+#[cfg(any(test, feature = "fleet-sim"))]
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::device::MacHardwareInfo;
+#[cfg(feature = "fleet-sim")]
+use anyhow::{Context, Result};
+#[cfg(feature = "fleet-sim")]
+use crate::config::Config;
+
+/// One simulated daemon in a fleet test, selected with `--fleet-instance <n>`
+///
+/// Each instance gets its own data dir, so its key, config, state and sync
+/// cursor never collide with another instance's, and hardware derived from
+/// its number, so the server sees a distinct device per instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FleetInstance(pub u32);
+
+impl FleetInstance {
+    /// This instance's data dir under `base`
+    #[cfg(any(test, feature = "fleet-sim"))]
+    pub fn data_dir(self, base: &Path) -> PathBuf {
+        base.join("fleet").join(format!("instance-{}", self.0))
+    }
+
+    /// Make this instance's data dir, under the normal one, the process's data dir
+    ///
+    /// Every config, key and state path follows `Config::data_dir`, so this
+    /// must run before any of them is read.
+    #[cfg(feature = "fleet-sim")]
+    pub fn activate(self) -> Result<PathBuf> {
+        let dir = self.data_dir(&Config::data_dir()?);
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Failed to create fleet data dir {}", dir.display()))?;
+        Config::override_data_dir(dir.clone())?;
+        Ok(dir)
+    }
+
+    /// Deterministic synthetic hardware, stable across restarts of this instance
+    pub fn hardware(self) -> MacHardwareInfo {
+        let digest = hex::encode_upper(Sha256::digest(format!("sacas-fleet-{}", self.0)));
+        MacHardwareInfo {
+            hardware_uuid: format!(
+                "{}-{}-{}-{}-{}",
+                &digest[0..8],
+                &digest[8..12],
+                &digest[12..16],
+                &digest[16..20],
+                &digest[20..32]
+            ),
+            serial_number: format!("FLEET{:07}", self.0),
+            model_identifier: "Mac14,3".to_string(),
+            board_id: format!("Mac-FLEET{}", &digest[32..48]),
+            rom_version: "fleet-sim".to_string(),
+            cpu_brand: "Apple M2 (simulated)".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::device::DeviceIdentity;
+
+    #[test]
+    fn test_instances_have_distinct_identities() {
+        let base = tempfile::tempdir().unwrap();
+        let (a, b) = (FleetInstance(0), FleetInstance(1));
+        assert_ne!(a.data_dir(base.path()), b.data_dir(base.path()));
+
+        let key_a = DeviceIdentity::load_or_generate(&a.data_dir(base.path()).join("device.key")).unwrap();
+        let key_b = DeviceIdentity::load_or_generate(&b.data_dir(base.path()).join("device.key")).unwrap();
+        assert_ne!(key_a.public_key_base64(), key_b.public_key_base64());
+
+        // Restarting an instance keeps its key and fingerprint
+        let again = DeviceIdentity::load_or_generate(&a.data_dir(base.path()).join("device.key")).unwrap();
+        assert_eq!(again.public_key_base64(), key_a.public_key_base64());
+        assert_eq!(a.hardware().generate_fingerprint(), FleetInstance(0).hardware().generate_fingerprint());
+        assert_ne!(a.hardware().generate_fingerprint(), b.hardware().generate_fingerprint());
+        assert_ne!(a.hardware().serial_number, b.hardware().serial_number);
+    }
+}
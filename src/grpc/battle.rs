@@ -0,0 +1,112 @@
+// sacas-daemon/src/grpc/battle.rs
+// Mapping a resolved battle into the ExecuteAttack response
+
+use tonic::Status;
+
+use crate::combat::client::{BattleResult, LayerResult};
+use crate::combat::cooldown::CooldownActive;
+use crate::combat::BudgetError;
+use crate::http::RateLimited;
+use crate::karma_gate::KarmaTooLow;
+use crate::state::BindingLost;
+use super::service::proto::{BattleLayerDetail, ExecuteAttackResponse};
+
+/// The full response for `result`, legacy summary fields included
+///
+/// A successful L2 intel roll reveals the defender's L3 allocation.
+pub fn attack_response(result: &BattleResult, passive_yield: f64) -> ExecuteAttackResponse {
+    let layers = &result.layers;
+
+    ExecuteAttackResponse {
+        session_id: result.battle_id.clone(),
+        l1_crushed: layers.l1.success,
+        l2_intel_success: layers.l2.success,
+        l2_revealed_d3: layers.l2.success.then_some(layers.l3.defense),
+        l3_parasitized: layers.l3.success,
+        stolen_entropy: result.loot.entropy_looted.value(),
+        passive_yield,
+        layers: [&layers.l1, &layers.l2, &layers.l3]
+            .into_iter()
+            .zip(1..)
+            .map(|(layer, n)| layer_detail(n, layer))
+            .collect(),
+        outcome: result.outcome.clone(),
+    }
+}
+
+fn layer_detail(layer: u32, result: &LayerResult) -> BattleLayerDetail {
+    BattleLayerDetail {
+        layer,
+        success: result.success,
+        attack: result.attack,
+        defense: result.defense,
+        threshold: result.threshold,
+        success_rate: result.success_rate,
+        roll: result.roll,
+        miss: result.miss.unwrap_or(false),
+    }
+}
+
+/// Status for a failed attack; refusals the caller can act on keep their own code
+pub fn attack_status(err: anyhow::Error) -> Status {
+    if err.is::<BudgetError>() || err.is::<KarmaTooLow>() || err.is::<BindingLost>() {
+        return Status::failed_precondition(err.to_string());
+    }
+    if err.is::<CooldownActive>() || err.is::<RateLimited>() {
+        return Status::resource_exhausted(err.to_string());
+    }
+    Status::unavailable(format!("Attack failed: {:#}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_battle_result_mapped() {
+        let result: BattleResult = serde_json::from_str(r#"{
+            "success": true,
+            "battle_id": "battle-42",
+            "outcome": "PARASITIZED",
+            "layers": {
+                "l1": {"success": true, "attack": 600, "defense": 400, "threshold": 500},
+                "l2": {"success": true, "attack": 200, "defense": 150, "success_rate": 0.57, "roll": 0.31},
+                "l3": {"success": true, "attack": 200, "defense": 90, "success_rate": 0.69, "roll": 0.12, "miss": false}
+            },
+            "loot": {"entropy_looted": 1250, "attacker_entropy_after": 9000, "defender_entropy_after": 3000}
+        }"#).unwrap();
+
+        let response = attack_response(&result, 0.5);
+        assert_eq!(response.session_id, "battle-42");
+        assert_eq!(response.outcome, "PARASITIZED");
+        assert!(response.l1_crushed && response.l2_intel_success && response.l3_parasitized);
+        assert_eq!(response.l2_revealed_d3, Some(90));
+        assert_eq!(response.stolen_entropy, 1250);
+
+        assert_eq!(response.layers.iter().map(|l| l.layer).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(response.layers[0].threshold, Some(500));
+        assert_eq!((response.layers[0].roll, response.layers[0].miss), (None, false));
+        assert_eq!((response.layers[1].success_rate, response.layers[1].roll), (Some(0.57), Some(0.31)));
+        assert_eq!((response.layers[2].attack, response.layers[2].defense), (200, 90));
+    }
+
+    #[test]
+    fn test_failed_intel_reveals_nothing() {
+        let result: BattleResult = serde_json::from_str(r#"{
+            "success": true,
+            "battle_id": "battle-43",
+            "outcome": "REPELLED",
+            "layers": {
+                "l1": {"success": false, "attack": 100, "defense": 400},
+                "l2": {"success": false, "attack": 0, "defense": 150, "miss": true},
+                "l3": {"success": false, "attack": 0, "defense": 90}
+            },
+            "loot": {"entropy_looted": 0, "attacker_entropy_after": 9000, "defender_entropy_after": 3000}
+        }"#).unwrap();
+
+        let response = attack_response(&result, 0.0);
+        assert_eq!(response.l2_revealed_d3, None);
+        assert!(response.layers[1].miss);
+        assert!(!response.l3_parasitized);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod service;
+mod battle;
+mod ledger_csv;
+mod radar;
 
 pub use service::{start_grpc_server, proto};
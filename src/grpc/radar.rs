@@ -0,0 +1,71 @@
+// sacas-daemon/src/grpc/radar.rs
+// Mapping radar scan results into the RunRadarScan response and visible nodes
+
+use chrono::{DateTime, Utc};
+use tonic::Status;
+
+use crate::http::RateLimited;
+use crate::karma_gate::KarmaTooLow;
+use crate::radar::client::{RadarScanResult, RadarTarget as ScannedTarget};
+use crate::radar::RadarError;
+use crate::state::BindingLost;
+use crate::types::{DefenseArray, Node};
+use super::service::proto::{Defense, RadarTarget, RunRadarScanResponse};
+
+pub fn scan_response(result: &RadarScanResult, seen_at: DateTime<Utc>) -> RunRadarScanResponse {
+    RunRadarScanResponse {
+        scan_id: result.scan_id.clone(),
+        cost: result.cost,
+        entropy_remaining: result.entropy_remaining,
+        targets: result.targets.iter().map(|t| RadarTarget {
+            device_id: t.device_id.clone(),
+            locked: is_locked(t),
+            distance: t.distance,
+            karma: t.karma,
+            karma_min: t.karma_range.map(|[min, _]| min),
+            karma_max: t.karma_range.map(|[_, max]| max),
+            defense: t.defense.as_ref().map(|d| Defense { l1: d.l1, l2: d.l2, l3: d.l3, cooldown_seconds: None }),
+            seen_at: seen_at.timestamp(),
+        }).collect(),
+    }
+}
+
+/// Scanned targets as visible nodes
+///
+/// Fuzzy targets get the middle of their karma range and full noise.
+pub fn visible_nodes(targets: &[ScannedTarget], seen_at: DateTime<Utc>) -> Vec<Node> {
+    targets.iter().map(|t| Node {
+        id: t.device_id.clone(),
+        karma: t.karma.or(t.karma_range.map(|[min, max]| min + (max - min) / 2)).unwrap_or(0),
+        distance: t.distance,
+        noise: if is_locked(t) { 0.0 } else { 1.0 },
+        estimated_defense: t.defense.as_ref().map(|d| DefenseArray {
+            l1: d.l1,
+            l2: d.l2,
+            l3: d.l3,
+            last_update: seen_at,
+            cooldown_ends: None,
+        }),
+    }).collect()
+}
+
+fn is_locked(target: &ScannedTarget) -> bool {
+    target.visibility == "LOCKED"
+}
+
+/// Status for a failed scan; refusals the caller can act on keep their own code
+pub fn scan_status(err: anyhow::Error) -> Status {
+    if let Some(radar) = err.downcast_ref::<RadarError>() {
+        return match radar {
+            RadarError::InsufficientEntropy { .. } => Status::failed_precondition(radar.to_string()),
+            RadarError::Cooldown { .. } => Status::resource_exhausted(radar.to_string()),
+        };
+    }
+    if err.is::<KarmaTooLow>() || err.is::<BindingLost>() {
+        return Status::failed_precondition(err.to_string());
+    }
+    if err.is::<RateLimited>() {
+        return Status::resource_exhausted(err.to_string());
+    }
+    Status::unavailable(format!("Radar scan failed: {:#}", err))
+}
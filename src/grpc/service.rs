@@ -1,5 +1,18 @@
 use tonic::{transport::Server, Request, Response, Status};
-use crate::state::StateManager;
+use crate::build_info::BUILD_INFO;
+use crate::combat::{estimate_battle, CombatClient, DefenseAllocator, LayerPoints};
+use crate::config::GrpcConfig;
+use crate::event_log::EventLog;
+use crate::network::{AnchorError, AnchorRegistry};
+use crate::radar::RadarClient;
+use crate::state::{BindingLost, StateManager};
+use crate::status_feed::StatusUpdate;
+use crate::sync::OfflineQueue;
+use crate::types::{GameState, SpendReason};
+use super::battle::{attack_response, attack_status};
+use super::ledger_csv::ledger_csv_lines;
+use super::radar::{scan_response, scan_status, visible_nodes};
+use std::pin::Pin;
 use std::sync::Arc;
 use tracing::info;
 
@@ -10,13 +23,176 @@ pub mod proto {
 use proto::game_service_server::{GameService, GameServiceServer};
 use proto::*;
 
+/// Ledger rows sent per ExportLedger chunk
+const LEDGER_CHUNK_ROWS: usize = 100;
+
 pub struct GameServiceImpl {
     state_manager: Arc<StateManager>,
+    anchors: AnchorRegistry,
+    sync_backlog: OfflineQueue,
+    admin_rpcs: bool,
+    /// Runs paid scans for RunRadarScan; unset until the device is registered
+    radar: Option<Arc<RadarClient>>,
+    /// Runs ExecuteAttack against the server; unset until the device is registered
+    combat: Option<Arc<CombatClient>>,
+    events: EventLog,
 }
 
 impl GameServiceImpl {
-    pub fn new(state_manager: Arc<StateManager>) -> Self {
-        Self { state_manager }
+    pub fn new(state_manager: Arc<StateManager>, anchors: AnchorRegistry, sync_backlog: OfflineQueue) -> Self {
+        Self {
+            state_manager,
+            anchors,
+            sync_backlog,
+            admin_rpcs: false,
+            radar: None,
+            combat: None,
+            events: EventLog::global().clone(),
+        }
+    }
+
+    /// Serve TailEvents from `events` instead of the daemon's own log
+    pub fn with_event_log(mut self, events: EventLog) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Serve RunRadarScan through `radar`
+    pub fn with_radar(mut self, radar: Option<Arc<RadarClient>>) -> Self {
+        self.radar = radar;
+        self
+    }
+
+    /// Serve ExecuteAttack through `combat`
+    pub fn with_combat(mut self, combat: Option<Arc<CombatClient>>) -> Self {
+        self.combat = combat;
+        self
+    }
+
+    /// Serve admin RPCs instead of refusing them
+    pub fn with_admin_rpcs(mut self, enabled: bool) -> Self {
+        self.admin_rpcs = enabled;
+        self
+    }
+
+    async fn anchor_list(&self) -> Vec<AnchorInfo> {
+        self.anchors.list().await.into_iter().map(|a| AnchorInfo {
+            id: a.id,
+            ip: a.ip,
+            region: a.region,
+        }).collect()
+    }
+}
+
+/// RPCs that change game state are refused once the device binding broke
+fn binding_status(err: BindingLost) -> Status {
+    Status::failed_precondition(err.to_string())
+}
+
+fn anchor_status(err: AnchorError) -> Status {
+    match err {
+        AnchorError::EmptyId | AnchorError::InvalidAddress(_) => Status::invalid_argument(err.to_string()),
+        AnchorError::DuplicateId(_) => Status::already_exists(err.to_string()),
+        AnchorError::NotFound(_) => Status::not_found(err.to_string()),
+        AnchorError::LastAnchor(_) => Status::failed_precondition(err.to_string()),
+        AnchorError::Persist(_) => Status::internal(err.to_string()),
+    }
+}
+
+impl From<&crate::types::Parasite> for Parasite {
+    fn from(parasite: &crate::types::Parasite) -> Self {
+        Self {
+            node_id: parasite.node_id.clone(),
+            tax_rate: parasite.tax_rate,
+            yield_per_tick: parasite.yield_per_tick,
+            total_collected: parasite.total_collected,
+            established_at: parasite.established_at.timestamp(),
+        }
+    }
+}
+
+impl From<&crate::types::Climate> for Climate {
+    fn from(climate: &crate::types::Climate) -> Self {
+        let modifiers = climate.typed_modifiers();
+        Self {
+            code: climate.code.clone(),
+            description: climate.description.clone(),
+            modifiers: Some(ClimateModifiers {
+                yield_multiplier: modifiers.yield_multiplier,
+                attack_multiplier: modifiers.attack_multiplier,
+                defense_multiplier: modifiers.defense_multiplier,
+                other: modifiers.other.into_iter().collect(),
+            }),
+        }
+    }
+}
+
+impl From<crate::moltbook::client::MoltbookAgent> for MoltbookProfile {
+    fn from(agent: crate::moltbook::client::MoltbookAgent) -> Self {
+        Self {
+            name: agent.name,
+            description: agent.description,
+            karma: agent.karma,
+            follower_count: agent.follower_count,
+            following_count: agent.following_count,
+            is_claimed: agent.is_claimed,
+            is_active: agent.is_active,
+            created_at: agent.created_at,
+            last_active: agent.last_active,
+            owner: agent.owner.map(|o| MoltbookOwner {
+                x_handle: o.x_handle,
+                x_name: o.x_name,
+                x_avatar: o.x_avatar,
+                x_bio: o.x_bio,
+                x_follower_count: o.x_follower_count,
+                x_following_count: o.x_following_count,
+                x_verified: o.x_verified,
+            }),
+        }
+    }
+}
+
+fn status_response(state: &GameState, state_manager: &StateManager, sync_backlog: &OfflineQueue) -> GetStatusResponse {
+    let cooldown_seconds = if let Some(cooldown_ends) = state.player.defense.cooldown_ends {
+        let now = chrono::Utc::now();
+        if cooldown_ends > now {
+            Some((cooldown_ends - now).num_seconds())
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
+    GetStatusResponse {
+        player_id: state.player.id.clone(),
+        karma: state.player.karma,
+        entropy: state.player.entropy,
+        capacity: state.player.capacity,
+        yield_per_tick: state.player.calculate_yield() as f64,
+        defense: Some(Defense {
+            l1: state.player.defense.l1,
+            l2: state.player.defense.l2,
+            l3: state.player.defense.l3,
+            cooldown_seconds,
+        }),
+        position: Some(Position {
+            latency_vector: state.player.position.latency_vector.clone(),
+            coords: state.player.position.coords.map(|(x, y)| Coords { x, y }),
+        }),
+        network_quality: state.player.network_quality,
+        parasite_count: state.parasites.len() as u32,
+        passive_income: state.player.passive_income,
+        climate: Some(Climate::from(&state.climate)),
+        pending_sync_deltas: sync_backlog.len() as u32,
+        pending_entropy: sync_backlog.pending_entropy(),
+        build_info: Some(BuildInfo {
+            version: BUILD_INFO.version.to_string(),
+            git_hash: BUILD_INFO.git_hash.to_string(),
+        }),
+        checkpoint_failing: state_manager.is_checkpoint_failing(),
+        mining_healthy: state_manager.is_mining_healthy(),
+        key_rejected: state_manager.is_key_rejected(),
     }
 }
 
@@ -27,44 +203,61 @@ impl GameService for GameServiceImpl {
         _request: Request<GetStatusRequest>,
     ) -> Result<Response<GetStatusResponse>, Status> {
         let state = self.state_manager.get_snapshot().await;
-        
-        let cooldown_seconds = if let Some(cooldown_ends) = state.player.defense.cooldown_ends {
-            let now = chrono::Utc::now();
-            if cooldown_ends > now {
-                Some((cooldown_ends - now).num_seconds())
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        Ok(Response::new(status_response(&state, &self.state_manager, &self.sync_backlog)))
+    }
 
-        let response = GetStatusResponse {
-            player_id: state.player.id.clone(),
-            karma: state.player.karma,
-            entropy: state.player.entropy,
-            capacity: state.player.capacity,
-            yield_per_tick: state.player.calculate_yield() as f64,
-            defense: Some(Defense {
-                l1: state.player.defense.l1,
-                l2: state.player.defense.l2,
-                l3: state.player.defense.l3,
-                cooldown_seconds,
-            }),
-            position: Some(Position {
-                latency_vector: state.player.position.latency_vector,
-                coords: state.player.position.coords.map(|(x, y)| Coords { x, y }),
-            }),
-            network_quality: state.player.network_quality,
-            parasite_count: state.parasites.len() as u32,
-            passive_income: state.player.passive_income,
-            climate: Some(Climate {
-                code: state.climate.code,
-                description: state.climate.description,
-            }),
+    type WatchStatusStream = Pin<Box<dyn futures_util::Stream<Item = Result<GetStatusResponse, Status>> + Send>>;
+
+    async fn watch_status(
+        &self,
+        _request: Request<WatchStatusRequest>,
+    ) -> Result<Response<Self::WatchStatusStream>, Status> {
+        use futures_util::StreamExt;
+
+        // Subscribe first so no change between the two is missed
+        let subscriber = self.state_manager.subscribe_status();
+        let current = self.state_manager.get_snapshot().await;
+        let first = status_response(&current, &self.state_manager, &self.sync_backlog);
+
+        let context = (self.state_manager.clone(), self.sync_backlog.clone());
+        let updates = futures_util::stream::unfold((subscriber, context), |(mut subscriber, context)| async move {
+            let state = match subscriber.next().await? {
+                StatusUpdate::Changed(state) | StatusUpdate::Resync { state, .. } => state,
+            };
+            let response = status_response(&state, &context.0, &context.1);
+            Some((response, (subscriber, context)))
+        });
+
+        Ok(Response::new(Box::pin(futures_util::stream::once(async { first }).chain(updates).map(Ok))))
+    }
+
+    type TailEventsStream = Pin<Box<dyn futures_util::Stream<Item = Result<LogEvent, Status>> + Send>>;
+
+    async fn tail_events(
+        &self,
+        request: Request<TailEventsRequest>,
+    ) -> Result<Response<Self::TailEventsStream>, Status> {
+        let level = request.into_inner().level;
+        let max_level = match level.as_str() {
+            "" => tracing::Level::WARN,
+            name => name
+                .parse()
+                .map_err(|_| Status::invalid_argument(format!("unknown log level {:?}", name)))?,
         };
 
-        Ok(Response::new(response))
+        let subscriber = self.events.subscribe(max_level);
+        let events = futures_util::stream::unfold(subscriber, |mut subscriber| async move {
+            let event = subscriber.next().await?;
+            let event = LogEvent {
+                timestamp_ms: event.at.timestamp_millis(),
+                level: event.level.to_string(),
+                target: event.target,
+                message: event.message,
+            };
+            Some((Ok(event), subscriber))
+        });
+
+        Ok(Response::new(Box::pin(events)))
     }
 
     async fn scan_network(
@@ -94,6 +287,25 @@ impl GameService for GameServiceImpl {
         Ok(Response::new(ScanNetworkResponse { nodes }))
     }
 
+    async fn run_radar_scan(
+        &self,
+        request: Request<RunRadarScanRequest>,
+    ) -> Result<Response<RunRadarScanResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
+        let Some(radar) = &self.radar else {
+            return Err(Status::unavailable("Radar scans need a registered device"));
+        };
+
+        let req = request.into_inner();
+        let result = radar.scan(req.max_distance).await.map_err(scan_status)?;
+
+        let seen_at = chrono::Utc::now();
+        self.state_manager.update_visible_nodes(visible_nodes(&result.targets, seen_at)).await;
+        info!("📡 On-demand radar scan {}: {} targets for {} Ω", result.scan_id, result.targets.len(), result.cost);
+
+        Ok(Response::new(scan_response(&result, seen_at)))
+    }
+
     async fn simulate_battle(
         &self,
         request: Request<SimulateBattleRequest>,
@@ -129,26 +341,26 @@ impl GameService for GameServiceImpl {
         &self,
         request: Request<ExecuteAttackRequest>,
     ) -> Result<Response<ExecuteAttackResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
         let req = request.into_inner();
         
-        // Mock implementation
+        let Some(combat) = &self.combat else {
+            return Err(Status::unavailable("Attacks need a registered device"));
+        };
+
+        // The server sizes the attack itself; `attack_array` is not sent
         info!("🎯 Executing attack on {}", req.target_id);
-        
-        Ok(Response::new(ExecuteAttackResponse {
-            session_id: uuid::Uuid::new_v4().to_string(),
-            l1_crushed: true,
-            l2_intel_success: false,
-            l2_revealed_d3: None,
-            l3_parasitized: true,
-            stolen_entropy: 5000,
-            passive_yield: 0.5,
-        }))
+        let result = combat.attack(&req.target_id).await.map_err(attack_status)?;
+        let passive_yield = self.state_manager.get_snapshot().await.player.passive_income;
+
+        Ok(Response::new(attack_response(&result, passive_yield)))
     }
 
     async fn update_defense(
         &self,
         request: Request<UpdateDefenseRequest>,
     ) -> Result<Response<UpdateDefenseResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
         let req = request.into_inner();
         
         if req.defense_array.len() != 3 {
@@ -180,18 +392,22 @@ impl GameService for GameServiceImpl {
     ) -> Result<Response<GetParasitesResponse>, Status> {
         let state = self.state_manager.get_snapshot().await;
         
-        let parasites = state.parasites.iter().map(|p| {
-            Parasite {
-                node_id: p.node_id.clone(),
-                tax_rate: p.tax_rate,
-                yield_per_tick: p.yield_per_tick,
-                total_collected: p.total_collected,
-            }
-        }).collect();
+        let parasites = state.parasites.iter().map(Parasite::from).collect();
 
         Ok(Response::new(GetParasitesResponse { parasites }))
     }
 
+    async fn get_parasite(
+        &self,
+        request: Request<GetParasiteRequest>,
+    ) -> Result<Response<GetParasiteResponse>, Status> {
+        let node_id = request.into_inner().node_id;
+        let parasite = self.state_manager.get_parasite(&node_id).await
+            .ok_or_else(|| Status::not_found(format!("No parasite on {}", node_id)))?;
+
+        Ok(Response::new(GetParasiteResponse { parasite: Some(Parasite::from(&parasite)) }))
+    }
+
     async fn get_climate(
         &self,
         _request: Request<GetClimateRequest>,
@@ -199,27 +415,653 @@ impl GameService for GameServiceImpl {
         let state = self.state_manager.get_snapshot().await;
         
         Ok(Response::new(GetClimateResponse {
-            climate: Some(Climate {
-                code: state.climate.code,
-                description: state.climate.description,
-            }),
+            climate: Some(Climate::from(&state.climate)),
+        }))
+    }
+
+    async fn suggest_defense(
+        &self,
+        request: Request<SuggestDefenseRequest>,
+    ) -> Result<Response<SuggestDefenseResponse>, Status> {
+        let req = request.into_inner();
+        let state = self.state_manager.get_snapshot().await;
+
+        let budget = req.budget.unwrap_or(state.player.entropy);
+        let rec = DefenseAllocator::recommend(budget, &state.climate);
+
+        Ok(Response::new(SuggestDefenseResponse {
+            l1: rec.l1,
+            l2: rec.l2,
+            l3: rec.l3,
+            climate_code: state.climate.code,
+            reason: rec.reason,
+        }))
+    }
+
+    async fn estimate_defense_strength(
+        &self,
+        request: Request<EstimateDefenseStrengthRequest>,
+    ) -> Result<Response<EstimateDefenseStrengthResponse>, Status> {
+        let req = request.into_inner();
+        let current = LayerPoints::from(&self.state_manager.get_snapshot().await.player.defense);
+
+        let attack = LayerPoints { l1: req.attack_l1, l2: req.attack_l2, l3: req.attack_l3 };
+        let defense = LayerPoints {
+            l1: req.defense_l1.unwrap_or(current.l1),
+            l2: req.defense_l2.unwrap_or(current.l2),
+            l3: req.defense_l3.unwrap_or(current.l3),
+        };
+        let odds = estimate_battle(attack, defense);
+
+        Ok(Response::new(EstimateDefenseStrengthResponse {
+            l1_crush_probability: odds.l1_win,
+            l2_intel_probability: odds.l2_success,
+            l3_parasitize_probability: odds.l3_parasitize,
+            defense_l1: defense.l1,
+            defense_l2: defense.l2,
+            defense_l3: defense.l3,
+            note: "Estimate from the published battle model; climate and server-side factors are not applied".to_string(),
+        }))
+    }
+
+    async fn list_anchors(
+        &self,
+        _request: Request<ListAnchorsRequest>,
+    ) -> Result<Response<ListAnchorsResponse>, Status> {
+        Ok(Response::new(ListAnchorsResponse {
+            anchors: self.anchor_list().await,
+        }))
+    }
+
+    async fn add_anchor(
+        &self,
+        request: Request<AddAnchorRequest>,
+    ) -> Result<Response<AddAnchorResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
+        let anchor = request
+            .into_inner()
+            .anchor
+            .ok_or_else(|| Status::invalid_argument("anchor is required"))?;
+
+        self.anchors.add(crate::config::Anchor {
+            id: anchor.id,
+            ip: anchor.ip,
+            region: anchor.region,
+        }).await.map_err(anchor_status)?;
+
+        Ok(Response::new(AddAnchorResponse {
+            anchors: self.anchor_list().await,
+        }))
+    }
+
+    async fn remove_anchor(
+        &self,
+        request: Request<RemoveAnchorRequest>,
+    ) -> Result<Response<RemoveAnchorResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
+        let id = request.into_inner().id;
+        self.anchors.remove(&id).await.map_err(anchor_status)?;
+
+        Ok(Response::new(RemoveAnchorResponse {
+            anchors: self.anchor_list().await,
+        }))
+    }
+
+    async fn get_moltbook_profile(
+        &self,
+        _request: Request<GetMoltbookProfileRequest>,
+    ) -> Result<Response<GetMoltbookProfileResponse>, Status> {
+        let profile = self.state_manager.get_snapshot().await.moltbook_profile
+            .ok_or_else(|| Status::not_found("No Moltbook profile fetched yet"))?;
+
+        Ok(Response::new(GetMoltbookProfileResponse {
+            profile: Some(profile.into()),
         }))
     }
+
+    async fn get_anomaly_history(
+        &self,
+        _request: Request<GetAnomalyHistoryRequest>,
+    ) -> Result<Response<GetAnomalyHistoryResponse>, Status> {
+        let history = self.state_manager.get_snapshot().await.anomalies;
+
+        Ok(Response::new(GetAnomalyHistoryResponse {
+            records: history.records.into_iter().map(|r| AnomalyRecord {
+                timestamp: r.at.timestamp(),
+                confidence: r.confidence,
+                reasons: r.reasons,
+            }).collect(),
+            consecutive_warnings: history.consecutive,
+        }))
+    }
+
+    async fn clear_anomaly_history(
+        &self,
+        _request: Request<ClearAnomalyHistoryRequest>,
+    ) -> Result<Response<ClearAnomalyHistoryResponse>, Status> {
+        self.state_manager.ensure_binding().map_err(binding_status)?;
+        let cleared = self.state_manager.clear_anomaly_history().await;
+        info!("🧹 Cleared {} anomaly warnings", cleared);
+
+        Ok(Response::new(ClearAnomalyHistoryResponse {
+            cleared: cleared as u32,
+        }))
+    }
+
+    type ExportLedgerStream = Pin<Box<dyn futures_util::Stream<Item = Result<LedgerCsvChunk, Status>> + Send>>;
+
+    async fn export_ledger(
+        &self,
+        request: Request<ExportLedgerRequest>,
+    ) -> Result<Response<Self::ExportLedgerStream>, Status> {
+        let req = request.into_inner();
+        let since = req.since.map(|secs| chrono::DateTime::from_timestamp(secs, 0));
+        let until = req.until.map(|secs| chrono::DateTime::from_timestamp(secs, 0));
+        if since == Some(None) || until == Some(None) {
+            return Err(Status::invalid_argument("timestamp out of range"));
+        }
+
+        let ledger = self.state_manager.get_snapshot().await.ledger;
+        let lines = ledger_csv_lines(&ledger, since.flatten(), until.flatten());
+        let chunks: Vec<LedgerCsvChunk> = lines
+            .chunks(LEDGER_CHUNK_ROWS)
+            .map(|rows| LedgerCsvChunk { csv: rows.iter().map(|row| format!("{}\n", row)).collect() })
+            .collect();
+
+        Ok(Response::new(Box::pin(futures_util::stream::iter(chunks.into_iter().map(Ok)))))
+    }
+
+    async fn spend_entropy(
+        &self,
+        request: Request<SpendEntropyRequest>,
+    ) -> Result<Response<SpendEntropyResponse>, Status> {
+        if !self.admin_rpcs {
+            return Err(Status::permission_denied("Admin RPCs are disabled (grpc.allow_admin_rpcs)"));
+        }
+        self.state_manager.ensure_binding().map_err(binding_status)?;
+
+        let req = request.into_inner();
+        if req.amount == 0 || req.reason.trim().is_empty() {
+            return Err(Status::invalid_argument("amount and reason are required"));
+        }
+
+        let balance = self.state_manager
+            .spend_entropy(req.amount, SpendReason::Admin(req.reason.clone()))
+            .await
+            .map_err(|e| Status::failed_precondition(e.to_string()))?;
+        info!("💸 Admin spend of {} ({}); {} left", req.amount, req.reason, balance);
+
+        Ok(Response::new(SpendEntropyResponse { balance }))
+    }
 }
 
 pub async fn start_grpc_server(
     addr: String,
     state_manager: Arc<StateManager>,
+    anchors: AnchorRegistry,
+    sync_backlog: OfflineQueue,
+    radar: Option<Arc<RadarClient>>,
+    combat: Option<Arc<CombatClient>>,
+    limits: GrpcConfig,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let addr = addr.parse()?;
-    let service = GameServiceImpl::new(state_manager);
 
     info!("🚀 gRPC server listening on {}", addr);
 
-    Server::builder()
-        .add_service(GameServiceServer::new(service))
+    server_builder(&limits)
+        .add_service(game_service(state_manager, anchors, sync_backlog, radar, combat, &limits))
         .serve(addr)
         .await?;
 
     Ok(())
 }
+
+/// Server builder with the configured transport limits applied
+fn server_builder(limits: &GrpcConfig) -> Server {
+    Server::builder()
+        .max_concurrent_streams(Some(limits.max_concurrent_streams))
+        .concurrency_limit_per_connection(limits.concurrency_limit_per_connection)
+}
+
+fn game_service(
+    state_manager: Arc<StateManager>,
+    anchors: AnchorRegistry,
+    sync_backlog: OfflineQueue,
+    radar: Option<Arc<RadarClient>>,
+    combat: Option<Arc<CombatClient>>,
+    limits: &GrpcConfig,
+) -> GameServiceServer<GameServiceImpl> {
+    GameServiceServer::new(
+        GameServiceImpl::new(state_manager, anchors, sync_backlog)
+            .with_admin_rpcs(limits.allow_admin_rpcs)
+            .with_radar(radar)
+            .with_combat(combat),
+    )
+        .max_decoding_message_size(limits.max_decoding_message_size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+    use tonic::codegen::http::uri::PathAndQuery;
+
+    #[tokio::test]
+    async fn test_oversized_request_rejected() {
+        let limits = GrpcConfig {
+            max_decoding_message_size: 1024,
+            ..GrpcConfig::default()
+        };
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let incoming = futures_util::stream::unfold(listener, |listener| async move {
+            Some((listener.accept().await.map(|(stream, _)| stream), listener))
+        });
+        tokio::spawn(
+            server_builder(&limits)
+                .add_service(game_service(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default(), None, None, &limits))
+                .serve_with_incoming(incoming),
+        );
+
+        let channel = tonic::transport::Channel::from_shared(format!("http://{}", addr))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = tonic::client::Grpc::new(channel);
+        client.ready().await.unwrap();
+
+        let request = SimulateBattleRequest {
+            target_id: "x".repeat(4096),
+            ..Default::default()
+        };
+        let status = client
+            .unary::<_, SimulateBattleResponse, _>(
+                Request::new(request),
+                PathAndQuery::from_static("/sacas.GameService/SimulateBattle"),
+                tonic::codec::ProstCodec::default(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::OutOfRange);
+    }
+
+    #[tokio::test]
+    async fn test_anchor_rpcs() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        let added = service.add_anchor(Request::new(AddAnchorRequest {
+            anchor: Some(AnchorInfo {
+                id: "quad9".to_string(),
+                ip: "9.9.9.9".to_string(),
+                region: "global".to_string(),
+            }),
+        })).await.unwrap().into_inner();
+        assert_eq!(added.anchors.len(), 1);
+
+        let duplicate = service.add_anchor(Request::new(AddAnchorRequest {
+            anchor: Some(added.anchors[0].clone()),
+        })).await.unwrap_err();
+        assert_eq!(duplicate.code(), tonic::Code::AlreadyExists);
+
+        let missing = service.remove_anchor(Request::new(RemoveAnchorRequest {
+            id: "nope".to_string(),
+        })).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        service.remove_anchor(Request::new(RemoveAnchorRequest {
+            id: "quad9".to_string(),
+        })).await.unwrap();
+        let listed = service.list_anchors(Request::new(ListAnchorsRequest {})).await.unwrap().into_inner();
+        assert!(listed.anchors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_sync_backlog() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let backlog = OfflineQueue::default();
+        let service = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), backlog.clone());
+
+        backlog.enqueue(40, 1.0);
+        backlog.enqueue(25, 1.0);
+        let status = service.get_status(Request::new(GetStatusRequest {})).await.unwrap().into_inner();
+
+        assert_eq!(status.pending_sync_deltas, 2);
+        assert_eq!(status.pending_entropy, 65);
+    }
+
+    #[tokio::test]
+    async fn test_status_reports_build_info() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        let status = service.get_status(Request::new(GetStatusRequest {})).await.unwrap().into_inner();
+
+        let build_info = status.build_info.unwrap();
+        assert_eq!(build_info.version, env!("CARGO_PKG_VERSION"));
+        assert!(!build_info.git_hash.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_climate_modifiers_round_trip() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        state_manager.update_climate(crate::types::Climate {
+            code: "DROUGHT".to_string(),
+            description: "Scarce entropy".to_string(),
+            modifiers: serde_json::json!({"yield_multiplier": 0.6, "defense_multiplier": 1.25, "radar_range": 0.8}),
+            start_time: chrono::Utc::now(),
+        }).await;
+
+        let climate = service.get_climate(Request::new(GetClimateRequest {}))
+            .await.unwrap().into_inner().climate.unwrap();
+        assert_eq!(climate.code, "DROUGHT");
+        assert_eq!(climate.description, "Scarce entropy");
+
+        let modifiers = climate.modifiers.unwrap();
+        assert_eq!(modifiers.yield_multiplier, 0.6);
+        assert_eq!(modifiers.attack_multiplier, 1.0);
+        assert_eq!(modifiers.defense_multiplier, 1.25);
+        assert_eq!(modifiers.other.get("radar_range"), Some(&0.8));
+    }
+
+    #[tokio::test]
+    async fn test_moltbook_profile_rpc() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        let missing = service.get_moltbook_profile(Request::new(GetMoltbookProfileRequest {})).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        let agent: crate::moltbook::client::MoltbookAgent = serde_json::from_value(serde_json::json!({
+            "name": "sacas-agent", "karma": 1500, "follower_count": 7,
+            "is_claimed": false, "is_active": true,
+            "owner": { "x_handle": "owner", "x_verified": false }
+        })).unwrap();
+        state_manager.update_moltbook_profile(agent).await;
+
+        let profile = service.get_moltbook_profile(Request::new(GetMoltbookProfileRequest {}))
+            .await.unwrap().into_inner().profile.unwrap();
+        assert_eq!(profile.name, "sacas-agent");
+        assert_eq!(profile.karma, 1500);
+        assert_eq!(profile.follower_count, Some(7));
+        assert_eq!(profile.following_count, None);
+        assert!(!profile.is_claimed);
+        assert_eq!(profile.owner.unwrap().x_handle.as_deref(), Some("owner"));
+    }
+
+    #[tokio::test]
+    async fn test_get_parasite_rpc() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let request = |node_id: &str| Request::new(GetParasiteRequest { node_id: node_id.to_string() });
+
+        let missing = service.get_parasite(request("host-1")).await.unwrap_err();
+        assert_eq!(missing.code(), tonic::Code::NotFound);
+
+        let established = crate::types::Parasite::established("host-1", None, Some(2.5));
+        state_manager.add_parasite(established.clone()).await;
+        let parasite = service.get_parasite(request("host-1")).await.unwrap().into_inner().parasite.unwrap();
+        assert_eq!(parasite.yield_per_tick, 2.5);
+        assert_eq!(parasite.tax_rate, crate::types::PARASITE_TAX_RATE);
+        assert_eq!(parasite.established_at, established.established_at.timestamp());
+    }
+
+    #[tokio::test]
+    async fn test_estimate_defense_strength_rpc() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let mut defense = state_manager.get_snapshot().await.player.defense;
+        (defense.l1, defense.l2, defense.l3) = (150, 200, 300);
+        state_manager.replace_defense(defense).await;
+
+        let request = |defense_l3| Request::new(EstimateDefenseStrengthRequest {
+            attack_l1: 181,
+            attack_l2: 150,
+            attack_l3: 316,
+            defense_l3,
+            ..Default::default()
+        });
+
+        // Against the current defense, L1 breaks and L3 just clears its threshold
+        let odds = service.estimate_defense_strength(request(None)).await.unwrap().into_inner();
+        assert_eq!((odds.defense_l1, odds.defense_l2, odds.defense_l3), (150, 200, 300));
+        assert_eq!(odds.l1_crush_probability, 1.0);
+        assert!(odds.l3_parasitize_probability > 0.9);
+        assert!(odds.note.contains("Estimate"));
+
+        // A hypothetical stronger core holds
+        let odds = service.estimate_defense_strength(request(Some(400))).await.unwrap().into_inner();
+        assert_eq!(odds.defense_l3, 400);
+        assert_eq!(odds.l3_parasitize_probability, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_history_rpcs() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        state_manager.record_sync_anomaly(Some(crate::types::AnomalyRecord {
+            at: chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            confidence: 0.85,
+            reasons: vec!["uptime regressed".to_string()],
+        })).await;
+
+        let history = service.get_anomaly_history(Request::new(GetAnomalyHistoryRequest {}))
+            .await.unwrap().into_inner();
+        assert_eq!(history.consecutive_warnings, 1);
+        assert_eq!(history.records.len(), 1);
+        assert_eq!(history.records[0].timestamp, 1_700_000_000);
+        assert_eq!(history.records[0].confidence, 0.85);
+        assert_eq!(history.records[0].reasons, ["uptime regressed"]);
+
+        let cleared = service.clear_anomaly_history(Request::new(ClearAnomalyHistoryRequest {}))
+            .await.unwrap().into_inner();
+        assert_eq!(cleared.cleared, 1);
+        let history = service.get_anomaly_history(Request::new(GetAnomalyHistoryRequest {}))
+            .await.unwrap().into_inner();
+        assert!(history.records.is_empty());
+        assert_eq!(history.consecutive_warnings, 0);
+    }
+
+    #[tokio::test]
+    async fn test_spend_entropy_rpc_guarded() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(300).await;
+        let request = || Request::new(SpendEntropyRequest { amount: 200, reason: "sink test".to_string() });
+
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let denied = service.spend_entropy(request()).await.unwrap_err();
+        assert_eq!(denied.code(), tonic::Code::PermissionDenied);
+
+        let service = service.with_admin_rpcs(true);
+        assert_eq!(service.spend_entropy(request()).await.unwrap().into_inner().balance, 100);
+
+        let short = service.spend_entropy(request()).await.unwrap_err();
+        assert_eq!(short.code(), tonic::Code::FailedPrecondition);
+
+        let ledger = state_manager.get_snapshot().await.ledger;
+        assert_eq!(ledger.len(), 1);
+        assert_eq!(ledger[0].reason, SpendReason::Admin("sink test".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_mutating_rpcs_refused_after_binding_lost() {
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(300).await;
+        state_manager.set_binding_lost();
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default())
+            .with_admin_rpcs(true);
+
+        let defense = service.update_defense(Request::new(UpdateDefenseRequest { defense_array: vec![1, 2, 3] })).await;
+        assert_eq!(defense.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        let spend = service.spend_entropy(Request::new(SpendEntropyRequest { amount: 10, reason: "test".to_string() })).await;
+        assert_eq!(spend.unwrap_err().code(), tonic::Code::FailedPrecondition);
+        let cleared = service.clear_anomaly_history(Request::new(ClearAnomalyHistoryRequest {})).await;
+        assert_eq!(cleared.unwrap_err().code(), tonic::Code::FailedPrecondition);
+
+        let player = state_manager.get_snapshot().await.player;
+        assert_eq!((player.entropy, player.defense.l1), (300, 0));
+
+        // Reads still work
+        assert!(service.get_status(Request::new(GetStatusRequest {})).await.is_ok());
+    }
+
+    fn radar_service(state_manager: &Arc<StateManager>, server_url: &str) -> GameServiceImpl {
+        let radar = RadarClient::new(
+            crate::http::HttpClient::default(),
+            crate::http::ServerPool::new(&[server_url.to_string()]),
+            "dev".to_string(),
+            crate::test_support::test_signer(),
+            (**state_manager).clone(),
+        );
+        GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default())
+            .with_radar(Some(Arc::new(radar)))
+    }
+
+    #[tokio::test]
+    async fn test_run_radar_scan_updates_visible_nodes() {
+        let body = r#"{"success":true,"scan_id":"s1","cost":10,"entropy_remaining":90,
+            "summary":{"total":2,"locked":1,"fuzzy":1},"targets":[
+            {"device_id":"near","visibility":"LOCKED","distance":120.0,"karma":400,
+             "defense":{"l1":5,"l2":6,"l3":7,"total":18}},
+            {"device_id":"far","visibility":"FUZZY","distance":900.0,"karma_range":[100,300]}]}"#;
+        let server = crate::test_support::MockServer::respond_with(200, body).await;
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(100).await;
+
+        let response = radar_service(&state_manager, &server.url)
+            .run_radar_scan(Request::new(RunRadarScanRequest { max_distance: Some(1000) }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!((response.scan_id.as_str(), response.cost), ("s1", 10));
+        assert!(response.targets[0].locked);
+        assert_eq!(response.targets[0].defense.as_ref().unwrap().l3, 7);
+        assert_eq!((response.targets[1].karma_min, response.targets[1].karma_max), (Some(100), Some(300)));
+        assert!(response.targets.iter().all(|t| t.seen_at > 0));
+
+        let state = state_manager.get_snapshot().await;
+        assert_eq!(state.player.entropy, 90);
+        let nodes: Vec<(&str, u64)> = state.visible_nodes.iter().map(|n| (n.id.as_str(), n.karma)).collect();
+        assert_eq!(nodes, [("near", 400), ("far", 200)]);
+    }
+
+    #[tokio::test]
+    async fn test_run_radar_scan_without_entropy() {
+        let server = crate::test_support::MockServer::respond_with(500, "should not be called").await;
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(5).await;
+
+        let err = radar_service(&state_manager, &server.url)
+            .run_radar_scan(Request::new(RunRadarScanRequest { max_distance: None }))
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+        assert!(server.requests().is_empty());
+        assert_eq!(state_manager.get_snapshot().await.player.entropy, 5);
+
+        // Without a registered device there is no radar to run
+        let unregistered = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let err = unregistered.run_radar_scan(Request::new(RunRadarScanRequest { max_distance: None })).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_execute_attack_maps_server_result() {
+        let body = r#"{"success":true,"battle_id":"battle-7","outcome":"PARASITIZED",
+            "layers":{"l1":{"success":true,"attack":600,"defense":400},
+                      "l2":{"success":true,"attack":200,"defense":150},
+                      "l3":{"success":true,"attack":200,"defense":90}},
+            "loot":{"entropy_looted":1250,"attacker_entropy_after":9000,"defender_entropy_after":3000}}"#;
+        let server = crate::test_support::MockServer::respond_with(200, body).await;
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let combat = crate::test_support::combat_client(&server.url, (*state_manager).clone());
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default())
+            .with_combat(Some(Arc::new(combat)));
+
+        let request = ExecuteAttackRequest { target_id: "victim".to_string(), attack_array: vec![] };
+        let response = service.execute_attack(Request::new(request.clone())).await.unwrap().into_inner();
+
+        assert_eq!((response.session_id.as_str(), response.outcome.as_str()), ("battle-7", "PARASITIZED"));
+        assert_eq!((response.stolen_entropy, response.l2_revealed_d3), (1250, Some(90)));
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/api/game/battle/attack");
+        assert!(requests[0].body.contains("victim"));
+
+        // Without a registered device there is nothing to attack with
+        let unregistered = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let err = unregistered.execute_attack(Request::new(request)).await.unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unavailable);
+    }
+
+    #[tokio::test]
+    async fn test_export_ledger_streams_running_totals() {
+        use futures_util::StreamExt;
+
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        state_manager.update_entropy(1000).await;
+        for _ in 0..150 {
+            state_manager.spend_entropy(2, SpendReason::Radar).await.unwrap();
+        }
+        let service = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default());
+
+        let stream = service.export_ledger(Request::new(ExportLedgerRequest { since: None, until: None }))
+            .await.unwrap().into_inner();
+        let chunks: Vec<String> = stream.map(|chunk| chunk.unwrap().csv).collect().await;
+        assert_eq!(chunks.len(), 2);
+
+        let csv = chunks.concat();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "timestamp,source,delta,running_total");
+        assert_eq!(lines.len(), 151);
+        assert!(lines[1].ends_with(",radar,-2,998"));
+        assert!(lines[150].ends_with(",radar,-2,700"));
+    }
+
+    #[tokio::test]
+    async fn test_tail_events_streams_new_events() {
+        use futures_util::StreamExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let events = EventLog::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(events.layer()));
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager, AnchorRegistry::new(vec![]), OfflineQueue::default())
+            .with_event_log(events);
+
+        let bad = service.tail_events(Request::new(TailEventsRequest { level: "loud".to_string() })).await;
+        assert_eq!(bad.err().unwrap().code(), tonic::Code::InvalidArgument);
+
+        let request = Request::new(TailEventsRequest { level: "info".to_string() });
+        let mut stream = service.tail_events(request).await.unwrap().into_inner();
+        tracing::debug!("below the filter");
+        tracing::info!("mining tick");
+        tracing::warn!("probe failed");
+
+        let first = stream.next().await.unwrap().unwrap();
+        assert_eq!((first.level.as_str(), first.message.as_str()), ("INFO", "mining tick"));
+        assert_eq!(stream.next().await.unwrap().unwrap().level, "WARN");
+    }
+
+    #[tokio::test]
+    async fn test_watch_status_streams_changes() {
+        use futures_util::StreamExt;
+
+        let state_manager = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let service = GameServiceImpl::new(state_manager.clone(), AnchorRegistry::new(vec![]), OfflineQueue::default());
+        let mut stream = service.watch_status(Request::new(WatchStatusRequest {})).await.unwrap().into_inner();
+
+        assert_eq!(stream.next().await.unwrap().unwrap().entropy, 0);
+
+        state_manager.update_entropy(250).await;
+        assert_eq!(stream.next().await.unwrap().unwrap().entropy, 250);
+    }
+}
@@ -0,0 +1,208 @@
+// sacas-daemon/src/history.rs
+// Oldest-first histories pruned by entry count and age
+
+use std::collections::VecDeque;
+use std::ops::Deref;
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::types::{AnomalyRecord, LedgerEntry, ANOMALY_HISTORY_LIMIT, LEDGER_LIMIT};
+
+/// Anything recorded with the time it happened
+pub trait Timestamped {
+    fn at(&self) -> DateTime<Utc>;
+}
+
+impl Timestamped for LedgerEntry {
+    fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+}
+
+impl Timestamped for AnomalyRecord {
+    fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+}
+
+/// How much of one history to keep
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct RetentionConfig {
+    /// Most entries kept (0 keeps none)
+    pub max_entries: usize,
+    /// Entries older than this many days are dropped (0 disables)
+    #[serde(default)]
+    pub max_age_days: u64,
+}
+
+impl RetentionConfig {
+    pub fn entries(max_entries: usize) -> Self {
+        Self { max_entries, max_age_days: 0 }
+    }
+
+    fn cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.max_age_days == 0 {
+            return None;
+        }
+        let days = i64::try_from(self.max_age_days).unwrap_or(i64::MAX);
+        Some(now - Duration::try_days(days).unwrap_or(Duration::MAX))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct HistoryConfig {
+    pub ledger: RetentionConfig,
+    pub anomalies: RetentionConfig,
+    /// Seconds between sweeps that drop aged-out entries from idle histories
+    pub sweep_interval_secs: u64,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            ledger: RetentionConfig::entries(LEDGER_LIMIT),
+            anomalies: RetentionConfig::entries(ANOMALY_HISTORY_LIMIT),
+            sweep_interval_secs: 3600,
+        }
+    }
+}
+
+/// Entries oldest first, trimmed from the front on every insert and sweep
+///
+/// Serializes as a plain list. Entries are appended in time order, so
+/// pruning only pops from the front and costs nothing when nothing expired.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct BoundedHistory<T> {
+    entries: VecDeque<T>,
+}
+
+impl<T> Default for BoundedHistory<T> {
+    fn default() -> Self {
+        Self { entries: VecDeque::new() }
+    }
+}
+
+impl<T: Timestamped> BoundedHistory<T> {
+    /// Append `entry`, then drop whatever `retention` no longer allows
+    pub fn push(&mut self, entry: T, retention: &RetentionConfig, now: DateTime<Utc>) {
+        self.entries.push_back(entry);
+        self.prune(retention, now);
+    }
+
+    /// Drop entries past the count or age limit; returns how many went
+    pub fn prune(&mut self, retention: &RetentionConfig, now: DateTime<Utc>) -> usize {
+        let before = self.entries.len();
+
+        let excess = self.entries.len().saturating_sub(retention.max_entries);
+        self.entries.drain(..excess);
+
+        if let Some(cutoff) = retention.cutoff(now) {
+            while self.entries.front().is_some_and(|e| e.at() < cutoff) {
+                self.entries.pop_front();
+            }
+        }
+
+        before - self.entries.len()
+    }
+}
+
+impl<T> BoundedHistory<T> {
+    /// Entries oldest first, for updating in place
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.entries.iter_mut()
+    }
+}
+
+impl<T> Deref for BoundedHistory<T> {
+    type Target = VecDeque<T>;
+
+    fn deref(&self) -> &VecDeque<T> {
+        &self.entries
+    }
+}
+
+/// Entries must arrive oldest first; nothing is pruned until asked
+impl<T> FromIterator<T> for BoundedHistory<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self { entries: iter.into_iter().collect() }
+    }
+}
+
+impl<T> IntoIterator for BoundedHistory<T> {
+    type Item = T;
+    type IntoIter = std::collections::vec_deque::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a BoundedHistory<T> {
+    type Item = &'a T;
+    type IntoIter = std::collections::vec_deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Event(DateTime<Utc>);
+
+    impl Timestamped for Event {
+        fn at(&self) -> DateTime<Utc> {
+            self.0
+        }
+    }
+
+    fn day(n: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + n * 86_400, 0).unwrap()
+    }
+
+    #[test]
+    fn test_count_pruning_drops_oldest() {
+        let retention = RetentionConfig::entries(3);
+        let mut history = BoundedHistory::default();
+        for n in 0..5 {
+            history.push(Event(day(n)), &retention, day(n));
+        }
+
+        assert_eq!(history.iter().map(|e| e.0).collect::<Vec<_>>(), [day(2), day(3), day(4)]);
+
+        // A lowered limit applies on the next sweep
+        assert_eq!(history.prune(&RetentionConfig::entries(1), day(4)), 2);
+        assert_eq!(history.len(), 1);
+    }
+
+    #[test]
+    fn test_age_pruning_on_insert_and_sweep() {
+        let retention = RetentionConfig { max_entries: 100, max_age_days: 7 };
+        let mut history = BoundedHistory::default();
+        history.push(Event(day(0)), &retention, day(0));
+        history.push(Event(day(5)), &retention, day(5));
+
+        // Inserting on day 8 ages out day 0 but keeps day 5
+        history.push(Event(day(8)), &retention, day(8));
+        assert_eq!(history.iter().map(|e| e.0).collect::<Vec<_>>(), [day(5), day(8)]);
+
+        // An idle history still ages out on a sweep
+        assert_eq!(history.prune(&retention, day(13)), 1);
+        assert_eq!(history.prune(&retention, day(30)), 1);
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_serializes_as_plain_list() {
+        let mut history = BoundedHistory::default();
+        history.push(Event(day(0)), &RetentionConfig::entries(10), day(0));
+        let json = serde_json::to_string(&history).unwrap();
+        assert_eq!(json, r#"["2023-11-14T22:13:20Z"]"#);
+        assert_eq!(serde_json::from_str::<BoundedHistory<Event>>(&json).unwrap(), history);
+    }
+}
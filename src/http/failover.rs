@@ -0,0 +1,193 @@
+// sacas-daemon/src/http/failover.rs
+// Ordered list of SACAS server base URLs with last-good failover
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use reqwest::{RequestBuilder, Response};
+use tracing::{error, info, warn};
+
+use super::{send_traced, UpgradeRequired};
+
+/// The configured SACAS servers, shared by every client that talks to them
+///
+/// Requests go to the last server that answered first, then to the others in
+/// configured order. Clones share the last-good marker.
+#[derive(Debug, Clone)]
+pub struct ServerPool {
+    urls: Arc<Vec<String>>,
+    last_good: Arc<AtomicUsize>,
+}
+
+impl ServerPool {
+    pub fn new(urls: &[String]) -> Self {
+        Self {
+            urls: Arc::new(urls.iter().map(|u| u.trim_end_matches('/').to_string()).collect()),
+            last_good: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Base URL of the server that most recently answered
+    pub fn current(&self) -> &str {
+        self.urls
+            .get(self.last_good.load(Ordering::Relaxed))
+            .map_or("", String::as_str)
+    }
+
+    /// Server indices in the order they should be tried
+    fn attempt_order(&self) -> Vec<usize> {
+        let first = self.last_good.load(Ordering::Relaxed);
+        std::iter::once(first)
+            .filter(|&i| i < self.urls.len())
+            .chain((0..self.urls.len()).filter(|&i| i != first))
+            .collect()
+    }
+
+    /// Send `path` to each server in turn until one answers
+    ///
+    /// `build` turns a full URL into a request; each attempt carries its own
+    /// `X-Request-ID` (see `send_traced`). Connection errors move on to the
+    /// next server. Idempotent requests also move on after a 5xx or any other
+    /// send error; anything else may already have been processed, so its 5xx
+    /// response or error is returned as-is (see `outcome_unknown`). Any other
+    /// response is returned as-is and marks that server last-good. If every
+    /// server fails, the last 5xx response is returned, or the last
+    /// connection error. A 426 fails with `UpgradeRequired` straight away,
+    /// since the servers share an API.
+    pub async fn send<F>(&self, path: &str, build: F) -> Result<Response>
+    where
+        F: Fn(&str) -> RequestBuilder,
+    {
+        let mut last_response = None;
+        let mut last_error = None;
+
+        for index in self.attempt_order() {
+            let base = &self.urls[index];
+            let (client, request) = build(&format!("{}{}", base, path)).build_split();
+            let request = request.context("Failed to build request")?;
+            let retry_safe = request.method().is_idempotent();
+
+            match send_traced(RequestBuilder::from_parts(client, request)).await {
+                Ok(response) if response.status() == reqwest::StatusCode::UPGRADE_REQUIRED => {
+                    let refused = UpgradeRequired { server: base.clone() };
+                    error!("⛔ {}", refused);
+                    return Err(refused.into());
+                }
+                Ok(response) if response.status().is_server_error() && retry_safe => {
+                    warn!("🔀 {} answered {}; trying next server", base, response.status());
+                    last_response = Some(response);
+                }
+                Ok(response) if response.status().is_server_error() => {
+                    warn!("⚠️  {} answered {}; not retrying a request it may have processed", base, response.status());
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    if self.last_good.swap(index, Ordering::Relaxed) != index {
+                        info!("🔀 Switched to server {}", base);
+                    }
+                    return Ok(response);
+                }
+                Err(e) if retry_safe || e.is_connect() => {
+                    warn!("🔀 {} unreachable: {}; trying next server", base, e);
+                    last_error = Some(e);
+                }
+                Err(e) => {
+                    return Err(anyhow::Error::new(e).context(format!("Request to {} failed", base)));
+                }
+            }
+        }
+
+        match (last_response, last_error) {
+            (Some(response), _) => Ok(response),
+            (None, Some(e)) => Err(anyhow::Error::new(e)
+                .context(format!("All {} configured servers are unreachable", self.urls.len()))),
+            (None, None) => anyhow::bail!("No servers configured"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpClient;
+    use crate::test_support::MockServer;
+
+    /// A URL on a port nothing listens on
+    async fn dead_url() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+        url
+    }
+
+    #[tokio::test]
+    async fn test_failover_tries_servers_in_order() {
+        let down = dead_url().await;
+        let failing = MockServer::respond_with(503, "maintenance").await;
+        let healthy = MockServer::respond_with(200, "{}").await;
+        let pool = ServerPool::new(&[down, failing.url.clone(), healthy.url.clone()]);
+        let http = HttpClient::default();
+
+        let response = pool.send("/api/ping", |url| http.get(url)).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(failing.requests().len(), 1);
+        assert_eq!(healthy.requests()[0].path, "/api/ping");
+        assert_eq!(pool.current(), healthy.url);
+    }
+
+    #[tokio::test]
+    async fn test_last_good_server_preferred() {
+        let primary = MockServer::respond_with(502, "bad gateway").await;
+        let mirror = MockServer::respond_with(200, "{}").await;
+        let pool = ServerPool::new(&[primary.url.clone(), mirror.url.clone()]);
+        let http = HttpClient::default();
+
+        pool.send("/api/ping", |url| http.get(url)).await.unwrap();
+        pool.clone().send("/api/ping", |url| http.get(url)).await.unwrap();
+
+        assert_eq!(primary.requests().len(), 1);
+        assert_eq!(mirror.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_do_not_fail_over() {
+        let primary = MockServer::respond_with(401, "bad signature").await;
+        let mirror = MockServer::respond_with(200, "{}").await;
+        let pool = ServerPool::new(&[primary.url.clone(), mirror.url.clone()]);
+        let http = HttpClient::default();
+
+        let response = pool.send("/api/ping", |url| http.get(url)).await.unwrap();
+
+        assert_eq!(response.status(), 401);
+        assert!(mirror.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_posts_not_retried_after_server_error() {
+        let primary = MockServer::respond_with(500, "oops").await;
+        let mirror = MockServer::respond_with(200, "{}").await;
+        let pool = ServerPool::new(&[primary.url.clone(), mirror.url.clone()]);
+        let http = HttpClient::default();
+
+        // The primary may have acted on it, so the mirror must not see it again
+        let response = pool.send("/api/attack", |url| http.post(url).body("{}")).await.unwrap();
+
+        assert_eq!(response.status(), 500);
+        assert!(mirror.requests().is_empty());
+        assert_eq!(pool.current(), primary.url);
+    }
+
+    #[tokio::test]
+    async fn test_posts_fail_over_when_unreachable() {
+        let mirror = MockServer::respond_with(200, "{}").await;
+        let pool = ServerPool::new(&[dead_url().await, mirror.url.clone()]);
+        let http = HttpClient::default();
+
+        let response = pool.send("/api/attack", |url| http.post(url).body("{}")).await.unwrap();
+
+        assert_eq!(response.status(), 200);
+        assert_eq!(mirror.requests().len(), 1);
+    }
+}
@@ -0,0 +1,219 @@
+// sacas-daemon/src/http/mod.rs
+// Shared HTTP client used by the sync, combat, radar and Moltbook clients
+
+mod failover;
+mod rate_limit;
+
+pub use failover::ServerPool;
+pub use rate_limit::{EndpointCategory, RateLimitConfig, RateLimited};
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use reqwest::{RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tracing::{debug, info_span, warn, Instrument};
+
+use crate::build_info::API_VERSION;
+use crate::config::HttpConfig;
+use rate_limit::RateLimiter;
+
+/// Header carrying the per-request correlation id
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Header carrying `build_info::API_VERSION` on every server request
+pub const API_VERSION_HEADER: &str = "x-api-version";
+
+/// The server answered 426: it no longer accepts this daemon's API version
+#[derive(Debug, Error)]
+#[error("{server} no longer accepts API version {API_VERSION}; please update the daemon")]
+pub struct UpgradeRequired {
+    pub server: String,
+}
+
+/// Send `request` with a fresh `X-Request-ID` and the `X-API-Version`,
+/// logging the outcome in a span that carries the same id
+///
+/// Both only travel as headers, so signed bodies are unaffected.
+pub async fn send_traced(request: RequestBuilder) -> reqwest::Result<Response> {
+    let request_id = uuid::Uuid::new_v4().to_string();
+    let (client, request) = request
+        .header(REQUEST_ID_HEADER, &request_id)
+        .header(API_VERSION_HEADER, API_VERSION)
+        .build_split();
+    let request = request?;
+    let (method, path) = (request.method().clone(), request.url().path().to_string());
+
+    async move {
+        match client.execute(request).await {
+            Ok(response) => {
+                debug!("{} {} → {}", method, path, response.status());
+                Ok(response)
+            }
+            Err(e) => {
+                warn!("⚠️  {} {} failed: {}", method, path, e);
+                Err(e)
+            }
+        }
+    }
+    .instrument(info_span!("http", %request_id))
+    .await
+}
+
+/// Whether a failed request may still have been processed by the server
+///
+/// Connection and request-building failures never reached it; a timeout or
+/// a response lost on the way back might have.
+pub fn outcome_unknown(err: &anyhow::Error) -> bool {
+    err.chain()
+        .filter_map(|e| e.downcast_ref::<reqwest::Error>())
+        .any(|e| !e.is_connect() && !e.is_builder())
+}
+
+/// Thin wrapper around `reqwest::Client` that enforces response size limits
+/// and per-category request budgets
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    max_response_bytes: usize,
+    limiter: Arc<RateLimiter>,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            max_response_bytes: config.max_response_bytes,
+            limiter: Arc::new(RateLimiter::new(config.rate_limit.clone())),
+        }
+    }
+
+    /// Spend one request from the category's budget before sending
+    pub fn acquire(&self, category: EndpointCategory) -> Result<(), RateLimited> {
+        self.limiter.acquire(category)
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    /// Read the response body, refusing anything over the configured limit
+    pub async fn read_bytes(&self, mut response: Response) -> Result<Vec<u8>> {
+        let limit = self.max_response_bytes;
+
+        if let Some(length) = response.content_length() {
+            if length > limit as u64 {
+                anyhow::bail!("Response body of {} bytes exceeds the {} byte limit", length, limit);
+            }
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.context("Failed to read response body")? {
+            if body.len() + chunk.len() > limit {
+                anyhow::bail!("Response body exceeds the {} byte limit", limit);
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(body)
+    }
+
+    /// Read and deserialize a JSON response body within the size limit
+    pub async fn read_json<T: DeserializeOwned>(&self, response: Response) -> Result<T> {
+        let body = self.read_bytes(response).await?;
+        serde_json::from_slice(&body).context("Failed to parse response JSON")
+    }
+
+    /// Read a response body as text within the size limit
+    pub async fn read_text(&self, response: Response) -> Result<String> {
+        let body = self.read_bytes(response).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new(&HttpConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockServer;
+
+    #[tokio::test]
+    async fn test_oversized_response_rejected() {
+        let body = format!(r#"{{"padding":"{}"}}"#, "x".repeat(2048));
+        let server = MockServer::respond_with(200, &body).await;
+        let client = HttpClient::new(&HttpConfig { max_response_bytes: 1024, ..Default::default() });
+
+        let response = client.get(&server.url).send().await.unwrap();
+        let err = client.read_json::<serde_json::Value>(response).await.unwrap_err();
+
+        assert!(err.to_string().contains("1024 byte limit"));
+    }
+
+    /// Log sink for asserting on span fields
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_each_request_gets_traced_id() {
+        let server = MockServer::respond_with(200, "{}").await;
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let client = HttpClient::default();
+        for _ in 0..2 {
+            send_traced(client.post(&format!("{}/api/sync", server.url)).body("signed")).await.unwrap();
+        }
+
+        let ids: Vec<String> = server
+            .requests()
+            .iter()
+            .map(|r| r.header(REQUEST_ID_HEADER).unwrap().to_string())
+            .collect();
+        assert_ne!(ids[0], ids[1]);
+        assert!(server.requests().iter().all(|r| r.body == "signed"));
+        assert!(server.requests().iter().all(|r| r.header(API_VERSION_HEADER) == Some(API_VERSION)));
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        for id in &ids {
+            assert!(logs.contains(&format!("request_id={}", id)), "{} not in logs:\n{}", id, logs);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_response_within_limit() {
+        let server = MockServer::respond_with(200, r#"{"ok":true}"#).await;
+        let client = HttpClient::new(&HttpConfig { max_response_bytes: 1024, ..Default::default() });
+
+        let response = client.get(&server.url).send().await.unwrap();
+        let value: serde_json::Value = client.read_json(response).await.unwrap();
+
+        assert_eq!(value["ok"], true);
+    }
+}
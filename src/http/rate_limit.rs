@@ -0,0 +1,182 @@
+// sacas-daemon/src/http/rate_limit.rs
+// Client-side token buckets for outbound signed requests
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Endpoint groups that get their own request budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EndpointCategory {
+    Sync,
+    Combat,
+    Radar,
+}
+
+impl fmt::Display for EndpointCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EndpointCategory::Sync => "sync",
+            EndpointCategory::Combat => "combat",
+            EndpointCategory::Radar => "radar",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returned when a request would exceed its category's budget
+#[derive(Debug, Error)]
+#[error("Rate limited on {category} requests, retry after {retry_after:?}")]
+pub struct RateLimited {
+    pub category: EndpointCategory,
+    pub retry_after: Duration,
+}
+
+/// Burst size and sustained rate for one endpoint category
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct BucketConfig {
+    /// Requests allowed back-to-back before throttling kicks in
+    pub burst: u32,
+    /// Sustained requests per minute
+    pub per_minute: u32,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RateLimitConfig {
+    pub sync: BucketConfig,
+    pub combat: BucketConfig,
+    pub radar: BucketConfig,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            sync: BucketConfig { burst: 5, per_minute: 12 },
+            combat: BucketConfig { burst: 5, per_minute: 10 },
+            radar: BucketConfig { burst: 10, per_minute: 30 },
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn bucket(&self, category: EndpointCategory) -> BucketConfig {
+        match category {
+            EndpointCategory::Sync => self.sync,
+            EndpointCategory::Combat => self.combat,
+            EndpointCategory::Radar => self.radar,
+        }
+    }
+}
+
+/// Classic token bucket: refills continuously up to `capacity`
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(config: BucketConfig, now: Instant) -> Self {
+        Self {
+            capacity: config.burst as f64,
+            tokens: config.burst as f64,
+            refill_per_sec: config.per_minute as f64 / 60.0,
+            last_refill: now,
+        }
+    }
+
+    /// Take one token, or report how long until one is available
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        if self.refill_per_sec <= 0.0 {
+            return Err(Duration::MAX);
+        }
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec))
+    }
+}
+
+/// Per-category token buckets shared by every clone of the HTTP client
+#[derive(Debug)]
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<EndpointCategory, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn acquire(&self, category: EndpointCategory) -> Result<(), RateLimited> {
+        self.acquire_at(category, Instant::now())
+    }
+
+    fn acquire_at(&self, category: EndpointCategory, now: Instant) -> Result<(), RateLimited> {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets
+            .entry(category)
+            .or_insert_with(|| TokenBucket::new(self.config.bucket(category), now));
+
+        bucket
+            .try_acquire(now)
+            .map_err(|retry_after| RateLimited { category, retry_after })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limiter(burst: u32, per_minute: u32) -> RateLimiter {
+        let bucket = BucketConfig { burst, per_minute };
+        RateLimiter::new(RateLimitConfig { sync: bucket, combat: bucket, radar: bucket })
+    }
+
+    #[test]
+    fn test_burst_then_reject() {
+        let limiter = limiter(2, 60);
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(EndpointCategory::Combat, now).is_ok());
+        assert!(limiter.acquire_at(EndpointCategory::Combat, now).is_ok());
+
+        let err = limiter.acquire_at(EndpointCategory::Combat, now).unwrap_err();
+        assert_eq!(err.category, EndpointCategory::Combat);
+        assert_eq!(err.retry_after, Duration::from_secs(1));
+
+        // Other categories keep their own budget
+        assert!(limiter.acquire_at(EndpointCategory::Radar, now).is_ok());
+    }
+
+    #[test]
+    fn test_refill_over_time() {
+        let limiter = limiter(1, 60);
+        let now = Instant::now();
+
+        assert!(limiter.acquire_at(EndpointCategory::Sync, now).is_ok());
+        assert!(limiter.acquire_at(EndpointCategory::Sync, now + Duration::from_millis(500)).is_err());
+        assert!(limiter.acquire_at(EndpointCategory::Sync, now + Duration::from_secs(1)).is_ok());
+
+        // Refill never exceeds the burst size
+        let later = now + Duration::from_secs(3600);
+        assert!(limiter.acquire_at(EndpointCategory::Sync, later).is_ok());
+        assert!(limiter.acquire_at(EndpointCategory::Sync, later).is_err());
+    }
+}
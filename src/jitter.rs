@@ -0,0 +1,107 @@
+// sacas-daemon/src/jitter.rs
+// Random offsets for periodic loops so a fleet started together doesn't tick in step
+
+use std::time::Duration;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Jitter never exceeds this share of the interval, whatever the config says
+const MAX_JITTER_FRACTION: f64 = 0.5;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct JitterConfig {
+    /// Largest offset as a percentage of the loop interval (0 disables)
+    ///
+    /// A first sync or probe configured to run right at startup is not delayed.
+    pub percent: f64,
+    /// Also jitter every later tick, not just the first
+    pub every_tick: bool,
+}
+
+impl Default for JitterConfig {
+    fn default() -> Self {
+        Self {
+            percent: 10.0,
+            every_tick: false,
+        }
+    }
+}
+
+impl JitterConfig {
+    /// No jitter at all: every loop keeps its exact cadence
+    pub const NONE: Self = Self { percent: 0.0, every_tick: false };
+
+    fn fraction(&self) -> f64 {
+        if self.percent.is_finite() {
+            (self.percent / 100.0).clamp(0.0, MAX_JITTER_FRACTION)
+        } else {
+            0.0
+        }
+    }
+
+    /// Delay before a loop's first tick: `base` plus up to the jitter fraction of `period`
+    ///
+    /// Only ever later, so a configured warm-up is still honored.
+    pub fn first(&self, base: Duration, period: Duration) -> Duration {
+        base + self.offset(period, rand::thread_rng().gen_range(0.0..=1.0))
+    }
+
+    /// Gap before a later tick: `period` moved either way by up to the jitter fraction
+    ///
+    /// Symmetric so the average cadence stays at `period`; unchanged unless
+    /// `every_tick` is set.
+    pub fn next(&self, period: Duration) -> Duration {
+        if !self.every_tick {
+            return period;
+        }
+        self.shift(period, rand::thread_rng().gen_range(-1.0..=1.0))
+    }
+
+    /// `unit` in 0..=1 scales the offset added for the first tick
+    fn offset(&self, period: Duration, unit: f64) -> Duration {
+        period.mul_f64(self.fraction() * unit.clamp(0.0, 1.0))
+    }
+
+    /// `unit` in -1..=1 scales the shift applied to a later tick
+    fn shift(&self, period: Duration, unit: f64) -> Duration {
+        period.mul_f64(1.0 + self.fraction() * unit.clamp(-1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_secs(300);
+
+    fn jitter(percent: f64) -> JitterConfig {
+        JitterConfig { percent, every_tick: true }
+    }
+
+    #[test]
+    fn test_jitter_stays_within_fraction() {
+        let j = jitter(10.0);
+        assert_eq!(j.shift(PERIOD, -1.0), Duration::from_secs(270));
+        assert_eq!(j.shift(PERIOD, 1.0), Duration::from_secs(330));
+        assert_eq!(j.offset(PERIOD, 1.0), Duration::from_secs(30));
+
+        for _ in 0..1000 {
+            let next = j.next(PERIOD);
+            assert!((Duration::from_secs(270)..=Duration::from_secs(330)).contains(&next));
+            let first = j.first(Duration::from_secs(10), PERIOD);
+            assert!((Duration::from_secs(10)..=Duration::from_secs(40)).contains(&first));
+        }
+    }
+
+    #[test]
+    fn test_jitter_bounded_and_optional() {
+        // Capped at half the interval, and a bad value disables it
+        assert_eq!(jitter(400.0).shift(PERIOD, -1.0), Duration::from_secs(150));
+        assert_eq!(jitter(f64::NAN).shift(PERIOD, 1.0), PERIOD);
+        assert_eq!(jitter(-5.0).offset(PERIOD, 1.0), Duration::ZERO);
+
+        let first_only = JitterConfig { every_tick: false, ..jitter(10.0) };
+        assert_eq!(first_only.next(PERIOD), PERIOD);
+    }
+}
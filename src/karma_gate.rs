@@ -0,0 +1,97 @@
+// sacas-daemon/src/karma_gate.rs
+// Client-side karma thresholds for server features like radar and attack
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::state::StateManager;
+
+/// Karma a device needs before trying each feature (0 = always allowed)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct KarmaGateConfig {
+    pub radar: u64,
+    pub attack: u64,
+}
+
+#[derive(Debug, Error, PartialEq)]
+#[error("{feature} needs {required} karma, device has {karma}")]
+pub struct KarmaTooLow {
+    pub feature: &'static str,
+    pub required: u64,
+    pub karma: u64,
+}
+
+/// Short-circuits a feature while karma is below its threshold
+///
+/// Karma is read from state on every check, so the feature comes back on
+/// its own once a sync raises karma. Clones share the blocked flag, which
+/// keeps the disabled/re-enabled log lines to one per transition.
+#[derive(Clone)]
+pub struct KarmaGate {
+    feature: &'static str,
+    required: u64,
+    state: StateManager,
+    blocked: Arc<AtomicBool>,
+}
+
+impl KarmaGate {
+    pub fn new(feature: &'static str, required: u64, state: StateManager) -> Self {
+        Self {
+            feature,
+            required,
+            state,
+            blocked: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub async fn check(&self) -> Result<(), KarmaTooLow> {
+        let karma = self.state.get_snapshot().await.player.karma;
+
+        if karma >= self.required {
+            if self.blocked.swap(false, Ordering::Relaxed) {
+                info!("🔓 {} re-enabled: karma {} reached {}", self.feature, karma, self.required);
+            }
+            return Ok(());
+        }
+
+        if !self.blocked.swap(true, Ordering::Relaxed) {
+            warn!("🔒 {} disabled until karma reaches {} (have {})", self.feature, self.required, karma);
+        }
+        Err(KarmaTooLow {
+            feature: self.feature,
+            required: self.required,
+            karma,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_gate_at_threshold_boundary() {
+        let state = StateManager::in_memory("dev".to_string(), 499);
+        let gate = KarmaGate::new("radar", 500, state.clone());
+
+        assert_eq!(
+            gate.check().await,
+            Err(KarmaTooLow { feature: "radar", required: 500, karma: 499 })
+        );
+
+        // A sync bringing karma up to the threshold re-enables the feature
+        state.update_karma(500).await;
+        assert_eq!(gate.check().await, Ok(()));
+        assert!(!gate.blocked.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_zero_threshold_never_blocks() {
+        let gate = KarmaGate::new("attack", 0, StateManager::in_memory("dev".to_string(), 0));
+        assert_eq!(gate.check().await, Ok(()));
+    }
+}
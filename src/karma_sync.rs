@@ -1,14 +1,27 @@
-use tokio::time::{interval, Duration};
+use tokio::time::{sleep, Duration};
 use tracing::{info, error};
 use std::path::PathBuf;
+use std::sync::Arc;
 use anyhow::Result;
-use crate::moltbook::MoltbookClient;
+use crate::moltbook::{MoltbookClient, MoltbookError};
 use crate::config::Config;
+use crate::jitter::JitterConfig;
+use crate::notifier::Notifier;
+use crate::state::StateManager;
+
+/// First retry after a network failure; doubles per consecutive failure
+const NETWORK_RETRY_BASE: Duration = Duration::from_secs(60);
+
+/// Wait after a 429 that didn't say how long to back off
+const RATE_LIMIT_DEFAULT: Duration = Duration::from_secs(15 * 60);
 
 pub struct KarmaSyncService {
     moltbook_client: MoltbookClient,
     config_path: PathBuf,
     sync_interval_hours: u64,
+    state: StateManager,
+    notifier: Arc<dyn Notifier>,
+    jitter: JitterConfig,
 }
 
 impl KarmaSyncService {
@@ -16,43 +29,80 @@ impl KarmaSyncService {
         moltbook_client: MoltbookClient,
         config_path: PathBuf,
         sync_interval_hours: u64,
+        state: StateManager,
+        notifier: Arc<dyn Notifier>,
     ) -> Self {
         Self {
             moltbook_client,
             config_path,
             sync_interval_hours,
+            state,
+            notifier,
+            jitter: JitterConfig::NONE,
         }
     }
+
+    /// Offset the first sync, and optionally later scheduled ones, by `jitter`
+    ///
+    /// Retries keep their exact delay so a server's Retry-After is honored.
+    pub fn with_jitter(mut self, jitter: JitterConfig) -> Self {
+        self.jitter = jitter;
+        self
+    }
     
+    /// Sync now, then every interval; failed syncs retry per `retry_delay`
+    ///
+    /// Stops for good when Moltbook rejects the API key, after notifying
+    /// the user.
     pub async fn run(&self) {
-        let interval_duration = Duration::from_secs(self.sync_interval_hours * 3600);
-        let mut ticker = interval(interval_duration);
+        let period = Duration::from_secs(self.sync_interval_hours * 3600);
+        let mut failures = 0;
         
         info!(
             "🔄 Karma sync service started (interval: {}h)",
             self.sync_interval_hours
         );
+        sleep(self.jitter.first(Duration::ZERO, period)).await;
         
-        // First sync immediately
-        if let Err(e) = self.sync_once().await {
-            error!("❌ Initial karma sync failed: {}", e);
-        }
-        
-        // Periodic sync
         loop {
-            ticker.tick().await;
+            let delay = match self.sync_once().await {
+                Ok(()) => {
+                    failures = 0;
+                    period
+                }
+                Err(e) => {
+                    failures += 1;
+                    error!("❌ Karma sync failed: {}", e);
+                    match retry_delay(&e, failures, period) {
+                        Some(delay) => delay,
+                        None => {
+                            error!("🛑 Karma sync stopped: update moltbook.api_key and restart the daemon");
+                            self.notifier.notify(
+                                "Moltbook token rejected",
+                                "Karma sync stopped. Update moltbook.api_key and restart the daemon.",
+                            );
+                            return;
+                        }
+                    }
+                }
+            };
             
-            info!("⏰ Running scheduled karma sync...");
-            
-            if let Err(e) = self.sync_once().await {
-                error!("❌ Karma sync failed: {}", e);
+            if delay < period {
+                info!("⏳ Retrying karma sync in {}s", delay.as_secs());
+                sleep(delay).await;
+            } else {
+                sleep(self.jitter.next(delay)).await;
             }
+            
+            info!("⏰ Running scheduled karma sync...");
         }
     }
     
     async fn sync_once(&self) -> Result<()> {
-        // 1. Fetch latest Karma from Moltbook
-        let karma = self.moltbook_client.fetch_karma().await?;
+        // 1. Fetch latest profile from Moltbook and cache it for the UI
+        let profile = self.moltbook_client.fetch_profile().await?;
+        let karma = profile.karma;
+        self.state.update_moltbook_profile(profile).await;
         
         // 2. Load current configuration
         let mut config = Config::load()?;
@@ -82,3 +132,52 @@ impl KarmaSyncService {
         Ok(())
     }
 }
+
+/// How long to wait before the next sync after `failures` consecutive failures
+///
+/// `None` means don't retry: the API key was rejected. Network and server
+/// errors back off exponentially up to the regular interval; a rate limit
+/// waits as long as the server asked.
+fn retry_delay(err: &anyhow::Error, failures: u32, period: Duration) -> Option<Duration> {
+    match err.downcast_ref::<MoltbookError>() {
+        Some(MoltbookError::Unauthorized) => None,
+        Some(MoltbookError::RateLimited { retry_after }) => Some(retry_after.unwrap_or(RATE_LIMIT_DEFAULT)),
+        Some(MoltbookError::Network(_)) => {
+            let backoff = NETWORK_RETRY_BASE.saturating_mul(1 << failures.saturating_sub(1).min(16));
+            Some(backoff.min(period))
+        }
+        // A missing agent, a bad response or a local config error won't
+        // clear up sooner by retrying
+        Some(MoltbookError::NotFound(_) | MoltbookError::Api(_)) | None => Some(period),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_secs(6 * 3600);
+
+    fn delay(err: MoltbookError, failures: u32) -> Option<Duration> {
+        retry_delay(&anyhow::Error::from(err), failures, PERIOD)
+    }
+
+    #[test]
+    fn test_retry_policy_per_error() {
+        assert_eq!(delay(MoltbookError::Unauthorized, 1), None);
+        assert_eq!(delay(MoltbookError::NotFound("agent".to_string()), 1), Some(PERIOD));
+        assert_eq!(
+            delay(MoltbookError::RateLimited { retry_after: Some(Duration::from_secs(90)) }, 1),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(delay(MoltbookError::RateLimited { retry_after: None }, 1), Some(RATE_LIMIT_DEFAULT));
+    }
+
+    #[test]
+    fn test_network_errors_back_off_to_interval() {
+        let network = || MoltbookError::Network("connection refused".to_string());
+        assert_eq!(delay(network(), 1), Some(Duration::from_secs(60)));
+        assert_eq!(delay(network(), 3), Some(Duration::from_secs(240)));
+        assert_eq!(delay(network(), 30), Some(PERIOD));
+    }
+}
@@ -1,52 +1,101 @@
 use anyhow::Result;
+use clap::Parser;
+use std::sync::Arc;
+use tokio::sync::Notify;
 use tracing::{info, error, warn};
-use tracing_subscriber;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::EnvFilter;
 
+mod build_info;
+mod capacity_alert;
+mod cli;
 mod config;
 mod daemon;
+mod event_log;
 mod network;
 mod mining;
 mod grpc;
 mod state;
+mod store;
+mod status_feed;
 mod types;
 mod sync;  // New: periodic sync
+mod http;  // Shared HTTP client
+mod jitter;
+mod history;
 
 // New modules
 mod device;
+mod fleet;
 mod moltbook;
+mod karma_gate;
 mod karma_sync;
+mod metrics;
+mod notifier;
+mod panic_hook;
+mod subsystem;
+mod wake;
 mod combat;  // Combat system
 mod radar;   // Radar scanning
 mod websocket;  // WebSocket client
 
-use crate::config::Config;
+#[cfg(test)]
+mod test_support;
+
+use crate::combat::{run_auto_attack, run_auto_defense, run_defense_reconciliation, run_parasite_reconciliation, selector_by_name, AutoDefense, AUTO_DEFENSE_CHECK_SECS};
+use crate::combat::target::TargetSelector;
+use crate::config::{Config, DeviceBinding};
 use crate::daemon::OmniDaemon;
-use crate::device::{MacHardwareInfo, MacValidator, VMDetector, register_device};
+use crate::fleet::FleetInstance;
+use crate::device::{check_native, DeviceError, EnforcementLevel, MacHardwareInfo, MacValidator, SystemCommands, ValidationCheck, VMDetector, register_device};
 use crate::moltbook::MoltbookClient;
+use crate::panic_hook::respawn_on_panic;
+use crate::subsystem::{Subsystem, SubsystemSpawner};
 use crate::karma_sync::KarmaSyncService;
 use crate::sync::start_sync_loop;
 use crate::websocket::WebSocketClient;
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let cli = cli::Cli::parse();
+
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter("sacas_daemon=debug,info")
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(EnvFilter::new("sacas_daemon=debug,info"))
+        .with(event_log::EventLog::global().layer())
         .init();
 
+    // Before anything reads a data path, so subcommands see the instance too
+    #[cfg(feature = "fleet-sim")]
+    if let Some(instance) = cli.fleet_instance().map(FleetInstance) {
+        let dir = instance.activate()?;
+        info!("🧪 Fleet instance {} using {}", instance.0, dir.display());
+    }
+
+    if let Some(command) = cli.command {
+        return cli::run(command).await;
+    }
+
     println!("\n╔════════════════════════════════════════════════════════╗");
     println!("║                  SACAS DAEMON v1.0.0                  ║");
     println!("║           The Entropy Protocol - Mac Edition          ║");
     println!("╚════════════════════════════════════════════════════════╝\n");
 
-    info!("🚀 SACAS Daemon starting...");
+    info!("🚀 SACAS Daemon {} starting...", build_info::BUILD_INFO);
     
     // ========================================
     // Phase 1: Hardware Validation
     // ========================================
     info!("🔍 Phase 1: Hardware Validation");
     
-    let hw_info = match MacHardwareInfo::collect() {
+    let collected = if cli.dry_run() {
+        warn!("🧪 Dry run: using synthetic hardware, device validation skipped");
+        Ok(FleetInstance(cli.fleet_instance().unwrap_or(0)).hardware())
+    } else {
+        MacHardwareInfo::collect()
+    };
+    let hw_info = match collected {
         Ok(info) => {
             info!("  ✓ Hardware UUID: {}", info.hardware_uuid);
             info!("  ✓ Serial Number: {}", info.serial_number);
@@ -63,47 +112,87 @@ async fn main() -> Result<()> {
         }
     };
     
-    // ========================================
-    // Phase 2: Mac Model Validation
-    // ========================================
-    if let Err(e) = MacValidator::validate(&hw_info) {
-        error!("\n{}", e);
-        error!("\n🚫 SACAS only runs on genuine Apple Silicon Mac computers.");
-        error!("   Supported models: Mac mini, MacBook Pro, MacBook Air, iMac, Mac Studio, Mac Pro");
-        std::process::exit(1);
-    }
-    
-    let friendly_name = MacValidator::get_friendly_name(&hw_info.model_identifier);
-    info!("✅ Mac validation passed: {}", friendly_name);
-    
-    // ========================================
-    // Phase 3: Virtual Machine Detection
-    // ========================================
-    match VMDetector::detect() {
-        Ok(warnings) if !warnings.is_empty() => {
-            error!("\n❌ Virtual machine detected:");
-            for warning in &warnings {
-                error!("   - {}", warning);
+    if !cli.dry_run() {
+        // ========================================
+        // Phase 2: Mac Model Validation
+        // ========================================
+        let enforcement = Config::enforcement_policy();
+        if enforcement.level == EnforcementLevel::WarnOnly {
+            warn!("⚠️  Enforcement level is WarnOnly: non-critical validation failures will not stop SACAS");
+        }
+
+        match MacValidator::validate(&hw_info) {
+            Err(e) if enforcement.is_fatal(ValidationCheck::for_model_error(&e)) => {
+                error!("\n{}", e);
+                error!("\n🚫 SACAS only runs on genuine Apple Silicon Mac computers.");
+                error!("   Supported models: Mac mini, MacBook Pro, MacBook Air, iMac, Mac Studio, Mac Pro");
+                std::process::exit(1);
+            }
+            Err(e) => {
+                warn!("⚠️  Mac validation failed (continuing, WarnOnly): {}", e);
+            }
+            Ok(()) => {
+                let friendly_name = MacValidator::get_friendly_name(&hw_info.model_identifier);
+                info!("✅ Mac validation passed: {}", friendly_name);
             }
-            error!("\n🚫 SACAS does not support virtual machines.");
-            error!("   Please run SACAS on a real Mac computer.");
-            std::process::exit(1);
         }
-        Ok(_) => {
-            info!("✅ VM detection passed - Running on real hardware");
+
+        // ========================================
+        // Phase 3: Virtual Machine Detection
+        // ========================================
+        match VMDetector::detect() {
+            Ok(warnings) if !warnings.is_empty() && !enforcement.is_fatal(ValidationCheck::VmSignals(warnings.len())) => {
+                warn!("⚠️  {} VM signal(s) detected (continuing, WarnOnly):", warnings.len());
+                for warning in &warnings {
+                    warn!("   - {}", warning);
+                }
+            }
+            Ok(warnings) if !warnings.is_empty() => {
+                error!("\n❌ Virtual machine detected:");
+                for warning in &warnings {
+                    error!("   - {}", warning);
+                }
+                error!("\n🚫 SACAS does not support virtual machines.");
+                error!("   Please run SACAS on a real Mac computer.");
+                std::process::exit(1);
+            }
+            Ok(_) => {
+                info!("✅ VM detection passed - Running on real hardware");
+            }
+            Err(e) => {
+                warn!("⚠️  VM detection error: {}", e);
+                warn!("   Proceeding with caution...");
+            }
         }
-        Err(e) => {
-            warn!("⚠️  VM detection error: {}", e);
-            warn!("   Proceeding with caution...");
+
+        // Rosetta skews timing-sensitive logic even on genuine hardware
+        match check_native(&SystemCommands) {
+            Err(e @ DeviceError::Translated) if enforcement.is_fatal(ValidationCheck::Translation) => {
+                error!("\n{}", e);
+                std::process::exit(1);
+            }
+            Err(DeviceError::Translated) => {
+                warn!("⚠️  Running under Rosetta translation (continuing, WarnOnly); use the native arm64 build");
+            }
+            Err(e) => {
+                warn!("⚠️  Rosetta check error: {}", e);
+            }
+            Ok(()) => {}
         }
     }
-    
+
     // ========================================
     // Phase 4: Configuration Load/Create
     // ========================================
     info!("\n📝 Phase 2: Configuration");
     
-    let config_path = Config::config_path();
+    let config_path = match Config::config_path() {
+        Ok(path) => path,
+        Err(e) => {
+            error!("❌ {}", e);
+            std::process::exit(1);
+        }
+    };
     let config = if config_path.exists() {
         // Load existing configuration
         info!("Loading existing configuration...");
@@ -115,17 +204,45 @@ async fn main() -> Result<()> {
             }
         };
         
-        // Verify device binding
-        let current_fp = hw_info.generate_fingerprint();
-        if cfg.device.device_fingerprint != current_fp {
-            error!("\n❌ DEVICE FINGERPRINT MISMATCH!");
-            error!("   Expected: {}", cfg.device.device_fingerprint);
-            error!("   Actual:   {}", current_fp);
-            error!("\n🚫 This configuration is bound to a different device.");
-            error!("   Original device: {} ({})", cfg.device.model_identifier, cfg.device.serial_number);
-            error!("   Current device:  {} ({})", hw_info.model_identifier, hw_info.serial_number);
-            error!("\n   If you've replaced your hardware, please delete: {:?}", config_path);
-            std::process::exit(1);
+        // Verify device binding field by field
+        let binding = cfg.verify_device_binding(&hw_info);
+        let fatal = binding.is_fatal(&cfg.enforcement);
+        match binding {
+            DeviceBinding::Verified => {}
+            DeviceBinding::Drifted(mismatches) => {
+                warn!("\n⚠️  Device details changed since binding:");
+                for m in &mismatches {
+                    warn!("   {}: expected {}, found {}", m.field, m.expected, m.actual);
+                }
+                if fatal {
+                    error!("🚫 enforcement.strict_binding is set; refusing to start on changed hardware details");
+                    std::process::exit(1);
+                }
+                warn!("   Platform UUID and serial number still match, so the binding holds");
+            }
+            DeviceBinding::Replaced(mismatches) => {
+                error!("\n❌ DEVICE FINGERPRINT MISMATCH!");
+                for m in &mismatches {
+                    error!("   {}: expected {}, found {}", m.field, m.expected, m.actual);
+                }
+                error!("\n🚫 This configuration is bound to a different device.");
+                error!("   Original device: {} ({})", cfg.device.model_identifier, cfg.device.serial_number);
+                error!("   Current device:  {} ({})", hw_info.model_identifier, hw_info.serial_number);
+                error!("\n   If you've replaced your hardware, please delete: {:?}", config_path);
+                std::process::exit(1);
+            }
+            DeviceBinding::Tampered(mismatches) => {
+                error!("\n❌ DEVICE BINDING INCONSISTENT!");
+                for m in &mismatches {
+                    error!("   {}: expected {}, found {}", m.field, m.expected, m.actual);
+                }
+                error!("\n🚫 Some bound fields match this device and others don't.");
+                error!("   The configuration appears to have been modified: {:?}", config_path);
+                if device::heal::detect_reinstall(&cfg, &hw_info).is_some() {
+                    error!("   If macOS was reinstalled on this Mac, run: sacas-daemon heal-fingerprint");
+                }
+                std::process::exit(1);
+            }
         }
         
         info!("✅ Device binding verified");
@@ -138,10 +255,7 @@ async fn main() -> Result<()> {
         info!("🆕 First time setup - creating configuration");
         
         // Generate or load device identity
-        let identity_path = dirs::home_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join(".sacas")
-            .join("device.key");
+        let identity_path = Config::identity_path()?;
         
         let identity = match device::DeviceIdentity::load_or_generate(&identity_path) {
             Ok(i) => i,
@@ -176,6 +290,10 @@ async fn main() -> Result<()> {
     // Phase 4.5: Device Registration
     // ========================================
     let mut config = config;  // Make mutable for registration update
+    if cli.dry_run() {
+        // Re-collecting real hardware would never match the synthetic binding
+        config.sync.binding_check_every = 0;
+    }
     
     if config.device_id.is_none() {
         // Note: Device is now auto-registered through create_with_device
@@ -190,72 +308,143 @@ async fn main() -> Result<()> {
     }
     
     // ========================================
-    // Phase 5: Karma Synchronization Service
+    // Phase 5: Start Game Daemon
+    // ========================================
+    info!("\n🎮 Phase 3: Game Daemon");
+    
+    let daemon = OmniDaemon::new(config.clone()).await?;
+    info!("✓ Daemon initialized");
+
+    // Report panics in background tasks instead of letting them die silently
+    panic_hook::install(panic_hook::PanicReporter::new(
+        &config.panic,
+        notifier::notifier_for(config.notifications.backend),
+        (*daemon.get_state()).clone(),
+    ));
+    
+    // ========================================
+    // Phase 6: Karma Synchronization Service
+    // (after the daemon, whose state caches the Moltbook profile)
     // ========================================
-    info!("\n🔄 Phase 3: Karma Synchronization");
+    // Optional services, each skipped when `[subsystems]` turns it off
+    let mut subsystems = SubsystemSpawner::new(config.subsystems);
+
+    info!("\n🔄 Phase 4: Karma Synchronization");
     
     // Start Karma Sync Service if Moltbook is configured
     if let Some(ref mb_config) = config.moltbook {
         let moltbook_client = MoltbookClient::new(
+            http::HttpClient::new(&config.http),
             mb_config.api_url.clone(),
             mb_config.api_key.clone(),
             mb_config.agent_name.clone(),
         );
         let karma_sync = KarmaSyncService::new(
             moltbook_client,
-            Config::config_path(),
+            config_path.clone(),
             mb_config.sync_interval_hours,
-        );
-        
-        info!("✓ Karma sync enabled (interval: {}h)", mb_config.sync_interval_hours);
+            (*daemon.get_state()).clone(),
+            notifier::notifier_for(config.notifications.backend),
+        )
+        .with_jitter(config.jitter);
         
         // Start Karma sync service (background task)
-        tokio::spawn(async move {
-            karma_sync.run().await;
-        });
-        
-        info!("✓ Karma sync task started");
+        if subsystems.spawn(Subsystem::KarmaSync, async move { karma_sync.run().await }) {
+            info!("✓ Karma sync task started (interval: {}h)", mb_config.sync_interval_hours);
+        }
     } else {
         info!("⊘ Karma sync disabled (no Moltbook config)");
     }
   
-    // ========================================
-    // Phase 6: Start Game Daemon
-    // ========================================
-    info!("\n🎮 Phase 4: Game Daemon");
-    
-    let daemon = OmniDaemon::new(config.clone()).await?;
-    info!("✓ Daemon initialized");
-    
     // ========================================
     // Phase 7: Start Device Sync Loop
     // ========================================
-    if config.device_id.is_some() {
+    // Only a registered device has a combat client
+    if let Some(combat_client) = daemon.get_combat() {
         info!("\n🔄 Starting device sync loop...");
         let sync_config = config.clone();
-        let sync_state = daemon.get_state().get_handle();
+        let sync_state = daemon.get_state();
+        let sync_backlog = daemon.get_sync_backlog();
         
         // Load identity for signed sync
-        let identity_path = dirs::home_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join(".sacas")
-            .join("device.key");
+        let identity_path = Config::identity_path()?;
         
         let sync_identity = device::DeviceIdentity::load_or_generate(&identity_path)?;
+        let sync_now = Arc::new(Notify::new());
+
+        // WebSocket events; a reconnect triggers an immediate sync
+        let notifier = notifier::notifier_for(config.notifications.backend);
+        info!("✓ Battle notifications via {}", notifier.name());
+        let ws = WebSocketClient::new(config.server_url.primary().to_string(), config.device_id.clone().unwrap(), sync_identity.signer().clone())
+            .with_tls(config.tls.clone())
+            .with_path(config.websocket.path.clone())
+            .with_auth_timeout(std::time::Duration::from_secs(config.websocket.auth_timeout_secs))
+            .with_battle_dedup_window(config.websocket.battle_dedup_window)
+            .with_notifier(notifier.clone())
+            .with_sync_trigger(sync_now.clone())
+            .with_state(daemon.get_state());
+        if subsystems.spawn(Subsystem::WebSocket, async move { ws.run().await }) {
+            info!("✓ WebSocket event listener started");
+        }
+
+        // Keep local defense and parasites in line with the server
+        let respawn_delay = std::time::Duration::from_secs(config.panic.respawn_delay_secs);
+        if config.combat.defense_reconcile_secs > 0 {
+            let defense_client = combat_client.clone();
+            let defense_state = (*daemon.get_state()).clone();
+            let period = std::time::Duration::from_secs(config.combat.defense_reconcile_secs);
+            subsystems.spawn(Subsystem::AutoCombat, respawn_on_panic("Defense reconciliation", respawn_delay, move || {
+                run_defense_reconciliation(defense_client.clone(), defense_state.clone(), period)
+            }));
+        }
+        if let Some(auto) = AutoDefense::from_config(&config.combat) {
+            let auto_client = combat_client.clone();
+            let auto_state = (*daemon.get_state()).clone();
+            let period = std::time::Duration::from_secs(AUTO_DEFENSE_CHECK_SECS);
+            subsystems.spawn(Subsystem::AutoCombat, respawn_on_panic("Starter defense", respawn_delay, move || {
+                run_auto_defense(auto_client.clone(), auto_state.clone(), auto, period)
+            }));
+        }
+        let auto_selector = selector_by_name(&config.combat.target_strategy).map(Arc::<dyn TargetSelector>::from);
+        if let (true, Some(radar), Some(selector)) = (config.combat.auto_attack_secs > 0, daemon.get_radar(), auto_selector) {
+            let attack_client = combat_client.clone();
+            let attack_state = (*daemon.get_state()).clone();
+            let bands = config.combat.auto_attack_bands.clone();
+            let period = std::time::Duration::from_secs(config.combat.auto_attack_secs);
+            subsystems.spawn(Subsystem::AutoCombat, respawn_on_panic("Auto-attack", respawn_delay, move || {
+                run_auto_attack(attack_client.clone(), radar.clone(), attack_state.clone(), selector.clone(), bands.clone(), period)
+            }));
+        }
+        if config.combat.parasite_reconcile_secs > 0 {
+            let parasite_state = (*daemon.get_state()).clone();
+            let period = std::time::Duration::from_secs(config.combat.parasite_reconcile_secs);
+            subsystems.spawn(Subsystem::AutoCombat, respawn_on_panic("Parasite reconciliation", respawn_delay, move || {
+                run_parasite_reconciliation(combat_client.clone(), parasite_state.clone(), period)
+            }));
+        }
         
-        tokio::spawn(async move {
-            if let Err(e) = start_sync_loop(sync_config, sync_state, sync_identity).await {
-                error!("❌ Sync loop error: {}", e);
+        tokio::spawn(respawn_on_panic("Sync loop", respawn_delay, move || {
+            let (config, state, identity) = (sync_config.clone(), sync_state.clone(), sync_identity.clone());
+            let (sync_now, backlog) = (sync_now.clone(), sync_backlog.clone());
+            async move {
+                if let Err(e) = start_sync_loop(config, state, identity, sync_now, backlog).await {
+                    error!("❌ Sync loop error: {}", e);
+                }
             }
-        });
+        }));
         
-        info!("✓ Signed sync loop started (5-minute intervals)");
+        info!("✓ Signed sync loop started ({}s intervals)", config.sync.interval_secs);
     } else {
         warn!("⚠️  Sync loop disabled - Device not registered");
     }
 
     info!("\n🚀 All systems ready - Starting game loops...\n");
-    daemon.run().await?;
+    let abandoned = daemon.run().await?;
+
+    // Don't let the runtime wait on work the grace period gave up on
+    if !abandoned.is_empty() {
+        std::process::exit(1);
+    }
 
     Ok(())
 }
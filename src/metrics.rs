@@ -0,0 +1,119 @@
+// sacas-daemon/src/metrics.rs
+// Prometheus text endpoint for scraping daemon state
+
+use std::fmt::Write as _;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tracing::{debug, info};
+
+use crate::build_info::BUILD_INFO;
+use crate::state::StateManager;
+use crate::sync::OfflineQueue;
+use crate::types::GameState;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct MetricsConfig {
+    /// Serve `GET /metrics` on localhost
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_port")]
+    pub port: u16,
+}
+
+fn default_port() -> u16 {
+    9090
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self { enabled: false, port: default_port() }
+    }
+}
+
+/// Current values in the Prometheus text format
+pub fn render(state: &GameState, backlog: &OfflineQueue) -> String {
+    let mut out = String::new();
+    let build = format!(r#"sacas_build_info{{version="{}",git_hash="{}"}}"#, BUILD_INFO.version, BUILD_INFO.git_hash);
+    gauge(&mut out, &build, "Daemon version and source commit", 1);
+    gauge(&mut out, "sacas_entropy", "Entropy held by the device", state.player.entropy);
+    gauge(&mut out, "sacas_karma", "Karma the server last reported", state.player.karma);
+    gauge(&mut out, "sacas_pending_sync_deltas", "Entropy deltas waiting to sync", backlog.len());
+    gauge(&mut out, "sacas_pending_entropy", "Sum of the deltas waiting to sync", backlog.pending_entropy());
+    out
+}
+
+/// `series` is the metric name, optionally followed by `{labels}`
+fn gauge(out: &mut String, series: &str, help: &str, value: impl std::fmt::Display) {
+    let name = series.split('{').next().unwrap_or(series);
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} gauge", name);
+    let _ = writeln!(out, "{} {}", series, value);
+}
+
+/// Answer scrapes on `listener` until the task is dropped
+///
+/// Only `GET /metrics` is served; anything else gets a 404. Each
+/// connection gets one response and is closed.
+pub async fn serve_metrics(listener: TcpListener, state: Arc<StateManager>, backlog: OfflineQueue) -> Result<()> {
+    info!("📈 Metrics on http://{}/metrics", listener.local_addr()?);
+    loop {
+        let (stream, _) = listener.accept().await.context("Failed to accept metrics connection")?;
+        let (state, backlog) = (state.clone(), backlog.clone());
+        tokio::spawn(async move {
+            if let Err(e) = respond(stream, &state, &backlog).await {
+                debug!("Metrics scrape failed: {}", e);
+            }
+        });
+    }
+}
+
+async fn respond(mut stream: TcpStream, state: &StateManager, backlog: &OfflineQueue) -> Result<()> {
+    // The request line fits in the first read; headers and body are ignored
+    let mut buf = [0u8; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+
+    let (status, body) = match request.split_whitespace().take(2).collect::<Vec<_>>()[..] {
+        ["GET", "/metrics"] => ("200 OK", render(&state.get_snapshot().await, backlog)),
+        _ => ("404 Not Found", String::new()),
+    };
+    let response = format!(
+        "HTTP/1.1 {}\r\ncontent-type: text/plain; version=0.0.4\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scrape_reports_sync_backlog() {
+        let state = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let backlog = OfflineQueue::default();
+        backlog.enqueue(40, 1.0);
+        backlog.enqueue(25, 1.0);
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_metrics(listener, state, backlog));
+
+        let body = reqwest::get(format!("{}/metrics", url)).await.unwrap().text().await.unwrap();
+        assert!(body.contains("\nsacas_pending_sync_deltas 2\n"));
+        assert!(body.contains("\nsacas_pending_entropy 65\n"));
+        let build = format!(r#"sacas_build_info{{version="{}",git_hash="#, env!("CARGO_PKG_VERSION"));
+        assert!(body.contains(&build));
+
+        let missing = reqwest::get(format!("{}/other", url)).await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+}
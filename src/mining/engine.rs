@@ -1,57 +1,249 @@
-use crate::state::StateManager;
-use tokio::time::{interval, Duration};
+use crate::jitter::JitterConfig;
+use crate::mining::{MiningHeartbeat, YieldStrategy};
+use crate::state::{overflow_decay, StateManager};
+use crate::types::{format_entropy, Player};
+use crate::wake::{interval_at, WakeDetector};
+use std::time::Instant;
+use tokio::time::Duration;
 use tracing::{info, debug};
 
+/// One tick's (base, passive) yield for `player`
+pub fn tick_yield(strategy: &dyn YieldStrategy, player: &Player, multiplier: f64, tick_interval_secs: u64) -> (u64, u64) {
+    let base = strategy.calculate(player.karma, player.network_quality, multiplier);
+    let passive = (player.passive_income * tick_interval_secs as f64) as u64;
+    (base, passive)
+}
+
 pub struct MiningEngine {
     state_manager: StateManager,
     tick_interval_secs: u64,
+    yield_strategy: Box<dyn YieldStrategy>,
+    base_multiplier: f64,
+    heartbeat: MiningHeartbeat,
+    warmup: Duration,
+    jitter: JitterConfig,
 }
 
 impl MiningEngine {
-    pub fn new(state_manager: StateManager, tick_interval_secs: u64) -> Self {
+    pub fn new(
+        state_manager: StateManager,
+        tick_interval_secs: u64,
+        yield_strategy: Box<dyn YieldStrategy>,
+        base_multiplier: f64,
+    ) -> Self {
         Self {
             state_manager,
             tick_interval_secs,
+            yield_strategy,
+            base_multiplier,
+            heartbeat: MiningHeartbeat::default(),
+            warmup: Duration::ZERO,
+            jitter: JitterConfig::NONE,
+        }
+    }
+
+    /// Beats once per completed tick, for the watchdog
+    pub fn heartbeat(&self) -> MiningHeartbeat {
+        self.heartbeat.clone()
+    }
+
+    /// Hold yield back until the first network probe lands, for at most `timeout`
+    ///
+    /// Until then `network_quality` is the 1.0 default rather than a
+    /// measurement. A zero timeout disables the warm-up.
+    pub fn with_warmup(mut self, timeout: Duration) -> Self {
+        self.warmup = timeout;
+        self
+    }
+
+    /// Offset the first tick, and optionally later ones, by `jitter`
+    pub fn with_jitter(mut self, jitter: JitterConfig) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// (tick interval, multiplier): the server's when it sent verified ones, else local
+    fn effective_params(&self) -> (u64, f64) {
+        match self.state_manager.server_mining_params() {
+            Some(params) => (params.tick_interval_secs, params.base_multiplier),
+            None => (self.tick_interval_secs, self.base_multiplier),
         }
     }
 
+    fn warming_up(&self, started: Instant, now: Instant) -> bool {
+        !self.state_manager.has_network_quality() && now.saturating_duration_since(started) < self.warmup
+    }
+
     pub async fn run(&self) {
-        let mut ticker = interval(Duration::from_secs(self.tick_interval_secs));
-        
-        info!("⛏️  Mining engine started (tick every {}s)", self.tick_interval_secs);
+        let mut params_rx = self.state_manager.watch_server_mining_params();
+        let mut tick_secs = self.effective_params().0;
+        let period = Duration::from_secs(tick_secs);
+        let mut ticker = interval_at(tokio::time::Instant::now() + self.jitter.first(Duration::ZERO, period), period);
+        let started = Instant::now();
+        let mut warming = self.warming_up(started, started);
+        let mut wake = WakeDetector::default();
+
+        info!("⛏️  Mining engine started (tick every {}s)", tick_secs);
+        if warming {
+            info!("⛏️  Warming up: yield starts after the first network probe (at most {}s)", self.warmup.as_secs());
+        }
 
         loop {
-            ticker.tick().await;
-            
-            let state = self.state_manager.get_snapshot().await;
-            
-            // Calculate base yield
-            let yield_value = state.player.calculate_yield();
-            
-            // Add passive income
-            let passive = (state.player.passive_income * self.tick_interval_secs as f64) as u64;
-            
-            let total_income = yield_value + passive;
-            
-            // Update balance
-            self.state_manager.update_entropy(total_income as i64).await;
-            
-            let new_state = self.state_manager.get_snapshot().await;
-            
-            debug!(
-                "Mining tick: +{} Ω (base: {}, passive: {}) | Total: {} / {} Ω",
-                total_income,
-                yield_value,
-                passive,
-                new_state.player.entropy,
-                new_state.player.capacity
-            );
-
-            // Check for decay
-            if new_state.player.entropy > new_state.player.capacity {
-                let excess = new_state.player.entropy - new_state.player.capacity;
-                info!("⚠️  Entropy exceeds capacity! Decay will occur: -{} Ω/tick", (excess as f64 * 0.02) as u64);
+            tokio::select! {
+                _ = ticker.tick() => {
+                    if self.jitter.every_tick {
+                        ticker.reset_after(self.jitter.next(Duration::from_secs(tick_secs)));
+                    }
+                }
+                Ok(()) = params_rx.changed() => {
+                    let (secs, multiplier) = self.effective_params();
+                    info!("⛏️  Server mining parameters applied: tick every {}s, multiplier {}", secs, multiplier);
+                    if secs != tick_secs {
+                        tick_secs = secs;
+                        let period = Duration::from_secs(secs);
+                        ticker = interval_at(tokio::time::Instant::now() + period, period);
+                    }
+                    // The next tick is a full new interval away; the watchdog counts from here
+                    self.heartbeat.beat();
+                    continue;
+                }
+            }
+
+            // Missed ticks are skipped, not credited: one tick is mined after a sleep
+            if let Some(asleep) = wake.check() {
+                info!("💤 Woke after {}m asleep; resuming mining without catching up", asleep.as_secs() / 60);
             }
+
+            let mined = self.tick(started, Instant::now()).await;
+            if warming && mined.is_some() {
+                warming = false;
+                if self.state_manager.has_network_quality() {
+                    info!("⛏️  Warm-up done, network quality measured");
+                } else {
+                    info!("⛏️  Warm-up timed out before any probe, mining at default network quality");
+                }
+            }
+
+            self.heartbeat.beat();
         }
     }
+
+    /// Credit one tick of yield; `None` while still warming up, or once the
+    /// device binding is lost
+    async fn tick(&self, started: Instant, now: Instant) -> Option<u64> {
+        if self.warming_up(started, now) || self.state_manager.is_binding_lost() {
+            return None;
+        }
+
+        let state = self.state_manager.get_snapshot().await;
+
+        let (tick_secs, multiplier) = self.effective_params();
+        let (yield_value, passive) = tick_yield(self.yield_strategy.as_ref(), &state.player, multiplier, tick_secs);
+        let total_income = yield_value.saturating_add(passive);
+
+        // Update balance
+        self.state_manager.update_entropy(i64::try_from(total_income).unwrap_or(i64::MAX)).await;
+
+        let new_state = self.state_manager.get_snapshot().await;
+
+        debug!(
+            "Mining tick: +{} (base: {}, passive: {}) | Total: {} / {}",
+            format_entropy(total_income),
+            yield_value,
+            passive,
+            format_entropy(new_state.player.entropy),
+            format_entropy(new_state.player.capacity)
+        );
+
+        // Check for decay
+        if new_state.player.entropy > new_state.player.capacity {
+            info!("⚠️  Entropy exceeds capacity! Decay of -{}/tick applies once the grace period ends",
+                format_entropy(overflow_decay(new_state.player.entropy, new_state.player.capacity)));
+        }
+
+        Some(total_income)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mining::params::MiningParams;
+    use crate::mining::yield_strategy::SqrtYield;
+    use std::sync::Arc;
+
+    fn engine(state: &StateManager) -> MiningEngine {
+        MiningEngine::new(state.clone(), 10, Box::new(SqrtYield), 1.0).with_warmup(Duration::from_secs(60))
+    }
+
+    #[tokio::test]
+    async fn test_yield_suppressed_until_first_probe() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let engine = engine(&state);
+        let started = Instant::now();
+
+        assert_eq!(engine.tick(started, started + Duration::from_secs(10)).await, None);
+        assert_eq!(state.get_snapshot().await.player.entropy, 0);
+
+        state.update_network_quality(0.8).await;
+        let mined = engine.tick(started, started + Duration::from_secs(20)).await.unwrap();
+        assert!(mined > 0);
+        assert_eq!(state.get_snapshot().await.player.entropy, mined);
+    }
+
+    #[tokio::test]
+    async fn test_server_params_override_local_multiplier() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let engine = MiningEngine::new(state.clone(), 10, Box::new(SqrtYield), 1.0);
+        let now = Instant::now();
+
+        // sqrt(100) × 1.0 quality × multiplier
+        assert_eq!(engine.tick(now, now).await, Some(10));
+
+        state.set_server_mining_params(MiningParams { tick_interval_secs: 5, base_multiplier: 2.0, issued_at: 1 });
+        assert_eq!(engine.effective_params(), (5, 2.0));
+        assert_eq!(engine.tick(now, now).await, Some(20));
+    }
+
+    #[tokio::test]
+    async fn test_lost_binding_halts_yield() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let engine = MiningEngine::new(state.clone(), 10, Box::new(SqrtYield), 1.0);
+        let now = Instant::now();
+        assert_eq!(engine.tick(now, now).await, Some(10));
+
+        state.set_binding_lost();
+        assert_eq!(engine.tick(now, now).await, None);
+        assert_eq!(state.get_snapshot().await.player.entropy, 10);
+    }
+
+    #[tokio::test]
+    async fn test_clock_jump_does_not_burst_ticks() {
+        tokio::time::pause();
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let engine = Arc::new(MiningEngine::new(state.clone(), 10, Box::new(SqrtYield), 1.0));
+        let runner = engine.clone();
+        let task = tokio::spawn(async move { runner.run().await });
+
+        // Time is paused, so this lets the engine run without reaching its next tick
+        let settle = || tokio::time::sleep(Duration::from_millis(1));
+        settle().await;
+        assert_eq!(state.get_snapshot().await.player.entropy, 10);
+
+        // An hour asleep is 360 missed ticks; only one of them is mined
+        tokio::time::advance(Duration::from_secs(3600)).await;
+        settle().await;
+        assert_eq!(state.get_snapshot().await.player.entropy, 20);
+        task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_warmup_times_out_without_probe() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let engine = engine(&state);
+        let started = Instant::now();
+
+        assert_eq!(engine.tick(started, started + Duration::from_secs(59)).await, None);
+        assert!(engine.tick(started, started + Duration::from_secs(60)).await.is_some());
+    }
 }
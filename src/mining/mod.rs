@@ -1,3 +1,9 @@
+pub mod bench;
 pub mod engine;
+pub mod params;
+pub mod yield_strategy;
+pub mod watchdog;
 
 pub use engine::MiningEngine;
+pub use yield_strategy::{strategy_by_name, YieldStrategy};
+pub use watchdog::{MiningHeartbeat, MiningWatchdog};
@@ -0,0 +1,216 @@
+// sacas-daemon/src/mining/params.rs
+// Server-signed mining parameters that override the local mining config
+
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::{Config, SyncConfig};
+
+/// Longest tick interval the server may set
+const MAX_TICK_INTERVAL_SECS: u64 = 3600;
+/// Largest yield multiplier the server may set
+const MAX_BASE_MULTIPLIER: f64 = 100.0;
+/// The multiplier is signed in whole millionths
+const MULTIPLIER_SCALE: f64 = 1_000_000.0;
+
+/// Mining economics the server can adjust centrally
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MiningParams {
+    pub tick_interval_secs: u64,
+    pub base_multiplier: f64,
+    /// When the server issued these; older issues never replace newer ones
+    pub issued_at: i64,
+}
+
+/// Parameters as they arrive in a sync response, signed by the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedMiningParams {
+    #[serde(flatten)]
+    pub params: MiningParams,
+    pub signature: String,
+}
+
+impl SignedMiningParams {
+    /// Format: MINING_PARAMS|tick_interval_secs|base_multiplier_millionths|issued_at
+    ///
+    /// The multiplier goes in as an integer so the text signed never depends
+    /// on how either side prints a float.
+    fn canonical_message(&self) -> String {
+        format!(
+            "MINING_PARAMS|{}|{}|{}",
+            self.params.tick_interval_secs,
+            multiplier_millionths(self.params.base_multiplier),
+            self.params.issued_at
+        )
+    }
+}
+
+/// NaN maps to 0, which the range check rejects
+fn multiplier_millionths(multiplier: f64) -> i64 {
+    (multiplier * MULTIPLIER_SCALE).round() as i64
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+pub enum MiningParamsError {
+    #[error("Mining parameters are not signed by the configured server key")]
+    BadSignature,
+    #[error("Mining parameters out of range: {0}")]
+    OutOfRange(String),
+}
+
+/// Verifies server parameters against a pinned key and persists accepted ones
+pub struct ServerParamsTrust {
+    key: VerifyingKey,
+    path: PathBuf,
+}
+
+impl ServerParamsTrust {
+    /// `key_base64` is the server's Ed25519 public key; accepted parameters go to `path`
+    pub fn new(key_base64: &str, path: PathBuf) -> Result<Self> {
+        let bytes = base64::decode(key_base64).context("Invalid base64 in sync.server_params_key")?;
+        let bytes: [u8; 32] = bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("sync.server_params_key must be a 32-byte Ed25519 key"))?;
+        let key = VerifyingKey::from_bytes(&bytes).context("Invalid sync.server_params_key")?;
+        Ok(Self { key, path })
+    }
+
+    /// Trust for `sync.server_params_key`, or `None` when no key is configured
+    pub fn from_config(sync: &SyncConfig) -> Result<Option<Self>> {
+        sync.server_params_key
+            .as_deref()
+            .map(|key| Self::new(key, Config::mining_params_path()?))
+            .transpose()
+    }
+
+    pub fn verify(&self, signed: &SignedMiningParams) -> Result<MiningParams, MiningParamsError> {
+        let signature = base64::decode(&signed.signature)
+            .ok()
+            .and_then(|bytes| Signature::from_slice(&bytes).ok())
+            .ok_or(MiningParamsError::BadSignature)?;
+        self.key
+            .verify(signed.canonical_message().as_bytes(), &signature)
+            .map_err(|_| MiningParamsError::BadSignature)?;
+
+        // Apply exactly the multiplier that was signed
+        let mut params = signed.params;
+        params.base_multiplier = multiplier_millionths(params.base_multiplier) as f64 / MULTIPLIER_SCALE;
+        if params.tick_interval_secs == 0 || params.tick_interval_secs > MAX_TICK_INTERVAL_SECS {
+            return Err(MiningParamsError::OutOfRange(format!("tick_interval_secs {}", params.tick_interval_secs)));
+        }
+        if !params.base_multiplier.is_finite() || params.base_multiplier <= 0.0 || params.base_multiplier > MAX_BASE_MULTIPLIER {
+            return Err(MiningParamsError::OutOfRange(format!("base_multiplier {}", params.base_multiplier)));
+        }
+        Ok(params)
+    }
+
+    /// Verify `signed` and persist it if it is newer than `current`
+    ///
+    /// Returns the parameters to apply, or `None` when they are already
+    /// applied or older than what is.
+    pub fn accept(&self, signed: &SignedMiningParams, current: Option<MiningParams>) -> Result<Option<MiningParams>> {
+        let params = self.verify(signed)?;
+        if current.is_some_and(|c| c.issued_at >= params.issued_at) {
+            return Ok(None);
+        }
+
+        let json = serde_json::to_vec_pretty(signed)?;
+        let tmp_path = self.path.with_extension("tmp");
+        std::fs::write(&tmp_path, json)
+            .and_then(|()| std::fs::rename(&tmp_path, &self.path))
+            .with_context(|| format!("Failed to persist mining parameters to {:?}", self.path))?;
+        Ok(Some(params))
+    }
+
+    /// Previously accepted parameters, re-verified; `None` if missing or invalid
+    pub fn load(&self) -> Option<MiningParams> {
+        let raw = std::fs::read_to_string(&self.path).ok()?;
+        let signed: SignedMiningParams = match serde_json::from_str(&raw) {
+            Ok(signed) => signed,
+            Err(e) => {
+                warn!("⚠️  Ignoring unreadable mining parameters at {:?}: {}", self.path, e);
+                return None;
+            }
+        };
+
+        match self.verify(&signed) {
+            Ok(params) => Some(params),
+            Err(e) => {
+                warn!("⚠️  Ignoring persisted mining parameters: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::{Signer, SigningKey};
+
+    fn server_key() -> SigningKey {
+        SigningKey::from_bytes(&[9u8; 32])
+    }
+
+    fn signed(tick_interval_secs: u64, base_multiplier: f64, issued_at: i64) -> SignedMiningParams {
+        let mut signed = SignedMiningParams {
+            params: MiningParams { tick_interval_secs, base_multiplier, issued_at },
+            signature: String::new(),
+        };
+        signed.signature = base64::encode(server_key().sign(signed.canonical_message().as_bytes()).to_bytes());
+        signed
+    }
+
+    fn trust(dir: &std::path::Path) -> ServerParamsTrust {
+        let key = base64::encode(server_key().verifying_key().to_bytes());
+        ServerParamsTrust::new(&key, dir.join("mining_params.json")).unwrap()
+    }
+
+    #[test]
+    fn test_verified_params_applied_and_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let trust = trust(dir.path());
+        assert_eq!(trust.load(), None);
+
+        let params = trust.accept(&signed(10, 0.75, 100), None).unwrap().unwrap();
+        assert_eq!(params, MiningParams { tick_interval_secs: 10, base_multiplier: 0.75, issued_at: 100 });
+        assert_eq!(trust.load(), Some(params));
+
+        // Re-sent or older parameters don't replace what is applied
+        assert_eq!(trust.accept(&signed(10, 0.75, 100), Some(params)).unwrap(), None);
+        assert_eq!(trust.accept(&signed(5, 2.0, 50), Some(params)).unwrap(), None);
+    }
+
+    #[test]
+    fn test_multiplier_signed_in_millionths() {
+        assert_eq!(signed(10, 0.1 + 0.2, 100).canonical_message(), "MINING_PARAMS|10|300000|100");
+
+        let dir = tempfile::tempdir().unwrap();
+        let params = trust(dir.path()).verify(&signed(10, 0.1 + 0.2, 100)).unwrap();
+        assert_eq!(params.base_multiplier, 0.3);
+    }
+
+    #[test]
+    fn test_invalid_params_fall_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let trust = trust(dir.path());
+
+        let mut tampered = signed(10, 0.75, 100);
+        tampered.params.base_multiplier = 50.0;
+        assert_eq!(trust.verify(&tampered), Err(MiningParamsError::BadSignature));
+        assert!(matches!(trust.verify(&signed(0, 0.75, 100)), Err(MiningParamsError::OutOfRange(_))));
+        assert!(matches!(trust.verify(&signed(10, f64::NAN, 100)), Err(MiningParamsError::OutOfRange(_))));
+
+        assert!(trust.accept(&tampered, None).is_err());
+        assert_eq!(trust.load(), None);
+
+        // A persisted file that no longer verifies is ignored
+        std::fs::write(dir.path().join("mining_params.json"), serde_json::to_string(&tampered).unwrap()).unwrap();
+        assert_eq!(trust.load(), None);
+    }
+}
@@ -0,0 +1,114 @@
+// sacas-daemon/src/mining/watchdog.rs
+// Detects a mining loop that has stopped ticking
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::time::Instant;
+use tracing::{info, warn};
+
+use crate::state::StateManager;
+
+/// When the mining loop last completed a tick; clones share it
+#[derive(Clone)]
+pub struct MiningHeartbeat {
+    last_tick: Arc<Mutex<Instant>>,
+}
+
+impl Default for MiningHeartbeat {
+    fn default() -> Self {
+        Self {
+            last_tick: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+}
+
+impl MiningHeartbeat {
+    pub fn beat(&self) {
+        *self.last_tick.lock().unwrap() = Instant::now();
+    }
+
+    pub fn last_tick(&self) -> Instant {
+        *self.last_tick.lock().unwrap()
+    }
+}
+
+/// Flags mining as stalled once the heartbeat is older than `ticks` ticks
+///
+/// A tick is the one mining runs at: the server's tick interval once
+/// verified server parameters are applied, otherwise `local_tick`. The
+/// verdict is mirrored into `StateManager::is_mining_healthy` for the
+/// status RPC, and each change is logged once.
+pub struct MiningWatchdog {
+    heartbeat: MiningHeartbeat,
+    local_tick: Duration,
+    ticks: u32,
+    state: Arc<StateManager>,
+}
+
+impl MiningWatchdog {
+    pub fn new(heartbeat: MiningHeartbeat, local_tick: Duration, ticks: u32, state: Arc<StateManager>) -> Self {
+        Self { heartbeat, local_tick, ticks, state }
+    }
+
+    /// How long mining may go without a tick, re-read on every check so a
+    /// server parameter change applies at once
+    pub fn limit(&self) -> Duration {
+        let tick = match self.state.server_mining_params() {
+            Some(params) => Duration::from_secs(params.tick_interval_secs),
+            None => self.local_tick,
+        };
+        tick * self.ticks
+    }
+
+    /// Whether mining is stalled as of `now`
+    pub fn check(&self, now: Instant) -> bool {
+        let limit = self.limit();
+        let silent_for = now.saturating_duration_since(self.heartbeat.last_tick());
+        let stalled = silent_for > limit;
+
+        let was_healthy = self.state.set_mining_healthy(!stalled);
+        if stalled && was_healthy {
+            warn!("⛏️  Mining stalled: no tick for {}s (limit {}s)", silent_for.as_secs(), limit.as_secs());
+        } else if !stalled && !was_healthy {
+            info!("⛏️  Mining ticking again");
+        }
+
+        stalled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mining::params::MiningParams;
+
+    #[test]
+    fn test_stalled_heartbeat_trips_watchdog() {
+        let state = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let heartbeat = MiningHeartbeat::default();
+        let watchdog = MiningWatchdog::new(heartbeat.clone(), Duration::from_secs(5), 5, state.clone());
+        let start = heartbeat.last_tick();
+
+        assert!(!watchdog.check(start + Duration::from_secs(25)));
+        assert!(state.is_mining_healthy());
+
+        assert!(watchdog.check(start + Duration::from_secs(26)));
+        assert!(!state.is_mining_healthy());
+
+        // A fresh tick clears the stall
+        heartbeat.beat();
+        assert!(!watchdog.check(Instant::now()));
+        assert!(state.is_mining_healthy());
+    }
+
+    #[test]
+    fn test_limit_follows_server_tick() {
+        let state = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let watchdog = MiningWatchdog::new(MiningHeartbeat::default(), Duration::from_secs(10), 5, state.clone());
+        assert_eq!(watchdog.limit(), Duration::from_secs(50));
+
+        state.set_server_mining_params(MiningParams { tick_interval_secs: 3600, base_multiplier: 1.0, issued_at: 1 });
+        assert_eq!(watchdog.limit(), Duration::from_secs(18_000));
+    }
+}
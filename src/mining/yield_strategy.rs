@@ -0,0 +1,59 @@
+// sacas-daemon/src/mining/yield_strategy.rs
+// Swappable mining yield formulas
+
+/// Computes the base Ω yield for one mining tick
+pub trait YieldStrategy: Send + Sync {
+    fn calculate(&self, karma: u64, network_quality: f64, multiplier: f64) -> u64;
+}
+
+/// Default formula: sqrt(karma) × quality × multiplier
+pub struct SqrtYield;
+
+impl YieldStrategy for SqrtYield {
+    fn calculate(&self, karma: u64, network_quality: f64, multiplier: f64) -> u64 {
+        let base = (karma as f64).sqrt();
+        (base * network_quality * multiplier) as u64
+    }
+}
+
+/// Experimental formula: karma × quality × multiplier
+pub struct LinearYield;
+
+impl YieldStrategy for LinearYield {
+    fn calculate(&self, karma: u64, network_quality: f64, multiplier: f64) -> u64 {
+        (karma as f64 * network_quality * multiplier) as u64
+    }
+}
+
+/// Look up a strategy by its `mining.yield_strategy` config name
+pub fn strategy_by_name(name: &str) -> Option<Box<dyn YieldStrategy>> {
+    match name {
+        "sqrt" => Some(Box::new(SqrtYield)),
+        "linear" => Some(Box::new(LinearYield)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Player;
+
+    #[test]
+    fn test_default_matches_player_yield() {
+        let mut player = Player::new("dev".to_string(), 1000);
+        player.network_quality = 1.2;
+
+        let strategy = strategy_by_name("sqrt").unwrap();
+        assert_eq!(strategy.calculate(1000, 1.2, 0.5), player.calculate_yield());
+        assert_eq!(strategy.calculate(1000, 1.0, 0.5), 15);
+    }
+
+    #[test]
+    fn test_linear_strategy() {
+        let strategy = strategy_by_name("linear").unwrap();
+        assert_eq!(strategy.calculate(1000, 1.0, 0.5), 500);
+        assert_eq!(strategy.calculate(1000, 0.1, 0.5), 50);
+        assert!(strategy_by_name("cubic").is_none());
+    }
+}
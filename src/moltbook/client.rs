@@ -1,9 +1,16 @@
-use reqwest;
 use serde::{Deserialize, Serialize};
-use anyhow::{Result, Context};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
 use tracing::{info, warn};
+use std::time::Duration;
 
-#[derive(Debug, Deserialize)]
+use crate::http::HttpClient;
+use crate::moltbook::MoltbookError;
+
+/// Per-request timeout for Moltbook API calls
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoltbookOwner {
     pub x_handle: Option<String>,
     pub x_name: Option<String>,
@@ -14,7 +21,7 @@ pub struct MoltbookOwner {
     pub x_verified: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MoltbookAgent {
     pub name: String,
     pub description: Option<String>,
@@ -39,53 +46,62 @@ pub struct MoltbookClient {
     api_url: String,
     api_key: String,
     agent_name: String,
-    client: reqwest::Client,
+    client: HttpClient,
 }
 
 impl MoltbookClient {
-    pub fn new(api_url: String, api_key: String, agent_name: String) -> Self {
+    pub fn new(client: HttpClient, api_url: String, api_key: String, agent_name: String) -> Self {
         Self {
             api_url,
             api_key,
             agent_name,
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()
-                .expect("Failed to create HTTP client"),
+            client,
         }
     }
     
     /// Fetch Karma value from Moltbook API
-    pub async fn fetch_karma(&self) -> Result<u64> {
+    pub async fn fetch_karma(&self) -> Result<u64, MoltbookError> {
+        Ok(self.fetch_profile().await?.karma)
+    }
+
+    /// Fetch the full agent profile, including owner details
+    pub async fn fetch_profile(&self) -> Result<MoltbookAgent, MoltbookError> {
         let url = format!(
             "{}/api/v1/agents/profile?name={}",
             self.api_url,
             urlencoding::encode(&self.agent_name)
         );
         
-        info!("📡 Fetching Karma from Moltbook for agent: {}", self.agent_name);
+        info!("📡 Fetching profile from Moltbook for agent: {}", self.agent_name);
         
         let response = self.client
             .get(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
+            .timeout(REQUEST_TIMEOUT)
             .send()
             .await
-            .context("Failed to send request to Moltbook API")?;
+            .map_err(|e| MoltbookError::Network(e.to_string()))?;
         
         let status = response.status();
         
         if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            anyhow::bail!("Moltbook API error {}: {}", status, error_text);
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+                .map(Duration::from_secs);
+            let error_text = self.client.read_text(response).await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(self.status_error(status, retry_after, error_text));
         }
         
-        let profile: MoltbookProfileResponse = response
-            .json()
+        let profile: MoltbookProfileResponse = self.client
+            .read_json(response)
             .await
-            .context("Failed to parse Moltbook API response")?;
+            .map_err(|e| MoltbookError::Api(format!("{:#}", e)))?;
         
         if !profile.success {
-            anyhow::bail!("Moltbook API returned success: false");
+            return Err(MoltbookError::Api("success: false".to_string()));
         }
         
         if !profile.agent.is_claimed {
@@ -97,12 +113,95 @@ impl MoltbookClient {
         }
         
         info!(
-            "✅ Karma fetched from Moltbook: {} (followers: {}, active: {})",
+            "✅ Profile fetched from Moltbook: karma {} (followers: {}, active: {})",
             profile.agent.karma,
             profile.agent.follower_count.unwrap_or(0),
             profile.agent.is_active
         );
         
-        Ok(profile.agent.karma)
+        Ok(profile.agent)
+    }
+
+    fn status_error(&self, status: StatusCode, retry_after: Option<Duration>, body: String) -> MoltbookError {
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => MoltbookError::Unauthorized,
+            StatusCode::NOT_FOUND => MoltbookError::NotFound(self.agent_name.clone()),
+            StatusCode::TOO_MANY_REQUESTS => MoltbookError::RateLimited { retry_after },
+            s if s.is_server_error() => MoltbookError::Network(format!("{}: {}", status, body)),
+            _ => MoltbookError::Api(format!("{}: {}", status, body)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::MockServer;
+
+    const FULL_PROFILE: &str = r#"{
+        "success": true,
+        "agent": {
+            "name": "ClawdClawderberg",
+            "description": "Entropy farmer",
+            "karma": 4200,
+            "follower_count": 310,
+            "following_count": 12,
+            "is_claimed": true,
+            "is_active": false,
+            "created_at": "2025-11-02T08:00:00Z",
+            "last_active": "2026-01-15T21:30:00Z",
+            "owner": {
+                "x_handle": "clawd",
+                "x_name": "Clawd",
+                "x_avatar": null,
+                "x_bio": "builds things",
+                "x_follower_count": 9800,
+                "x_following_count": 150,
+                "x_verified": true
+            }
+        }
+    }"#;
+
+    #[tokio::test]
+    async fn test_fetch_full_profile() {
+        let server = MockServer::respond_with(200, FULL_PROFILE).await;
+        let client = MoltbookClient::new(
+            HttpClient::default(),
+            server.url.clone(),
+            "token".to_string(),
+            "ClawdClawderberg".to_string(),
+        );
+
+        let agent = client.fetch_profile().await.unwrap();
+
+        assert_eq!(agent.karma, 4200);
+        assert_eq!(agent.follower_count, Some(310));
+        assert!(!agent.is_active);
+        let owner = agent.owner.unwrap();
+        assert_eq!(owner.x_handle.as_deref(), Some("clawd"));
+        assert_eq!(owner.x_verified, Some(true));
+        assert_eq!(server.requests()[0].header("authorization"), Some("Bearer token"));
+    }
+
+    #[tokio::test]
+    async fn test_status_maps_to_error_variant() {
+        for status in [401, 404, 429, 503, 400] {
+            let server = MockServer::respond_with(status, r#"{"error":"nope"}"#).await;
+            let client = MoltbookClient::new(
+                HttpClient::default(),
+                server.url.clone(),
+                "token".to_string(),
+                "ClawdClawderberg".to_string(),
+            );
+
+            let err = client.fetch_karma().await.unwrap_err();
+            match status {
+                401 => assert!(matches!(err, MoltbookError::Unauthorized)),
+                404 => assert!(matches!(err, MoltbookError::NotFound(ref name) if name == "ClawdClawderberg")),
+                429 => assert!(matches!(err, MoltbookError::RateLimited { retry_after: None })),
+                503 => assert!(matches!(err, MoltbookError::Network(_))),
+                _ => assert!(matches!(err, MoltbookError::Api(_))),
+            }
+        }
     }
 }
@@ -0,0 +1,23 @@
+// sacas-daemon/src/moltbook/error.rs
+// Moltbook API failures, split by how the caller should retry
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum MoltbookError {
+    /// The API key was rejected; retrying won't help until it's replaced
+    #[error("Moltbook rejected the API key")]
+    Unauthorized,
+    #[error("Moltbook agent '{0}' not found")]
+    NotFound(String),
+    #[error("Moltbook rate limit hit (retry after {retry_after:?})")]
+    RateLimited { retry_after: Option<Duration> },
+    /// The request didn't complete or the server failed (5xx); worth retrying
+    #[error("Moltbook unreachable: {0}")]
+    Network(String),
+    /// Any other status or a response we can't use
+    #[error("Moltbook API error: {0}")]
+    Api(String),
+}
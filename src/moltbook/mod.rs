@@ -1,3 +1,5 @@
 pub mod client;
+pub mod error;
 
 pub use client::MoltbookClient;
+pub use error::MoltbookError;
@@ -0,0 +1,265 @@
+// sacas-daemon/src/network/anchors.rs
+// Live, shared anchor set used by the network probe and managed over gRPC
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use super::EmptyAnchors;
+use crate::config::{Anchor, Config};
+
+#[derive(Debug, Error)]
+pub enum AnchorError {
+    #[error("Anchor id must not be empty")]
+    EmptyId,
+
+    #[error("Anchor '{0}' already exists")]
+    DuplicateId(String),
+
+    #[error("Invalid anchor address '{0}': expected an IP address or hostname")]
+    InvalidAddress(String),
+
+    #[error("Anchor '{0}' not found")]
+    NotFound(String),
+
+    #[error("Anchor '{0}' is the last one; network.empty_anchors is Refuse")]
+    LastAnchor(String),
+
+    #[error("Failed to persist anchors to config: {0}")]
+    Persist(String),
+}
+
+/// Cloneable handle to the anchor set
+///
+/// The probe reads the current set on every tick, so changes take effect
+/// on the next probe without a restart. When a config path is set, every
+/// change is written back to `[network].anchors`.
+#[derive(Clone)]
+pub struct AnchorRegistry {
+    anchors: Arc<RwLock<Vec<Anchor>>>,
+    config_path: Option<PathBuf>,
+    empty: EmptyAnchors,
+}
+
+impl AnchorRegistry {
+    /// Registry over `anchors`, with repeated ids dropped (see `dedupe_anchors`)
+    pub fn new(anchors: Vec<Anchor>) -> Self {
+        let (anchors, _) = dedupe_anchors(anchors);
+        Self {
+            anchors: Arc::new(RwLock::new(anchors)),
+            config_path: None,
+            empty: EmptyAnchors::default(),
+        }
+    }
+
+    /// Persist every change to the config file at `path`
+    pub fn persisted_to(mut self, path: PathBuf) -> Self {
+        self.config_path = Some(path);
+        self
+    }
+
+    /// Apply `policy` when a removal would leave no anchors
+    pub fn with_empty_policy(mut self, policy: EmptyAnchors) -> Self {
+        self.empty = policy;
+        self
+    }
+
+    pub async fn list(&self) -> Vec<Anchor> {
+        self.anchors.read().await.clone()
+    }
+
+    pub async fn add(&self, anchor: Anchor) -> Result<(), AnchorError> {
+        if anchor.id.trim().is_empty() {
+            return Err(AnchorError::EmptyId);
+        }
+        if !is_valid_host(&anchor.ip) {
+            return Err(AnchorError::InvalidAddress(anchor.ip));
+        }
+
+        let mut anchors = self.anchors.write().await;
+        if anchors.iter().any(|a| a.id == anchor.id) {
+            return Err(AnchorError::DuplicateId(anchor.id));
+        }
+
+        let mut updated = anchors.clone();
+        info!("📍 Adding anchor {} ({}, {})", anchor.id, anchor.ip, anchor.region);
+        updated.push(anchor);
+
+        self.persist(&updated)?;
+        *anchors = updated;
+        Ok(())
+    }
+
+    pub async fn remove(&self, id: &str) -> Result<Anchor, AnchorError> {
+        let mut anchors = self.anchors.write().await;
+        let index = anchors
+            .iter()
+            .position(|a| a.id == id)
+            .ok_or_else(|| AnchorError::NotFound(id.to_string()))?;
+        if anchors.len() == 1 && self.empty == EmptyAnchors::Refuse {
+            return Err(AnchorError::LastAnchor(id.to_string()));
+        }
+
+        let mut updated = anchors.clone();
+        let removed = updated.remove(index);
+        info!("📍 Removing anchor {} ({})", removed.id, removed.ip);
+
+        self.persist(&updated)?;
+        *anchors = updated;
+        Ok(removed)
+    }
+
+    /// Write the anchor list into the config file, leaving other settings alone
+    fn persist(&self, anchors: &[Anchor]) -> Result<(), AnchorError> {
+        let Some(path) = &self.config_path else {
+            return Ok(());
+        };
+
+        let mut config = Config::load_from(path).map_err(|e| AnchorError::Persist(e.to_string()))?;
+        config.network.anchors = anchors.to_vec();
+        config.save(path).map_err(|e| AnchorError::Persist(e.to_string()))
+    }
+}
+
+/// Accept IP literals and syntactically valid hostnames
+fn is_valid_host(host: &str) -> bool {
+    if host.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+
+    if host.is_empty() || host.len() > 253 {
+        return false;
+    }
+
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Drop anchors whose id was already listed, keeping the first in order
+///
+/// Each duplicate would add a redundant latency vector entry. Distinct ids
+/// sharing an address are kept but warned about; those addresses are
+/// returned.
+pub fn dedupe_anchors(anchors: Vec<Anchor>) -> (Vec<Anchor>, Vec<String>) {
+    let mut kept: Vec<Anchor> = Vec::with_capacity(anchors.len());
+    let mut shared_ips = Vec::new();
+
+    for anchor in anchors {
+        if kept.iter().any(|a| a.id == anchor.id) {
+            warn!("📍 Ignoring duplicate anchor id '{}' ({})", anchor.id, anchor.ip);
+            continue;
+        }
+        if let Some(first) = kept.iter().find(|a| a.ip.eq_ignore_ascii_case(&anchor.ip)) {
+            warn!("📍 Anchors '{}' and '{}' both probe {}", first.id, anchor.id, anchor.ip);
+            if !shared_ips.contains(&anchor.ip) {
+                shared_ips.push(anchor.ip.clone());
+            }
+        }
+        kept.push(anchor);
+    }
+
+    (kept, shared_ips)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::sample_config;
+
+    fn anchor(id: &str, ip: &str) -> Anchor {
+        Anchor {
+            id: id.to_string(),
+            ip: ip.to_string(),
+            region: "test".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_add_and_remove() {
+        let registry = AnchorRegistry::new(vec![anchor("cloudflare", "1.1.1.1")]);
+
+        registry.add(anchor("quad9", "9.9.9.9")).await.unwrap();
+        registry.add(anchor("local", "anchor.example.net")).await.unwrap();
+        assert_eq!(registry.list().await.len(), 3);
+
+        let removed = registry.remove("cloudflare").await.unwrap();
+        assert_eq!(removed.ip, "1.1.1.1");
+
+        let ids: Vec<String> = registry.list().await.into_iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec!["quad9", "local"]);
+    }
+
+    #[tokio::test]
+    async fn test_last_anchor_kept_when_refusing_empty() {
+        let registry = AnchorRegistry::new(vec![anchor("cloudflare", "1.1.1.1"), anchor("quad9", "9.9.9.9")])
+            .with_empty_policy(EmptyAnchors::Refuse);
+
+        registry.remove("quad9").await.unwrap();
+        assert!(matches!(registry.remove("cloudflare").await, Err(AnchorError::LastAnchor(_))));
+        assert_eq!(registry.list().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_ids_dropped_in_order() {
+        let registry = AnchorRegistry::new(vec![
+            anchor("cloudflare", "1.1.1.1"),
+            anchor("quad9", "9.9.9.9"),
+            anchor("cloudflare", "1.0.0.1"),
+            anchor("google", "8.8.8.8"),
+            anchor("quad9", "9.9.9.9"),
+        ]);
+
+        let anchors = registry.list().await;
+        let ids: Vec<&str> = anchors.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["cloudflare", "quad9", "google"]);
+        assert_eq!(anchors[0].ip, "1.1.1.1");
+    }
+
+    #[test]
+    fn test_shared_ip_with_distinct_ids_warned() {
+        let (anchors, shared) = dedupe_anchors(vec![
+            anchor("cloudflare", "1.1.1.1"),
+            anchor("cf-backup", "1.1.1.1"),
+            anchor("quad9", "9.9.9.9"),
+        ]);
+
+        assert_eq!(anchors.len(), 3);
+        assert_eq!(shared, ["1.1.1.1"]);
+    }
+
+    #[tokio::test]
+    async fn test_validation() {
+        let registry = AnchorRegistry::new(vec![anchor("cloudflare", "1.1.1.1")]);
+
+        assert!(matches!(registry.add(anchor("cloudflare", "1.0.0.1")).await, Err(AnchorError::DuplicateId(_))));
+        assert!(matches!(registry.add(anchor(" ", "1.0.0.1")).await, Err(AnchorError::EmptyId)));
+        assert!(matches!(registry.add(anchor("bad", "not a host")).await, Err(AnchorError::InvalidAddress(_))));
+        assert!(matches!(registry.add(anchor("bad", "-bad.example")).await, Err(AnchorError::InvalidAddress(_))));
+        assert!(matches!(registry.remove("missing").await, Err(AnchorError::NotFound(_))));
+        assert_eq!(registry.list().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_changes_persisted_to_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        sample_config().save(&path).unwrap();
+
+        let registry = AnchorRegistry::new(vec![]).persisted_to(path.clone());
+        registry.add(anchor("quad9", "9.9.9.9")).await.unwrap();
+
+        let saved = Config::load_from(&path).unwrap();
+        assert_eq!(saved.network.anchors.len(), 1);
+        assert_eq!(saved.network.anchors[0].id, "quad9");
+        assert_eq!(saved.device_id, sample_config().device_id);
+    }
+}
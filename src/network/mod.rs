@@ -1,3 +1,6 @@
+pub mod anchors;
 pub mod probe;
+pub mod telemetry;
 
-pub use probe::NetworkProbe;
+pub use anchors::{AnchorError, AnchorRegistry};
+pub use probe::{EmptyAnchors, NetworkProbe, ProbeSchedule};
@@ -1,4 +1,5 @@
 use crate::config::Anchor;
+use super::AnchorRegistry;
 use crate::types::LatencyVector;
 use anyhow::{Result, Context};
 use surge_ping::{Client, Config as PingConfig, PingIdentifier, PingSequence, ICMP};
@@ -7,14 +8,15 @@ use std::time::Duration;
 use tokio::time::timeout;
 use tracing::{debug, warn};
 use chrono::Utc;
+use serde::{Deserialize, Serialize};
 
 pub struct NetworkProbe {
-    anchors: Vec<Anchor>,
+    anchors: AnchorRegistry,
     ping_client: Client,
 }
 
 impl NetworkProbe {
-    pub fn new(anchors: Vec<Anchor>) -> Result<Self> {
+    pub fn new(anchors: AnchorRegistry) -> Result<Self> {
         let config = PingConfig::default();
         let ping_client = Client::new(&config)
             .context("Failed to create ping client")?;
@@ -28,7 +30,8 @@ impl NetworkProbe {
     pub async fn build_latency_vector(&self) -> Result<LatencyVector> {
         let mut latencies = Vec::new();
 
-        for anchor in &self.anchors {
+        // Snapshot the live set so runtime anchor changes apply next tick
+        for anchor in &self.anchors.list().await {
             let latency = self.ping_anchor(anchor).await;
             latencies.push(latency);
         }
@@ -45,11 +48,11 @@ impl NetworkProbe {
     }
 
     async fn ping_anchor(&self, anchor: &Anchor) -> f64 {
-        let ip: IpAddr = match anchor.ip.parse() {
+        let ip = match resolve_anchor(&anchor.ip).await {
             Ok(ip) => ip,
             Err(e) => {
-                warn!("Invalid IP for anchor {}: {}", anchor.id, e);
-                return 999.0; // Return high latency for invalid IPs
+                warn!("Invalid address for anchor {}: {}", anchor.id, e);
+                return 999.0; // Return high latency for unresolvable anchors
             }
         };
 
@@ -110,7 +113,13 @@ impl NetworkProbe {
         base64::encode(hash)
     }
 
+    /// Quality score for one latency vector; `NEUTRAL_QUALITY` when it is empty
     pub fn calculate_network_quality(&self, latencies: &[f64]) -> f64 {
+        // No anchors means nothing measured, not an infinitely slow network
+        if latencies.is_empty() {
+            return NEUTRAL_QUALITY;
+        }
+
         // Calculate network quality based on latencies
         let avg_latency: f64 = latencies.iter().sum::<f64>() / latencies.len() as f64;
         
@@ -124,11 +133,98 @@ impl NetworkProbe {
         } else if avg_latency < 500.0 {
             0.5
         } else {
-            0.1
+            QUALITY_FLOOR
         }
     }
 }
 
+/// Lowest network quality `calculate_network_quality` reports
+pub const QUALITY_FLOOR: f64 = 0.1;
+
+/// Quality reported when there are no anchors to measure against
+pub const NEUTRAL_QUALITY: f64 = 1.0;
+
+/// What to do when the anchor list is empty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EmptyAnchors {
+    /// Keep running at `NEUTRAL_QUALITY`, warning on every probe
+    #[default]
+    Neutral,
+    /// Reject a config without anchors, and refuse to remove the last one
+    Refuse,
+}
+
+/// Probe cadence that backs off while the network is down
+///
+/// Each probe at the quality floor (or failing outright) doubles the delay
+/// up to `max`; the first better result returns to `base`. The cap keeps
+/// probing often enough to notice recovery.
+#[derive(Debug)]
+pub struct ProbeSchedule {
+    base: Duration,
+    max: Duration,
+    current: Duration,
+    first_immediately: bool,
+}
+
+impl ProbeSchedule {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self {
+            base,
+            max: max.max(base),
+            current: base,
+            first_immediately: true,
+        }
+    }
+
+    /// Whether the first probe runs at startup or one interval later
+    pub fn with_first_probe_immediately(mut self, immediately: bool) -> Self {
+        self.first_immediately = immediately;
+        self
+    }
+
+    /// Delay before the first probe, before jitter
+    ///
+    /// Mining warm-up waits on that probe, so this is scheduled explicitly
+    /// rather than left to how an interval's first tick behaves.
+    pub fn first_delay(&self) -> Duration {
+        if self.first_immediately {
+            Duration::ZERO
+        } else {
+            self.base
+        }
+    }
+
+    /// The configured interval used while the network is healthy
+    pub fn base(&self) -> Duration {
+        self.base
+    }
+
+    /// Record a probe's quality (`None` if it failed) and return the delay
+    /// until the next one
+    pub fn next_delay(&mut self, quality: Option<f64>) -> Duration {
+        self.current = match quality {
+            Some(q) if q > QUALITY_FLOOR => self.base,
+            _ => self.current.saturating_mul(2).min(self.max),
+        };
+        self.current
+    }
+}
+
+/// Anchors may be IP literals or hostnames
+async fn resolve_anchor(host: &str) -> Result<IpAddr> {
+    if let Ok(ip) = host.parse() {
+        return Ok(ip);
+    }
+
+    tokio::net::lookup_host((host, 0))
+        .await
+        .with_context(|| format!("Failed to resolve {}", host))?
+        .next()
+        .map(|addr| addr.ip())
+        .ok_or_else(|| anyhow::anyhow!("No addresses for {}", host))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,10 +239,57 @@ mod tests {
             }
         ];
 
-        let probe = NetworkProbe::new(anchors).unwrap();
+        let probe = NetworkProbe::new(AnchorRegistry::new(anchors)).unwrap();
         let vector = probe.build_latency_vector().await.unwrap();
 
         assert_eq!(vector.data.len(), 1);
         println!("Latency: {:?}", vector.data);
     }
+
+    #[tokio::test]
+    async fn test_probe_uses_updated_anchors() {
+        let registry = AnchorRegistry::new(vec![]);
+        let probe = NetworkProbe::new(registry.clone()).unwrap();
+        assert!(probe.build_latency_vector().await.unwrap().data.is_empty());
+
+        registry.add(Anchor {
+            id: "loopback".to_string(),
+            ip: "127.0.0.1".to_string(),
+            region: "local".to_string(),
+        }).await.unwrap();
+
+        assert_eq!(probe.build_latency_vector().await.unwrap().data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_empty_anchors_give_neutral_quality() {
+        let probe = NetworkProbe::new(AnchorRegistry::new(vec![])).unwrap();
+        let vector = probe.build_latency_vector().await.unwrap();
+
+        let quality = probe.calculate_network_quality(&vector.data);
+        assert!(quality.is_finite());
+        assert_eq!(quality, NEUTRAL_QUALITY);
+        assert_eq!(probe.calculate_network_quality(&[20.0]), 1.5);
+    }
+
+    #[test]
+    fn test_probe_backoff_grows_to_cap() {
+        let mut schedule = ProbeSchedule::new(Duration::from_secs(60), Duration::from_secs(300));
+
+        let delays: Vec<u64> = (0..5)
+            .map(|_| schedule.next_delay(Some(QUALITY_FLOOR)).as_secs())
+            .collect();
+        assert_eq!(delays, vec![120, 240, 300, 300, 300]);
+        assert_eq!(schedule.next_delay(None).as_secs(), 300);
+    }
+
+    #[test]
+    fn test_probe_backoff_resets_on_recovery() {
+        let mut schedule = ProbeSchedule::new(Duration::from_secs(60), Duration::from_secs(600));
+        schedule.next_delay(Some(QUALITY_FLOOR));
+        schedule.next_delay(None);
+
+        assert_eq!(schedule.next_delay(Some(1.0)).as_secs(), 60);
+        assert_eq!(schedule.next_delay(Some(QUALITY_FLOOR)).as_secs(), 120);
+    }
 }
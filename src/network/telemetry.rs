@@ -0,0 +1,103 @@
+// sacas-daemon/src/network/telemetry.rs
+// Opt-in sampling of which probe latency vectors get uploaded
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TelemetrySamplingConfig {
+    /// Upload signed latency vectors at all (off unless opted in)
+    pub enabled: bool,
+    /// Upload every Nth probe (0 disables the periodic trigger)
+    pub every_nth: u32,
+    /// Upload as soon as quality moves this far from the last upload (0 disables)
+    pub quality_change: f64,
+}
+
+impl Default for TelemetrySamplingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            every_nth: 10,
+            quality_change: 0.25,
+        }
+    }
+}
+
+/// Decides which probes have their latency vector uploaded
+///
+/// Only the upload is sampled; local quality is updated on every probe
+/// regardless of what this says.
+#[derive(Debug)]
+pub struct LatencySampler {
+    config: TelemetrySamplingConfig,
+    probes_since_upload: u32,
+    last_uploaded_quality: Option<f64>,
+}
+
+impl LatencySampler {
+    pub fn new(config: TelemetrySamplingConfig) -> Self {
+        Self {
+            config,
+            probes_since_upload: 0,
+            last_uploaded_quality: None,
+        }
+    }
+
+    /// Record a probe with `quality`; true if its vector should be uploaded
+    ///
+    /// The first probe after startup is always sampled so the server has a
+    /// baseline to compare against.
+    pub fn should_upload(&mut self, quality: f64) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+
+        self.probes_since_upload += 1;
+        let nth = self.config.every_nth > 0 && self.probes_since_upload >= self.config.every_nth;
+        let changed = match self.last_uploaded_quality {
+            None => true,
+            Some(last) => self.config.quality_change > 0.0 && (quality - last).abs() >= self.config.quality_change,
+        };
+
+        if nth || changed {
+            self.probes_since_upload = 0;
+            self.last_uploaded_quality = Some(quality);
+        }
+        nth || changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sampler(every_nth: u32, quality_change: f64) -> LatencySampler {
+        LatencySampler::new(TelemetrySamplingConfig { enabled: true, every_nth, quality_change })
+    }
+
+    #[test]
+    fn test_every_nth_probe_sampled() {
+        let mut sampler = sampler(3, 0.0);
+        let sampled: Vec<bool> = (0..7).map(|_| sampler.should_upload(1.0)).collect();
+        assert_eq!(sampled, [true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn test_quality_change_sampled() {
+        let mut sampler = sampler(0, 0.25);
+        assert!(sampler.should_upload(1.0));
+        assert!(!sampler.should_upload(1.1));
+        assert!(!sampler.should_upload(0.8));
+
+        // Measured from the last upload, not the last probe
+        assert!(sampler.should_upload(0.75));
+        assert!(!sampler.should_upload(0.9));
+        assert!(sampler.should_upload(1.5));
+    }
+
+    #[test]
+    fn test_disabled_never_samples() {
+        let mut sampler = LatencySampler::new(TelemetrySamplingConfig::default());
+        assert!((0..20).all(|i| !sampler.should_upload(i as f64 / 10.0)));
+    }
+}
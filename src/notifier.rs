@@ -0,0 +1,124 @@
+// sacas-daemon/src/notifier.rs
+// Desktop notifications behind a backend chosen in config
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NotificationBackend {
+    /// Spawn `osascript` per notification (the long-standing behavior)
+    #[default]
+    Osascript,
+    /// Post through the native notification framework, no child process
+    UserNotification,
+    /// Don't show notifications
+    None,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NotificationConfig {
+    pub backend: NotificationBackend,
+}
+
+/// Shows a desktop notification; failures are ignored
+///
+/// Every backend is a no-op off macOS.
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn notify(&self, title: &str, body: &str);
+}
+
+pub struct OsascriptNotifier;
+
+impl Notifier for OsascriptNotifier {
+    fn name(&self) -> &'static str {
+        "osascript"
+    }
+
+    #[cfg(target_os = "macos")]
+    fn notify(&self, title: &str, body: &str) {
+        let script = format!(
+            r#"display notification "{}" with title "SACAS Daemon" subtitle "{}""#,
+            body.replace('"', r#"\""#),
+            title.replace('"', r#"\""#)
+        );
+
+        let _ = std::process::Command::new("osascript")
+            .arg("-e")
+            .arg(&script)
+            .spawn();
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn notify(&self, _title: &str, _body: &str) {}
+}
+
+pub struct UserNotificationNotifier;
+
+impl Notifier for UserNotificationNotifier {
+    fn name(&self) -> &'static str {
+        "user_notification"
+    }
+
+    #[cfg(target_os = "macos")]
+    fn notify(&self, title: &str, body: &str) {
+        let (title, body) = (title.to_string(), body.to_string());
+
+        // Delivery can block briefly; keep it off the async workers
+        std::thread::spawn(move || {
+            static APP: std::sync::Once = std::sync::Once::new();
+            APP.call_once(|| {
+                let bundle = mac_notification_sys::get_bundle_identifier_or_default("Terminal");
+                let _ = mac_notification_sys::set_application(&bundle);
+            });
+
+            if let Err(e) = mac_notification_sys::send_notification("SACAS Daemon", Some(&title), &body, None) {
+                tracing::debug!("Notification not delivered: {}", e);
+            }
+        });
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn notify(&self, _title: &str, _body: &str) {}
+}
+
+pub struct NoopNotifier;
+
+impl Notifier for NoopNotifier {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    fn notify(&self, _title: &str, _body: &str) {}
+}
+
+/// The notifier for a configured backend
+pub fn notifier_for(backend: NotificationBackend) -> Arc<dyn Notifier> {
+    match backend {
+        NotificationBackend::Osascript => Arc::new(OsascriptNotifier),
+        NotificationBackend::UserNotification => Arc::new(UserNotificationNotifier),
+        NotificationBackend::None => Arc::new(NoopNotifier),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_selection() {
+        let config: NotificationConfig = toml::from_str(r#"backend = "UserNotification""#).unwrap();
+        assert_eq!(notifier_for(config.backend).name(), "user_notification");
+
+        assert_eq!(notifier_for(NotificationConfig::default().backend).name(), "osascript");
+        assert_eq!(notifier_for(NotificationBackend::None).name(), "none");
+    }
+
+    #[test]
+    fn test_noop_backend_accepts_anything() {
+        let notifier = notifier_for(NotificationBackend::None);
+        notifier.notify("", "");
+        notifier.notify("⚠️ Under Attack!", "quote \" and\nnewline");
+    }
+}
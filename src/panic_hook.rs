@@ -0,0 +1,186 @@
+// sacas-daemon/src/panic_hook.rs
+// Panic reporting and respawning, so a panicked background task doesn't die silently
+
+use std::future::Future;
+use std::panic::PanicHookInfo;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tracing::{error, info, warn};
+
+use crate::notifier::Notifier;
+use crate::state::StateManager;
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PanicConfig {
+    /// Show a desktop notification when a task panics
+    pub notify: bool,
+    /// Write a best-effort state checkpoint from the panic hook
+    pub checkpoint: bool,
+    /// Delay before a panicked background loop is started again (0 leaves it stopped)
+    pub respawn_delay_secs: u64,
+}
+
+impl Default for PanicConfig {
+    fn default() -> Self {
+        Self {
+            notify: true,
+            checkpoint: true,
+            respawn_delay_secs: 5,
+        }
+    }
+}
+
+/// What the panic hook does before the default hook prints the panic
+pub struct PanicReporter {
+    notifier: Option<Arc<dyn Notifier>>,
+    checkpoint: Option<StateManager>,
+}
+
+impl PanicReporter {
+    pub fn new(config: &PanicConfig, notifier: Arc<dyn Notifier>, state: StateManager) -> Self {
+        Self {
+            notifier: config.notify.then_some(notifier),
+            checkpoint: config.checkpoint.then_some(state),
+        }
+    }
+
+    fn report(&self, info: &PanicHookInfo) {
+        let location = info
+            .location()
+            .map(|l| l.to_string())
+            .unwrap_or_else(|| "unknown location".to_string());
+        let thread = std::thread::current();
+        let message = panic_message(info);
+        error!("💥 Panic on {} at {}: {}", thread.name().unwrap_or("unnamed thread"), location, message);
+
+        if let Some(notifier) = &self.notifier {
+            notifier.notify("💥 Daemon Task Crashed", &format!("{} ({})", message, location));
+        }
+
+        // The hook can't await, so this skips the write if a writer holds the state
+        if let Some(state) = &self.checkpoint {
+            match state.try_checkpoint_now() {
+                Ok(true) => info!("💾 State checkpointed after panic"),
+                Ok(false) => {}
+                Err(e) => warn!("⚠️  State checkpoint after panic failed: {:#}", e),
+            }
+        }
+    }
+}
+
+/// Run `reporter` on every panic, then the previously installed hook
+pub fn install(reporter: PanicReporter) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        reporter.report(info);
+        previous(info);
+    }));
+}
+
+fn panic_message<'a>(info: &'a PanicHookInfo) -> &'a str {
+    let payload = info.payload();
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("non-string panic payload")
+}
+
+/// Run the loop built by `make` in its own task, building a fresh one each
+/// time it panics
+///
+/// A loop that returns is not restarted, nor is one that panics when `delay`
+/// is zero.
+pub async fn respawn_on_panic<F, Fut>(name: &'static str, delay: Duration, make: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    loop {
+        match tokio::spawn(make()).await {
+            Ok(()) => return,
+            Err(e) if e.is_panic() && !delay.is_zero() => {
+                warn!("🔁 {} panicked; restarting in {}s", name, delay.as_secs_f64());
+                tokio::time::sleep(delay).await;
+            }
+            Err(e) => {
+                error!("❌ {} stopped: {}", name, e);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingNotifier {
+        bodies: Mutex<Vec<String>>,
+    }
+
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        fn notify(&self, _title: &str, body: &str) {
+            self.bodies.lock().unwrap().push(body.to_string());
+        }
+    }
+
+    type Hook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+    /// Puts back the panic hook that was set before the test, even if it fails
+    struct RestoreHook(Option<Hook>);
+
+    impl Drop for RestoreHook {
+        fn drop(&mut self) {
+            if let Some(previous) = self.0.take() {
+                drop(std::panic::take_hook());
+                std::panic::set_hook(previous);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_is_reported() {
+        let state = StateManager::in_memory("dev".to_string(), 10);
+        state.update_entropy(42).await;
+
+        let _restore = RestoreHook(Some(std::panic::take_hook()));
+        let notifier = Arc::new(RecordingNotifier::default());
+        install(PanicReporter::new(&PanicConfig::default(), notifier.clone(), state.clone()));
+
+        let result = tokio::spawn(async { panic!("sync loop exploded") }).await;
+        assert!(result.unwrap_err().is_panic());
+
+        // The hook is process-wide until restored, so other tests' panics may be recorded too
+        let bodies = notifier.bodies.lock().unwrap();
+        let body = bodies.iter().find(|b| b.contains("sync loop exploded")).unwrap();
+        assert!(body.contains("panic_hook.rs"));
+        assert!(state.store().load_snapshot().unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_panicked_loop_respawned() {
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        respawn_on_panic("test loop", Duration::from_millis(1), move || {
+            let counter = counter.clone();
+            async move {
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("flaky loop");
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+}
@@ -2,9 +2,19 @@
 // Radar HTTP client for network scanning
 
 use anyhow::{Result, Context};
-use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use tracing::{info, debug};
+use crate::device::{Signer, SigningContext, Stamp, Stamper};
+use crate::http::{outcome_unknown, EndpointCategory, HttpClient, ServerPool};
+use crate::karma_gate::KarmaGate;
+use crate::state::StateManager;
+use crate::types::SpendReason;
+use super::RadarError;
+use tracing::{info, debug, warn};
+use std::collections::HashMap;
+use std::future::Future;
+
+/// Entropy cost of a single radar scan (Ω)
+pub const SCAN_COST: u64 = 10;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RadarTarget {
@@ -41,45 +51,112 @@ pub struct ScanSummary {
     pub fuzzy: usize,
 }
 
+/// Merged result of scanning several distance bands
+#[derive(Debug)]
+pub struct BandScanResult {
+    pub bands_scanned: Vec<u64>,
+    pub targets: Vec<RadarTarget>,
+    pub total_cost: u64,
+    pub entropy_remaining: Option<u64>,
+}
+
 pub struct RadarClient {
-    client: Client,
-    api_base: String,
+    client: HttpClient,
+    servers: ServerPool,
     device_id: String,
-    private_key: ed25519_dalek::SigningKey,
+    signer: Box<dyn Signer>,
+    state: StateManager,
+    karma_gate: Option<KarmaGate>,
+    stamper: Stamper,
 }
 
 impl RadarClient {
     pub fn new(
-        api_base: String,
+        client: HttpClient,
+        servers: ServerPool,
         device_id: String,
-        private_key: ed25519_dalek::SigningKey,
+        signer: impl Signer + 'static,
+        state: StateManager,
     ) -> Self {
         Self {
-            client: Client::new(),
-            api_base,
+            client,
+            servers,
             device_id,
-            private_key,
+            signer: Box::new(signer),
+            state,
+            karma_gate: None,
+            stamper: Stamper::default(),
         }
     }
 
+    /// Skip scans while karma is below the gate's threshold
+    pub fn with_karma_gate(mut self, gate: KarmaGate) -> Self {
+        self.karma_gate = Some(gate);
+        self
+    }
+
     /// Scan network for targets
+    ///
+    /// Refuses locally when the device binding broke, karma is below the
+    /// gate, or the device can't
+    /// cover the scan cost without dipping below its entropy floor, and
+    /// deducts the server-reported cost from local entropy on success.
+    /// When the server may have charged a scan whose response never
+    /// arrived, the cost is left pending for the next sync to settle.
     pub async fn scan(&self, max_distance: Option<u64>) -> Result<RadarScanResult> {
-        let url = format!("{}/api/game/radar/scan", self.api_base);
+        self.state.ensure_binding()?;
+        if let Some(gate) = &self.karma_gate {
+            gate.check().await?;
+        }
+
+        let available = self.state.spendable_entropy().await;
+        if available < SCAN_COST {
+            return Err(RadarError::InsufficientEntropy {
+                required: SCAN_COST,
+                available,
+            }.into());
+        }
+
+        let path = "/api/game/radar/scan";
         
         let body = serde_json::json!({
             "max_distance": max_distance.unwrap_or(5000),
-            "cost_omega": 10
+            "cost_omega": SCAN_COST
         });
 
-        let response = self.signed_post(&url, &body).await?;
+        let stamp = self.stamper.stamp();
+        let nonce = stamp.nonce.clone();
+        let response = match self.signed_post(path, &body, stamp).await {
+            Ok(response) => response,
+            Err(e) => return Err(self.pending_if_unknown(e, nonce).await),
+        };
         
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse().ok())
+                .map(std::time::Duration::from_secs);
+            return Err(RadarError::Cooldown { retry_after }.into());
+        }
         if !response.status().is_success() {
             let status = response.status();
-            let text = response.text().await?;
+            let text = self.client.read_text(response).await?;
             anyhow::bail!("Radar scan failed ({}): {}", status, text);
         }
 
-        let result: RadarScanResult = response.json().await?;
+        // Past a success status the server has charged, even if the body is unreadable
+        let result: RadarScanResult = match self.client.read_json(response).await {
+            Ok(result) => result,
+            Err(e) => {
+                self.leave_pending(nonce).await;
+                return Err(e);
+            }
+        };
+        // The server has already charged the scan; local state just follows
+        self.state.record_server_charge(result.cost, SpendReason::Radar).await;
+
         info!("Radar scan complete: {} targets ({} locked, {} fuzzy)",
             result.summary.total,
             result.summary.locked,
@@ -89,16 +166,46 @@ impl RadarClient {
         Ok(result)
     }
 
+    /// Leave the scan cost pending when `err` doesn't rule out a charge
+    async fn pending_if_unknown(&self, err: anyhow::Error, nonce: String) -> anyhow::Error {
+        if outcome_unknown(&err) {
+            self.leave_pending(nonce).await;
+        }
+        err
+    }
+
+    /// Record the scan under `nonce` for the next sync to confirm
+    async fn leave_pending(&self, nonce: String) {
+        warn!("⚠️  Radar scan outcome unknown; {} Ω pending until the next sync", SCAN_COST);
+        self.state.record_pending_spend(SCAN_COST, SpendReason::Radar, nonce).await;
+    }
+
+    /// Scan several distance bands in one operation
+    ///
+    /// Bands are scanned in the given order. Scanning stops early once the
+    /// entropy left after the previous scan can no longer cover another one.
+    pub async fn scan_bands(&self, bands: &[u64]) -> Result<BandScanResult> {
+        let result = run_band_scans(bands, |band| self.scan(Some(band))).await?;
+
+        info!("Band scan complete: {} bands, {} unique targets, cost {} Ω",
+            result.bands_scanned.len(),
+            result.targets.len(),
+            result.total_cost
+        );
+
+        Ok(result)
+    }
+
     /// Get only LOCKED targets (attackable)
-    pub fn get_locked_targets(scan: &RadarScanResult) -> Vec<&RadarTarget> {
-        scan.targets
+    pub fn get_locked_targets(targets: &[RadarTarget]) -> Vec<&RadarTarget> {
+        targets
             .iter()
             .filter(|t| t.visibility == "LOCKED")
             .collect()
     }
 
     /// Find best target (lowest total defense)
-    pub fn find_weakest_target<'a>(targets: &'a [&'a RadarTarget]) -> Option<&'a RadarTarget> {
+    pub fn find_weakest_target<'a>(targets: &[&'a RadarTarget]) -> Option<&'a RadarTarget> {
         targets
             .iter()
             .filter_map(|t| {
@@ -108,42 +215,247 @@ impl RadarClient {
             .map(|(target, _)| target)
     }
 
-    /// Sign and send POST request with Ed25519 signature
+    /// Send a POST request signed by the device's `Signer`
     async fn signed_post(
         &self,
-        url: &str,
+        path: &str,
         body: &serde_json::Value,
+        stamp: Stamp,
     ) -> Result<reqwest::Response> {
-        use ed25519_dalek::Signer;
-        use std::time::{SystemTime, UNIX_EPOCH};
-
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)?
-            .as_secs();
-        let nonce = uuid::Uuid::new_v4().to_string();
+        self.client.acquire(EndpointCategory::Radar)?;
 
         let body_str = body.to_string();
-        let message = format!("POST|{}|{}|{}|{}", 
-            url.split("/api/").nth(1).unwrap_or(""),
-            body_str,
-            timestamp,
-            nonce
-        );
+        let signing = SigningContext::for_api("POST", path, &body_str, &stamp);
+        let Stamp { timestamp, nonce } = stamp;
+
+        let sig_hex = self.signer.sign_hex(signing.canonical.as_bytes());
 
-        let signature = self.private_key.sign(message.as_bytes());
-        let sig_hex = hex::encode(signature.to_bytes());
-
-        let response = self.client
-            .post(url)
-            .header("X-Device-ID", &self.device_id)
-            .header("X-Signature", sig_hex)
-            .header("X-Timestamp", timestamp.to_string())
-            .header("X-Nonce", nonce)
-            .header("Content-Type", "application/json")
-            .body(body_str)
-            .send()
+        let response = self.servers
+            .send(path, |url| {
+                self.client
+                    .post(url)
+                    .header("X-Device-ID", &self.device_id)
+                    .header("X-Signature", &sig_hex)
+                    .header("X-Signature-Alg", self.signer.algorithm())
+                    .header("X-Timestamp", timestamp.to_string())
+                    .header("X-Nonce", &nonce)
+                    .header("Content-Type", "application/json")
+                    .body(body_str.clone())
+            })
             .await?;
 
+        signing.log_if_rejected(response.status());
         Ok(response)
     }
 }
+
+/// Drive a sequence of band scans, stopping when the budget runs out
+async fn run_band_scans<F, Fut>(bands: &[u64], mut scan: F) -> Result<BandScanResult>
+where
+    F: FnMut(u64) -> Fut,
+    Fut: Future<Output = Result<RadarScanResult>>,
+{
+    let mut bands_scanned = Vec::new();
+    let mut targets = Vec::new();
+    let mut total_cost = 0;
+    let mut entropy_remaining: Option<u64> = None;
+
+    for &band in bands {
+        if let Some(remaining) = entropy_remaining {
+            if remaining < SCAN_COST {
+                warn!("Stopping band scan at {}: {} Ω left, scan costs {} Ω",
+                    band, remaining, SCAN_COST);
+                break;
+            }
+        }
+
+        let result = scan(band).await?;
+        debug!("Band {} scanned: {} targets", band, result.targets.len());
+
+        bands_scanned.push(band);
+        total_cost += result.cost;
+        entropy_remaining = Some(result.entropy_remaining);
+        targets.extend(result.targets);
+    }
+
+    Ok(BandScanResult {
+        bands_scanned,
+        targets: merge_targets(targets),
+        total_cost,
+        entropy_remaining,
+    })
+}
+
+/// Deduplicate targets by device_id, keeping the closest sighting
+fn merge_targets(targets: Vec<RadarTarget>) -> Vec<RadarTarget> {
+    let mut closest: HashMap<String, RadarTarget> = HashMap::new();
+
+    for target in targets {
+        match closest.get(&target.device_id) {
+            Some(existing) if existing.distance <= target.distance => {}
+            _ => {
+                closest.insert(target.device_id.clone(), target);
+            }
+        }
+    }
+
+    let mut merged: Vec<RadarTarget> = closest.into_values().collect();
+    merged.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{test_signer, MockServer};
+
+    fn target(id: &str, distance: f64) -> RadarTarget {
+        RadarTarget {
+            device_id: id.to_string(),
+            visibility: "LOCKED".to_string(),
+            distance,
+            karma: None,
+            karma_range: None,
+            defense: None,
+        }
+    }
+
+    fn scan_result(targets: Vec<RadarTarget>, entropy_remaining: u64) -> RadarScanResult {
+        RadarScanResult {
+            success: true,
+            scan_id: "scan".to_string(),
+            cost: SCAN_COST,
+            summary: ScanSummary { total: targets.len(), locked: targets.len(), fuzzy: 0 },
+            targets,
+            entropy_remaining,
+        }
+    }
+
+    async fn radar_client(api_base: &str, entropy: u64) -> (RadarClient, StateManager) {
+        let state = StateManager::in_memory("dev-123".to_string(), 100);
+        let client = RadarClient::new(
+            HttpClient::default(),
+            ServerPool::new(&[api_base.to_string()]),
+            "dev-123".to_string(),
+            test_signer(),
+            state.clone(),
+        );
+
+        state.state.write().await.player.entropy = entropy;
+
+        (client, state)
+    }
+
+    #[tokio::test]
+    async fn test_scan_skipped_below_karma_gate() {
+        let server = MockServer::respond_with(500, "should not be called").await;
+        let (client, state) = radar_client(&server.url, 1000).await;
+        let client = client.with_karma_gate(KarmaGate::new("radar", 101, state.clone()));
+
+        let err = client.scan(None).await.unwrap_err();
+
+        assert!(err.downcast_ref::<crate::karma_gate::KarmaTooLow>().is_some());
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_refused_after_binding_lost() {
+        let server = MockServer::respond_with(500, "should not be called").await;
+        let (client, state) = radar_client(&server.url, 1000).await;
+        state.set_binding_lost();
+
+        let err = client.scan(None).await.unwrap_err();
+
+        assert!(err.is::<crate::state::BindingLost>());
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_refused_without_entropy() {
+        let server = MockServer::respond_with(500, "should not be called").await;
+        let (client, _) = radar_client(&server.url, SCAN_COST - 1).await;
+
+        let err = client.scan(None).await.unwrap_err();
+
+        assert!(matches!(
+            err.downcast_ref::<RadarError>(),
+            Some(RadarError::InsufficientEntropy { required: SCAN_COST, available: 9 })
+        ));
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_scan_deducts_cost() {
+        let body = r#"{"success":true,"scan_id":"s1","cost":10,"targets":[],
+            "summary":{"total":0,"locked":0,"fuzzy":0},"entropy_remaining":90}"#;
+        let server = MockServer::respond_with(200, body).await;
+        let (client, state) = radar_client(&server.url, 100).await;
+
+        let result = client.scan(Some(1000)).await.unwrap();
+
+        assert_eq!(result.cost, 10);
+        assert_eq!(state.get_snapshot().await.player.entropy, 90);
+        assert_eq!(server.requests()[0].path, "/api/game/radar/scan");
+        assert_eq!(server.requests()[0].header("x-signature-alg"), Some("ed25519"));
+    }
+
+    #[tokio::test]
+    async fn test_refused_scan_deducts_nothing() {
+        let server = MockServer::respond_with(400, r#"{"error":"bad request"}"#).await;
+        let (client, state) = radar_client(&server.url, 100).await;
+
+        assert!(client.scan(Some(1000)).await.is_err());
+
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 100);
+        assert!(snapshot.ledger.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_unreadable_scan_response_leaves_cost_pending() {
+        let server = MockServer::respond_with(200, r#"{"success":true,"scan_id":"#).await;
+        let (client, state) = radar_client(&server.url, 100).await;
+
+        assert!(client.scan(Some(1000)).await.is_err());
+
+        // Nothing deducted yet; the entry waits for the server to confirm the nonce
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 100);
+        assert_eq!(snapshot.ledger.len(), 1);
+        assert!(snapshot.ledger[0].pending);
+        assert_eq!(snapshot.ledger[0].nonce.as_deref(), server.requests()[0].header("x-nonce"));
+    }
+
+    #[test]
+    fn test_merge_keeps_closest() {
+        let merged = merge_targets(vec![
+            target("a", 900.0),
+            target("b", 300.0),
+            target("a", 150.0),
+            target("a", 400.0),
+        ]);
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].device_id, "a");
+        assert_eq!(merged[0].distance, 150.0);
+        assert_eq!(merged[1].device_id, "b");
+    }
+
+    #[tokio::test]
+    async fn test_band_scan_stops_when_budget_exhausted() {
+        // 25 Ω left after the first scan, 5 Ω after the second
+        let remaining = [25, 5, 0];
+        let mut calls = 0;
+
+        let result = run_band_scans(&[1000, 2000, 3000], |band| {
+            let r = scan_result(vec![target(&band.to_string(), band as f64)], remaining[calls]);
+            calls += 1;
+            async move { Ok(r) }
+        }).await.unwrap();
+
+        assert_eq!(result.bands_scanned, vec![1000, 2000]);
+        assert_eq!(result.total_cost, 2 * SCAN_COST);
+        assert_eq!(result.entropy_remaining, Some(5));
+        assert_eq!(result.targets.len(), 2);
+    }
+}
@@ -0,0 +1,15 @@
+// sacas-daemon/src/radar/error.rs
+
+use std::time::Duration;
+
+use thiserror::Error;
+
+/// Radar failures the caller may want to handle specifically
+#[derive(Debug, Error)]
+pub enum RadarError {
+    #[error("Insufficient entropy for radar scan: costs {required} Ω, have {available} Ω")]
+    InsufficientEntropy { required: u64, available: u64 },
+
+    #[error("Radar is cooling down (retry after {retry_after:?})")]
+    Cooldown { retry_after: Option<Duration> },
+}
@@ -3,5 +3,7 @@
 // Provides HTTP client for radar scanning API endpoints
 
 pub mod client;
+pub mod error;
 
 pub use client::RadarClient;
+pub use error::RadarError;
@@ -1,14 +1,88 @@
 use crate::types::*;
+use anyhow::Result;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use chrono::Utc;
+use tokio::sync::{broadcast, watch, RwLock};
+use chrono::{DateTime, Utc};
+use tracing::warn;
 
+use crate::history::HistoryConfig;
+use crate::mining::params::MiningParams;
+use crate::status_feed::{StatusSubscriber, DEFAULT_STATUS_CHANNEL_CAPACITY};
+use crate::store::{JournalWriter, StateStore};
+
+/// Entropy is never allowed to exceed this multiple of capacity
+const MAX_ENTROPY_CAPACITY_FACTOR: u64 = 10;
+
+/// Highest balance `capacity` allows before clamping
+pub fn max_entropy(capacity: u64) -> u64 {
+    capacity.saturating_mul(MAX_ENTROPY_CAPACITY_FACTOR)
+}
+
+/// Entropy lost to one decay step: 2% of the excess over capacity
+pub fn overflow_decay(entropy: u64, capacity: u64) -> u64 {
+    (entropy.saturating_sub(capacity) as f64 * 0.02) as u64
+}
+
+/// Passive income is the parasites' combined yield per tick
+fn passive_income(parasites: &[Parasite]) -> f64 {
+    parasites.iter().map(|p| p.yield_per_tick).sum()
+}
+
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("Insufficient entropy: {requested} needed, {available} available")]
+pub struct InsufficientEntropy {
+    pub requested: u64,
+    pub available: u64,
+}
+
+/// Game actions refused after the device binding broke mid-run
+#[derive(Debug, thiserror::Error, PartialEq)]
+#[error("Hardware no longer matches the device binding; restart on the bound device")]
+pub struct BindingLost;
+
+/// Change in account linkage reported by a sync
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManagedTransition {
+    Unchanged,
+    /// Device became linked to a human account
+    Linked,
+    /// Device was unlinked (or first reported unlinked) and is autonomous again
+    Unlinked,
+}
+
+/// Handle to the shared game state; clones share the same state and dirty flag
+#[derive(Clone)]
 pub struct StateManager {
     pub state: Arc<RwLock<GameState>>,
+    dirty: Arc<AtomicBool>,
+    checkpoint_failing: Arc<AtomicBool>,
+    mining_healthy: Arc<AtomicBool>,
+    /// Set when the hardware stops matching the device binding mid-run
+    binding_lost: Arc<AtomicBool>,
+    /// Set while the server keeps refusing this device's signed requests
+    key_rejected: Arc<AtomicBool>,
+    network_measured: Arc<AtomicBool>,
+    decay_grace_secs: i64,
+    capacity_formula: CapacityFormula,
+    entropy_floor: u64,
+    history: HistoryConfig,
+    /// Change notices for status subscribers, numbered by `change_seq`
+    changes: broadcast::Sender<u64>,
+    change_seq: Arc<AtomicU64>,
+    /// Sampled latency vector waiting for the next sync to upload it
+    latency_sample: Arc<std::sync::Mutex<Option<LatencyVector>>>,
+    /// Server-signed mining parameters overriding the local config, once verified
+    server_mining_params: Arc<watch::Sender<Option<MiningParams>>>,
+    /// Where checkpoints and settled ledger entries go
+    store: Arc<dyn StateStore>,
+    /// Appends settled ledger entries to `store` off the state lock
+    journal: JournalWriter,
 }
 
 impl StateManager {
-    pub fn new(player_id: String, karma: u64) -> Self {
+    /// Fresh state for `player_id`, checkpointed and journaled to `store`
+    pub fn new(player_id: String, karma: u64, store: Arc<dyn StateStore>) -> Self {
         let state = GameState {
             player: Player::new(player_id, karma),
             visible_nodes: vec![],
@@ -19,34 +93,443 @@ impl StateManager {
                 modifiers: serde_json::json!({}),
                 start_time: Utc::now(),
             },
+            managed: None,
+            moltbook_profile: None,
+            anomalies: AnomalyHistory::default(),
+            ledger: Default::default(),
         };
 
         Self {
             state: Arc::new(RwLock::new(state)),
+            dirty: Arc::new(AtomicBool::new(false)),
+            checkpoint_failing: Arc::new(AtomicBool::new(false)),
+            mining_healthy: Arc::new(AtomicBool::new(true)),
+            binding_lost: Arc::new(AtomicBool::new(false)),
+            key_rejected: Arc::new(AtomicBool::new(false)),
+            network_measured: Arc::new(AtomicBool::new(false)),
+            decay_grace_secs: 0,
+            capacity_formula: CapacityFormula::default(),
+            entropy_floor: 0,
+            history: HistoryConfig::default(),
+            changes: broadcast::channel(DEFAULT_STATUS_CHANNEL_CAPACITY).0,
+            change_seq: Arc::new(AtomicU64::new(0)),
+            latency_sample: Arc::new(std::sync::Mutex::new(None)),
+            server_mining_params: Arc::new(watch::channel(None).0),
+            journal: JournalWriter::spawn(store.clone()),
+            store,
+        }
+    }
+
+    /// Fresh state kept only in memory
+    #[cfg(test)]
+    pub fn in_memory(player_id: String, karma: u64) -> Self {
+        Self::new(player_id, karma, Arc::new(crate::store::MemoryStore::default()))
+    }
+
+    /// The store checkpoints and ledger entries are written to
+    pub fn store(&self) -> &dyn StateStore {
+        self.store.as_ref()
+    }
+
+    /// Let entropy sit over capacity for `secs` before decay applies
+    pub fn with_decay_grace(mut self, secs: u64) -> Self {
+        self.decay_grace_secs = i64::try_from(secs).unwrap_or(i64::MAX);
+        self
+    }
+
+    /// Derive capacity from karma with `formula`, now and on every karma update
+    pub fn with_capacity_formula(mut self, formula: CapacityFormula) -> Self {
+        self.capacity_formula = formula;
+        if let Ok(mut state) = self.state.try_write() {
+            state.player.capacity = capacity_from_karma(state.player.karma, formula);
+        }
+        self
+    }
+
+    /// Keep `floor` entropy out of reach of automatic spending
+    pub fn with_entropy_floor(mut self, floor: u64) -> Self {
+        self.entropy_floor = floor;
+        self
+    }
+
+    /// Keep ledger and anomaly history within `history` limits
+    pub fn with_history_retention(mut self, history: HistoryConfig) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Buffer `capacity` change notices per status subscriber
+    ///
+    /// Set it while building the manager, before it is cloned or subscribed to.
+    pub fn with_status_channel_capacity(mut self, capacity: usize) -> Self {
+        self.changes = broadcast::channel(capacity.max(1)).0;
+        self
+    }
+
+    /// Receive a notice after every state change
+    pub fn subscribe_status(&self) -> StatusSubscriber {
+        StatusSubscriber::new(self.changes.subscribe(), self.clone())
+    }
+
+    /// Entropy automatic spending may use: the balance above the floor
+    pub async fn spendable_entropy(&self) -> u64 {
+        self.state.read().await.player.entropy.saturating_sub(self.entropy_floor)
+    }
+
+    /// Flag unsaved changes and notify status subscribers
+    ///
+    /// Called with the state write lock held, so a subscriber woken by the
+    /// notice reads the state only after the change is complete.
+    fn mark_dirty(&self) {
+        self.dirty.store(true, Ordering::Release);
+        let seq = self.change_seq.fetch_add(1, Ordering::AcqRel) + 1;
+        // No subscribers is fine
+        let _ = self.changes.send(seq);
+    }
+
+    /// Whether state changed since the last successful snapshot
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Whether checkpoints are failing even after retries
+    pub fn is_checkpoint_failing(&self) -> bool {
+        self.checkpoint_failing.load(Ordering::Acquire)
+    }
+
+    /// Record whether checkpoints are failing; returns the previous value
+    pub fn set_checkpoint_failing(&self, failing: bool) -> bool {
+        self.checkpoint_failing.swap(failing, Ordering::AcqRel)
+    }
+
+    /// Whether the server is rejecting this device's key
+    pub fn is_key_rejected(&self) -> bool {
+        self.key_rejected.load(Ordering::Acquire)
+    }
+
+    /// Record whether the server rejects this device's key; returns the previous value
+    pub fn set_key_rejected(&self, rejected: bool) -> bool {
+        self.key_rejected.swap(rejected, Ordering::AcqRel)
+    }
+
+    /// Whether the mining loop is ticking, as judged by its watchdog
+    pub fn is_mining_healthy(&self) -> bool {
+        self.mining_healthy.load(Ordering::Acquire)
+    }
+
+    /// Record the watchdog's verdict; returns the previous value
+    pub fn set_mining_healthy(&self, healthy: bool) -> bool {
+        self.mining_healthy.swap(healthy, Ordering::AcqRel)
+    }
+
+    /// Whether the device binding broke mid-run; mining and sync stop until restart
+    pub fn is_binding_lost(&self) -> bool {
+        self.binding_lost.load(Ordering::Acquire)
+    }
+
+    /// Fail with `BindingLost` once the binding broke, for actions that must stop with sync
+    pub fn ensure_binding(&self) -> Result<(), BindingLost> {
+        if self.is_binding_lost() {
+            return Err(BindingLost);
+        }
+        Ok(())
+    }
+
+    /// Halt mining and sync because the hardware no longer matches the binding
+    pub fn set_binding_lost(&self) {
+        self.binding_lost.store(true, Ordering::Release);
+    }
+
+    /// Whether a probe has reported network quality since startup
+    pub fn has_network_quality(&self) -> bool {
+        self.network_measured.load(Ordering::Acquire)
+    }
+
+    /// Verified server mining parameters in effect, if any
+    pub fn server_mining_params(&self) -> Option<MiningParams> {
+        *self.server_mining_params.borrow()
+    }
+
+    /// Override the local mining config with verified server parameters
+    pub fn set_server_mining_params(&self, params: MiningParams) {
+        self.server_mining_params.send_replace(Some(params));
+    }
+
+    /// Notified whenever server mining parameters are applied
+    pub fn watch_server_mining_params(&self) -> watch::Receiver<Option<MiningParams>> {
+        self.server_mining_params.subscribe()
+    }
+
+    /// Hold `vector` for upload, replacing any sample not yet sent
+    pub fn queue_latency_sample(&self, vector: LatencyVector) {
+        *self.latency_sample.lock().unwrap() = Some(vector);
+    }
+
+    /// Take the pending latency sample, if one was queued since the last take
+    pub fn take_latency_sample(&self) -> Option<LatencyVector> {
+        self.latency_sample.lock().unwrap().take()
+    }
+
+    /// Write the current state to the store
+    pub async fn save_snapshot(&self) -> Result<()> {
+        // Clear first so mutations racing with the write mark it dirty again
+        self.dirty.store(false, Ordering::Release);
+
+        let snapshot = self.get_snapshot().await;
+        if let Err(e) = self.store.save_snapshot(&snapshot) {
+            self.mark_dirty();
+            return Err(e);
         }
+
+        Ok(())
     }
 
-    pub fn get_handle(&self) -> Arc<RwLock<GameState>> {
-        self.state.clone()
+    /// Save a snapshot only if something changed; returns whether it wrote
+    pub async fn checkpoint(&self) -> Result<bool> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+
+        self.save_snapshot().await?;
+        Ok(true)
+    }
+
+    /// `checkpoint` for code that can't await, such as a panic hook
+    ///
+    /// Also skips the write while a writer holds the state.
+    pub fn try_checkpoint_now(&self) -> Result<bool> {
+        if !self.is_dirty() {
+            return Ok(false);
+        }
+        let Ok(state) = self.state.try_read() else {
+            return Ok(false);
+        };
+
+        self.dirty.store(false, Ordering::Release);
+        if let Err(e) = self.store.save_snapshot(&state) {
+            self.mark_dirty();
+            return Err(e);
+        }
+        Ok(true)
+    }
+
+    /// Pick up the saved snapshot and any spends journaled after it
+    ///
+    /// Call once at startup, before anything else changes state. A snapshot
+    /// saved for another player is ignored. Journaled spends newer than the
+    /// snapshot, and pending spends settled since, are merged into its
+    /// ledger and the latest journaled balance adopted. Returns whether a
+    /// snapshot was loaded.
+    pub async fn restore(&self) -> Result<bool> {
+        let Some(mut saved) = self.store.load_snapshot()? else {
+            return Ok(false);
+        };
+        let journal = self.store.read_ledger()?;
+
+        let mut state = self.state.write().await;
+        if saved.player.id != state.player.id {
+            warn!("⚠️  Saved state belongs to '{}', not '{}'; starting fresh", saved.player.id, state.player.id);
+            return Ok(false);
+        }
+
+        let saved_at = saved.player.last_update;
+        let now = Utc::now();
+        let mut merged = false;
+        for entry in journal {
+            let settled = saved.ledger.iter_mut()
+                .find(|e| e.pending && e.nonce.is_some() && e.nonce == entry.nonce);
+            let balance = entry.balance;
+            let at = entry.at;
+            match settled {
+                Some(pending) => *pending = entry,
+                None if entry.at > saved_at => saved.ledger.push(entry, &self.history.ledger, now),
+                None => continue,
+            }
+            saved.player.entropy = balance;
+            saved.player.last_update = saved.player.last_update.max(at);
+            merged = true;
+        }
+
+        saved.player.capacity = capacity_from_karma(saved.player.karma, self.capacity_formula);
+        *state = saved;
+        if merged {
+            self.mark_dirty();
+        }
+        self.recompute_passive_income(&mut state);
+        Ok(true)
     }
 
     pub async fn update_entropy(&self, delta: i64) {
+        self.update_entropy_at(delta, Utc::now()).await
+    }
+
+    async fn update_entropy_at(&self, delta: i64, now: DateTime<Utc>) {
         let mut state = self.state.write().await;
+        self.mark_dirty();
         
         if delta >= 0 {
-            state.player.entropy += delta as u64;
+            state.player.entropy = state.player.entropy.saturating_add(delta as u64);
         } else {
-            state.player.entropy = state.player.entropy.saturating_sub(delta.abs() as u64);
+            state.player.entropy = state.player.entropy.saturating_sub(delta.unsigned_abs());
+        }
+
+        // Capacity 0 means it isn't known yet, so there's nothing to cap against
+        if state.player.capacity == 0 {
+            state.player.over_capacity_since = None;
+            state.player.last_update = now;
+            return;
+        }
+
+        // Clamp implausible balances before they can corrupt later math
+        let max_entropy = max_entropy(state.player.capacity);
+        if state.player.entropy > max_entropy {
+            warn!(
+                "⚠️  Entropy {} exceeds sane maximum {} ({}× capacity), clamping",
+                format_entropy(state.player.entropy), format_entropy(max_entropy), MAX_ENTROPY_CAPACITY_FACTOR
+            );
+            state.player.entropy = max_entropy;
         }
 
-        // Check for decay
+        // Decay only once entropy has stayed over capacity for the grace period
         if state.player.entropy > state.player.capacity {
-            let excess = state.player.entropy - state.player.capacity;
-            let decay = (excess as f64 * 0.02) as u64;
-            state.player.entropy = state.player.entropy.saturating_sub(decay);
+            let since = *state.player.over_capacity_since.get_or_insert(now);
+            if (now - since).num_seconds() >= self.decay_grace_secs {
+                let decay = overflow_decay(state.player.entropy, state.player.capacity);
+                state.player.entropy = state.player.entropy.saturating_sub(decay);
+            }
+        } else {
+            state.player.over_capacity_since = None;
+        }
+
+        state.player.last_update = now;
+    }
+
+    /// Take `amount` entropy for `reason` only if a reserve stays untouched
+    ///
+    /// `reserve` receives the current capacity and is evaluated under the
+    /// same write lock as the deduction; it is held back on top of the
+    /// entropy floor. The spend is recorded in the ledger. Returns the new
+    /// balance, or the `(available, floor)` pair when the spend is refused.
+    pub async fn try_spend_entropy(
+        &self,
+        amount: u64,
+        reason: SpendReason,
+        reserve: impl FnOnce(u64) -> u64,
+    ) -> Result<u64, (u64, u64)> {
+        let mut state = self.state.write().await;
+        let available = state.player.entropy;
+        let floor = reserve(state.player.capacity).saturating_add(self.entropy_floor);
+
+        if amount > available || available - amount < floor {
+            return Err((available, floor));
+        }
+
+        let now = Utc::now();
+        state.player.entropy = available - amount;
+        state.player.last_update = now;
+        let balance = state.player.entropy;
+        let entry = LedgerEntry { at: now, amount, reason, balance, pending: false, nonce: None };
+        self.journal.append(entry.clone());
+        state.ledger.push(entry, &self.history.ledger, now);
+        self.mark_dirty();
+        Ok(balance)
+    }
+
+    /// Take `amount` entropy for `reason` and record it in the ledger
+    ///
+    /// Nothing changes if less than `amount` is available. Only admin
+    /// spends may dip below the entropy floor. Returns the remaining balance.
+    pub async fn spend_entropy(&self, amount: u64, reason: SpendReason) -> Result<u64, InsufficientEntropy> {
+        let mut state = self.state.write().await;
+        let floor = match reason {
+            SpendReason::Admin(_) => 0,
+            _ => self.entropy_floor,
+        };
+        let available = state.player.entropy.saturating_sub(floor);
+        if amount > available {
+            return Err(InsufficientEntropy { requested: amount, available });
+        }
+
+        let now = Utc::now();
+        state.player.entropy -= amount;
+        state.player.last_update = now;
+        let balance = state.player.entropy;
+        let entry = LedgerEntry { at: now, amount, reason, balance, pending: false, nonce: None };
+        self.journal.append(entry.clone());
+        state.ledger.push(entry, &self.history.ledger, now);
+        self.mark_dirty();
+
+        Ok(balance)
+    }
+
+    /// Follow a charge the server already made, recording it in the ledger
+    ///
+    /// Unlike `spend_entropy` this can't be refused: a local balance that
+    /// is behind the server's is taken down to zero. Returns the balance.
+    pub async fn record_server_charge(&self, amount: u64, reason: SpendReason) -> u64 {
+        let mut state = self.state.write().await;
+        if amount > state.player.entropy {
+            warn!("⚠️  Local state behind server: charge of {} exceeds local {}",
+                format_entropy(amount), format_entropy(state.player.entropy));
         }
 
-        state.player.last_update = Utc::now();
+        let now = Utc::now();
+        state.player.entropy = state.player.entropy.saturating_sub(amount);
+        state.player.last_update = now;
+        let balance = state.player.entropy;
+        let entry = LedgerEntry { at: now, amount, reason, balance, pending: false, nonce: None };
+        self.journal.append(entry.clone());
+        state.ledger.push(entry, &self.history.ledger, now);
+        self.mark_dirty();
+        balance
+    }
+
+    /// Record a spend whose request may have reached the server, without deducting it
+    ///
+    /// Used when a charged request's outcome is unknown: the entry stays
+    /// pending until the server confirms a charge for `nonce`, the signed
+    /// request's nonce.
+    pub async fn record_pending_spend(&self, amount: u64, reason: SpendReason, nonce: String) {
+        let mut state = self.state.write().await;
+        let now = Utc::now();
+        let balance = state.player.entropy;
+        let entry = LedgerEntry { at: now, amount, reason, balance, pending: true, nonce: Some(nonce) };
+        state.ledger.push(entry, &self.history.ledger, now);
+        self.mark_dirty();
+    }
+
+    /// Settle the pending spends the server confirms in `charges`
+    ///
+    /// Each pending entry whose nonce the server lists is deducted at the
+    /// amount the server charged. Entries it doesn't list stay pending; a
+    /// request that never reached the server is never listed, and ledger
+    /// retention drops it in time. Returns the total deducted.
+    pub async fn settle_pending_spends(&self, charges: &[ServerCharge]) -> u64 {
+        let mut guard = self.state.write().await;
+        let state = &mut *guard;
+
+        let mut entropy = state.player.entropy;
+        let mut confirmed = 0;
+        let mut settled = false;
+        for entry in state.ledger.iter_mut().filter(|e| e.pending) {
+            let Some(charge) = charges.iter().find(|c| entry.nonce.as_deref() == Some(c.nonce.as_str())) else {
+                continue;
+            };
+            let amount = charge.amount.value();
+            entropy = entropy.saturating_sub(amount);
+            confirmed += amount;
+            settled = true;
+            entry.amount = amount;
+            entry.pending = false;
+            entry.balance = entropy;
+            self.journal.append(entry.clone());
+        }
+
+        if settled {
+            state.player.entropy = entropy;
+            self.mark_dirty();
+        }
+        confirmed
     }
 
     pub async fn update_defense(&self, l1: u64, l2: u64, l3: u64) -> Result<(), String> {
@@ -77,44 +560,569 @@ impl StateManager {
         state.player.defense.cooldown_ends = Some(
             Utc::now() + chrono::Duration::seconds(inertia_seconds as i64)
         );
+        self.mark_dirty();
 
         Ok(())
     }
 
+    /// Adopt the server's defense allocation and cooldown as-is
+    ///
+    /// Skips the cooldown and entropy checks of `update_defense`: the server
+    /// already applied them. Returns the local defense it replaced.
+    pub async fn replace_defense(&self, defense: DefenseArray) -> DefenseArray {
+        let mut state = self.state.write().await;
+        self.mark_dirty();
+        std::mem::replace(&mut state.player.defense, defense)
+    }
+
     pub async fn update_network_quality(&self, quality: f64) {
         let mut state = self.state.write().await;
+        self.mark_dirty();
         state.player.network_quality = quality.clamp(0.1, 1.5);
+        self.network_measured.store(true, Ordering::Release);
     }
 
-    pub async fn update_karma(&self, new_karma: u64) {
+    /// Set karma and the capacity derived from it; returns the previous karma
+    pub async fn update_karma(&self, new_karma: u64) -> u64 {
         let mut state = self.state.write().await;
-        state.player.karma = new_karma;
+        self.mark_dirty();
+        let previous = std::mem::replace(&mut state.player.karma, new_karma);
         // Recalculate capacity when karma changes
-        state.player.capacity = new_karma * 100;
+        state.player.capacity = capacity_from_karma(new_karma, self.capacity_formula);
+        previous
     }
 
-    pub async fn add_parasite(&self, parasite: Parasite) {
+    /// Record the anomaly warning from a successful sync, or its absence
+    ///
+    /// A sync without a warning ends the consecutive streak. Returns the
+    /// streak length.
+    pub async fn record_sync_anomaly(&self, warning: Option<AnomalyRecord>) -> u32 {
         let mut state = self.state.write().await;
+        let history = &mut state.anomalies;
+
+        match warning {
+            Some(record) => {
+                history.records.push(record, &self.history.anomalies, Utc::now());
+                history.consecutive += 1;
+            }
+            None if history.consecutive == 0 => return 0,
+            None => history.consecutive = 0,
+        }
+
+        self.mark_dirty();
+        history.consecutive
+    }
+
+    /// Drop history entries the retention limits no longer allow
+    ///
+    /// Inserts already prune; this catches entries that aged out while
+    /// nothing new was recorded. Returns how many were dropped.
+    pub async fn prune_history(&self) -> usize {
+        let mut state = self.state.write().await;
+        let now = Utc::now();
+        let pruned = state.ledger.prune(&self.history.ledger, now)
+            + state.anomalies.records.prune(&self.history.anomalies, now);
+        self.journal.prune(self.history.ledger, now);
+        if pruned > 0 {
+            self.mark_dirty();
+        }
+        pruned
+    }
+
+    /// Forget all recorded anomaly warnings; returns how many there were
+    pub async fn clear_anomaly_history(&self) -> usize {
+        let mut state = self.state.write().await;
+        let cleared = state.anomalies.records.len();
+        state.anomalies = AnomalyHistory::default();
+        self.mark_dirty();
+        cleared
+    }
+
+    /// Record the server-reported `managed` flag and report how it changed
+    pub async fn update_managed(&self, managed: bool) -> ManagedTransition {
+        let mut state = self.state.write().await;
+        let previous = state.managed.replace(managed);
+
+        if previous == Some(managed) {
+            return ManagedTransition::Unchanged;
+        }
+
+        self.mark_dirty();
+        if managed {
+            ManagedTransition::Linked
+        } else {
+            ManagedTransition::Unlinked
+        }
+    }
+
+    /// Whether the server last reported a human account managing this device
+    ///
+    /// Automatic combat defers to the owner while this holds, and resumes
+    /// once the device is unlinked.
+    pub async fn is_managed(&self) -> bool {
+        self.state.read().await.managed == Some(true)
+    }
+
+    /// Add `parasite` unless its node already hosts one; returns whether it was added
+    pub async fn add_parasite(&self, parasite: Parasite) -> bool {
+        let mut state = self.state.write().await;
+        if state.parasites.iter().any(|p| p.node_id == parasite.node_id) {
+            return false;
+        }
+        self.mark_dirty();
         state.parasites.push(parasite);
-        
-        // Recalculate passive income
-        state.player.passive_income = state.parasites
-            .iter()
-            .map(|p| p.yield_per_tick)
-            .sum();
+        self.recompute_passive_income(&mut state);
+        true
+    }
+
+    /// The parasite on `node_id`, if there is one
+    pub async fn get_parasite(&self, node_id: &str) -> Option<Parasite> {
+        let state = self.state.read().await;
+        state.parasites.iter().find(|p| p.node_id == node_id).cloned()
+    }
+
+    /// Drop the parasite on `node_id`, returning it if there was one
+    pub async fn remove_parasite(&self, node_id: &str) -> Option<Parasite> {
+        let mut state = self.state.write().await;
+        let index = state.parasites.iter().position(|p| p.node_id == node_id)?;
+        self.mark_dirty();
+        let removed = state.parasites.remove(index);
+        self.recompute_passive_income(&mut state);
+        Some(removed)
+    }
+
+    /// Adopt `parasites` as the whole list and recompute passive income
+    ///
+    /// State is only marked dirty when the list actually changed. Returns the
+    /// new passive income.
+    pub async fn replace_parasites(&self, parasites: Vec<Parasite>) -> f64 {
+        let mut state = self.state.write().await;
+        if state.parasites != parasites {
+            self.mark_dirty();
+            state.parasites = parasites;
+        }
+        self.recompute_passive_income(&mut state)
+    }
+
+    /// Rebuild `passive_income` from the parasite list in `state`
+    ///
+    /// Runs after every parasite change and after `restore`, so any drift
+    /// is repaired rather than carried forward.
+    fn recompute_passive_income(&self, state: &mut GameState) -> f64 {
+        let income = passive_income(&state.parasites);
+        if state.player.passive_income != income {
+            self.mark_dirty();
+            state.player.passive_income = income;
+        }
+        income
     }
 
     pub async fn update_visible_nodes(&self, nodes: Vec<Node>) {
         let mut state = self.state.write().await;
+        self.mark_dirty();
         state.visible_nodes = nodes;
     }
 
     pub async fn update_climate(&self, climate: Climate) {
         let mut state = self.state.write().await;
+        self.mark_dirty();
         state.climate = climate;
     }
 
+    pub async fn update_moltbook_profile(&self, profile: crate::moltbook::client::MoltbookAgent) {
+        let mut state = self.state.write().await;
+        self.mark_dirty();
+        state.moltbook_profile = Some(profile);
+    }
+
+    /// Wait until every ledger entry recorded so far is in the journal
+    ///
+    /// A failed append is logged; the entry is still in state and the
+    /// next checkpoint saves it there.
+    pub async fn flush_journal(&self) {
+        self.journal.flush().await;
+    }
+
     pub async fn get_snapshot(&self) -> GameState {
         self.state.read().await.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parasite(node_id: &str, yield_per_tick: f64) -> Parasite {
+        Parasite {
+            node_id: node_id.to_string(),
+            tax_rate: 0.1,
+            yield_per_tick,
+            total_collected: 0,
+            established_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_passive_income_tracks_parasite_changes() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        let income = || async { manager.get_snapshot().await.player.passive_income };
+
+        manager.add_parasite(parasite("a", 1.5)).await;
+        manager.add_parasite(parasite("b", 2.0)).await;
+        manager.add_parasite(parasite("c", 0.25)).await;
+        // A node hosts at most one parasite
+        assert!(!manager.add_parasite(parasite("a", 9.0)).await);
+        assert_eq!(income().await, 3.75);
+
+        assert_eq!(manager.remove_parasite("b").await.unwrap().node_id, "b");
+        assert!(manager.remove_parasite("b").await.is_none());
+        assert_eq!(income().await, 1.75);
+
+        manager.remove_parasite("a").await;
+        manager.remove_parasite("c").await;
+        assert_eq!(income().await, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_recompute_repairs_drift() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.add_parasite(parasite("a", 4.0)).await;
+        manager.state.write().await.player.passive_income = 99.0;
+
+        assert_eq!(manager.recompute_passive_income(&mut *manager.state.write().await), 4.0);
+        assert_eq!(manager.get_snapshot().await.player.passive_income, 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_restore_recomputes_passive_income() {
+        let store: Arc<dyn StateStore> = Arc::new(crate::store::MemoryStore::default());
+        let first = StateManager::new("dev".to_string(), 100, store.clone());
+        first.add_parasite(parasite("a", 1.5)).await;
+        first.add_parasite(parasite("b", 2.0)).await;
+        first.state.write().await.player.passive_income = 99.0;
+        first.checkpoint().await.unwrap();
+
+        let restored = StateManager::new("dev".to_string(), 100, store);
+        assert!(restored.restore().await.unwrap());
+        assert_eq!(restored.get_snapshot().await.player.passive_income, 3.5);
+        assert!(restored.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_skips_clean_state() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+
+        assert!(!manager.checkpoint().await.unwrap());
+        assert!(manager.store().load_snapshot().unwrap().is_none());
+
+        manager.update_entropy(50).await;
+        assert!(manager.is_dirty());
+        assert!(manager.checkpoint().await.unwrap());
+        assert!(!manager.is_dirty());
+
+        let saved = manager.store().load_snapshot().unwrap().unwrap();
+        assert_eq!(saved.player.entropy, 50);
+
+        // Nothing changed since the last write
+        assert!(!manager.checkpoint().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_large_delta_saturates() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        let capacity = manager.get_snapshot().await.player.capacity;
+
+        manager.update_entropy(i64::MAX).await;
+        manager.update_entropy(i64::MAX).await;
+
+        let entropy = manager.get_snapshot().await.player.entropy;
+        assert!(entropy <= capacity * MAX_ENTROPY_CAPACITY_FACTOR);
+        assert!(entropy > capacity);
+    }
+
+    #[tokio::test]
+    async fn test_zero_capacity_leaves_balance_uncapped() {
+        let manager = StateManager::in_memory("dev".to_string(), 100).with_decay_grace(0);
+        manager.update_entropy(500).await;
+        manager.update_karma(0).await;
+        assert_eq!(manager.get_snapshot().await.player.capacity, 0);
+
+        // Neither clamped to zero nor decayed
+        manager.update_entropy(10).await;
+        manager.update_entropy(0).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, 510);
+    }
+
+    #[tokio::test]
+    async fn test_negative_delta_never_underflows() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.update_entropy(500).await;
+
+        manager.update_entropy(i64::MIN).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, 0);
+
+        // Decay just above capacity stays at or above capacity
+        let capacity = manager.get_snapshot().await.player.capacity as i64;
+        manager.update_entropy(capacity + 1).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, capacity as u64 + 1);
+    }
+
+    #[tokio::test]
+    async fn test_decay_after_grace_expires() {
+        let manager = StateManager::in_memory("dev".to_string(), 10).with_decay_grace(60);
+        let start = Utc::now();
+
+        // Capacity 1000; overshoot by 500 is tolerated inside the grace period
+        manager.update_entropy_at(1500, start).await;
+        manager.update_entropy_at(0, start + chrono::Duration::seconds(59)).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, 1500);
+
+        manager.update_entropy_at(0, start + chrono::Duration::seconds(60)).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, 1490);
+    }
+
+    #[tokio::test]
+    async fn test_drop_below_capacity_resets_grace() {
+        let manager = StateManager::in_memory("dev".to_string(), 10).with_decay_grace(60);
+        let start = Utc::now();
+
+        manager.update_entropy_at(1500, start).await;
+        manager.update_entropy_at(-600, start + chrono::Duration::seconds(30)).await;
+        assert_eq!(manager.get_snapshot().await.player.over_capacity_since, None);
+
+        // A new overshoot starts a fresh grace period
+        manager.update_entropy_at(600, start + chrono::Duration::seconds(50)).await;
+        manager.update_entropy_at(0, start + chrono::Duration::seconds(100)).await;
+        assert_eq!(manager.get_snapshot().await.player.entropy, 1500);
+    }
+
+    #[tokio::test]
+    async fn test_managed_transitions() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+
+        assert_eq!(manager.update_managed(false).await, ManagedTransition::Unlinked);
+        assert_eq!(manager.update_managed(false).await, ManagedTransition::Unchanged);
+        assert_eq!(manager.update_managed(true).await, ManagedTransition::Linked);
+        assert_eq!(manager.update_managed(true).await, ManagedTransition::Unchanged);
+        assert_eq!(manager.update_managed(false).await, ManagedTransition::Unlinked);
+    }
+
+    #[tokio::test]
+    async fn test_managed_state_survives_restart() {
+        let store: Arc<dyn StateStore> = Arc::new(crate::store::MemoryStore::default());
+        let first = StateManager::new("dev".to_string(), 100, store.clone());
+        assert_eq!(first.update_managed(true).await, ManagedTransition::Linked);
+        first.checkpoint().await.unwrap();
+
+        // The first sync after a restart only reports a real change
+        let restarted = StateManager::new("dev".to_string(), 100, store);
+        restarted.restore().await.unwrap();
+        assert!(restarted.is_managed().await);
+        assert_eq!(restarted.update_managed(true).await, ManagedTransition::Unchanged);
+        assert_eq!(restarted.update_managed(false).await, ManagedTransition::Unlinked);
+        assert!(!restarted.is_managed().await);
+    }
+
+    #[tokio::test]
+    async fn test_clones_share_dirty_flag() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        let other = manager.clone();
+
+        other.update_karma(200).await;
+        assert!(manager.is_dirty());
+    }
+
+    fn anomaly(confidence: f64) -> AnomalyRecord {
+        AnomalyRecord {
+            at: Utc::now(),
+            confidence,
+            reasons: vec!["entropy rate above hardware ceiling".to_string()],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_anomaly_history_streak_and_bound() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+
+        assert_eq!(manager.record_sync_anomaly(Some(anomaly(0.4))).await, 1);
+        assert_eq!(manager.record_sync_anomaly(Some(anomaly(0.7))).await, 2);
+        assert_eq!(manager.record_sync_anomaly(None).await, 0);
+        assert_eq!(manager.record_sync_anomaly(Some(anomaly(0.9))).await, 1);
+
+        let history = manager.get_snapshot().await.anomalies;
+        let confidences: Vec<f64> = history.records.iter().map(|r| r.confidence).collect();
+        assert_eq!(confidences, [0.4, 0.7, 0.9]);
+
+        for _ in 0..ANOMALY_HISTORY_LIMIT {
+            manager.record_sync_anomaly(Some(anomaly(0.5))).await;
+        }
+        let history = manager.get_snapshot().await.anomalies;
+        assert_eq!(history.records.len(), ANOMALY_HISTORY_LIMIT);
+        assert_eq!(history.consecutive, ANOMALY_HISTORY_LIMIT as u32 + 1);
+    }
+
+    #[tokio::test]
+    async fn test_clear_anomaly_history() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.record_sync_anomaly(Some(anomaly(0.8))).await;
+        manager.record_sync_anomaly(Some(anomaly(0.8))).await;
+
+        assert_eq!(manager.clear_anomaly_history().await, 2);
+        let history = manager.get_snapshot().await.anomalies;
+        assert!(history.records.is_empty());
+        assert_eq!(history.consecutive, 0);
+    }
+
+    #[tokio::test]
+    async fn test_karma_update_uses_capacity_formula() {
+        let manager = StateManager::in_memory("dev".to_string(), 100)
+            .with_capacity_formula(CapacityFormula::Sqrt { scale: 1000.0 });
+        assert_eq!(manager.get_snapshot().await.player.capacity, 10_000);
+
+        manager.update_karma(900).await;
+        assert_eq!(manager.get_snapshot().await.player.capacity, 30_000);
+    }
+
+    #[tokio::test]
+    async fn test_spend_entropy_records_to_ledger() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.update_entropy(500).await;
+
+        assert_eq!(manager.spend_entropy(120, SpendReason::Radar).await, Ok(380));
+
+        let state = manager.get_snapshot().await;
+        assert_eq!(state.player.entropy, 380);
+        assert_eq!(state.ledger.len(), 1);
+        assert_eq!(state.ledger[0].amount, 120);
+        assert_eq!(state.ledger[0].reason, SpendReason::Radar);
+        assert_eq!(state.ledger[0].balance, 380);
+        manager.flush_journal().await;
+        assert_eq!(manager.store().read_ledger().unwrap(), state.ledger.iter().cloned().collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_pending_spend_journaled_once_settled() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.update_entropy(500).await;
+
+        manager.record_pending_spend(100, SpendReason::Radar, "n-1".to_string()).await;
+        manager.record_pending_spend(300, SpendReason::Radar, "n-2".to_string()).await;
+        manager.flush_journal().await;
+        assert!(manager.store().read_ledger().unwrap().is_empty());
+
+        // The server confirms only the first, at its own price
+        let charges = [ServerCharge { nonce: "n-1".to_string(), amount: EntropyAmount(90) }];
+        assert_eq!(manager.settle_pending_spends(&charges).await, 90);
+        manager.flush_journal().await;
+        let journal = manager.store().read_ledger().unwrap();
+        assert_eq!(journal.len(), 1);
+        assert_eq!((journal[0].amount, journal[0].balance, journal[0].pending), (90, 410, false));
+
+        // The unconfirmed spend stays pending, and settling is idempotent
+        let snapshot = manager.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 410);
+        assert!(snapshot.ledger[1].pending);
+        assert_eq!(manager.settle_pending_spends(&charges).await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_pending_spends_leave_state_clean() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.record_pending_spend(100, SpendReason::Radar, "n-1".to_string()).await;
+        manager.checkpoint().await.unwrap();
+
+        let charges = [ServerCharge { nonce: "other".to_string(), amount: EntropyAmount(100) }];
+        assert_eq!(manager.settle_pending_spends(&charges).await, 0);
+        assert!(!manager.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn test_restore_merges_spends_journaled_after_snapshot() {
+        let store: Arc<dyn StateStore> = Arc::new(crate::store::MemoryStore::default());
+        let first = StateManager::new("dev".to_string(), 100, store.clone());
+        first.update_entropy(500).await;
+        first.spend_entropy(100, SpendReason::Radar).await.unwrap();
+        first.record_pending_spend(50, SpendReason::Radar, "n-1".to_string()).await;
+        first.checkpoint().await.unwrap();
+
+        // Neither of these reaches a snapshot before the restart
+        first.spend_entropy(30, SpendReason::Radar).await.unwrap();
+        let charges = [ServerCharge { nonce: "n-1".to_string(), amount: EntropyAmount(40) }];
+        first.settle_pending_spends(&charges).await;
+        first.flush_journal().await;
+
+        let restored = StateManager::new("dev".to_string(), 100, store.clone());
+        assert!(restored.restore().await.unwrap());
+        let state = restored.get_snapshot().await;
+        assert_eq!(state.player.entropy, 330);
+        let ledger: Vec<(u64, bool)> = state.ledger.iter().map(|e| (e.amount, e.pending)).collect();
+        assert_eq!(ledger, [(100, false), (40, false), (30, false)]);
+
+        // Someone else's snapshot is left alone
+        let other = StateManager::new("other".to_string(), 100, store);
+        assert!(!other.restore().await.unwrap());
+        assert_eq!(other.get_snapshot().await.player.entropy, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ledger_follows_history_retention() {
+        use crate::history::RetentionConfig;
+
+        let manager = StateManager::in_memory("dev".to_string(), 100).with_history_retention(HistoryConfig {
+            ledger: RetentionConfig { max_entries: 2, max_age_days: 30 },
+            ..HistoryConfig::default()
+        });
+        manager.update_entropy(500).await;
+        for amount in 1..=3 {
+            manager.spend_entropy(amount, SpendReason::Radar).await.unwrap();
+        }
+        let amounts: Vec<u64> = manager.get_snapshot().await.ledger.iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, [2, 3]);
+        assert_eq!(manager.prune_history().await, 0);
+
+        // The journal is trimmed to the same limits on the sweep
+        manager.flush_journal().await;
+        let journaled: Vec<u64> = manager.store().read_ledger().unwrap().iter().map(|e| e.amount).collect();
+        assert_eq!(journaled, [2, 3]);
+
+        // An entry loaded from an old state file ages out on the next sweep
+        let at = Utc::now() - chrono::Duration::days(31);
+        let mut state = manager.state.write().await;
+        state.ledger = Default::default();
+        state.ledger.push(LedgerEntry { at, amount: 1, reason: SpendReason::Radar, balance: 0, pending: false, nonce: None }, &RetentionConfig::entries(10), at);
+        drop(state);
+        assert_eq!(manager.prune_history().await, 1);
+        assert!(manager.get_snapshot().await.ledger.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_spend_entropy_rejects_insufficient_funds() {
+        let manager = StateManager::in_memory("dev".to_string(), 100);
+        manager.update_entropy(50).await;
+
+        assert_eq!(
+            manager.spend_entropy(51, SpendReason::Admin("test".to_string())).await,
+            Err(InsufficientEntropy { requested: 51, available: 50 })
+        );
+        let state = manager.get_snapshot().await;
+        assert_eq!(state.player.entropy, 50);
+        assert!(state.ledger.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entropy_floor_holds_against_auto_spend() {
+        let manager = StateManager::in_memory("dev".to_string(), 100).with_entropy_floor(200);
+        manager.update_entropy(500).await;
+
+        assert_eq!(manager.spendable_entropy().await, 300);
+        assert_eq!(
+            manager.spend_entropy(301, SpendReason::Radar).await,
+            Err(InsufficientEntropy { requested: 301, available: 300 })
+        );
+        assert_eq!(manager.try_spend_entropy(301, SpendReason::Radar, |_| 0).await, Err((500, 200)));
+        assert_eq!(manager.spend_entropy(300, SpendReason::Radar).await, Ok(200));
+
+        // A deliberate admin spend may go below it
+        assert_eq!(manager.spend_entropy(150, SpendReason::Admin("manual".to_string())).await, Ok(50));
+    }
+}
@@ -0,0 +1,96 @@
+// sacas-daemon/src/status_feed.rs
+// Pushes state changes to streaming status consumers with bounded memory
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::state::StateManager;
+use crate::types::GameState;
+
+/// Change notices buffered per subscriber before it counts as lagging
+pub const DEFAULT_STATUS_CHANNEL_CAPACITY: usize = 64;
+
+/// What a subscriber sees after a change
+#[derive(Debug)]
+pub enum StatusUpdate {
+    /// State after one or more changes; a burst is coalesced into one update
+    Changed(GameState),
+    /// The subscriber fell behind and `missed` notices were dropped; this is
+    /// the latest full state to resync from
+    Resync { missed: u64, state: GameState },
+}
+
+/// One consumer of state changes
+///
+/// The channel only carries change sequence numbers, so a slow consumer
+/// costs at most the channel capacity. Each update reads the current state,
+/// so consumers always see the latest view even when notices were dropped.
+pub struct StatusSubscriber {
+    rx: broadcast::Receiver<u64>,
+    state: StateManager,
+}
+
+impl StatusSubscriber {
+    pub fn new(rx: broadcast::Receiver<u64>, state: StateManager) -> Self {
+        Self { rx, state }
+    }
+
+    /// Wait for the next change; `None` once the state manager is gone
+    pub async fn next(&mut self) -> Option<StatusUpdate> {
+        match self.rx.recv().await {
+            Ok(_) => {
+                // Fold notices that queued up meanwhile into this update
+                while self.rx.try_recv().is_ok() {}
+                Some(StatusUpdate::Changed(self.state.get_snapshot().await))
+            }
+            Err(RecvError::Lagged(missed)) => {
+                warn!("📡 Status subscriber lagged by {} changes; resyncing from snapshot", missed);
+                Some(StatusUpdate::Resync { missed, state: self.state.get_snapshot().await })
+            }
+            Err(RecvError::Closed) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_lagged_subscriber_resyncs_to_snapshot() {
+        let state = StateManager::in_memory("dev".to_string(), 100).with_status_channel_capacity(4);
+        let mut subscriber = state.subscribe_status();
+
+        for _ in 0..10 {
+            state.update_entropy(10).await;
+        }
+
+        match subscriber.next().await {
+            Some(StatusUpdate::Resync { missed, state }) => {
+                assert_eq!(missed, 6);
+                assert_eq!(state.player.entropy, 100);
+            }
+            other => panic!("expected a resync, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_burst_coalesced_into_one_update() {
+        let state = StateManager::in_memory("dev".to_string(), 100);
+        let mut subscriber = state.subscribe_status();
+
+        state.update_entropy(10).await;
+        state.update_entropy(20).await;
+
+        match subscriber.next().await {
+            Some(StatusUpdate::Changed(snapshot)) => assert_eq!(snapshot.player.entropy, 30),
+            other => panic!("expected a change, got {:?}", other),
+        }
+
+        state.update_entropy(5).await;
+        match subscriber.next().await {
+            Some(StatusUpdate::Changed(snapshot)) => assert_eq!(snapshot.player.entropy, 35),
+            other => panic!("expected a change, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,306 @@
+// sacas-daemon/src/store.rs
+// Where state snapshots and the spend ledger are persisted
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc};
+#[cfg(test)]
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+use crate::history::{BoundedHistory, RetentionConfig};
+use crate::types::{GameState, LedgerEntry};
+
+/// Persistence behind `StateManager`
+///
+/// The snapshot is the whole state, replaced on every checkpoint. The
+/// ledger is a journal of settled spends, appended as they happen and
+/// trimmed to the same `history.ledger` retention as state.
+pub trait StateStore: Send + Sync {
+    /// Replace the saved snapshot with `state`
+    fn save_snapshot(&self, state: &GameState) -> Result<()>;
+
+    /// The saved snapshot, or `None` if nothing has been saved yet
+    fn load_snapshot(&self) -> Result<Option<GameState>>;
+
+    /// Add `entry` to the end of the ledger journal
+    fn append_ledger(&self, entry: &LedgerEntry) -> Result<()>;
+
+    /// Every journaled entry, oldest first
+    fn read_ledger(&self) -> Result<Vec<LedgerEntry>>;
+
+    /// Drop journaled entries `retention` no longer allows; returns how many went
+    fn prune_ledger(&self, retention: &RetentionConfig, now: DateTime<Utc>) -> Result<usize>;
+}
+
+/// JSON snapshot plus a JSON-lines ledger journal on disk
+pub struct FileStore {
+    state_path: PathBuf,
+    ledger_path: PathBuf,
+}
+
+impl FileStore {
+    pub fn new(state_path: PathBuf, ledger_path: PathBuf) -> Self {
+        Self { state_path, ledger_path }
+    }
+}
+
+impl StateStore for FileStore {
+    /// Written atomically (temp file + rename)
+    fn save_snapshot(&self, state: &GameState) -> Result<()> {
+        create_parent(&self.state_path)?;
+
+        let json = serde_json::to_vec_pretty(state)
+            .context("Failed to serialize state snapshot")?;
+
+        replace_file(&self.state_path, &json)
+    }
+
+    fn load_snapshot(&self) -> Result<Option<GameState>> {
+        if !self.state_path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read(&self.state_path)
+            .with_context(|| format!("Failed to read {}", self.state_path.display()))?;
+        let state = serde_json::from_slice(&raw)
+            .with_context(|| format!("Failed to parse {}", self.state_path.display()))?;
+        Ok(Some(state))
+    }
+
+    fn append_ledger(&self, entry: &LedgerEntry) -> Result<()> {
+        create_parent(&self.ledger_path)?;
+
+        let mut line = serde_json::to_string(entry).context("Failed to serialize ledger entry")?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.ledger_path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .with_context(|| format!("Failed to append to {}", self.ledger_path.display()))
+    }
+
+    fn read_ledger(&self) -> Result<Vec<LedgerEntry>> {
+        if !self.ledger_path.exists() {
+            return Ok(Vec::new());
+        }
+        let raw = fs::read_to_string(&self.ledger_path)
+            .with_context(|| format!("Failed to read {}", self.ledger_path.display()))?;
+        raw.lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Bad ledger entry on line {} of {}", i + 1, self.ledger_path.display()))
+            })
+            .collect()
+    }
+
+    /// Rewritten atomically, and only when something expired
+    fn prune_ledger(&self, retention: &RetentionConfig, now: DateTime<Utc>) -> Result<usize> {
+        let mut ledger: BoundedHistory<LedgerEntry> = self.read_ledger()?.into_iter().collect();
+        let pruned = ledger.prune(retention, now);
+        if pruned == 0 {
+            return Ok(0);
+        }
+
+        let mut lines = Vec::new();
+        for entry in &ledger {
+            serde_json::to_writer(&mut lines, entry).context("Failed to serialize ledger entry")?;
+            lines.push(b'\n');
+        }
+        replace_file(&self.ledger_path, &lines)?;
+        Ok(pruned)
+    }
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+    Ok(())
+}
+
+/// Write `contents` to a temp file beside `path`, then rename it over `path`
+fn replace_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, contents)
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to move {} to {}", tmp_path.display(), path.display()))
+}
+
+enum JournalOp {
+    Append(LedgerEntry),
+    Prune(RetentionConfig, DateTime<Utc>),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes the ledger journal in order on a thread of its own
+///
+/// Spends are recorded under the state write lock; queueing the journal
+/// IO keeps disk writes out of it. The thread exits once every clone is
+/// dropped.
+#[derive(Clone)]
+pub struct JournalWriter {
+    ops: mpsc::Sender<JournalOp>,
+}
+
+impl JournalWriter {
+    pub fn spawn(store: Arc<dyn StateStore>) -> Self {
+        let (ops, queue) = mpsc::channel();
+        std::thread::Builder::new()
+            .name("ledger-journal".to_string())
+            .spawn(move || {
+                for op in queue {
+                    match op {
+                        JournalOp::Append(entry) => {
+                            if let Err(e) = store.append_ledger(&entry) {
+                                warn!("⚠️  Failed to journal ledger entry: {:#}", e);
+                            }
+                        }
+                        JournalOp::Prune(retention, now) => {
+                            if let Err(e) = store.prune_ledger(&retention, now) {
+                                warn!("⚠️  Failed to prune ledger journal: {:#}", e);
+                            }
+                        }
+                        JournalOp::Flush(done) => {
+                            let _ = done.send(());
+                        }
+                    }
+                }
+            })
+            .expect("Failed to spawn the ledger journal thread");
+        Self { ops }
+    }
+
+    /// Queue `entry` for the end of the journal
+    pub fn append(&self, entry: LedgerEntry) {
+        self.send(JournalOp::Append(entry));
+    }
+
+    /// Queue a prune of entries `retention` no longer allows at `now`
+    pub fn prune(&self, retention: RetentionConfig, now: DateTime<Utc>) {
+        self.send(JournalOp::Prune(retention, now));
+    }
+
+    /// Wait until everything queued so far has been written
+    pub async fn flush(&self) {
+        let (done, written) = oneshot::channel();
+        self.send(JournalOp::Flush(done));
+        let _ = written.await;
+    }
+
+    fn send(&self, op: JournalOp) {
+        // The thread only stops when every sender is gone
+        let _ = self.ops.send(op);
+    }
+}
+
+/// Keeps everything in memory, for tests
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStore {
+    snapshot: Mutex<Option<GameState>>,
+    ledger: Mutex<Vec<LedgerEntry>>,
+}
+
+#[cfg(test)]
+impl StateStore for MemoryStore {
+    fn save_snapshot(&self, state: &GameState) -> Result<()> {
+        *self.snapshot.lock().unwrap() = Some(state.clone());
+        Ok(())
+    }
+
+    fn load_snapshot(&self) -> Result<Option<GameState>> {
+        Ok(self.snapshot.lock().unwrap().clone())
+    }
+
+    fn append_ledger(&self, entry: &LedgerEntry) -> Result<()> {
+        self.ledger.lock().unwrap().push(entry.clone());
+        Ok(())
+    }
+
+    fn read_ledger(&self) -> Result<Vec<LedgerEntry>> {
+        Ok(self.ledger.lock().unwrap().clone())
+    }
+
+    fn prune_ledger(&self, retention: &RetentionConfig, now: DateTime<Utc>) -> Result<usize> {
+        let mut ledger = self.ledger.lock().unwrap();
+        let mut kept: BoundedHistory<LedgerEntry> = ledger.drain(..).collect();
+        let pruned = kept.prune(retention, now);
+        ledger.extend(kept);
+        Ok(pruned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::StateManager;
+    use crate::types::SpendReason;
+    use chrono::Utc;
+
+    fn entry(amount: u64) -> LedgerEntry {
+        LedgerEntry { at: Utc::now(), amount, reason: SpendReason::Radar, balance: 1000 - amount, pending: false, nonce: None }
+    }
+
+    async fn exercise(store: &dyn StateStore) {
+        assert!(store.load_snapshot().unwrap().is_none());
+        assert!(store.read_ledger().unwrap().is_empty());
+
+        let mut state = StateManager::in_memory("dev".to_string(), 100).get_snapshot().await;
+        store.save_snapshot(&state).unwrap();
+        state.player.entropy = 77;
+        store.save_snapshot(&state).unwrap();
+        assert_eq!(store.load_snapshot().unwrap().unwrap().player.entropy, 77);
+
+        store.append_ledger(&entry(10)).unwrap();
+        store.append_ledger(&entry(25)).unwrap();
+        let amounts: Vec<u64> = store.read_ledger().unwrap().iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, [10, 25]);
+
+        assert_eq!(store.prune_ledger(&RetentionConfig::entries(1), Utc::now()).unwrap(), 1);
+        assert_eq!(store.prune_ledger(&RetentionConfig::entries(1), Utc::now()).unwrap(), 0);
+        let amounts: Vec<u64> = store.read_ledger().unwrap().iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, [25]);
+    }
+
+    #[tokio::test]
+    async fn test_memory_store() {
+        exercise(&MemoryStore::default()).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::new(dir.path().join("state.json"), dir.path().join("sub").join("ledger.jsonl"));
+        exercise(&store).await;
+
+        // A second store over the same files sees the same data
+        let reopened = FileStore::new(dir.path().join("state.json"), dir.path().join("sub").join("ledger.jsonl"));
+        assert_eq!(reopened.read_ledger().unwrap().len(), 1);
+        assert!(!dir.path().join("state.tmp").exists());
+        assert!(!dir.path().join("sub").join("ledger.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn test_journal_writer_keeps_order() {
+        let store = Arc::new(MemoryStore::default());
+        let writer = JournalWriter::spawn(store.clone());
+        for amount in 1..=5 {
+            writer.append(entry(amount));
+        }
+        writer.prune(RetentionConfig::entries(3), Utc::now());
+        writer.flush().await;
+
+        let amounts: Vec<u64> = store.read_ledger().unwrap().iter().map(|e| e.amount).collect();
+        assert_eq!(amounts, [3, 4, 5]);
+    }
+}
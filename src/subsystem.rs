@@ -0,0 +1,133 @@
+// sacas-daemon/src/subsystem.rs
+// Config switches for optional background services, and a spawner that honors them
+
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+/// Background services that can be turned off in `[subsystems]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+    Grpc,
+    WebSocket,
+    NetworkProbe,
+    KarmaSync,
+    AutoCombat,
+}
+
+impl Subsystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            Subsystem::Grpc => "gRPC server",
+            Subsystem::WebSocket => "WebSocket listener",
+            Subsystem::NetworkProbe => "Network probe",
+            Subsystem::KarmaSync => "Karma sync",
+            Subsystem::AutoCombat => "Combat reconciliation",
+        }
+    }
+}
+
+/// Every subsystem runs unless switched off here
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct SubsystemConfig {
+    /// Local gRPC API for the CLI and UI
+    #[serde(default = "default_enabled")]
+    pub grpc: bool,
+    /// Server event stream for battle notifications and sync triggers
+    #[serde(default = "default_enabled")]
+    pub websocket: bool,
+    /// Anchor latency probe feeding network quality; without it mining
+    /// starts once `mining.warmup_timeout_secs` runs out
+    #[serde(default = "default_enabled")]
+    pub network_probe: bool,
+    /// Moltbook karma sync (also needs `[moltbook]`)
+    #[serde(default = "default_enabled")]
+    pub karma_sync: bool,
+    /// Background defense and parasite reconciliation with the server
+    #[serde(default = "default_enabled")]
+    pub auto_combat: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl Default for SubsystemConfig {
+    fn default() -> Self {
+        Self {
+            grpc: true,
+            websocket: true,
+            network_probe: true,
+            karma_sync: true,
+            auto_combat: true,
+        }
+    }
+}
+
+impl SubsystemConfig {
+    pub fn is_enabled(&self, subsystem: Subsystem) -> bool {
+        match subsystem {
+            Subsystem::Grpc => self.grpc,
+            Subsystem::WebSocket => self.websocket,
+            Subsystem::NetworkProbe => self.network_probe,
+            Subsystem::KarmaSync => self.karma_sync,
+            Subsystem::AutoCombat => self.auto_combat,
+        }
+    }
+}
+
+/// Spawns a subsystem's task only when config enables it
+pub struct SubsystemSpawner {
+    config: SubsystemConfig,
+    started: Vec<Subsystem>,
+}
+
+impl SubsystemSpawner {
+    pub fn new(config: SubsystemConfig) -> Self {
+        Self { config, started: Vec::new() }
+    }
+
+    /// Spawn `task` unless `subsystem` is disabled; returns whether it was spawned
+    pub fn spawn<F>(&mut self, subsystem: Subsystem, task: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if !self.config.is_enabled(subsystem) {
+            info!("⊘ {} disabled in config", subsystem.name());
+            return false;
+        }
+        tokio::spawn(task);
+        self.started.push(subsystem);
+        true
+    }
+
+    /// Subsystems spawned so far, in order
+    pub fn started(&self) -> &[Subsystem] {
+        &self.started
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_disabled_subsystem_not_started() {
+        let config: SubsystemConfig = toml::from_str("grpc = false\nauto_combat = false").unwrap();
+        let mut spawner = SubsystemSpawner::new(config);
+
+        let grpc_ran = Arc::new(AtomicBool::new(false));
+        let ran = grpc_ran.clone();
+        assert!(!spawner.spawn(Subsystem::Grpc, async move { ran.store(true, Ordering::SeqCst) }));
+        assert!(!spawner.spawn(Subsystem::AutoCombat, async {}));
+        assert!(spawner.spawn(Subsystem::NetworkProbe, async {}));
+        assert!(spawner.spawn(Subsystem::WebSocket, async {}));
+
+        tokio::task::yield_now().await;
+        assert!(!grpc_ran.load(Ordering::SeqCst));
+        assert_eq!(spawner.started(), [Subsystem::NetworkProbe, Subsystem::WebSocket]);
+    }
+}
@@ -1,22 +1,162 @@
+pub mod offline;
 pub mod signed_sync;
+mod uptime;
+pub mod verify;
+
+pub use offline::OfflineQueue;
+use uptime::UptimeClock;
 
 use anyhow::{Result, Context};
+use futures_util::{stream, StreamExt};
 use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tokio::sync::Notify;
 use tokio::time;
-use tokio::sync::RwLock;
-use tracing::{info, warn, error};
+use tracing::{debug, info, warn, error};
+
+use crate::config::{Config, Service, SyncConfig};
+use crate::state::{ManagedTransition, StateManager};
+use crate::config::DeviceBinding;
+use crate::device::{check_registration, BindingGuard, DeviceIdentity, DuplicateRegistration};
+use crate::jitter::JitterConfig;
+use crate::build_info::API_VERSION;
+use crate::http::{EndpointCategory, HttpClient, RateLimited, ServerPool, UpgradeRequired};
+use crate::mining::params::{ServerParamsTrust, SignedMiningParams};
+use crate::notifier::{notifier_for, NoopNotifier, Notifier};
+use crate::types::{format_entropy, AnomalyRecord, ServerCharge};
+use signed_sync::{
+    negotiate_schema, SignedHeartbeatRequest, SignedLatencySample, SignedSyncRequest, SyncResponse,
+    SYNC_SCHEMA_LATEST, SYNC_SCHEMA_MIN,
+};
+
+/// Progress carried between sync ticks
+struct SyncCursor {
+    last_synced_entropy: i64,
+    schema_version: u32,
+    /// Deltas that failed to sync, shared with the status RPC
+    backlog: OfflineQueue,
+    replay: ReplayPacing,
+    threshold: SyncThreshold,
+    /// When the delta currently being held back was first deferred
+    deferred_since: Option<std::time::Instant>,
+    /// Tells the user about server-side changes worth noticing
+    notifier: Arc<dyn Notifier>,
+    /// Verifies server mining parameters; without it they are ignored
+    params_trust: Option<ServerParamsTrust>,
+    /// Set once the server answers for another device; syncing stops until restart
+    registration_conflict: Option<DuplicateRegistration>,
+    /// Re-checks the hardware binding every few syncs; without it, only startup checks
+    binding_guard: Option<BindingGuard>,
+    /// Set once the server refuses this daemon's API version
+    upgrade_required: bool,
+    /// Stop syncing after `upgrade_required` instead of retrying every tick
+    observe_on_upgrade: bool,
+    /// Sync requests refused with 401 in a row
+    auth_rejections: u32,
+    /// Refusals in a row that mean the server rejects the device key (0 never)
+    key_rejected_after: u32,
+}
+
+impl Default for SyncCursor {
+    fn default() -> Self {
+        Self {
+            last_synced_entropy: 0,
+            schema_version: SYNC_SCHEMA_LATEST,
+            backlog: OfflineQueue::default(),
+            replay: ReplayPacing::from(&SyncConfig::default()),
+            threshold: SyncThreshold::from(&SyncConfig::default()),
+            deferred_since: None,
+            notifier: Arc::new(NoopNotifier),
+            params_trust: None,
+            registration_conflict: None,
+            binding_guard: None,
+            upgrade_required: false,
+            observe_on_upgrade: false,
+            auth_rejections: 0,
+            key_rejected_after: SyncConfig::default().key_rejected_after,
+        }
+    }
+}
+
+/// How fast the offline backlog is replayed
+#[derive(Debug, Clone, Copy)]
+struct ReplayPacing {
+    concurrency: usize,
+    spacing: Duration,
+}
 
-use crate::config::Config;
-use crate::types::GameState;
-use crate::device::DeviceIdentity;
-use signed_sync::{SignedSyncRequest, SyncResponse};
+impl From<&SyncConfig> for ReplayPacing {
+    fn from(config: &SyncConfig) -> Self {
+        Self {
+            concurrency: config.replay_concurrency.max(1),
+            spacing: Duration::from_millis(config.replay_spacing_ms),
+        }
+    }
+}
+
+/// Minimum delta worth a sync request, so slow miners don't send a stream
+/// of tiny syncs
+#[derive(Debug, Clone, Copy)]
+struct SyncThreshold {
+    min_delta: u64,
+    min_percent: f64,
+    max_deferral: Duration,
+}
+
+impl From<&SyncConfig> for SyncThreshold {
+    fn from(config: &SyncConfig) -> Self {
+        Self {
+            min_delta: config.min_sync_delta,
+            min_percent: config.min_sync_delta_percent.max(0.0),
+            max_deferral: Duration::from_secs(config.max_deferral_secs),
+        }
+    }
+}
 
-/// Start periodic sync loop with Ed25519 signatures
+impl SyncThreshold {
+    /// Smallest delta synced right away at this capacity
+    fn min_delta_for(&self, capacity: u64) -> u64 {
+        let percent = (capacity as f64 * self.min_percent / 100.0).ceil() as u64;
+        self.min_delta.max(percent)
+    }
+
+    /// Whether to hold `delta` back, given how long it has been held already
+    fn should_defer(&self, delta: i64, capacity: u64, deferred_for: Duration) -> bool {
+        delta.unsigned_abs() < self.min_delta_for(capacity) && deferred_for < self.max_deferral
+    }
+}
+
+/// The server answered a sync with an error status
+#[derive(Debug, thiserror::Error)]
+#[error("Sync failed with status {status}: {body}")]
+struct SyncRejected {
+    status: reqwest::StatusCode,
+    body: String,
+}
+
+/// Whether `error` should stop a backlog replay until the next cycle
+///
+/// That is a non-retryable rejection (4xx other than 429), or the local
+/// sync rate limit running dry, since every later send would fail too.
+fn halts_replay(error: &anyhow::Error) -> bool {
+    let rejected = error.downcast_ref::<SyncRejected>().is_some_and(|r| {
+        r.status.is_client_error() && r.status != reqwest::StatusCode::TOO_MANY_REQUESTS
+    });
+    rejected || error.is::<RateLimited>()
+}
+
+/// Start periodic sync loop, signing with the device's `Signer`
+///
+/// Besides the regular interval, a sync runs whenever `sync_now` is
+/// notified (e.g. after the WebSocket reconnects and may have missed events).
+/// Deltas that fail to sync wait in `backlog` and ride along with the next one.
 pub async fn start_sync_loop(
     config: Config,
-    state: Arc<RwLock<GameState>>,
+    state: Arc<StateManager>,
     identity: DeviceIdentity,
+    sync_now: Arc<Notify>,
+    backlog: OfflineQueue,
 ) -> Result<()> {
     let device_id = match &config.device_id {
         Some(id) => id.clone(),
@@ -26,118 +166,1144 @@ pub async fn start_sync_loop(
         }
     };
 
-    info!("Starting signed sync loop (every 5 minutes)");
-    
-    let mut interval = time::interval(Duration::from_secs(300)); // 5 minutes
-    let mut last_synced_entropy: i64 = 0;
-    let start_time = std::time::Instant::now();
+    let http = HttpClient::new(&config.http);
+    let servers = ServerPool::new(&config.service_urls(Service::Sync));
 
-    loop {
-        interval.tick().await;
-
-        // Get current entropy from state
-        let current_entropy = {
-            let state_lock = state.read().await;
-            let entropy = state_lock.player.entropy as i64;
-            info!("🔍 Sync check: current_entropy={}, last_synced={}", entropy, last_synced_entropy);
-            entropy
-        };
+    info!("Starting signed sync loop (every {}s) via {}", config.sync.interval_secs, servers.current());
+    let mut interval = sync_interval(&config.sync, &config.jitter);
+    let period = interval.period();
+    let mut cursor = SyncCursor {
+        backlog,
+        replay: ReplayPacing::from(&config.sync),
+        threshold: SyncThreshold::from(&config.sync),
+        notifier: notifier_for(config.notifications.backend),
+        params_trust: ServerParamsTrust::from_config(&config.sync)?,
+        binding_guard: Some(BindingGuard::new(config.clone(), config.sync.binding_check_every)),
+        observe_on_upgrade: config.sync.observe_on_upgrade_required,
+        key_rejected_after: config.sync.key_rejected_after,
+        ..Default::default()
+    };
+    let uptime = UptimeClock::start(config.sync.max_uptime_secs);
+    let mut wake = crate::wake::WakeDetector::default();
 
-        let entropy_delta = current_entropy - last_synced_entropy;
-        
-        info!("📊 Entropy delta: {} Ω", entropy_delta);
+    reconcile_cursor(&http, &servers, &device_id, &state, &identity, &mut cursor).await;
 
-        if entropy_delta == 0 {
-            warn!("⚠️  No new entropy to sync (current: {}, last: {})", 
-                  current_entropy, last_synced_entropy);
-            continue;
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if config.jitter.every_tick {
+                    interval.reset_after(config.jitter.next(period));
+                }
+            }
+            _ = sync_now.notified() => {
+                info!("🔄 Out-of-band sync requested");
+            }
         }
 
-        // Calculate uptime
-        let uptime_seconds = start_time.elapsed().as_secs();
+        if let Some(asleep) = wake.check() {
+            info!("💤 Woke after {}m asleep; uptime {}s excludes the sleep", asleep.as_secs() / 60, uptime.seconds());
+        }
+        let uptime_seconds = uptime.seconds();
 
-        // Create signed sync request
-        let signed_request = SignedSyncRequest::create_and_sign(
+        sync_cycle(
+            &http,
+            &servers,
             &device_id,
-            entropy_delta,
-            1.0, // Network quality (currently fixed at 1.0)
-            uptime_seconds,
+            &state,
             &identity,
-        );
+            &mut cursor,
+            uptime_seconds,
+        ).await;
+    }
+}
+
+/// Start the cursor at the entropy the server already has
+///
+/// Local state may hold entropy the server has already counted (e.g. state
+/// restored from disk), which a cursor starting at 0 would sync again. A
+/// zero-delta sync returns the server total without changing it. The cursor
+/// never moves past local entropy, so a server ahead of local state doesn't
+/// produce a negative delta. If the server can't be reached the cursor is
+/// left as it is.
+async fn reconcile_cursor(
+    http: &HttpClient,
+    servers: &ServerPool,
+    device_id: &str,
+    state: &StateManager,
+    identity: &DeviceIdentity,
+    cursor: &mut SyncCursor,
+) {
+    let probe = SignedSyncRequest::create_and_sign(device_id, 0, 1.0, 0, cursor.schema_version, identity.signer());
+    let server_entropy = match sync_to_server(http, servers, probe).await {
+        Ok(SyncResponse { device_entropy: Some(entropy), .. }) => entropy,
+        Ok(_) => {
+            warn!("⚠️  Server did not report device entropy; sync cursor not reconciled");
+            return;
+        }
+        Err(e) => {
+            warn!("⚠️  Could not reconcile sync cursor with server: {}", e);
+            return;
+        }
+    };
 
-        // Attempt sync
-        match sync_to_server(&config.server_url, signed_request).await {
-            Ok(response) => {
-                info!("✅ Synced +{} Ω to server (signed)", entropy_delta);
-                info!("   Device total: {} Ω", response.device_entropy);
-                
-                // Update karma from server (in case it changed)
-                {
-                    let state_mgr = crate::state::StateManager {
-                        state: state.clone(),
-                    };
-                    state_mgr.update_karma(response.device_karma as u64).await;
+    let local_entropy = state.get_snapshot().await.player.entropy as i64;
+    if server_entropy > local_entropy {
+        warn!("⚠️  Server total {} is ahead of local state {}; syncing only new mining",
+            format_entropy(server_entropy), format_entropy(local_entropy));
+    }
+
+    cursor.last_synced_entropy = server_entropy.clamp(0, local_entropy.max(0));
+    info!("🔗 Sync cursor aligned to {} (server total {})",
+        format_entropy(cursor.last_synced_entropy), format_entropy(server_entropy));
+}
+
+/// Build the sync ticker
+///
+/// `tokio::time::interval` completes its first tick immediately, which would
+/// sync at startup before anything has been mined. Instead the first tick is
+/// scheduled explicitly: after `warmup_secs` when `first_sync_immediately` is
+/// set, otherwise one full interval after startup. Later ticks follow every
+/// `interval_secs`. Ticks missed while a sync is slow or the machine sleeps
+/// collapse into one late sync.
+/// `jitter` pushes a deferred first sync back by up to its fraction of the
+/// interval; an immediate one keeps its warm-up exactly.
+fn sync_interval(config: &SyncConfig, jitter: &JitterConfig) -> time::Interval {
+    let period = Duration::from_secs(config.interval_secs.max(1));
+    let first_delay = if config.first_sync_immediately {
+        Duration::from_secs(config.warmup_secs)
+    } else {
+        jitter.first(period, period)
+    };
+
+    crate::wake::interval_at(time::Instant::now() + first_delay, period)
+}
+
+/// One sync tick: always send a heartbeat, replay the backlog, then sync
+/// any new entropy
+///
+/// A new delta that fails to sync is queued for the next tick. If replay was
+/// halted the new delta is queued behind the backlog unsent. A delta below
+/// the sync threshold is left to accumulate until it crosses the threshold
+/// or has waited `max_deferral_secs`.
+async fn sync_cycle(
+    http: &HttpClient,
+    servers: &ServerPool,
+    device_id: &str,
+    state: &StateManager,
+    identity: &DeviceIdentity,
+    cursor: &mut SyncCursor,
+    uptime_seconds: u64,
+) {
+    if let Some(conflict) = &cursor.registration_conflict {
+        warn!("⛔ Sync paused: {}. Fix the config or device key, then restart", conflict);
+        return;
+    }
+    if state.is_binding_lost() {
+        warn!("⛔ Sync paused: hardware no longer matches the device binding. Restart on the bound device");
+        return;
+    }
+    if cursor.upgrade_required && cursor.observe_on_upgrade {
+        info!("👀 Observer mode: the server requires a newer daemon, so sync is paused");
+        return;
+    }
+    if let Some(guard) = cursor.binding_guard.as_mut() {
+        if let Some(changed) = guard.on_sync().await {
+            report_binding_change(&changed, cursor.notifier.as_ref());
+            state.set_binding_lost();
+            return;
+        }
+    }
+
+    // Get current entropy and network quality from state
+    let (current_entropy, capacity, network_quality) = {
+        let state_lock = state.state.read().await;
+        let entropy = state_lock.player.entropy as i64;
+        info!("🔍 Sync check: current_entropy={}, last_synced={}",
+            format_entropy(entropy), format_entropy(cursor.last_synced_entropy));
+        (entropy, state_lock.player.capacity, state_lock.player.network_quality)
+    };
+
+    // Heartbeat proves liveness even when there is nothing to sync
+    let heartbeat = SignedHeartbeatRequest::create_and_sign(
+        device_id,
+        network_quality,
+        uptime_seconds,
+        identity.signer(),
+    );
+    if let Err(e) = send_heartbeat(http, servers, heartbeat).await {
+        warn!("💔 Heartbeat failed: {}", e);
+    }
+
+    // Sampled telemetry is best-effort; a failed upload waits for the next sample
+    if let Some(vector) = state.take_latency_sample() {
+        let sample = SignedLatencySample::create_and_sign(device_id, &vector, identity.signer());
+        match send_latency_sample(http, servers, sample).await {
+            Ok(()) => debug!("📡 Uploaded latency sample ({} anchors)", vector.data.len()),
+            Err(e) => warn!("📡 Latency sample upload failed: {}", e),
+        }
+    }
+
+    let mut replay_halt = match cursor.backlog.is_empty() {
+        true => None,
+        false => replay_backlog(http, servers, device_id, identity, cursor, uptime_seconds).await,
+    };
+    if replay_halt.as_ref().is_some_and(|e| is_schema_rejection(e, cursor.schema_version)) {
+        downgrade_schema(cursor);
+        replay_halt = replay_backlog(http, servers, device_id, identity, cursor, uptime_seconds).await;
+    }
+    if replay_halt.as_ref().is_some_and(is_auth_rejection) && note_auth_rejection(state, cursor) {
+        warn!("🔑 Replay refused: the server rejects this device key");
+    }
+    let replay_halted = replay_halt.is_some();
+
+    let entropy_delta = current_entropy - cursor.last_synced_entropy;
+    info!("📊 Entropy delta: {}", format_entropy(entropy_delta));
+
+    if entropy_delta == 0 {
+        warn!("⚠️  No new entropy to sync (current: {}, last: {})",
+              current_entropy, cursor.last_synced_entropy);
+        return;
+    }
+
+    let now = std::time::Instant::now();
+    let deferred_for = cursor.deferred_since.map_or(Duration::ZERO, |since| now - since);
+    if cursor.threshold.should_defer(entropy_delta, capacity, deferred_for) {
+        cursor.deferred_since.get_or_insert(now);
+        info!("⏳ Deferring sync of {} (below threshold {}, held {}s)",
+            format_entropy(entropy_delta), format_entropy(cursor.threshold.min_delta_for(capacity)), deferred_for.as_secs());
+        return;
+    }
+    cursor.deferred_since = None;
+
+    if replay_halted {
+        cursor.backlog.enqueue(entropy_delta, network_quality);
+        cursor.last_synced_entropy = current_entropy;
+        warn!("⏸️  New delta queued behind {} unreplayed deltas", cursor.backlog.len() - 1);
+        return;
+    }
+
+    // Create signed sync request
+    let sign = |schema_version| SignedSyncRequest::create_and_sign(
+        device_id,
+        entropy_delta,
+        network_quality,
+        uptime_seconds,
+        schema_version,
+        identity.signer(),
+    );
+
+    // Attempt sync, once more on the oldest schema if the server refuses ours
+    let mut result = sync_to_server(http, servers, sign(cursor.schema_version)).await;
+    if result.as_ref().is_err_and(|e| is_schema_rejection(e, cursor.schema_version)) {
+        downgrade_schema(cursor);
+        result = sync_to_server(http, servers, sign(cursor.schema_version)).await;
+    }
+    match result {
+        Ok(response) => {
+            note_key_accepted(state, cursor);
+            if let Err(conflict) = check_registration(Some(device_id), response.device_id.as_deref().unwrap_or(device_id)) {
+                report_registration_conflict(&conflict, cursor.notifier.as_ref());
+                cursor.backlog.enqueue(entropy_delta, network_quality);
+                cursor.last_synced_entropy = current_entropy;
+                cursor.registration_conflict = Some(conflict);
+                return;
+            }
+
+            info!("✅ Synced +{} to server (signed)", format_entropy(entropy_delta));
+            if let Some(device_entropy) = response.device_entropy {
+                info!("   Device total: {}", format_entropy(device_entropy));
+            }
+            let settled = match &response.charges {
+                Some(charges) => settle_pending(state, charges).await,
+                None => 0,
+            };
+
+            // Update karma from server (in case it changed)
+            if let Some(device_karma) = response.device_karma {
+                let karma = server_karma(device_karma);
+                let previous = state.update_karma(karma).await;
+                info!("   Karma updated: {}", karma);
+                if is_drastic_karma_drop(previous, karma) {
+                    warn!("📉 Server reports karma {} (was {}); capacity shrinks to match", karma, previous);
+                    cursor.notifier.notify(
+                        "Karma dropped",
+                        &format!("The server now reports karma {} (was {}).", karma, previous),
+                    );
                 }
-                info!("   Karma updated: {}", response.device_karma);
-                
-                if response.managed {
+            }
+
+            if let Some(managed) = response.managed {
+                if managed {
                     info!("   📊 Device linked to human account");
                 } else {
                     info!("   🤖 Device operating autonomously");
                 }
 
-                // Warn if anomaly detected
-                if let Some(warning) = &response.warning {
-                    warn!("⚠️  Anomaly detected (confidence: {:.1}%)", warning.confidence * 100.0);
-                    for reason in &warning.reasons {
-                        warn!("   - {}", reason);
+                let transition = state.update_managed(managed).await;
+                handle_managed_transition(transition, response.claim_code.as_deref(), device_id);
+                // Saved now so a restart doesn't report the same transition again
+                if transition != ManagedTransition::Unchanged {
+                    if let Err(e) = state.checkpoint().await {
+                        warn!("⚠️  Failed to save the linked state: {:#}", e);
                     }
                 }
+            }
 
-                last_synced_entropy = current_entropy;
+            // Warn if anomaly detected, and keep it for GetAnomalyHistory
+            if let Some(warning) = &response.warning {
+                warn!("⚠️  Anomaly detected (confidence: {:.1}%)", warning.confidence * 100.0);
+                for reason in &warning.reasons {
+                    warn!("   - {}", reason);
+                }
             }
-            Err(e) => {
-                warn!("❌ Sync failed: {}. Will retry in 5 minutes", e);
+            let streak = state.record_sync_anomaly(response.warning.as_ref().map(|w| AnomalyRecord {
+                at: chrono::Utc::now(),
+                confidence: w.confidence,
+                reasons: w.reasons.clone(),
+            })).await;
+            if streak > 1 {
+                warn!("   {} syncs in a row flagged as anomalous", streak);
+            }
+
+            if let Some(signed) = &response.mining_params {
+                apply_server_params(state, cursor.params_trust.as_ref(), signed);
+            }
+
+            let schema_version = negotiate_schema(cursor.schema_version, response.schema_version);
+            if schema_version != cursor.schema_version {
+                info!("   Sync schema v{} → v{} (server accepted {:?})",
+                    cursor.schema_version, schema_version, response.schema_version);
+                cursor.schema_version = schema_version;
             }
+
+            // The server already counted settled charges; don't sync them again
+            cursor.last_synced_entropy = current_entropy - settled;
         }
+        Err(e) => {
+            if e.is::<UpgradeRequired>() {
+                report_upgrade_required(cursor);
+            }
+            cursor.backlog.enqueue(entropy_delta, network_quality);
+            cursor.last_synced_entropy = current_entropy;
+            if is_auth_rejection(&e) && note_auth_rejection(state, cursor) {
+                warn!("🔑 Sync refused: the server rejects this device key. {} deltas queued", cursor.backlog.len());
+                return;
+            }
+            warn!("❌ Sync failed: {}. {} deltas queued for the next cycle", e, cursor.backlog.len());
+        }
+    }
+}
+
+/// Settle the pending spends the server confirmed; returns the entropy deducted
+async fn settle_pending(state: &StateManager, charges: &[ServerCharge]) -> i64 {
+    let settled = state.settle_pending_spends(charges).await;
+    if settled > 0 {
+        info!("   Settled {} of pending charges", format_entropy(settled));
+    }
+    i64::try_from(settled).unwrap_or(i64::MAX)
+}
+
+/// Outcome of replaying one queued delta
+enum Replayed {
+    Synced,
+    Failed(anyhow::Error),
+    /// Not sent because replay was halted
+    Skipped,
+}
+
+/// Sync each queued delta on its own, oldest first
+///
+/// At most `replay.concurrency` requests are in flight, and request starts
+/// are at least `replay.spacing` apart. A non-retryable error or an empty
+/// rate-limit bucket stops further sends. Whatever didn't sync goes back to the front of the backlog in its
+/// original order. Returns the error that halted replay, if it was halted.
+async fn replay_backlog(
+    http: &HttpClient,
+    servers: &ServerPool,
+    device_id: &str,
+    identity: &DeviceIdentity,
+    cursor: &SyncCursor,
+    uptime_seconds: u64,
+) -> Option<anyhow::Error> {
+    let pending = cursor.backlog.drain();
+    let total = pending.iter().fold(0i64, |sum, d| sum.saturating_add(d.entropy_delta));
+    info!("🔁 Replaying {} queued deltas (total {})", pending.len(), format_entropy(total));
+
+    let halted = AtomicBool::new(false);
+    let pacer = (!cursor.replay.spacing.is_zero())
+        .then(|| tokio::sync::Mutex::new(time::interval(cursor.replay.spacing)));
+
+    let replays: Vec<_> = pending
+        .iter()
+        .map(|delta| {
+            let (halted, pacer) = (&halted, &pacer);
+            async move {
+                if let Some(pacer) = pacer {
+                    pacer.lock().await.tick().await;
+                }
+                if halted.load(Ordering::SeqCst) {
+                    return Replayed::Skipped;
+                }
+
+                let request = SignedSyncRequest::create_and_sign(
+                    device_id,
+                    delta.entropy_delta,
+                    delta.network_quality,
+                    uptime_seconds,
+                    cursor.schema_version,
+                    identity.signer(),
+                );
+                match sync_to_server(http, servers, request).await {
+                    Ok(_) => Replayed::Synced,
+                    Err(e) => {
+                        if halts_replay(&e) {
+                            halted.store(true, Ordering::SeqCst);
+                        }
+                        Replayed::Failed(e)
+                    }
+                }
+            }
+        })
+        .collect();
+    let outcomes: Vec<Replayed> = stream::iter(replays)
+        .buffered(cursor.replay.concurrency)
+        .collect()
+        .await;
+
+    let mut unsynced = Vec::new();
+    let mut synced_entropy = 0i64;
+    let mut halted_by = None;
+    for (delta, outcome) in pending.into_iter().zip(outcomes) {
+        match outcome {
+            Replayed::Synced => synced_entropy = synced_entropy.saturating_add(delta.entropy_delta),
+            Replayed::Failed(e) => {
+                warn!("   Replay of {} failed: {}", format_entropy(delta.entropy_delta), e);
+                unsynced.push(delta);
+                if halted_by.is_none() && halts_replay(&e) {
+                    halted_by = Some(e);
+                }
+            }
+            Replayed::Skipped => unsynced.push(delta),
+        }
+    }
+
+    info!("🔁 Replayed {} ({} deltas still queued)", format_entropy(synced_entropy), unsynced.len());
+    cursor.backlog.restore(unsynced);
+
+    if halted_by.is_some() {
+        warn!("⏸️  Replay stopped early; resuming next cycle");
+    }
+    halted_by
+}
+
+/// Karma to apply from a sync response
+///
+/// Negative values are clamped to 0 rather than cast, which would wrap to a
+/// huge karma and capacity. The resulting capacity of 0 leaves the balance
+/// uncapped rather than wiping it.
+fn server_karma(reported: i64) -> u64 {
+    u64::try_from(reported).unwrap_or_else(|_| {
+        warn!("⚠️  Server reported negative karma {}; using 0", reported);
+        0
+    })
+}
+
+/// Karma lost to a drop this large is worth telling the user about
+const KARMA_DROP_NOTIFY_RATIO: f64 = 0.5;
+
+fn is_drastic_karma_drop(previous: u64, karma: u64) -> bool {
+    previous > 0 && (karma as f64) < previous as f64 * (1.0 - KARMA_DROP_NOTIFY_RATIO)
+}
+
+/// React to the device being linked to or unlinked from a human account
+///
+/// Returns whether the claim notice was shown.
+fn handle_managed_transition(
+    transition: ManagedTransition,
+    claim_code: Option<&str>,
+    device_id: &str,
+) -> bool {
+    match transition {
+        ManagedTransition::Unchanged => false,
+        ManagedTransition::Linked => {
+            info!("🔗 Device is now managed by a human account; automatic combat paused");
+            false
+        }
+        ManagedTransition::Unlinked => {
+            warn!("🔓 Device is not linked to an account - running autonomously, automatic combat resumes");
+            match claim_code {
+                Some(code) => {
+                    crate::display_unclaimed_device_notice(code, device_id);
+                    true
+                }
+                None => {
+                    warn!("   No claim code provided by server; visit https://sacas.ai/claim");
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// Tell the user loudly; the stored device ID is never replaced automatically
+fn report_registration_conflict(conflict: &DuplicateRegistration, notifier: &dyn Notifier) {
+    error!("🚨 {}", conflict);
+    error!("   Nothing from this response was applied; progress stays under {}", conflict.stored);
+    error!("   Check that config.toml and device.key belong together, then restart the daemon");
+    notifier.notify(
+        "🚨 Device registered twice",
+        &format!("The server knows this device as {}, not {}. Syncing is paused.", conflict.returned, conflict.stored),
+    );
+}
+
+fn is_auth_rejection(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<SyncRejected>()
+        .is_some_and(|r| r.status == reqwest::StatusCode::UNAUTHORIZED)
+}
+
+/// Whether the server refused a sync sent with `schema_version` because of
+/// its schema
+///
+/// Servers that predate negotiation answer an unknown schema with a 400 or
+/// 422 whose body names the schema. The oldest schema has nothing to fall
+/// back to.
+fn is_schema_rejection(error: &anyhow::Error, schema_version: u32) -> bool {
+    schema_version > SYNC_SCHEMA_MIN
+        && error.downcast_ref::<SyncRejected>().is_some_and(|r| {
+            matches!(r.status, reqwest::StatusCode::BAD_REQUEST | reqwest::StatusCode::UNPROCESSABLE_ENTITY)
+                && r.body.to_ascii_lowercase().contains("schema")
+        })
+}
+
+/// Fall back to the oldest sync schema after the server refused the current one
+///
+/// A later response that echoes a newer accepted version moves it back up.
+fn downgrade_schema(cursor: &mut SyncCursor) {
+    warn!("⚠️  Server rejected sync schema v{}; falling back to v{}", cursor.schema_version, SYNC_SCHEMA_MIN);
+    cursor.schema_version = SYNC_SCHEMA_MIN;
+}
+
+/// Count a 401 sync; returns whether the device key now counts as rejected
+///
+/// One 401 can be clock skew or a server hiccup, so the key is only
+/// reported as rejected after `key_rejected_after` in a row. The report is
+/// made once; syncing carries on so a restored key is noticed.
+fn note_auth_rejection(state: &StateManager, cursor: &mut SyncCursor) -> bool {
+    cursor.auth_rejections = cursor.auth_rejections.saturating_add(1);
+    if cursor.key_rejected_after == 0 || cursor.auth_rejections < cursor.key_rejected_after {
+        return false;
+    }
+    if !state.set_key_rejected(true) {
+        error!("🔑 DEVICE KEY REJECTED BY SERVER ({} signed syncs refused in a row)", cursor.auth_rejections);
+        error!("   The key may have been revoked or the device deleted on the server");
+        error!("   Mining continues locally; progress is queued until the key is accepted");
+        error!("   To fix: re-register this device, or rotate its key and update device.key");
+        cursor.notifier.notify(
+            "🔑 Device Key Rejected",
+            "The server refuses this device's signatures. Re-register or rotate the device key.",
+        );
+    }
+    true
+}
+
+/// A sync went through, so the key is accepted again
+fn note_key_accepted(state: &StateManager, cursor: &mut SyncCursor) {
+    cursor.auth_rejections = 0;
+    if state.set_key_rejected(false) {
+        info!("🔑 Server accepts the device key again");
+    }
+}
+
+/// The server refused this daemon's API version; reported once per run
+fn report_upgrade_required(cursor: &mut SyncCursor) {
+    if std::mem::replace(&mut cursor.upgrade_required, true) {
+        return;
+    }
+    error!("⛔ The server no longer supports daemon API version {}; please update the daemon", API_VERSION);
+    if cursor.observe_on_upgrade {
+        warn!("👀 Entering observer mode: mining continues locally, syncing stops until the daemon is updated");
+    }
+    cursor.notifier.notify(
+        "⛔ Daemon Update Required",
+        "The server no longer accepts this daemon version. Please update SACAS Daemon.",
+    );
+}
+
+/// The hardware changed under a running daemon, e.g. a VM snapshot restored elsewhere
+fn report_binding_change(binding: &DeviceBinding, notifier: &dyn Notifier) {
+    let (DeviceBinding::Replaced(mismatches) | DeviceBinding::Tampered(mismatches) | DeviceBinding::Drifted(mismatches)) = binding else {
+        return;
+    };
+    error!("🚨 DEVICE BINDING CHANGED WHILE RUNNING!");
+    for m in mismatches {
+        error!("   {}: expected {}, found {}", m.field, m.expected, m.actual);
+    }
+    error!("   Mining and sync are halted; restart the daemon on the bound device");
+    notifier.notify(
+        "🚨 Hardware changed",
+        "This device no longer matches its binding. Mining and syncing are halted.",
+    );
+}
+
+/// Apply server mining parameters if they verify and are newer than those in effect
+///
+/// Anything else leaves the current parameters (or the local config) in place.
+fn apply_server_params(state: &StateManager, trust: Option<&ServerParamsTrust>, signed: &SignedMiningParams) {
+    let Some(trust) = trust else {
+        debug!("Ignoring server mining parameters: no sync.server_params_key configured");
+        return;
+    };
+
+    match trust.accept(signed, state.server_mining_params()) {
+        Ok(Some(params)) => state.set_server_mining_params(params),
+        Ok(None) => {}
+        Err(e) => warn!("⚠️  Rejected server mining parameters: {}", e),
+    }
+}
+
+/// Send a signed heartbeat so the server sees the device as alive
+async fn send_heartbeat(
+    http: &HttpClient,
+    servers: &ServerPool,
+    heartbeat: SignedHeartbeatRequest,
+) -> Result<()> {
+    http.acquire(EndpointCategory::Sync)?;
+
+    let path = format!("/api/devices/{}/heartbeat", heartbeat.device_id);
+    let response = servers
+        .send(&path, |url| {
+            let mut request_builder = http
+                .post(url)
+                .header("content-type", "application/json")
+                .body(heartbeat.body_string().to_string());
+
+            for (key, value) in heartbeat.headers() {
+                request_builder = request_builder.header(key, value);
+            }
+            request_builder
+        })
+        .await
+        .context("Failed to send heartbeat")?;
+    heartbeat.signing_context().log_if_rejected(response.status());
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = http.read_text(response).await.unwrap_or_default();
+        anyhow::bail!("Heartbeat failed with status {}: {}", status, error_text);
+    }
+
+    Ok(())
+}
+
+/// Upload one sampled latency vector
+async fn send_latency_sample(
+    http: &HttpClient,
+    servers: &ServerPool,
+    sample: SignedLatencySample,
+) -> Result<()> {
+    http.acquire(EndpointCategory::Sync)?;
+
+    let path = format!("/api/devices/{}/latency", sample.device_id);
+    let response = servers
+        .send(&path, |url| {
+            let mut request_builder = http
+                .post(url)
+                .header("content-type", "application/json")
+                .body(sample.body_string().to_string());
+
+            for (key, value) in sample.headers() {
+                request_builder = request_builder.header(key, value);
+            }
+            request_builder
+        })
+        .await
+        .context("Failed to send latency sample")?;
+    sample.signing_context().log_if_rejected(response.status());
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = http.read_text(response).await.unwrap_or_default();
+        anyhow::bail!("Latency sample upload failed with status {}: {}", status, error_text);
     }
+
+    Ok(())
 }
 
-/// Sync device data to server with Ed25519 signature
+/// Sync device data to server, signed by the device's `Signer`
 async fn sync_to_server(
-    server_url: &str,
+    http: &HttpClient,
+    servers: &ServerPool,
     signed_request: SignedSyncRequest,
 ) -> Result<SyncResponse> {
-    let client = reqwest::Client::new();
-    
+    http.acquire(EndpointCategory::Sync)?;
+
     // Build request with signature headers
     // CRITICAL: Use body_string() to send the EXACT JSON used for signing
     // Using .json() would re-serialize and could change format (1.0 -> 1)
-    let mut request_builder = client
-        .post(&format!("{}/api/devices/{}/sync", server_url, signed_request.device_id))
-        .header("content-type", "application/json")
-        .body(signed_request.body_string().to_string());
-
-    // Add signature headers
-    for (key, value) in signed_request.headers() {
-        request_builder = request_builder.header(key, value);
-    }
+    let path = format!("/api/devices/{}/sync", signed_request.device_id);
+    let response = servers
+        .send(&path, |url| {
+            let mut request_builder = http
+                .post(url)
+                .header("content-type", "application/json")
+                .body(signed_request.body_string().to_string());
 
-    let response = request_builder
-        .send()
+            // Add signature headers
+            for (key, value) in signed_request.headers() {
+                request_builder = request_builder.header(key, value);
+            }
+            request_builder
+        })
         .await
         .context("Failed to send signed sync request")?;
+    signed_request.signing_context().log_if_rejected(response.status());
 
     if !response.status().is_success() {
         let status = response.status();
-        let error_text = response.text().await.unwrap_or_default();
-        anyhow::bail!("Sync failed with status {}: {}", status, error_text);
+        let body = http.read_text(response).await.unwrap_or_default();
+        return Err(SyncRejected { status, body }.into());
     }
 
-    let sync_response: SyncResponse = response
-        .json()
+    let sync_response: SyncResponse = http
+        .read_json(response)
         .await
         .context("Failed to parse sync response")?;
 
     Ok(sync_response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{MockServer, RecordedRequest};
+    use crate::types::{Player, SpendReason};
+    use std::sync::atomic::AtomicUsize;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_first_sync_waits_for_warmup() {
+        let config = SyncConfig { interval_secs: 300, first_sync_immediately: true, warmup_secs: 30, ..Default::default() };
+        let start = time::Instant::now();
+        let mut interval = sync_interval(&config, &JitterConfig::NONE);
+
+        assert!(time::timeout(Duration::from_secs(29), interval.tick()).await.is_err());
+        interval.tick().await;
+        assert_eq!(start.elapsed(), Duration::from_secs(30));
+    }
+
+    #[tokio::test]
+    async fn test_immediate_first_sync_not_jittered() {
+        let config = SyncConfig { interval_secs: 300, first_sync_immediately: true, warmup_secs: 0, ..Default::default() };
+        let jitter = JitterConfig { percent: 50.0, every_tick: false };
+        let mut interval = sync_interval(&config, &jitter);
+
+        assert!(time::timeout(Duration::from_secs(1), interval.tick()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_first_sync_deferred_when_disabled() {
+        let config = SyncConfig { interval_secs: 300, first_sync_immediately: false, warmup_secs: 0, ..Default::default() };
+        let mut interval = sync_interval(&config, &JitterConfig::NONE);
+
+        assert!(time::timeout(Duration::from_millis(200), interval.tick()).await.is_err());
+    }
+
+    fn test_identity() -> DeviceIdentity {
+        DeviceIdentity::from_signing_key(ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]))
+    }
+
+    fn test_state(entropy: u64) -> StateManager {
+        let manager = StateManager::in_memory("dev-123".to_string(), 1000);
+        manager.state.try_write().unwrap().player = Player { entropy, ..Player::new("dev-123".to_string(), 1000) };
+        manager
+    }
+
+    #[tokio::test]
+    async fn test_negative_karma_clamped_to_zero() {
+        let server = MockServer::respond_with(200, r#"{"success":true,"device_karma":-5}"#).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut SyncCursor::default(), 60).await;
+
+        let player = state.get_snapshot().await.player;
+        assert_eq!((player.karma, player.capacity), (0, 0));
+        assert_eq!(player.entropy, 500);
+    }
+
+    #[tokio::test]
+    async fn test_pending_charges_settled_by_sync() {
+        // The server charged the first scan but never saw the second
+        let server = MockServer::respond_with(200, r#"{"success":true,"device_entropy":480,
+            "charges":[{"nonce":"n-1","amount":"20"}]}"#).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        state.record_pending_spend(20, SpendReason::Radar, "n-1".to_string()).await;
+        state.record_pending_spend(30, SpendReason::Radar, "n-2".to_string()).await;
+        let mut cursor = SyncCursor::default();
+
+        sync_cycle(&HttpClient::default(), &ServerPool::new(std::slice::from_ref(&server.url)), "dev-123", &state, &identity, &mut cursor, 60).await;
+
+        let snapshot = state.get_snapshot().await;
+        assert_eq!(snapshot.player.entropy, 480);
+        let ledger: Vec<_> = snapshot.ledger.iter().map(|e| (e.amount, e.pending, e.balance)).collect();
+        assert_eq!(ledger, [(20, false, 480), (30, true, 500)]);
+        assert_eq!(cursor.last_synced_entropy, 480);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_schema_falls_back_to_v1() {
+        let server = MockServer::start(|request| match request.body.contains("schema_version") {
+            true => (422, r#"{"error":"unsupported schema_version"}"#.to_string()),
+            false => (200, r#"{"success":true}"#.to_string()),
+        }).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        let mut cursor = SyncCursor::default();
+
+        sync_cycle(&HttpClient::default(), &ServerPool::new(std::slice::from_ref(&server.url)), "dev-123", &state, &identity, &mut cursor, 60).await;
+
+        assert_eq!(cursor.schema_version, SYNC_SCHEMA_MIN);
+        assert_eq!(cursor.last_synced_entropy, 500);
+        assert!(cursor.backlog.is_empty());
+        let sync_bodies: Vec<_> = server.requests().into_iter()
+            .filter(|r| r.path.ends_with("/sync"))
+            .map(|r| r.body)
+            .collect();
+        assert_eq!(sync_bodies.len(), 2);
+        assert!(sync_bodies[0].starts_with(r#"{"schema_version":2,"#));
+        assert!(!sync_bodies[1].contains("schema_version"));
+    }
+
+    #[tokio::test]
+    async fn test_other_bad_requests_keep_schema() {
+        let server = MockServer::respond_with(400, r#"{"error":"delta rejected"}"#).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        let mut cursor = SyncCursor::default();
+
+        sync_cycle(&HttpClient::default(), &ServerPool::new(std::slice::from_ref(&server.url)), "dev-123", &state, &identity, &mut cursor, 60).await;
+
+        assert_eq!(cursor.schema_version, SYNC_SCHEMA_LATEST);
+        assert_eq!(cursor.backlog.pending_entropy(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_upgrade_required_enters_observer_mode() {
+        let server = MockServer::respond_with(426, r#"{"error":"upgrade required"}"#).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+        let mut cursor = SyncCursor { observe_on_upgrade: true, ..Default::default() };
+
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+        assert!(cursor.upgrade_required);
+        assert_eq!(cursor.backlog.pending_entropy(), 500);
+
+        // Observing: later ticks don't contact the server
+        let sent = server.requests().len();
+        state.update_entropy(50).await;
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 120).await;
+        assert_eq!(server.requests().len(), sent);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_401_flags_key_rejected() {
+        let rejecting = Arc::new(AtomicBool::new(true));
+        let reject = rejecting.clone();
+        let server = MockServer::start(move |req| {
+            if req.path.ends_with("/sync") && reject.load(Ordering::SeqCst) {
+                (401, r#"{"error":"invalid signature"}"#.to_string())
+            } else {
+                (200, SYNC_OK.to_string())
+            }
+        })
+        .await;
+        let identity = test_identity();
+        let state = test_state(0);
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+        let mut cursor = SyncCursor { key_rejected_after: 3, ..Default::default() };
+        // Queued deltas are replayed every cycle; keep them under the rate limit
+        let mut http_config = crate::config::HttpConfig::default();
+        http_config.rate_limit.sync.burst = 100;
+        let http = HttpClient::new(&http_config);
+
+        for (cycle, expect_rejected) in [false, false, true, true].into_iter().enumerate() {
+            state.update_entropy(100).await;
+            sync_cycle(&http, &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+            assert_eq!(state.is_key_rejected(), expect_rejected, "after sync {}", cycle + 1);
+        }
+
+        // The key is accepted again once a sync goes through
+        rejecting.store(false, Ordering::SeqCst);
+        state.update_entropy(100).await;
+        sync_cycle(&http, &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+        assert!(!state.is_key_rejected());
+        assert_eq!(cursor.auth_rejections, 0);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_device_id_pauses_sync() {
+        let server = MockServer::respond_with(200, r#"{"success":true,"device_id":"dev-other","device_karma":5}"#).await;
+        let identity = test_identity();
+        let state = test_state(500);
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+        let mut cursor = SyncCursor::default();
+
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+
+        // Nothing from the foreign response is applied, and the delta is kept
+        assert_eq!(state.get_snapshot().await.player.karma, 1000);
+        assert_eq!(cursor.backlog.pending_entropy(), 500);
+        assert_eq!(cursor.registration_conflict.as_ref().unwrap().returned, "dev-other");
+
+        let sent = server.requests().len();
+        state.update_entropy(50).await;
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 120).await;
+        assert_eq!(server.requests().len(), sent);
+    }
+
+    #[test]
+    fn test_drastic_karma_drop() {
+        assert!(is_drastic_karma_drop(1000, 400));
+        assert!(!is_drastic_karma_drop(1000, 600));
+        assert!(!is_drastic_karma_drop(0, 0));
+    }
+
+    #[test]
+    fn test_claim_notice_only_on_unlink() {
+        assert!(handle_managed_transition(ManagedTransition::Unlinked, Some("ABC-123"), "dev-123"));
+        assert!(!handle_managed_transition(ManagedTransition::Linked, Some("ABC-123"), "dev-123"));
+        assert!(!handle_managed_transition(ManagedTransition::Unchanged, Some("ABC-123"), "dev-123"));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_sent_at_zero_delta() {
+        let server = MockServer::respond_with(200, "{}").await;
+        let identity = test_identity();
+        let state = test_state(0);
+        let mut cursor = SyncCursor::default();
+
+        sync_cycle(&HttpClient::default(), &ServerPool::new(std::slice::from_ref(&server.url)), "dev-123", &state, &identity, &mut cursor, 60).await;
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/api/devices/dev-123/heartbeat");
+        assert!(requests[0].body.contains(r#""uptime_seconds":60"#));
+        assert!(requests[0].header("x-signature").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_sync_queues_delta_then_replays() {
+        let down = MockServer::respond_with(503, "maintenance").await;
+        let identity = test_identity();
+        let state = test_state(150);
+        state.state.write().await.player.network_quality = 0.5;
+        let mut cursor = SyncCursor::default();
+        let backlog = cursor.backlog.clone();
+
+        let servers = ServerPool::new(std::slice::from_ref(&down.url));
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+        assert_eq!((backlog.len(), backlog.pending_entropy()), (1, 150));
+
+        let up = MockServer::respond_with(200, r#"{"success":true,"device_entropy":200,"device_karma":1000,"managed":true}"#).await;
+        state.update_entropy(50).await;
+        state.state.write().await.player.network_quality = 0.9;
+        let servers = ServerPool::new(std::slice::from_ref(&up.url));
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 120).await;
+
+        // Each delta carries the quality measured when it was first synced
+        assert!(backlog.is_empty());
+        let requests = up.requests();
+        assert!(requests[1].body.contains(r#""entropy_delta":150,"network_quality":0.5"#));
+        assert!(requests[2].body.contains(r#""entropy_delta":50,"network_quality":0.9"#));
+    }
+
+    #[test]
+    fn test_threshold_uses_larger_of_absolute_and_percent() {
+        let config = SyncConfig { min_sync_delta: 50, min_sync_delta_percent: 1.0, ..Default::default() };
+        let threshold = SyncThreshold::from(&config);
+
+        assert_eq!(threshold.min_delta_for(1_000), 50);
+        assert_eq!(threshold.min_delta_for(10_000), 100);
+        assert_eq!(SyncThreshold::from(&SyncConfig::default()).min_delta_for(10_000), 0);
+    }
+
+    #[test]
+    fn test_small_delta_deferred_until_max_deferral() {
+        let config = SyncConfig { min_sync_delta: 100, max_deferral_secs: 600, ..Default::default() };
+        let threshold = SyncThreshold::from(&config);
+
+        assert!(threshold.should_defer(40, 1_000, Duration::ZERO));
+        assert!(threshold.should_defer(-40, 1_000, Duration::from_secs(599)));
+        assert!(!threshold.should_defer(40, 1_000, Duration::from_secs(600)));
+        assert!(!threshold.should_defer(100, 1_000, Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn test_deferred_delta_accumulates_into_next_sync() {
+        let server = MockServer::respond_with(200, r#"{"success":true}"#).await;
+        let identity = test_identity();
+        let state = test_state(40);
+        let config = SyncConfig { min_sync_delta: 100, max_deferral_secs: 600, ..Default::default() };
+        let mut cursor = SyncCursor { threshold: SyncThreshold::from(&config), ..Default::default() };
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+
+        // Below threshold: only the heartbeat goes out
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 60).await;
+        assert_eq!(server.requests().len(), 1);
+        assert!(cursor.deferred_since.is_some());
+
+        // Crossing the threshold syncs everything held back so far
+        state.update_entropy(70).await;
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 120).await;
+        let requests = server.requests();
+        assert_eq!(delta_of(&requests[2]), 110);
+        assert!(cursor.deferred_since.is_none());
+
+        // Held past the max deferral: synced even though it is still small
+        state.update_entropy(5).await;
+        let now = std::time::Instant::now();
+        cursor.deferred_since = Some(now.checked_sub(Duration::from_secs(600)).unwrap_or_else(|| {
+            // The monotonic clock started under 600s ago; an immediate limit is just as passed
+            cursor.threshold.max_deferral = Duration::ZERO;
+            now
+        }));
+        sync_cycle(&HttpClient::default(), &servers, "dev-123", &state, &identity, &mut cursor, 180).await;
+        assert_eq!(delta_of(&server.requests()[4]), 5);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_aligned_to_server_entropy() {
+        let server = MockServer::respond_with(200, r#"{"success":true,"device_entropy":900}"#).await;
+        let identity = test_identity();
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+
+        // Restored state ahead of the server: only the difference is unsynced
+        let mut cursor = SyncCursor::default();
+        reconcile_cursor(&HttpClient::default(), &servers, "dev-123", &test_state(1000), &identity, &mut cursor).await;
+        assert_eq!(cursor.last_synced_entropy, 900);
+        assert_eq!(delta_of(&server.requests()[0]), 0);
+
+        // Server ahead of local state: never produce a negative delta
+        let mut cursor = SyncCursor::default();
+        reconcile_cursor(&HttpClient::default(), &servers, "dev-123", &test_state(500), &identity, &mut cursor).await;
+        assert_eq!(cursor.last_synced_entropy, 500);
+    }
+
+    #[tokio::test]
+    async fn test_fleet_instances_keep_separate_cursors() {
+        use crate::fleet::FleetInstance;
+
+        let server = MockServer::start(|req| {
+            let entropy = if req.header("x-device-id") == Some("fleet-dev-0") { 300 } else { 700 };
+            (200, format!(r#"{{"success":true,"device_entropy":{}}}"#, entropy))
+        })
+        .await;
+        let base = tempfile::tempdir().unwrap();
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+
+        let mut cursors = Vec::new();
+        for n in 0..2 {
+            let dir = FleetInstance(n).data_dir(base.path());
+            let identity = DeviceIdentity::load_or_generate(&dir.join("device.key")).unwrap();
+            let mut cursor = SyncCursor::default();
+            let device_id = format!("fleet-dev-{}", n);
+            reconcile_cursor(&HttpClient::default(), &servers, &device_id, &test_state(1000), &identity, &mut cursor).await;
+            cursors.push(cursor.last_synced_entropy);
+        }
+
+        assert_eq!(cursors, [300, 700]);
+    }
+
+    #[tokio::test]
+    async fn test_cursor_untouched_when_server_unreachable() {
+        let server = MockServer::respond_with(503, "maintenance").await;
+        let identity = test_identity();
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+
+        let mut cursor = SyncCursor::default();
+        reconcile_cursor(&HttpClient::default(), &servers, "dev-123", &test_state(1000), &identity, &mut cursor).await;
+        assert_eq!(cursor.last_synced_entropy, 0);
+    }
+
+    const SYNC_OK: &str = r#"{"success":true,"device_entropy":0,"device_karma":1000,"managed":true}"#;
+
+    fn delta_of(request: &RecordedRequest) -> i64 {
+        let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+        body["entropy_delta"].as_i64().unwrap()
+    }
+
+    async fn replay(server: &MockServer, deltas: &[i64], concurrency: usize) -> (SyncCursor, bool) {
+        let identity = test_identity();
+        let cursor = SyncCursor {
+            replay: ReplayPacing { concurrency, spacing: Duration::ZERO },
+            ..Default::default()
+        };
+        for &delta in deltas {
+            cursor.backlog.enqueue(delta, 1.0);
+        }
+
+        // Room for every delta; the default sync burst is 5
+        let mut http_config = crate::config::HttpConfig::default();
+        http_config.rate_limit.sync.burst = 100;
+        let servers = ServerPool::new(std::slice::from_ref(&server.url));
+        let halted = replay_backlog(&HttpClient::new(&http_config), &servers, "dev-123", &identity, &cursor, 60)
+            .await
+            .is_some();
+        (cursor, halted)
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn test_replay_respects_concurrency_bound() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let (counter, max) = (in_flight.clone(), peak.clone());
+        let server = MockServer::start(move |_| {
+            let now = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            max.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            counter.fetch_sub(1, Ordering::SeqCst);
+            (200, SYNC_OK.to_string())
+        }).await;
+
+        let (cursor, halted) = replay(&server, &[1, 2, 3, 4, 5, 6], 2).await;
+
+        assert!(!halted);
+        assert!(cursor.backlog.is_empty());
+        assert_eq!(server.requests().len(), 6);
+        assert!((1..=2).contains(&peak.load(Ordering::SeqCst)));
+    }
+
+    #[tokio::test]
+    async fn test_replay_stops_on_non_retryable_error() {
+        let server = MockServer::start(|request| match delta_of(request) {
+            30 => (400, "delta rejected".to_string()),
+            _ => (200, SYNC_OK.to_string()),
+        }).await;
+
+        let (cursor, halted) = replay(&server, &[10, 20, 30, 40, 50], 1).await;
+
+        assert!(halted);
+        let sent: Vec<i64> = server.requests().iter().map(delta_of).collect();
+        assert_eq!(sent, [10, 20, 30]);
+        let left: Vec<i64> = cursor.backlog.drain().iter().map(|d| d.entropy_delta).collect();
+        assert_eq!(left, [30, 40, 50]);
+    }
+
+    #[tokio::test]
+    async fn test_replay_continues_past_retryable_error() {
+        let server = MockServer::start(|request| match delta_of(request) {
+            20 => (429, "slow down".to_string()),
+            _ => (200, SYNC_OK.to_string()),
+        }).await;
+
+        let (cursor, halted) = replay(&server, &[10, 20, 30], 1).await;
+
+        assert!(!halted);
+        let left: Vec<i64> = cursor.backlog.drain().iter().map(|d| d.entropy_delta).collect();
+        assert_eq!(left, [20]);
+    }
+}
@@ -0,0 +1,88 @@
+// sacas-daemon/src/sync/offline.rs
+// Entropy deltas that failed to sync, held until the server is reachable again
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+/// One sync delta the server never acknowledged
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingDelta {
+    pub entropy_delta: i64,
+    /// Network quality measured when the delta was first synced, replayed as-is
+    pub network_quality: f64,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// Backlog of unsynced entropy shared between the sync loop and status RPCs
+#[derive(Debug, Clone, Default)]
+pub struct OfflineQueue {
+    entries: Arc<Mutex<VecDeque<PendingDelta>>>,
+}
+
+impl OfflineQueue {
+    pub fn enqueue(&self, entropy_delta: i64, network_quality: f64) {
+        self.entries.lock().unwrap().push_back(PendingDelta {
+            entropy_delta,
+            network_quality,
+            queued_at: Utc::now(),
+        });
+    }
+
+    /// Number of queued deltas
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Net entropy across all queued deltas
+    pub fn pending_entropy(&self) -> i64 {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .fold(0i64, |sum, d| sum.saturating_add(d.entropy_delta))
+    }
+
+    /// Put deltas taken with `drain` back at the front, keeping their order
+    pub fn restore(&self, deltas: Vec<PendingDelta>) {
+        let mut entries = self.entries.lock().unwrap();
+        for delta in deltas.into_iter().rev() {
+            entries.push_front(delta);
+        }
+    }
+
+    /// Remove and return every queued delta, oldest first
+    pub fn drain(&self) -> Vec<PendingDelta> {
+        self.entries.lock().unwrap().drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_totals_and_drain() {
+        let queue = OfflineQueue::default();
+        queue.enqueue(120, 0.9);
+        queue.clone().enqueue(-10, 1.0);
+
+        assert_eq!(queue.len(), 2);
+        assert_eq!(queue.pending_entropy(), 110);
+
+        let drained = queue.drain();
+        assert_eq!(drained.iter().map(|d| d.entropy_delta).collect::<Vec<_>>(), vec![120, -10]);
+        assert_eq!(drained[0].network_quality, 0.9);
+        assert!(queue.is_empty());
+
+        queue.enqueue(5, 1.0);
+        queue.restore(drained);
+        let order: Vec<_> = queue.drain().iter().map(|d| d.entropy_delta).collect();
+        assert_eq!(order, vec![120, -10, 5]);
+    }
+}
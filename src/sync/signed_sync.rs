@@ -1,9 +1,15 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
-use chrono::Utc;
+use tracing::warn;
 
-use crate::device::DeviceIdentity;
+use crate::device::{Signer, SigningContext, Stamper, SIGNATURE_ALG_HEADER};
+use crate::mining::params::SignedMiningParams;
+use crate::types::{LatencyVector, ServerCharge};
+
+/// Newest sync body schema this daemon can produce
+pub const SYNC_SCHEMA_LATEST: u32 = 2;
+/// Oldest schema still supported (v1 = body without a version field)
+pub const SYNC_SCHEMA_MIN: u32 = 1;
 
 #[derive(Debug, Serialize)]
 pub struct SignedSyncRequest {
@@ -15,7 +21,9 @@ pub struct SignedSyncRequest {
     // Signature fields
     pub timestamp: i64,        // Unix timestamp (seconds)
     pub nonce: String,         // UUID v4
-    pub signature: String,     // Base64 Ed25519 signature
+    pub signature: String,     // Base64 signature, scheme named by `algorithm`
+    #[serde(skip)]
+    pub algorithm: &'static str,
     #[serde(skip)] // Don't serialize this field directly, it's for internal caching
     body_json: String,  // Cached JSON string for signature consistency
 }
@@ -27,27 +35,40 @@ impl SignedSyncRequest {
         entropy_delta: i64,
         network_quality: f64,
         uptime_seconds: u64,
-        identity: &DeviceIdentity,
+        schema_version: u32,
+        signer: &dyn Signer,
     ) -> Self {
-        // Generate nonce (UUID v4)
-        let nonce = Uuid::new_v4().to_string();
-        
-        // Get current Unix timestamp
-        let timestamp = Utc::now().timestamp();
+        Self::create_and_sign_with(
+            device_id,
+            entropy_delta,
+            network_quality,
+            uptime_seconds,
+            schema_version,
+            signer,
+            &Stamper::default(),
+        )
+    }
+
+    /// Create and sign a sync request, taking the timestamp and nonce from `stamper`
+    pub fn create_and_sign_with(
+        device_id: &str,
+        entropy_delta: i64,
+        network_quality: f64,
+        uptime_seconds: u64,
+        schema_version: u32,
+        signer: &dyn Signer,
+        stamper: &Stamper,
+    ) -> Self {
+        let stamp = stamper.stamp();
         
-        // Generate body JSON manually to ensure float formatting consistency
-        // CRITICAL: Must use exact same format for signing and HTTP sending
-        // Using serde_json might normalize floats (1.0 -> 1), breaking signatures
-        let body_json = format!(
-            r#"{{"entropy_delta":{},"network_quality":{},"uptime_seconds":{}}}"#,
+        // CRITICAL: the canonical string is both signed and sent as-is
+        let body_json = SyncBody {
+            schema_version,
             entropy_delta,
-            if network_quality.fract() == 0.0 {
-                format!("{:.1}", network_quality)  // Force .0 for whole numbers
-            } else {
-                network_quality.to_string()
-            },
-            uptime_seconds
-        );
+            network_quality,
+            uptime_seconds,
+        }
+        .to_canonical_string();
         
         // Create request (without signature)
         let mut request = SignedSyncRequest {
@@ -55,9 +76,10 @@ impl SignedSyncRequest {
             entropy_delta,
             network_quality,
             uptime_seconds: uptime_seconds as i64,
-            timestamp,
-            nonce: nonce.clone(),
+            timestamp: stamp.timestamp,
+            nonce: stamp.nonce,
             signature: String::new(), // Will be filled
+            algorithm: signer.algorithm(),
             body_json,  // Use the same JSON string
         };
         
@@ -65,14 +87,14 @@ impl SignedSyncRequest {
         let canonical_message = request.canonical_message();
         
         // Sign the message
-        request.signature = identity.sign_base64(canonical_message.as_bytes());
+        request.signature = signer.sign_base64(canonical_message.as_bytes());
         
         request
     }
     
     /// Create canonical message for signature verification
     /// Format: METHOD|PATH|BODY_JSON|timestamp|nonce
-    fn canonical_message(&self) -> String {
+    pub(crate) fn canonical_message(&self) -> String {
         let canonical = format!(
             "POST|/api/devices/{}/sync|{}|{}|{}",
             self.device_id,
@@ -84,11 +106,17 @@ impl SignedSyncRequest {
         canonical
     }
     
+    /// Signing inputs, logged if the server rejects this request
+    pub fn signing_context(&self) -> SigningContext {
+        device_signing_context(&self.device_id, "sync", self.canonical_message(), self.timestamp, &self.nonce)
+    }
+
     /// Get headers for HTTP request
     pub fn headers(&self) -> Vec<(String, String)> {
         vec![
             ("x-device-id".to_string(), self.device_id.clone()),
             ("x-signature".to_string(), self.signature.clone()),
+            (SIGNATURE_ALG_HEADER.to_string(), self.algorithm.to_string()),
             ("x-timestamp".to_string(), self.timestamp.to_string()),
             ("x-nonce".to_string(), self.nonce.clone()),
         ]
@@ -100,15 +128,282 @@ impl SignedSyncRequest {
     }
 }
 
+/// Signed liveness ping, sent every interval independently of entropy sync
+#[derive(Debug)]
+pub struct SignedHeartbeatRequest {
+    pub device_id: String,
+    pub timestamp: i64,
+    pub nonce: String,
+    pub signature: String,
+    pub algorithm: &'static str,
+    body_json: String,
+}
+
+impl SignedHeartbeatRequest {
+    /// Create and sign a heartbeat request
+    pub fn create_and_sign(
+        device_id: &str,
+        network_quality: f64,
+        uptime_seconds: u64,
+        signer: &dyn Signer,
+    ) -> Self {
+        Self::create_and_sign_with(device_id, network_quality, uptime_seconds, signer, &Stamper::default())
+    }
+
+    /// Create and sign a heartbeat request, taking the timestamp and nonce from `stamper`
+    pub fn create_and_sign_with(
+        device_id: &str,
+        network_quality: f64,
+        uptime_seconds: u64,
+        signer: &dyn Signer,
+        stamper: &Stamper,
+    ) -> Self {
+        let body_json = format!(
+            r#"{{"network_quality":{},"uptime_seconds":{}}}"#,
+            format_quality(network_quality),
+            uptime_seconds
+        );
+
+        let stamp = stamper.stamp();
+        let mut request = SignedHeartbeatRequest {
+            device_id: device_id.to_string(),
+            timestamp: stamp.timestamp,
+            nonce: stamp.nonce,
+            signature: String::new(),
+            algorithm: signer.algorithm(),
+            body_json,
+        };
+
+        request.signature = signer.sign_base64(request.canonical_message().as_bytes());
+        request
+    }
+
+    /// Format: POST|/api/devices/{id}/heartbeat|BODY_JSON|timestamp|nonce
+    fn canonical_message(&self) -> String {
+        format!(
+            "POST|/api/devices/{}/heartbeat|{}|{}|{}",
+            self.device_id,
+            self.body_json,
+            self.timestamp,
+            self.nonce
+        )
+    }
+
+    /// Signing inputs, logged if the server rejects this request
+    pub fn signing_context(&self) -> SigningContext {
+        device_signing_context(&self.device_id, "heartbeat", self.canonical_message(), self.timestamp, &self.nonce)
+    }
+
+    /// Get headers for HTTP request
+    pub fn headers(&self) -> Vec<(String, String)> {
+        vec![
+            ("x-device-id".to_string(), self.device_id.clone()),
+            ("x-signature".to_string(), self.signature.clone()),
+            (SIGNATURE_ALG_HEADER.to_string(), self.algorithm.to_string()),
+            ("x-timestamp".to_string(), self.timestamp.to_string()),
+            ("x-nonce".to_string(), self.nonce.clone()),
+        ]
+    }
+
+    /// Get request body as JSON string (same as used for signing)
+    pub fn body_string(&self) -> &str {
+        &self.body_json
+    }
+}
+
+/// Signed upload of a sampled probe latency vector
+#[derive(Debug)]
+pub struct SignedLatencySample {
+    pub device_id: String,
+    pub timestamp: i64,
+    pub nonce: String,
+    pub signature: String,
+    pub algorithm: &'static str,
+    body_json: String,
+}
+
+impl SignedLatencySample {
+    /// Create and sign an upload of `vector`, which keeps its own probe signature
+    pub fn create_and_sign(device_id: &str, vector: &LatencyVector, signer: &dyn Signer) -> Self {
+        let body_json = serde_json::json!({
+            "measured_at": vector.timestamp.timestamp(),
+            "latencies": vector.data,
+            "vector_signature": vector.signature,
+        })
+        .to_string();
+
+        let stamp = Stamper::default().stamp();
+        let mut request = SignedLatencySample {
+            device_id: device_id.to_string(),
+            timestamp: stamp.timestamp,
+            nonce: stamp.nonce,
+            signature: String::new(),
+            algorithm: signer.algorithm(),
+            body_json,
+        };
+
+        request.signature = signer.sign_base64(request.canonical_message().as_bytes());
+        request
+    }
+
+    /// Format: POST|/api/devices/{id}/latency|BODY_JSON|timestamp|nonce
+    fn canonical_message(&self) -> String {
+        format!(
+            "POST|/api/devices/{}/latency|{}|{}|{}",
+            self.device_id,
+            self.body_json,
+            self.timestamp,
+            self.nonce
+        )
+    }
+
+    /// Signing inputs, logged if the server rejects this request
+    pub fn signing_context(&self) -> SigningContext {
+        device_signing_context(&self.device_id, "latency", self.canonical_message(), self.timestamp, &self.nonce)
+    }
+
+    /// Get headers for HTTP request
+    pub fn headers(&self) -> Vec<(String, String)> {
+        vec![
+            ("x-device-id".to_string(), self.device_id.clone()),
+            ("x-signature".to_string(), self.signature.clone()),
+            (SIGNATURE_ALG_HEADER.to_string(), self.algorithm.to_string()),
+            ("x-timestamp".to_string(), self.timestamp.to_string()),
+            ("x-nonce".to_string(), self.nonce.clone()),
+        ]
+    }
+
+    /// Get request body as JSON string (same as used for signing)
+    pub fn body_string(&self) -> &str {
+        &self.body_json
+    }
+}
+
+/// Signing inputs of a request to `/api/devices/{device_id}/{endpoint}`
+fn device_signing_context(device_id: &str, endpoint: &str, canonical: String, timestamp: i64, nonce: &str) -> SigningContext {
+    SigningContext {
+        path: format!("/api/devices/{}/{}", device_id, endpoint),
+        canonical,
+        timestamp,
+        nonce: nonce.to_string(),
+    }
+}
+
+/// Fields of a signed sync body
+///
+/// The body is signed byte for byte, so it is never built by serde, which
+/// could reorder keys or write 1.0 as 1. `to_canonical_string` is the one
+/// encoding, used for both signing and sending.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncBody {
+    pub schema_version: u32,
+    pub entropy_delta: i64,
+    pub network_quality: f64,
+    pub uptime_seconds: u64,
+}
+
+impl SyncBody {
+    /// Compact JSON object with no whitespace, keys in this order:
+    ///
+    /// 1. `schema_version`, only from v2 on
+    /// 2. `entropy_delta`, a signed integer
+    /// 3. `network_quality`, see `format_quality`
+    /// 4. `uptime_seconds`, an unsigned integer
+    pub fn to_canonical_string(&self) -> String {
+        let mut fields = Vec::with_capacity(4);
+        if self.schema_version >= 2 {
+            fields.push(format!(r#""schema_version":{}"#, self.schema_version));
+        }
+        fields.push(format!(r#""entropy_delta":{}"#, self.entropy_delta));
+        fields.push(format!(r#""network_quality":{}"#, format_quality(self.network_quality)));
+        fields.push(format!(r#""uptime_seconds":{}"#, self.uptime_seconds));
+
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Pick the schema for the next sync from the version the server accepted
+///
+/// A server that doesn't echo a version predates negotiation, so fall back
+/// to v1; an echoed version outside our range is ignored.
+pub fn negotiate_schema(current: u32, accepted: Option<u32>) -> u32 {
+    match accepted {
+        Some(v) if (SYNC_SCHEMA_MIN..=SYNC_SCHEMA_LATEST).contains(&v) => v,
+        Some(_) => current,
+        None => SYNC_SCHEMA_MIN,
+    }
+}
+
+/// Format network quality as the shortest decimal that reads back the same,
+/// with whole numbers keeping their ".0" (1.0, not 1)
+///
+/// JSON has no NaN or infinity, so those are written as 0.0.
+fn format_quality(network_quality: f64) -> String {
+    if !network_quality.is_finite() {
+        "0.0".to_string()
+    } else if network_quality.fract() == 0.0 {
+        format!("{:.1}", network_quality)
+    } else {
+        network_quality.to_string()
+    }
+}
+
+/// Server reply to a sync
+///
+/// Only `success` is required. Every other field is optional: a missing or
+/// oddly shaped value is logged and read as `None`, so an API change can't
+/// turn an accepted sync into a failure.
 #[derive(Debug, Deserialize)]
 pub struct SyncResponse {
     pub success: bool,
-    pub device_entropy: i64,
-    pub device_karma: i64,
-    pub managed: bool,
-    
-    #[serde(default)]
+    /// Device the server credited the sync to
+    #[serde(default, deserialize_with = "lenient")]
+    pub device_id: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub device_entropy: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub device_karma: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub managed: Option<bool>,
+
+    #[serde(default, deserialize_with = "lenient")]
     pub warning: Option<AnomalyWarning>,
+
+    /// Present while the device is unclaimed
+    #[serde(default, deserialize_with = "lenient")]
+    pub claim_code: Option<String>,
+
+    /// Sync body schema the server accepted (absent on pre-negotiation servers)
+    #[serde(default, deserialize_with = "lenient")]
+    pub schema_version: Option<u32>,
+
+    /// Centrally set mining parameters, signed by the server
+    #[serde(default, deserialize_with = "lenient")]
+    pub mining_params: Option<SignedMiningParams>,
+
+    /// Recent charges for this device's signed requests, by request nonce
+    #[serde(default, deserialize_with = "lenient")]
+    pub charges: Option<Vec<ServerCharge>>,
+}
+
+/// Read an optional field, turning a value of the wrong shape into `None`
+fn lenient<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+
+    match serde_json::from_value(value.clone()) {
+        Ok(parsed) => Ok(Some(parsed)),
+        Err(e) => {
+            warn!("⚠️  Ignoring sync response field with unexpected shape {}: {}", value, e);
+            Ok(None)
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -134,7 +429,8 @@ mod tests {
             1000,
             0.95,
             3600,
-            &identity,
+            SYNC_SCHEMA_LATEST,
+            identity.signer(),
         );
         
         assert_eq!(request.device_id, "test-device-123");
@@ -146,18 +442,181 @@ mod tests {
     
     #[test]
     fn test_canonical_message_format() {
-        let request = SignedSyncRequest {
-            device_id: "dev-123".to_string(),
-            entropy_delta: 500,
-            network_quality: 1.0,
-            uptime_seconds: 60,
-            timestamp: 1738576800,
-            nonce: "nonce-123".to_string(),
-            signature: String::new(),
-        };
+        let stamper = Stamper::fixed(1738576800, "nonce-123");
+        let request = SignedSyncRequest::create_and_sign_with("dev-123", 500, 1.0, 60, 2, &FakeSigner, &stamper);
         
         let canonical = request.canonical_message();
         assert!(canonical.contains("POST|/api/devices/dev-123/sync"));
         assert!(canonical.contains("|1738576800|nonce-123"));
     }
+
+    fn canonical(schema_version: u32, entropy_delta: i64, network_quality: f64, uptime_seconds: u64) -> String {
+        SyncBody { schema_version, entropy_delta, network_quality, uptime_seconds }.to_canonical_string()
+    }
+
+    #[test]
+    fn test_sync_body_per_schema() {
+        assert_eq!(
+            canonical(1, 500, 1.0, 60),
+            r#"{"entropy_delta":500,"network_quality":1.0,"uptime_seconds":60}"#
+        );
+        assert_eq!(
+            canonical(2, 500, 0.95, 60),
+            r#"{"schema_version":2,"entropy_delta":500,"network_quality":0.95,"uptime_seconds":60}"#
+        );
+    }
+
+    #[test]
+    fn test_sync_body_number_formatting() {
+        assert_eq!(
+            canonical(2, -120, 0.0, 0),
+            r#"{"schema_version":2,"entropy_delta":-120,"network_quality":0.0,"uptime_seconds":0}"#
+        );
+        assert_eq!(
+            canonical(2, 0, 2.0, 86400),
+            r#"{"schema_version":2,"entropy_delta":0,"network_quality":2.0,"uptime_seconds":86400}"#
+        );
+        assert_eq!(
+            canonical(2, i64::MAX, 0.1 + 0.2, u64::MAX),
+            r#"{"schema_version":2,"entropy_delta":9223372036854775807,"network_quality":0.30000000000000004,"uptime_seconds":18446744073709551615}"#
+        );
+        assert_eq!(
+            canonical(1, 7, f64::NAN, 1),
+            r#"{"entropy_delta":7,"network_quality":0.0,"uptime_seconds":1}"#
+        );
+    }
+
+    #[test]
+    fn test_schema_negotiation() {
+        assert_eq!(negotiate_schema(2, Some(2)), 2);
+        assert_eq!(negotiate_schema(2, Some(1)), 1);
+        assert_eq!(negotiate_schema(1, Some(2)), 2);
+        assert_eq!(negotiate_schema(2, Some(9)), 2);
+        assert_eq!(negotiate_schema(2, None), SYNC_SCHEMA_MIN);
+    }
+
+    #[test]
+    fn test_sync_response_tolerates_missing_fields() {
+        let response: SyncResponse = serde_json::from_str(r#"{"success":true}"#).unwrap();
+        assert!(response.success);
+        assert_eq!(response.device_entropy, None);
+        assert_eq!(response.device_karma, None);
+        assert_eq!(response.managed, None);
+
+        let response: SyncResponse = serde_json::from_str(r#"{"success":true,"device_karma":"1200",
+            "managed":"yes","device_entropy":450,"warning":{"confidence":"high"},"renamed_field":1}"#).unwrap();
+        assert_eq!(response.device_entropy, Some(450));
+        assert_eq!(response.device_karma, None);
+        assert_eq!(response.managed, None);
+        assert!(response.warning.is_none());
+    }
+
+    #[test]
+    fn test_sync_response_requires_success() {
+        assert!(serde_json::from_str::<SyncResponse>(r#"{"device_karma":1000,"managed":true}"#).is_err());
+        assert!(serde_json::from_str::<SyncResponse>(r#"{"success":"true"}"#).is_err());
+    }
+
+    #[test]
+    fn test_sync_response_carries_mining_params() {
+        let response: SyncResponse = serde_json::from_str(
+            r#"{"success":true,"mining_params":{"tick_interval_secs":10,"base_multiplier":0.75,"issued_at":100,"signature":"c2ln"}}"#,
+        )
+        .unwrap();
+        let signed = response.mining_params.unwrap();
+        assert_eq!(signed.params.tick_interval_secs, 10);
+        assert_eq!(signed.signature, "c2ln");
+
+        // A malformed block is dropped rather than failing the sync
+        let response: SyncResponse = serde_json::from_str(r#"{"success":true,"mining_params":{"tick_interval_secs":"fast"}}"#).unwrap();
+        assert!(response.mining_params.is_none());
+    }
+
+    #[test]
+    fn test_heartbeat_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = DeviceIdentity::load_or_generate(&dir.path().join("device.key")).unwrap();
+
+        let request = SignedHeartbeatRequest::create_and_sign("dev-123", 1.0, 120, identity.signer());
+
+        assert_eq!(request.body_string(), r#"{"network_quality":1.0,"uptime_seconds":120}"#);
+
+        let canonical = request.canonical_message();
+        assert!(canonical.starts_with("POST|/api/devices/dev-123/heartbeat|{"));
+
+        use ed25519_dalek::{Signature, Verifier};
+        let sig_bytes = base64::decode(&request.signature).unwrap();
+        let signature = Signature::from_slice(&sig_bytes).unwrap();
+        assert!(identity.verifying_key.verify(canonical.as_bytes(), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_latency_sample_request() {
+        let dir = tempfile::tempdir().unwrap();
+        let identity = DeviceIdentity::load_or_generate(&dir.path().join("device.key")).unwrap();
+        let vector = LatencyVector {
+            timestamp: chrono::DateTime::from_timestamp(1738576800, 0).unwrap(),
+            data: vec![12.5, 40.0],
+            signature: "probe-sig".to_string(),
+        };
+
+        let request = SignedLatencySample::create_and_sign("dev-123", &vector, identity.signer());
+        assert_eq!(
+            request.body_string(),
+            r#"{"latencies":[12.5,40.0],"measured_at":1738576800,"vector_signature":"probe-sig"}"#
+        );
+
+        use ed25519_dalek::{Signature, Verifier};
+        let signature = Signature::from_slice(&base64::decode(&request.signature).unwrap()).unwrap();
+        assert!(identity.verifying_key.verify(request.canonical_message().as_bytes(), &signature).is_ok());
+    }
+
+    /// Signs with fixed bytes under a made-up scheme name
+    struct FakeSigner;
+
+    impl Signer for FakeSigner {
+        fn algorithm(&self) -> &'static str {
+            "fake-alg"
+        }
+
+        fn sign(&self, _message: &[u8]) -> Vec<u8> {
+            vec![1, 2, 3]
+        }
+    }
+
+    #[test]
+    fn test_signing_dispatches_through_signer() {
+        let request = SignedHeartbeatRequest::create_and_sign("dev-123", 1.0, 120, &FakeSigner);
+
+        assert_eq!(request.signature, base64::encode([1, 2, 3]));
+        assert!(request.headers().contains(&("x-signature-alg".to_string(), "fake-alg".to_string())));
+    }
+
+    fn fixed_identity() -> DeviceIdentity {
+        let signing_key = ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]);
+        DeviceIdentity::from_signing_key(signing_key)
+    }
+
+    #[test]
+    fn test_pinned_sync_signature() {
+        let stamper = Stamper::fixed(1738576800, "00000000-0000-4000-8000-000000000001");
+        let request = SignedSyncRequest::create_and_sign_with(
+            "dev-123", 500, 0.95, 3600, 2, fixed_identity().signer(), &stamper,
+        );
+
+        assert_eq!(
+            request.canonical_message(),
+            r#"POST|/api/devices/dev-123/sync|{"schema_version":2,"entropy_delta":500,"network_quality":0.95,"uptime_seconds":3600}|1738576800|00000000-0000-4000-8000-000000000001"#
+        );
+        assert_eq!(request.signature, "02PqfyS/rDYnbpYlVsTr4t75r5m6Rbh0pXvKlIqE5WgfKcp5kJUzgGqxPL6EXlAAYmu8D40ceKWw1EefNcwABw==");
+    }
+
+    #[test]
+    fn test_pinned_heartbeat_signature() {
+        let stamper = Stamper::fixed(1738576800, "00000000-0000-4000-8000-000000000002");
+        let request = SignedHeartbeatRequest::create_and_sign_with("dev-123", 1.0, 120, fixed_identity().signer(), &stamper);
+
+        assert_eq!(request.timestamp, 1738576800);
+        assert_eq!(request.signature, "3jiC6RnEVh2mGJLTRg2x1xRTLpT/TsvKbkn0o7WOyFEbDOuM1pGOquP3k8OTfjW/v0z8DajZ9SmwNky91tRXCQ==");
+    }
 }
@@ -0,0 +1,63 @@
+// sacas-daemon/src/sync/uptime.rs
+// The uptime_seconds reported in signed syncs and heartbeats
+
+use std::time::{Duration, Instant};
+
+/// Uptime of this daemon process, capped at a sane maximum
+///
+/// Uptime is per-process: it starts at zero when the sync loop starts and
+/// is not carried across restarts, so a restored state or a clock change
+/// can't inflate it. It comes from a monotonic clock, so it never goes
+/// backwards within a process, and stops while the machine sleeps: a laptop
+/// waking from a night's sleep reports the time it was awake, not the
+/// wall-clock time since start. Past `max` it stays at `max`, rather than
+/// growing to values the server's anomaly checks reject.
+#[derive(Debug, Clone, Copy)]
+pub struct UptimeClock {
+    started: Instant,
+    max: Duration,
+}
+
+impl UptimeClock {
+    /// Start counting now, capped at `max_secs`
+    pub fn start(max_secs: u64) -> Self {
+        Self::started_at(Instant::now(), max_secs)
+    }
+
+    fn started_at(started: Instant, max_secs: u64) -> Self {
+        // SignedSyncRequest keeps an i64 copy; stay where that cast can't wrap
+        let max_secs = max_secs.min(i64::MAX as u64);
+        Self { started, max: Duration::from_secs(max_secs) }
+    }
+
+    /// Whole seconds since start, at most the configured maximum
+    pub fn seconds(&self) -> u64 {
+        self.seconds_at(Instant::now())
+    }
+
+    fn seconds_at(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.started).min(self.max).as_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uptime_monotonic_within_process() {
+        let clock = UptimeClock::start(3600);
+        let readings: Vec<u64> = (0..100).map(|_| clock.seconds()).collect();
+        assert!(readings.windows(2).all(|w| w[0] <= w[1]));
+        assert!(readings[0] < 5);
+    }
+
+    #[test]
+    fn test_uptime_clamped_to_max() {
+        let started = Instant::now();
+        let later = started + Duration::from_secs(120);
+        assert_eq!(UptimeClock::started_at(started, 60).seconds_at(later), 60);
+        assert_eq!(UptimeClock::started_at(started, 3600).seconds_at(later), 120);
+        assert_eq!(UptimeClock::started_at(started, u64::MAX).max.as_secs(), i64::MAX as u64);
+    }
+}
@@ -0,0 +1,193 @@
+// sacas-daemon/src/test_support.rs
+// Test helpers: a minimal HTTP server, signed clients and a sample config
+
+use std::sync::{Arc, Mutex};
+use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::combat::CombatClient;
+use crate::config::{Config, DeviceConfig, MiningConfig, NetworkConfig};
+use crate::device::Ed25519Signer;
+use crate::http::{HttpClient, ServerPool};
+use crate::state::StateManager;
+
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+type Handler = dyn Fn(&RecordedRequest) -> (u16, String) + Send + Sync;
+
+/// Serves one canned response per request and records what it received
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockServer {
+    pub async fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&RecordedRequest) -> (u16, String) + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let recorded = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let recorded = recorded.clone();
+                let handler = handler.clone();
+                tokio::spawn(async move {
+                    let _ = serve_one(stream, &recorded, &*handler).await;
+                });
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Start a server that answers every request with the same response
+    pub async fn respond_with(status: u16, body: &str) -> Self {
+        let body = body.to_string();
+        Self::start(move |_| (status, body.clone())).await
+    }
+
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+async fn serve_one(
+    mut stream: TcpStream,
+    recorded: &Mutex<Vec<RecordedRequest>>,
+    handler: &Handler,
+) -> Option<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buf.len() < header_end + content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+
+    let body = String::from_utf8_lossy(&buf[header_end..]).to_string();
+    let request = RecordedRequest { method, path, headers, body };
+    let (status, response_body) = handler(&request);
+    recorded.lock().unwrap().push(request);
+
+    let response = format!(
+        "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        response_body.len(),
+        response_body
+    );
+    stream.write_all(response.as_bytes()).await.ok()?;
+    stream.shutdown().await.ok()
+}
+
+/// The fixed device key tests sign with
+pub fn test_signer() -> Ed25519Signer {
+    Ed25519Signer::new(ed25519_dalek::SigningKey::from_bytes(&[7u8; 32]))
+}
+
+/// Combat client for device "dev" talking to `url`
+pub fn combat_client(url: &str, state: StateManager) -> CombatClient {
+    CombatClient::new(HttpClient::default(), ServerPool::new(&[url.to_string()]), "dev".to_string(), test_signer(), state)
+}
+
+/// A complete config with defaults suitable for tests
+pub fn sample_config() -> Config {
+    Config {
+        device_id: Some("dev-123".to_string()),
+        display_name: None,
+        karma: 100,
+        server_url: "http://localhost".to_string().into(),
+        grpc_port: 50051,
+        moltbook: None,
+        device: DeviceConfig {
+            hardware_uuid: "A1B2C3D4-0000-1111-2222-333344445555".to_string(),
+            serial_number: "C02XK1ZZJGH5".to_string(),
+            model_identifier: "Mac14,3".to_string(),
+            device_fingerprint: "fingerprint".to_string(),
+            is_verified: true,
+            first_seen: Utc::now(),
+        },
+        network: NetworkConfig { probe_interval_secs: 60, probe_backoff_max_secs: 600, first_probe_immediately: true, anchors: vec![], telemetry: Default::default(), empty_anchors: Default::default() },
+        mining: MiningConfig {
+            tick_interval_secs: 1,
+            base_multiplier: 0.5,
+            yield_strategy: "sqrt".to_string(),
+            decay_grace_secs: 60,
+            capacity_formula: Default::default(),
+            watchdog_ticks: 5,
+            warmup_timeout_secs: 60,
+            restart_stalled: true,
+        },
+        http: Default::default(),
+        persistence: Default::default(),
+        grpc: Default::default(),
+        sync: Default::default(),
+        tls: Default::default(),
+        budget: Default::default(),
+        combat: Default::default(),
+        shutdown: Default::default(),
+        websocket: Default::default(),
+        enforcement: Default::default(),
+        notifications: Default::default(),
+        karma_gates: Default::default(),
+        endpoints: Default::default(),
+        capacity_alerts: Default::default(),
+        entropy_floor: Default::default(),
+        jitter: Default::default(),
+        history: Default::default(),
+        panic: Default::default(),
+        subsystems: Default::default(),
+        metrics: Default::default(),
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::fmt;
+
+use crate::history::BoundedHistory;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameState {
@@ -7,6 +10,87 @@ pub struct GameState {
     pub visible_nodes: Vec<Node>,
     pub parasites: Vec<Parasite>,
     pub climate: Climate,
+    /// Whether the device is linked to a human account (None until first sync)
+    #[serde(default)]
+    pub managed: Option<bool>,
+    /// Last Moltbook profile fetched by the karma sync
+    #[serde(default)]
+    pub moltbook_profile: Option<crate::moltbook::client::MoltbookAgent>,
+    /// Anomaly warnings returned by recent syncs
+    #[serde(default)]
+    pub anomalies: AnomalyHistory,
+    /// Recent explicit entropy spends, oldest first
+    #[serde(default)]
+    pub ledger: BoundedHistory<LedgerEntry>,
+}
+
+/// Default cap on ledger entries; older ones are dropped first
+pub const LEDGER_LIMIT: usize = 1000;
+
+/// What entropy was spent on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpendReason {
+    Defense,
+    Radar,
+    Combat,
+    /// Manual spend through the admin RPC, with the operator's note
+    Admin(String),
+}
+
+impl fmt::Display for SpendReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Defense => write!(f, "defense"),
+            Self::Radar => write!(f, "radar"),
+            Self::Combat => write!(f, "combat"),
+            Self::Admin(note) => write!(f, "admin: {}", note),
+        }
+    }
+}
+
+/// One entropy spend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    pub at: DateTime<Utc>,
+    pub amount: u64,
+    pub reason: SpendReason,
+    /// Entropy left after the spend
+    pub balance: u64,
+    /// Sent, but whether the server charged it is unknown; nothing has been
+    /// deducted yet and the next sync settles it
+    #[serde(default)]
+    pub pending: bool,
+    /// Nonce of the signed request behind a pending spend; the server
+    /// confirms charges by it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+}
+
+/// A charge the server confirms it made for one of this device's requests
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServerCharge {
+    /// Nonce of the signed request that was charged
+    pub nonce: String,
+    pub amount: EntropyAmount,
+}
+
+/// Default cap on anomaly warnings; older ones are dropped first
+pub const ANOMALY_HISTORY_LIMIT: usize = 100;
+
+/// One anomaly warning from a sync response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyRecord {
+    pub at: DateTime<Utc>,
+    pub confidence: f64,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AnomalyHistory {
+    /// Oldest first, within `history.anomalies` retention
+    pub records: BoundedHistory<AnomalyRecord>,
+    /// Syncs in a row that came back with a warning
+    pub consecutive: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +104,9 @@ pub struct Player {
     pub network_quality: f64,
     pub passive_income: f64,
     pub last_update: DateTime<Utc>,
+    /// When entropy last rose above capacity (None while at or below it)
+    #[serde(default)]
+    pub over_capacity_since: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -46,7 +133,7 @@ pub struct Node {
     pub estimated_defense: Option<DefenseArray>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Parasite {
     pub node_id: String,
     pub tax_rate: f64,
@@ -55,6 +142,25 @@ pub struct Parasite {
     pub established_at: DateTime<Utc>,
 }
 
+/// Share of a host's output routed to its parasite
+pub const PARASITE_TAX_RATE: f64 = 0.3;
+
+impl Parasite {
+    /// A parasite this device just established on `node_id`
+    ///
+    /// Rates the server didn't report fall back to the standard tax and no
+    /// yield; parasite reconciliation fills in the real values.
+    pub fn established(node_id: &str, tax_rate: Option<f64>, yield_per_tick: Option<f64>) -> Self {
+        Self {
+            node_id: node_id.to_string(),
+            tax_rate: tax_rate.unwrap_or(PARASITE_TAX_RATE),
+            yield_per_tick: yield_per_tick.unwrap_or(0.0),
+            total_collected: 0,
+            established_at: Utc::now(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Climate {
     pub code: String,
@@ -63,6 +169,41 @@ pub struct Climate {
     pub start_time: DateTime<Utc>,
 }
 
+/// Typed view of the climate's `modifiers` JSON
+///
+/// Missing or non-numeric multipliers read as 1.0. Other numeric entries are
+/// kept by name so newer server modifiers still reach the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClimateModifiers {
+    pub yield_multiplier: f64,
+    pub attack_multiplier: f64,
+    pub defense_multiplier: f64,
+    pub other: std::collections::BTreeMap<String, f64>,
+}
+
+impl Climate {
+    pub fn typed_modifiers(&self) -> ClimateModifiers {
+        const KNOWN: [&str; 3] = ["yield_multiplier", "attack_multiplier", "defense_multiplier"];
+        let multiplier = |name: &str| self.modifiers.get(name).and_then(serde_json::Value::as_f64).unwrap_or(1.0);
+
+        let other = self
+            .modifiers
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(name, _)| !KNOWN.contains(&name.as_str()))
+            .filter_map(|(name, value)| Some((name.clone(), value.as_f64()?)))
+            .collect();
+
+        ClimateModifiers {
+            yield_multiplier: multiplier("yield_multiplier"),
+            attack_multiplier: multiplier("attack_multiplier"),
+            defense_multiplier: multiplier("defense_multiplier"),
+            other,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LatencyVector {
     pub timestamp: DateTime<Utc>,
@@ -90,9 +231,37 @@ pub struct BattleResult {
     pub passive_yield: f64,
 }
 
+/// How karma converts to entropy capacity
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum CapacityFormula {
+    /// `karma × per_karma`
+    Linear { per_karma: u64 },
+    /// `scale × √karma`: diminishing returns
+    Sqrt { scale: f64 },
+    /// `scale × ln(1 + karma)`: strongly diminishing returns
+    Log { scale: f64 },
+}
+
+impl Default for CapacityFormula {
+    fn default() -> Self {
+        Self::Linear { per_karma: 100 }
+    }
+}
+
+/// Entropy capacity for `karma` under `formula`
+pub fn capacity_from_karma(karma: u64, formula: CapacityFormula) -> u64 {
+    match formula {
+        CapacityFormula::Linear { per_karma } => karma.saturating_mul(per_karma),
+        // Float-to-int `as` saturates, and maps NaN or negatives to 0
+        CapacityFormula::Sqrt { scale } => (scale * (karma as f64).sqrt()).round() as u64,
+        CapacityFormula::Log { scale } => (scale * (karma as f64).ln_1p()).round() as u64,
+    }
+}
+
 impl Player {
     pub fn new(id: String, karma: u64) -> Self {
-        let capacity = karma * 100;
+        let capacity = capacity_from_karma(karma, CapacityFormula::default());
         Self {
             id,
             karma,
@@ -112,15 +281,266 @@ impl Player {
             network_quality: 1.0,
             passive_income: 0.0,
             last_update: Utc::now(),
+            over_capacity_since: None,
         }
     }
 
+    /// Base yield per tick using the default formula and multiplier
     pub fn calculate_yield(&self) -> u64 {
-        let base = (self.karma as f64).sqrt();
-        (base * self.network_quality * 0.5) as u64
+        use crate::mining::yield_strategy::{SqrtYield, YieldStrategy};
+        SqrtYield.calculate(self.karma, self.network_quality, 0.5)
     }
 
     pub fn calculate_inertia_seconds(&self) -> u64 {
         ((self.karma as f64).ln() * 600.0) as u64
     }
 }
+
+/// Symbol used wherever entropy is shown to a human
+pub const ENTROPY_SYMBOL: &str = "Ω";
+
+/// Human-readable entropy for logs and UI, e.g. `950 Ω`, `1.2k Ω`, `3.4M Ω`
+///
+/// Values are rounded to one decimal and the trailing `.0` is dropped.
+/// API responses keep carrying the raw integer.
+pub fn format_entropy(value: impl Into<i128>) -> String {
+    const SUFFIXES: [&str; 5] = ["", "k", "M", "G", "T"];
+
+    let value = value.into();
+    let sign = if value < 0 { "-" } else { "" };
+    let magnitude = value.unsigned_abs();
+
+    if magnitude < 1000 {
+        return format!("{}{} {}", sign, magnitude, ENTROPY_SYMBOL);
+    }
+
+    let mut scaled = magnitude as f64;
+    let mut unit = 0;
+    while unit + 1 < SUFFIXES.len() && (scaled * 10.0).round() / 10.0 >= 1000.0 {
+        scaled /= 1000.0;
+        unit += 1;
+    }
+
+    let rounded = format!("{:.1}", scaled);
+    let rounded = rounded.strip_suffix(".0").unwrap_or(&rounded);
+    format!("{}{}{} {}", sign, rounded, SUFFIXES[unit], ENTROPY_SYMBOL)
+}
+
+/// Why an entropy amount string was rejected
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum EntropyParseError {
+    #[error("Entropy amount is empty")]
+    Empty,
+    #[error("Malformed entropy amount {0:?}")]
+    Malformed(String),
+    #[error("Entropy amount {0:?} is too large")]
+    Overflow(String),
+}
+
+/// Non-negative entropy as the server writes it in battle and loot fields
+///
+/// Accepts plain integers (`"1200"`), decimals (`"12.5"`) and the suffixes
+/// used by `format_entropy` (`"1.5k"`, `"3M"`, `"2G"`, `"1T"`). Fractions of
+/// a unit are rounded down. Serializes back as a plain integer string.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntropyAmount(pub u64);
+
+impl EntropyAmount {
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl std::str::FromStr for EntropyAmount {
+    type Err = EntropyParseError;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let s = raw.trim();
+        if s.is_empty() {
+            return Err(EntropyParseError::Empty);
+        }
+        let malformed = || EntropyParseError::Malformed(raw.to_string());
+        let overflow = || EntropyParseError::Overflow(raw.to_string());
+
+        let (number, multiplier) = match s.char_indices().last() {
+            Some((i, c)) if c.is_ascii_alphabetic() => {
+                let multiplier: u128 = match c.to_ascii_lowercase() {
+                    'k' => 1_000,
+                    'm' => 1_000_000,
+                    'g' => 1_000_000_000,
+                    't' => 1_000_000_000_000,
+                    _ => return Err(malformed()),
+                };
+                (&s[..i], multiplier)
+            }
+            _ => (s, 1),
+        };
+
+        let (whole, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let digits_only = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if whole.is_empty() || !digits_only(whole) || !digits_only(fraction) || number.ends_with('.') {
+            return Err(malformed());
+        }
+
+        let whole: u128 = whole.parse().map_err(|_| overflow())?;
+        // Digits past the 18th can't change the rounded-down result
+        let fraction = &fraction[..fraction.len().min(18)];
+        let fraction_value = match fraction {
+            "" => 0,
+            f => f.parse::<u128>().map_err(|_| malformed())? * multiplier / 10u128.pow(f.len() as u32),
+        };
+
+        whole
+            .checked_mul(multiplier)
+            .and_then(|v| v.checked_add(fraction_value))
+            .and_then(|v| u64::try_from(v).ok())
+            .map(Self)
+            .ok_or_else(overflow)
+    }
+}
+
+impl fmt::Display for EntropyAmount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl From<u64> for EntropyAmount {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+
+impl Serialize for EntropyAmount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EntropyAmount {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(u64),
+            Text(String),
+        }
+
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(n) => Ok(Self(n)),
+            Raw::Text(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Characters kept by `short_id`
+pub const SHORT_ID_CHARS: usize = 8;
+
+/// Leading characters of an id for logs and notifications
+///
+/// Never panics: ids shorter than `SHORT_ID_CHARS` come back whole and
+/// multibyte characters are never split.
+pub fn short_id(id: &str) -> &str {
+    match id.char_indices().nth(SHORT_ID_CHARS) {
+        Some((end, _)) => &id[..end],
+        None => id,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_id_truncation() {
+        assert_eq!(short_id("3f2a9c1e-77b0-4d2e"), "3f2a9c1e");
+        assert_eq!(short_id("3f2a9c1e"), "3f2a9c1e");
+        assert_eq!(short_id("abc"), "abc");
+        assert_eq!(short_id(""), "");
+        // 'é' and 'Ω' are two bytes each; slicing at byte 8 would panic
+        assert_eq!(short_id("aéΩbcdefgh"), "aéΩbcdef");
+    }
+
+    #[test]
+    fn test_entropy_amount_parses_server_forms() {
+        let parse = |s: &str| s.parse::<EntropyAmount>().map(EntropyAmount::value);
+        assert_eq!(parse("1200"), Ok(1200));
+        assert_eq!(parse(" 0 "), Ok(0));
+        assert_eq!(parse("12.9"), Ok(12));
+        assert_eq!(parse("1.5k"), Ok(1500));
+        assert_eq!(parse("3M"), Ok(3_000_000));
+        assert_eq!(parse("2.25G"), Ok(2_250_000_000));
+        assert_eq!(parse("18446744073709551615"), Ok(u64::MAX));
+        assert_eq!(parse("18446744073.709551615G"), Ok(u64::MAX));
+        assert_eq!(EntropyAmount(1500).to_string(), "1500");
+    }
+
+    #[test]
+    fn test_entropy_amount_rejects_bad_input() {
+        let parse = |s: &str| s.parse::<EntropyAmount>();
+        assert_eq!(parse(""), Err(EntropyParseError::Empty));
+        for bad in ["-5", "abc", "1.", ".5", "1.2.3", "12x", "k", "1 000", "NaN"] {
+            assert_eq!(parse(bad), Err(EntropyParseError::Malformed(bad.to_string())), "{bad}");
+        }
+        for huge in ["18446744073709551616", "99999999999999999999999999999999999999999", "20000000T"] {
+            assert_eq!(parse(huge), Err(EntropyParseError::Overflow(huge.to_string())), "{huge}");
+        }
+    }
+
+    #[test]
+    fn test_entropy_amount_deserializes_string_or_number() {
+        let amounts: Vec<EntropyAmount> = serde_json::from_str(r#"["42", 7, "1k"]"#).unwrap();
+        assert_eq!(amounts, [EntropyAmount(42), EntropyAmount(7), EntropyAmount(1000)]);
+        assert!(serde_json::from_str::<EntropyAmount>(r#""lots""#).is_err());
+        assert_eq!(serde_json::to_string(&EntropyAmount(42)).unwrap(), r#""42""#);
+    }
+
+    #[test]
+    fn test_typed_climate_modifiers() {
+        let climate = Climate {
+            code: "STORM".to_string(),
+            description: "Storm".to_string(),
+            modifiers: serde_json::json!({"yield_multiplier": 0.5, "attack_multiplier": "high", "loot_bonus": 1.2, "note": "x"}),
+            start_time: Utc::now(),
+        };
+
+        let modifiers = climate.typed_modifiers();
+        assert_eq!(modifiers.yield_multiplier, 0.5);
+        assert_eq!(modifiers.attack_multiplier, 1.0);
+        assert_eq!(modifiers.defense_multiplier, 1.0);
+        assert_eq!(modifiers.other.into_iter().collect::<Vec<_>>(), [("loot_bonus".to_string(), 1.2)]);
+    }
+
+    #[test]
+    fn test_capacity_formulas() {
+        assert_eq!(capacity_from_karma(0, CapacityFormula::default()), 0);
+        assert_eq!(capacity_from_karma(250, CapacityFormula::default()), 25_000);
+        assert_eq!(capacity_from_karma(u64::MAX, CapacityFormula::default()), u64::MAX);
+
+        let sqrt = CapacityFormula::Sqrt { scale: 1000.0 };
+        assert_eq!(capacity_from_karma(100, sqrt), 10_000);
+        assert_eq!(capacity_from_karma(400, sqrt), 20_000);
+
+        let log = CapacityFormula::Log { scale: 10_000.0 };
+        assert_eq!(capacity_from_karma(0, log), 0);
+        assert_eq!(capacity_from_karma(1000, log), 69_088);
+    }
+
+    #[test]
+    fn test_capacity_formula_from_config() {
+        let formula: CapacityFormula = toml::from_str("kind = \"Sqrt\"\nscale = 500.0").unwrap();
+        assert_eq!(formula, CapacityFormula::Sqrt { scale: 500.0 });
+    }
+
+    #[test]
+    fn test_format_entropy_magnitudes() {
+        assert_eq!(format_entropy(0u64), "0 Ω");
+        assert_eq!(format_entropy(950u64), "950 Ω");
+        assert_eq!(format_entropy(1_200u64), "1.2k Ω");
+        assert_eq!(format_entropy(15_000u64), "15k Ω");
+        assert_eq!(format_entropy(1_234_567u64), "1.2M Ω");
+        assert_eq!(format_entropy(999_999u64), "1M Ω");
+        assert_eq!(format_entropy(-2_500i64), "-2.5k Ω");
+        assert_eq!(format_entropy(u64::MAX), "18446744.1T Ω");
+    }
+}
@@ -0,0 +1,94 @@
+// sacas-daemon/src/wake.rs
+// Timers that survive system sleep: no catch-up bursts, and wake detection
+
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use tokio::time::{self, Interval, MissedTickBehavior};
+
+/// Wall-clock time beyond monotonic time that counts as a sleep
+pub const WAKE_GAP: Duration = Duration::from_secs(60);
+
+/// `tokio::time::interval`, minus the burst of missed ticks after a stall
+///
+/// The default interval fires every tick it missed back to back, so a
+/// laptop waking from a long sleep would mine or sync dozens of times at
+/// once. This one fires a single late tick and then returns to schedule.
+pub fn interval(period: Duration) -> Interval {
+    interval_at(time::Instant::now(), period)
+}
+
+/// `tokio::time::interval_at` with the same missed-tick handling as [`interval`]
+pub fn interval_at(start: time::Instant, period: Duration) -> Interval {
+    let mut ticker = time::interval_at(start, period);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    ticker
+}
+
+/// Notices when the machine slept between two checks
+///
+/// While macOS sleeps the monotonic clock behind `Instant` stops but the
+/// wall clock keeps going, so a wall-clock gap well past the monotonic one
+/// means the daemon was asleep for the difference.
+#[derive(Debug, Clone)]
+pub struct WakeDetector {
+    mono: Instant,
+    wall: DateTime<Utc>,
+}
+
+impl Default for WakeDetector {
+    fn default() -> Self {
+        Self { mono: Instant::now(), wall: Utc::now() }
+    }
+}
+
+impl WakeDetector {
+    /// How long the machine slept since the last check, if it did
+    pub fn check(&mut self) -> Option<Duration> {
+        self.check_at(Instant::now(), Utc::now())
+    }
+
+    fn check_at(&mut self, mono: Instant, wall: DateTime<Utc>) -> Option<Duration> {
+        let mono_elapsed = mono.saturating_duration_since(self.mono);
+        // A wall clock stepped backwards reads as no time passed
+        let wall_elapsed = (wall - self.wall).to_std().unwrap_or_default();
+        self.mono = mono;
+        self.wall = wall;
+
+        let asleep = wall_elapsed.saturating_sub(mono_elapsed);
+        (asleep >= WAKE_GAP).then_some(asleep)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::FutureExt;
+
+    #[test]
+    fn test_wall_clock_jump_detected_as_sleep() {
+        let mut detector = WakeDetector::default();
+        let (mono, wall) = (detector.mono, detector.wall);
+
+        let tick = Duration::from_secs(10);
+        assert_eq!(detector.check_at(mono + tick, wall + tick), None);
+
+        // Ten seconds of monotonic time, two hours of wall time
+        let asleep = detector.check_at(mono + tick * 2, wall + tick * 2 + chrono::Duration::hours(2));
+        assert_eq!(asleep, Some(Duration::from_secs(7200)));
+
+        // NTP nudges and a clock stepped back are not sleeps
+        assert_eq!(detector.check_at(mono + tick * 3, wall + tick * 3 + chrono::Duration::hours(2) + Duration::from_secs(5)), None);
+        assert_eq!(detector.check_at(mono + tick * 4, wall), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_missed_ticks_not_burst() {
+        let mut ticker = interval(Duration::from_secs(10));
+        ticker.tick().await;
+
+        time::advance(Duration::from_secs(3600)).await;
+        assert!(ticker.tick().now_or_never().is_some());
+        assert!(ticker.tick().now_or_never().is_none());
+    }
+}
@@ -2,12 +2,43 @@
 // WebSocket client with Ed25519 authentication
 
 use anyhow::{anyhow, Context, Result};
-use ed25519_dalek::{Signer, SigningKey};
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
+use sha2::{Digest, Sha256};
+use std::collections::{HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Notify};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{
+    connect_async_tls_with_config, tungstenite::Message, Connector, MaybeTlsStream, WebSocketStream,
+};
+
+use crate::build_info::API_VERSION;
+use crate::config::{TlsConfig, WebSocketConfig};
+use crate::device::{Signer, Stamp, Stamper};
+use crate::notifier::{notifier_for, NotificationBackend, Notifier};
+use crate::state::StateManager;
+use crate::types::{format_entropy, short_id, EntropyAmount, Parasite};
 use tracing::{debug, error, info, warn};
 
+/// Delay between reconnect attempts
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+/// The server accepted the connection but never answered AUTH
+#[derive(Debug, thiserror::Error)]
+#[error("No authentication response within {}s", .0.as_secs_f64())]
+pub struct AuthTimeout(pub Duration);
+
+/// Live connection state, observable through `WebSocketClient::subscribe_state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    Connecting,
+    Authenticated,
+    Disconnected,
+}
+
 #[derive(Debug, Serialize)]
 struct AuthMessage {
     r#type: String,
@@ -15,6 +46,92 @@ struct AuthMessage {
     timestamp: i64,
     nonce: String,
     signature: String,
+    /// Same value as the `X-Signature-Alg` header on HTTP requests
+    algorithm: &'static str,
+    /// Same value as the `X-API-Version` header on HTTP requests
+    api_version: &'static str,
+    /// Last event sequence received, so the server can replay what we missed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_seq: Option<u64>,
+}
+
+/// Sequence number carried by replayable server events
+#[derive(Debug, Deserialize)]
+struct EventEnvelope {
+    #[serde(default)]
+    seq: Option<u64>,
+}
+
+/// Tracks the newest event sequence seen across reconnects
+#[derive(Debug, Default)]
+struct EventCursor {
+    last_seq: Option<u64>,
+}
+
+impl EventCursor {
+    /// Record an event sequence, returning how many events were skipped
+    fn observe(&mut self, seq: u64) -> u64 {
+        let missed = match self.last_seq {
+            Some(last) if seq > last + 1 => seq - last - 1,
+            _ => 0,
+        };
+
+        if self.last_seq.is_none_or(|last| seq > last) {
+            self.last_seq = Some(seq);
+        }
+
+        missed
+    }
+}
+
+impl ServerMessage {
+    /// Dedup key for battle events; the same battle may arrive both as a
+    /// device event and as an epic-battle broadcast
+    fn battle_key(&self) -> Option<String> {
+        match self {
+            Self::BattleResult { data, .. } => Some(format!("battle_result:{}", data.battle_id)),
+            Self::BattleAttacked { data, .. } => Some(format!("battle_attacked:{}", data.battle_id)),
+            Self::EpicBattle { data, .. } => Some(format!("epic_battle:{}", data.battle_id)),
+            _ => None,
+        }
+    }
+}
+
+/// Battle ids already handled, bounded to the most recent `capacity`
+#[derive(Debug)]
+struct SeenBattles {
+    capacity: usize,
+    order: VecDeque<String>,
+    ids: HashSet<String>,
+}
+
+impl SeenBattles {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            ids: HashSet::new(),
+        }
+    }
+
+    /// Record `battle_id`, returning false if it is still in the window
+    fn first_sighting(&mut self, battle_id: &str) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        if self.ids.contains(battle_id) {
+            return false;
+        }
+
+        if self.order.len() == self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.ids.remove(&oldest);
+            }
+        }
+        self.order.push_back(battle_id.to_string());
+        self.ids.insert(battle_id.to_string());
+        true
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -25,6 +142,9 @@ enum ServerMessage {
         device_id: String,
         subscriptions: Vec<String>,
         server_time: i64,
+        /// Sequence the server resumes after (absent if it doesn't replay)
+        #[serde(default)]
+        resume_seq: Option<u64>,
     },
     #[serde(rename = "PING")]
     Ping { timestamp: i64 },
@@ -59,7 +179,16 @@ enum ServerMessage {
 struct BattleResultData {
     battle_id: String,
     outcome: String,
-    entropy_looted: String,
+    entropy_looted: EntropyAmount,
+    #[serde(default)]
+    defender_id: Option<String>,
+    #[serde(default)]
+    parasitized: bool,
+    /// Terms of a parasite established by this battle, when the server sends them
+    #[serde(default)]
+    tax_rate: Option<f64>,
+    #[serde(default)]
+    yield_per_tick: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -67,7 +196,7 @@ struct BattleAttackedData {
     battle_id: String,
     attacker_id: String,
     outcome: String,
-    entropy_lost: String,
+    entropy_lost: EntropyAmount,
     parasitized: bool,
 }
 
@@ -77,68 +206,186 @@ struct EpicBattleData {
     attacker_id: String,
     defender_id: String,
     outcome: String,
-    entropy_looted: String,
+    entropy_looted: EntropyAmount,
+    #[serde(default)]
+    parasitized: bool,
+}
+
+/// Whether a battle left the attacker with a parasite on the defender
+fn is_parasitized(flag: bool, outcome: &str) -> bool {
+    flag || outcome.eq_ignore_ascii_case("PARASITIZED")
 }
 
 pub struct WebSocketClient {
     server_url: String,
+    /// Path the WebSocket is mounted at, signed into the AUTH message
+    ws_path: String,
     device_id: String,
-    signing_key: SigningKey,
+    signer: Box<dyn Signer>,
+    state_tx: watch::Sender<ConnectionState>,
+    tls: TlsConfig,
+    cursor: Mutex<EventCursor>,
+    seen_battles: Mutex<SeenBattles>,
+    notifier: Arc<dyn Notifier>,
+    has_authenticated: AtomicBool,
+    sync_trigger: Arc<Notify>,
+    stamper: Stamper,
+    /// Limit on the wait for AUTH_SUCCESS, separate from any post-auth idle handling
+    auth_timeout: Duration,
+    /// Where parasites won in battle are recorded; without it they are only logged
+    state: Option<Arc<StateManager>>,
 }
 
 impl WebSocketClient {
-    pub fn new(server_url: String, device_id: String, private_key_base64: &str) -> Result<Self> {
-        // Decode private key
-        let private_key_bytes = base64::decode(private_key_base64)
-            .context("Failed to decode private key")?;
-        
-        let signing_key = SigningKey::from_bytes(
-            private_key_bytes.as_slice().try_into()
-                .map_err(|_| anyhow!("Invalid private key length"))?
-        );
+    pub fn new(server_url: String, device_id: String, signer: impl Signer + 'static) -> Self {
+        let (state_tx, _) = watch::channel(ConnectionState::Disconnected);
 
-        Ok(Self {
+        Self {
             server_url,
+            ws_path: WebSocketConfig::default().path,
             device_id,
-            signing_key,
-        })
+            signer: Box::new(signer),
+            state_tx,
+            tls: TlsConfig::default(),
+            cursor: Mutex::new(EventCursor::default()),
+            seen_battles: Mutex::new(SeenBattles::new(WebSocketConfig::default().battle_dedup_window)),
+            notifier: notifier_for(NotificationBackend::default()),
+            has_authenticated: AtomicBool::new(false),
+            sync_trigger: Arc::new(Notify::new()),
+            stamper: Stamper::default(),
+            auth_timeout: Duration::from_secs(WebSocketConfig::default().auth_timeout_secs),
+            state: None,
+        }
+    }
+
+    /// Connect to the WebSocket mounted at `path` instead of `/ws`
+    pub fn with_path(mut self, path: String) -> Self {
+        self.ws_path = path;
+        self
     }
 
-    /// Create Ed25519 signature for WebSocket authentication
-    fn create_auth_signature(&self) -> Result<(i64, String, String)> {
-        let timestamp = chrono::Utc::now().timestamp();
-        let nonce = uuid::Uuid::new_v4().to_string();
+    /// Notify `trigger` after every reconnect so entropy can be reconciled
+    pub fn with_sync_trigger(mut self, trigger: Arc<Notify>) -> Self {
+        self.sync_trigger = trigger;
+        self
+    }
+
+    /// How many recent battle ids to remember when dropping replayed events
+    pub fn with_battle_dedup_window(mut self, capacity: usize) -> Self {
+        self.seen_battles = Mutex::new(SeenBattles::new(capacity));
+        self
+    }
+
+    /// Where battle notifications are shown
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Record parasites from won battles in `state`, so passive income tracks them
+    pub fn with_state(mut self, state: Arc<StateManager>) -> Self {
+        self.state = Some(state);
+        self
+    }
 
-        // Canonical message: WS|/ws|AUTH|timestamp|nonce
-        let canonical = format!("WS|/ws|AUTH|{}|{}", timestamp, nonce);
+    /// Give up on a handshake with no auth response after `timeout`
+    pub fn with_auth_timeout(mut self, timeout: Duration) -> Self {
+        self.auth_timeout = timeout;
+        self
+    }
+
+    /// TLS requirements and certificate pin for the connection
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Subscribe to connection state changes
+    pub fn subscribe_state(&self) -> watch::Receiver<ConnectionState> {
+        self.state_tx.subscribe()
+    }
+
+    fn set_state(&self, state: ConnectionState) {
+        self.state_tx.send_replace(state);
+    }
+
+    /// Keep the connection alive, reconnecting after every disconnect
+    pub async fn run(&self) {
+        loop {
+            if let Err(e) = self.connect_and_listen().await {
+                error!("❌ WebSocket session failed: {}", e);
+            }
+
+            info!("🔁 Reconnecting WebSocket in {}s", RECONNECT_DELAY.as_secs());
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// Whether `msg` is a battle event already handled within the dedup window
+    fn is_replayed(&self, msg: &ServerMessage) -> bool {
+        msg.battle_key()
+            .is_some_and(|key| !self.seen_battles.lock().unwrap().first_sighting(&key))
+    }
+
+    /// Sign the AUTH message with the device signer
+    ///
+    /// `path` must be the path of the URL actually connected to.
+    fn create_auth_signature(&self, path: &str) -> Result<(i64, String, String)> {
+        let Stamp { timestamp, nonce } = self.stamper.stamp();
+
+        let canonical = auth_canonical_message(path, timestamp, &nonce);
         
         debug!("📝 Canonical message: {}", canonical);
 
-        // Sign with Ed25519
-        let signature = self.signing_key.sign(canonical.as_bytes());
-        let signature_base64 = base64::encode(signature.to_bytes());
+        let signature = self.signer.sign_base64(canonical.as_bytes());
 
-        Ok((timestamp, nonce, signature_base64))
+        Ok((timestamp, nonce, signature))
     }
 
     /// Connect and authenticate to WebSocket server
     pub async fn connect_and_listen(&self) -> Result<()> {
-        let ws_url = self.server_url.replace("https://", "wss://").replace("http://", "ws://");
-        let full_url = format!("{}/ws", ws_url);
+        self.set_state(ConnectionState::Connecting);
+        let result = self.session().await;
+        self.set_state(ConnectionState::Disconnected);
+        result
+    }
+
+    /// Run a single connect/auth/listen session
+    async fn session(&self) -> Result<()> {
+        let full_url = websocket_url(&self.server_url, &self.ws_path, &self.tls)?;
+        let signed_path = reqwest::Url::parse(&full_url)
+            .with_context(|| format!("Invalid WebSocket URL: {}", full_url))?
+            .path()
+            .to_string();
 
         info!("📡 Connecting to WebSocket: {}", full_url);
 
-        // Connect
-        let (ws_stream, _) = connect_async(&full_url)
-            .await
-            .context("Failed to connect to WebSocket server")?;
+        // Connect with an explicit TLS connector (TLS 1.2+, system trust store)
+        let connector = native_tls::TlsConnector::builder()
+            .min_protocol_version(Some(native_tls::Protocol::Tlsv12))
+            .build()
+            .context("Failed to build TLS connector")?;
+
+        let (ws_stream, _) = connect_async_tls_with_config(
+            &full_url,
+            None,
+            false,
+            Some(Connector::NativeTls(connector)),
+        )
+        .await
+        .context("Failed to connect to WebSocket server")?;
+
+        // Check the pin before anything signed goes over the wire
+        if let Some(pin) = &self.tls.server_cert_sha256 {
+            verify_cert_pin(&ws_stream, pin)?;
+        }
 
         info!("✅ WebSocket connected");
 
         let (mut write, mut read) = ws_stream.split();
 
         // Create and send authentication message
-        let (timestamp, nonce, signature) = self.create_auth_signature()?;
+        let (timestamp, nonce, signature) = self.create_auth_signature(&signed_path)?;
         
         let auth_msg = AuthMessage {
             r#type: "AUTH".to_string(),
@@ -146,6 +393,9 @@ impl WebSocketClient {
             timestamp,
             nonce,
             signature,
+            algorithm: self.signer.algorithm(),
+            api_version: API_VERSION,
+            last_seq: self.cursor.lock().unwrap().last_seq,
         };
 
         let auth_json = serde_json::to_string(&auth_msg)?;
@@ -153,18 +403,65 @@ impl WebSocketClient {
 
         info!("🔐 Sent authentication");
 
-        // Wait for auth response
-        let mut authenticated = false;
+        // A silent server would otherwise hold the handshake open forever
+        match tokio::time::timeout(self.auth_timeout, self.await_auth(&mut read)).await {
+            Err(_) => {
+                warn!("⏱️  No auth response within {}s", self.auth_timeout.as_secs());
+                return Err(AuthTimeout(self.auth_timeout).into());
+            }
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok(())) => {}
+        }
+
+        self.set_state(ConnectionState::Authenticated);
+
+        // Events may have been missed while we were away; reconcile entropy now
+        if self.has_authenticated.swap(true, Ordering::AcqRel) {
+            info!("🔄 Reconnected - requesting immediate entropy sync");
+            self.sync_trigger.notify_one();
+        }
+
+        // Listen for events
+        info!("👂 Listening for events...");
 
+        while let Some(msg) = read.next().await {
+            match msg {
+                Ok(Message::Text(text)) => {
+                    self.handle_message(&text).await;
+                }
+                Ok(Message::Ping(_)) => {
+                    // Auto-handled by tungstenite
+                }
+                Ok(Message::Close(frame)) => {
+                    warn!("📴 Connection closed: {:?}", frame);
+                    break;
+                }
+                Err(e) => {
+                    error!("❌ WebSocket error: {}", e);
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        info!("WebSocket connection ended");
+        Ok(())
+    }
+
+    /// Read messages until AUTH_SUCCESS arrives
+    async fn await_auth<S>(&self, read: &mut S) -> Result<()>
+    where
+        S: futures_util::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
         while let Some(msg) = read.next().await {
             match msg {
                 Ok(Message::Text(text)) => {
                     match serde_json::from_str::<ServerMessage>(&text) {
-                        Ok(ServerMessage::AuthSuccess { device_id, subscriptions, .. }) => {
+                        Ok(ServerMessage::AuthSuccess { device_id, subscriptions, resume_seq, .. }) => {
                             info!("✅ Authenticated as: {}", device_id);
                             info!("📢 Auto-subscribed to: {:?}", subscriptions);
-                            authenticated = true;
-                            break;
+                            self.check_resume(resume_seq);
+                            return Ok(());
                         }
                         Ok(_) => {
                             warn!("Unexpected message before auth: {}", text);
@@ -187,69 +484,98 @@ impl WebSocketClient {
             }
         }
 
-        if !authenticated {
-            return Err(anyhow!("Authentication timeout"));
-        }
+        Err(anyhow!("Connection closed before authentication"))
+    }
 
-        // Listen for events
-        info!("👂 Listening for events...");
+    /// Warn if the server can't resume exactly where we left off
+    fn check_resume(&self, resume_seq: Option<u64>) {
+        let Some(last_seq) = self.cursor.lock().unwrap().last_seq else {
+            return;
+        };
 
-        while let Some(msg) = read.next().await {
-            match msg {
-                Ok(Message::Text(text)) => {
-                    self.handle_message(&text).await;
-                }
-                Ok(Message::Ping(_)) => {
-                    // Auto-handled by tungstenite
-                }
-                Ok(Message::Close(frame)) => {
-                    warn!("📴 Connection closed: {:?}", frame);
-                    break;
-                }
-                Err(e) => {
-                    error!("❌ WebSocket error: {}", e);
-                    break;
-                }
-                _ => {}
+        match resume_seq {
+            Some(resume) if resume == last_seq => {
+                debug!("📼 Server resuming events after seq {}", resume);
+            }
+            Some(resume) => {
+                warn!("⚠️  Event gap: last received seq {}, server resumes after {}", last_seq, resume);
+            }
+            None => {
+                warn!("⚠️  Server did not confirm replay after seq {}; events may have been missed", last_seq);
             }
         }
+    }
 
-        info!("WebSocket connection ended");
-        Ok(())
+    /// Add a parasite won in battle to state; a host already recorded is left alone
+    async fn record_parasite(&self, parasite: Parasite) {
+        let Some(state) = &self.state else {
+            return;
+        };
+        let host = short_id(&parasite.node_id).to_string();
+        if state.add_parasite(parasite).await {
+            let income = state.get_snapshot().await.player.passive_income;
+            info!("🦠 Parasite established on {}; passive income now {:.2}/tick", host, income);
+        }
     }
 
     /// Handle incoming WebSocket messages
     async fn handle_message(&self, text: &str) {
+        if let Ok(EventEnvelope { seq: Some(seq) }) = serde_json::from_str(text) {
+            let missed = self.cursor.lock().unwrap().observe(seq);
+            if missed > 0 {
+                warn!("⚠️  Event gap: {} event(s) missed before seq {}", missed, seq);
+            }
+        }
+
         match serde_json::from_str::<ServerMessage>(text) {
+            Ok(msg) if self.is_replayed(&msg) => {
+                debug!("🔁 Ignoring replayed {:?} event", msg.battle_key());
+            }
             Ok(msg) => {
                 match msg {
                     ServerMessage::BattleResult { data, .. } => {
                         info!("⚔️  BATTLE RESULT: {} - Looted: {} entropy",
                             data.outcome, data.entropy_looted);
                         
-                        // macOS notification support (future feature)
-                        self.show_notification(
+                        self.notifier.notify(
                             "Battle Result",
-                            &format!("You {} and looted {} entropy!", 
-                                data.outcome.to_lowercase(), data.entropy_looted)
+                            &format!("You {} and looted {}!", 
+                                data.outcome.to_lowercase(), format_entropy(data.entropy_looted.value()))
                         );
+
+                        if is_parasitized(data.parasitized, &data.outcome) {
+                            match &data.defender_id {
+                                Some(host) => {
+                                    self.record_parasite(Parasite::established(host, data.tax_rate, data.yield_per_tick)).await;
+                                }
+                                None => warn!("⚠️  Battle {} parasitized a node but named no defender", data.battle_id),
+                            }
+                        }
                     }
                     
                     ServerMessage::BattleAttacked { data, .. } => {
                         warn!("🚨 UNDER ATTACK by {}! Lost: {} entropy (Parasitized: {})",
                             data.attacker_id, data.entropy_lost, data.parasitized);
                         
-                        // macOS notification support (future feature)
-                        self.show_notification(
+                        self.notifier.notify(
                             "⚠️ Under Attack!",
-                            &format!("Attacker: {}\nLost: {} entropy\nResult: {}",
-                                &data.attacker_id[..8], data.entropy_lost, data.outcome)
+                            &format!("Attacker: {}\nLost: {}\nResult: {}",
+                                short_id(&data.attacker_id), format_entropy(data.entropy_lost.value()), data.outcome)
                         );
                     }
                     
                     ServerMessage::EpicBattle { data, .. } => {
                         info!("🏆 EPIC BATTLE: {} vs {} - {} entropy looted!",
-                            &data.attacker_id[..8], &data.defender_id[..8], data.entropy_looted);
+                            short_id(&data.attacker_id), short_id(&data.defender_id), data.entropy_looted);
+
+                        // The broadcast may be the only word of our own win
+                        if data.attacker_id == self.device_id && is_parasitized(data.parasitized, &data.outcome) {
+                            if data.defender_id.is_empty() {
+                                warn!("⚠️  Battle {} parasitized a node but named no defender", data.battle_id);
+                            } else {
+                                self.record_parasite(Parasite::established(&data.defender_id, None, None)).await;
+                            }
+                        }
                     }
                     
                     ServerMessage::Ping { .. } => {
@@ -275,26 +601,358 @@ impl WebSocketClient {
             }
         }
     }
+}
 
-    /// Show macOS notification
-    #[cfg(target_os = "macos")]
-    fn show_notification(&self, title: &str, body: &str) {
-        use std::process::Command;
-        
-        let script = format!(
-            r#"display notification "{}" with title "SACAS Daemon" subtitle "{}""#,
-            body.replace('"', r#"\""#),
-            title.replace('"', r#"\""#)
-        );
-        
-        let _ = Command::new("osascript")
-            .arg("-e")
-            .arg(&script)
-            .spawn();
+/// Canonical AUTH message: `WS|<path>|AUTH|<timestamp>|<nonce>`
+fn auth_canonical_message(path: &str, timestamp: i64, nonce: &str) -> String {
+    format!("WS|{}|AUTH|{}|{}", path, timestamp, nonce)
+}
+
+/// Derive the WebSocket endpoint from the HTTP server URL and the mount path
+///
+/// `https` maps to `wss` and `http` to `ws`; plaintext is refused when TLS
+/// is required. A path prefix on the server URL is kept.
+fn websocket_url(server_url: &str, path: &str, tls: &TlsConfig) -> Result<String> {
+    let base = server_url.trim_end_matches('/');
+    let ws_url = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else if base.starts_with("wss://") || base.starts_with("ws://") {
+        base.to_string()
+    } else {
+        return Err(anyhow!("Unsupported server URL scheme: {}", server_url));
+    };
+
+    if tls.require_tls && ws_url.starts_with("ws://") {
+        return Err(anyhow!(
+            "Refusing plaintext WebSocket connection to {} (set tls.require_tls = false for local development)",
+            ws_url
+        ));
+    }
+
+    Ok(format!("{}/{}", ws_url, path.trim_start_matches('/')))
+}
+
+/// Reject the connection unless the server certificate matches the pin
+fn verify_cert_pin(stream: &WebSocketStream<MaybeTlsStream<TcpStream>>, pin: &str) -> Result<()> {
+    let der = match stream.get_ref() {
+        MaybeTlsStream::NativeTls(tls) => tls
+            .get_ref()
+            .peer_certificate()
+            .context("Failed to read server certificate")?
+            .ok_or_else(|| anyhow!("Server presented no certificate"))?
+            .to_der()
+            .context("Failed to encode server certificate")?,
+        _ => return Err(anyhow!("Certificate pin configured but connection is not TLS")),
+    };
+
+    if !cert_matches_pin(&der, pin) {
+        return Err(anyhow!("Server certificate does not match the configured pin"));
+    }
+
+    Ok(())
+}
+
+/// Compare a DER certificate against a hex SHA-256 pin (colons optional)
+fn cert_matches_pin(der: &[u8], pin: &str) -> bool {
+    let expected: String = pin.chars().filter(|c| *c != ':').collect();
+    hex::encode(Sha256::digest(der)).eq_ignore_ascii_case(&expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::test_signer;
+    use ed25519_dalek::SigningKey;
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    fn plaintext() -> TlsConfig {
+        TlsConfig { require_tls: false, server_cert_sha256: None }
+    }
+
+    #[test]
+    fn test_websocket_url_scheme_rewrite() {
+        let tls = plaintext();
+        assert_eq!(websocket_url("https://sacas.ai", "/ws", &tls).unwrap(), "wss://sacas.ai/ws");
+        assert_eq!(websocket_url("https://sacas.ai/", "/ws", &tls).unwrap(), "wss://sacas.ai/ws");
+        assert_eq!(websocket_url("http://localhost:3000", "/ws", &tls).unwrap(), "ws://localhost:3000/ws");
+        assert_eq!(websocket_url("wss://sacas.ai", "/ws", &tls).unwrap(), "wss://sacas.ai/ws");
+        assert!(websocket_url("ftp://sacas.ai", "/ws", &tls).is_err());
+    }
+
+    #[test]
+    fn test_custom_path_is_signed() {
+        let tls = plaintext();
+        let url = websocket_url("https://sacas.ai/game/", "/realtime", &tls).unwrap();
+        assert_eq!(url, "wss://sacas.ai/game/realtime");
+
+        let client = WebSocketClient::new("https://sacas.ai/game".to_string(), "dev".to_string(), test_signer())
+            .with_path("/realtime".to_string());
+        let path = reqwest::Url::parse(&url).unwrap().path().to_string();
+        let (timestamp, nonce, signature) = client.create_auth_signature(&path).unwrap();
+
+        let canonical = format!("WS|/game/realtime|AUTH|{}|{}", timestamp, nonce);
+        assert_eq!(auth_canonical_message(&path, timestamp, &nonce), canonical);
+        let signature = ed25519_dalek::Signature::from_slice(&base64::decode(signature).unwrap()).unwrap();
+        assert!(SigningKey::from_bytes(&[7u8; 32]).verifying_key().verify_strict(canonical.as_bytes(), &signature).is_ok());
+    }
+
+    #[test]
+    fn test_plaintext_rejected_when_tls_required() {
+        let tls = TlsConfig::default();
+        assert!(websocket_url("https://sacas.ai", "/ws", &tls).is_ok());
+
+        let err = websocket_url("http://sacas.ai", "/ws", &tls).unwrap_err();
+        assert!(err.to_string().contains("Refusing plaintext"));
+        assert!(websocket_url("ws://sacas.ai", "/ws", &tls).is_err());
     }
 
-    #[cfg(not(target_os = "macos"))]
-    fn show_notification(&self, _title: &str, _body: &str) {
-        // No-op for non-macOS
+    #[test]
+    fn test_cert_pin_comparison() {
+        let der = b"certificate";
+        let pin = hex::encode(Sha256::digest(der));
+
+        assert!(cert_matches_pin(der, &pin));
+        assert!(cert_matches_pin(der, &pin.to_uppercase()));
+        assert!(!cert_matches_pin(b"other", &pin));
+    }
+
+    #[tokio::test]
+    async fn test_state_transitions() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (auth_tx, auth_rx) = oneshot::channel::<()>();
+        let (close_tx, close_rx) = oneshot::channel::<()>();
+        let (auth_msg_tx, auth_msg_rx) = oneshot::channel::<String>();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let auth = ws.next().await.unwrap().unwrap();
+            auth_msg_tx.send(auth.into_text().unwrap()).unwrap();
+
+            auth_rx.await.unwrap();
+            let reply = r#"{"type":"AUTH_SUCCESS","device_id":"dev","subscriptions":[],"server_time":0}"#;
+            ws.send(Message::Text(reply.to_string())).await.unwrap();
+
+            close_rx.await.unwrap();
+            ws.close(None).await.unwrap();
+        });
+
+        let key = test_signer();
+        let client = WebSocketClient::new(format!("http://{}", addr), "dev".to_string(), key)
+            .with_tls(plaintext());
+        let mut state = client.subscribe_state();
+        assert_eq!(*state.borrow(), ConnectionState::Disconnected);
+
+        let session = tokio::spawn(async move { client.connect_and_listen().await });
+
+        state.wait_for(|s| *s == ConnectionState::Connecting).await.unwrap();
+        auth_tx.send(()).unwrap();
+        state.wait_for(|s| *s == ConnectionState::Authenticated).await.unwrap();
+        close_tx.send(()).unwrap();
+        state.wait_for(|s| *s == ConnectionState::Disconnected).await.unwrap();
+
+        assert!(session.await.unwrap().is_ok());
+        let auth: serde_json::Value = serde_json::from_str(&auth_msg_rx.await.unwrap()).unwrap();
+        assert_eq!(auth["algorithm"], "ed25519");
+    }
+
+    #[tokio::test]
+    async fn test_silent_server_times_out_auth() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await; // AUTH message, never answered
+            std::future::pending::<()>().await;
+        });
+
+        let key = test_signer();
+        let client = WebSocketClient::new(format!("http://{}", addr), "dev".to_string(), key)
+            .with_tls(plaintext())
+            .with_auth_timeout(Duration::from_millis(100));
+
+        let err = tokio::time::timeout(Duration::from_secs(5), client.connect_and_listen())
+            .await
+            .expect("handshake should give up on its own")
+            .unwrap_err();
+        assert!(err.downcast_ref::<AuthTimeout>().is_some());
+        assert_eq!(*client.subscribe_state().borrow(), ConnectionState::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_close_before_auth_is_not_authenticated() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await; // AUTH message, refused
+            ws.close(None).await.unwrap();
+        });
+
+        let key = test_signer();
+        let client = WebSocketClient::new(format!("http://{}", addr), "dev".to_string(), key)
+            .with_tls(plaintext());
+
+        let err = client.connect_and_listen().await.unwrap_err();
+
+        assert!(err.to_string().contains("closed before authentication"));
+        assert!(!client.has_authenticated.load(Ordering::Acquire));
+        assert_eq!(*client.subscribe_state().borrow(), ConnectionState::Disconnected);
+    }
+
+    #[test]
+    fn test_event_cursor_tracks_gaps() {
+        let mut cursor = EventCursor::default();
+
+        assert_eq!(cursor.observe(1), 0);
+        assert_eq!(cursor.observe(2), 0);
+        assert_eq!(cursor.observe(5), 2);
+        assert_eq!(cursor.last_seq, Some(5));
+
+        // Replayed or out-of-order events never move the cursor back
+        assert_eq!(cursor.observe(3), 0);
+        assert_eq!(cursor.last_seq, Some(5));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_resumes_and_triggers_sync() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (auth_tx, auth_rx) = oneshot::channel::<String>();
+
+        tokio::spawn(async move {
+            // First session: deliver one sequenced event, then drop
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            ws.next().await;
+            let reply = r#"{"type":"AUTH_SUCCESS","device_id":"dev","subscriptions":[],"server_time":0}"#;
+            ws.send(Message::Text(reply.to_string())).await.unwrap();
+            let event = r#"{"type":"battle_result","seq":7,"channel":"device:dev",
+                "data":{"battle_id":"b1","outcome":"WIN","entropy_looted":"10"}}"#;
+            ws.send(Message::Text(event.to_string())).await.unwrap();
+            ws.close(None).await.unwrap();
+
+            // Second session: report the re-auth message back to the test
+            let (tcp, _) = listener.accept().await.unwrap();
+            let mut ws = tokio_tungstenite::accept_async(tcp).await.unwrap();
+            let auth = ws.next().await.unwrap().unwrap().into_text().unwrap();
+            auth_tx.send(auth).unwrap();
+            let reply = r#"{"type":"AUTH_SUCCESS","device_id":"dev","subscriptions":[],"server_time":0,"resume_seq":7}"#;
+            ws.send(Message::Text(reply.to_string())).await.unwrap();
+            ws.close(None).await.unwrap();
+        });
+
+        let key = test_signer();
+        let trigger = Arc::new(Notify::new());
+        let client = WebSocketClient::new(format!("http://{}", addr), "dev".to_string(), key)
+            .with_tls(plaintext())
+            .with_sync_trigger(trigger.clone());
+
+        client.connect_and_listen().await.unwrap();
+        assert_eq!(client.cursor.lock().unwrap().last_seq, Some(7));
+        assert!(tokio::time::timeout(Duration::from_millis(50), trigger.notified()).await.is_err());
+
+        client.connect_and_listen().await.unwrap();
+        let auth: serde_json::Value = serde_json::from_str(&auth_rx.await.unwrap()).unwrap();
+        assert_eq!(auth["last_seq"], 7);
+        tokio::time::timeout(Duration::from_secs(1), trigger.notified()).await.unwrap();
+    }
+
+    #[test]
+    fn test_duplicate_battle_id_ignored() {
+        let key = test_signer();
+        let client = WebSocketClient::new("http://localhost".to_string(), "dev".to_string(), key);
+        let event = |kind: &str, id: &str| -> ServerMessage {
+            serde_json::from_str(&format!(r#"{{"type":"{kind}","channel":"c","broadcast_channel":"g","data":{{
+                "battle_id":"{id}","attacker_id":"a","defender_id":"d","outcome":"WIN",
+                "entropy_looted":"5","entropy_lost":"5","parasitized":false}}}}"#)).unwrap()
+        };
+
+        assert!(!client.is_replayed(&event("battle_result", "b1")));
+        assert!(client.is_replayed(&event("battle_result", "b1")));
+        // The broadcast of the same battle is a different event
+        assert!(!client.is_replayed(&event("epic_battle", "b1")));
+        assert!(!client.is_replayed(&event("battle_result", "b2")));
+    }
+
+    #[test]
+    fn test_seen_battles_evicts_oldest() {
+        let mut seen = SeenBattles::new(2);
+        assert!(seen.first_sighting("b1"));
+        assert!(seen.first_sighting("b2"));
+        assert!(seen.first_sighting("b3"));
+
+        assert!(seen.first_sighting("b1"), "b1 should have been evicted");
+        assert!(!seen.first_sighting("b3"));
+        assert_eq!(seen.order.len(), 2);
+
+        let mut disabled = SeenBattles::new(0);
+        assert!(disabled.first_sighting("b1") && disabled.first_sighting("b1"));
+    }
+
+    #[tokio::test]
+    async fn test_parasitize_outcome_adds_parasite() {
+        let key = test_signer();
+        let state = Arc::new(StateManager::in_memory("dev".to_string(), 100));
+        let client = WebSocketClient::new("http://localhost".to_string(), "dev".to_string(), key)
+            .with_state(state.clone());
+
+        client.handle_message(r#"{"type":"battle_result","channel":"c","data":{"battle_id":"b1",
+            "outcome":"WIN","entropy_looted":"5","defender_id":"host-1","parasitized":true,"yield_per_tick":2.5}}"#).await;
+        // Our own win, broadcast; no yield reported yet
+        client.handle_message(r#"{"type":"epic_battle","channel":"c","broadcast_channel":"g","data":{
+            "battle_id":"b2","attacker_id":"dev","defender_id":"host-2","outcome":"PARASITIZED","entropy_looted":"9"}}"#).await;
+        // Someone else's conquest and a plain win add nothing
+        client.handle_message(r#"{"type":"epic_battle","channel":"c","broadcast_channel":"g","data":{
+            "battle_id":"b3","attacker_id":"other","defender_id":"host-3","outcome":"PARASITIZED","entropy_looted":"9"}}"#).await;
+        client.handle_message(r#"{"type":"battle_result","channel":"c","data":{"battle_id":"b4",
+            "outcome":"WIN","entropy_looted":"5","defender_id":"host-4"}}"#).await;
+
+        let snapshot = state.get_snapshot().await;
+        let hosts: Vec<&str> = snapshot.parasites.iter().map(|p| p.node_id.as_str()).collect();
+        assert_eq!(hosts, ["host-1", "host-2"]);
+        assert_eq!(snapshot.player.passive_income, 2.5);
+    }
+
+    #[tokio::test]
+    async fn test_short_battle_ids_do_not_panic() {
+        #[derive(Default)]
+        struct RecordingNotifier(std::sync::Mutex<Vec<String>>);
+
+        impl Notifier for RecordingNotifier {
+            fn name(&self) -> &'static str {
+                "recording"
+            }
+
+            fn notify(&self, _title: &str, body: &str) {
+                self.0.lock().unwrap().push(body.to_string());
+            }
+        }
+
+        let key = test_signer();
+        let notifier = Arc::new(RecordingNotifier::default());
+        let state = Arc::new(StateManager::in_memory("ΩΩΩΩΩ".to_string(), 100));
+        let client = WebSocketClient::new("http://localhost".to_string(), "ΩΩΩΩΩ".to_string(), key)
+            .with_notifier(notifier.clone())
+            .with_state(state.clone());
+
+        client.handle_message(r#"{"type":"battle_attacked","channel":"c","data":{"battle_id":"b",
+            "attacker_id":"x1","outcome":"REPELLED","entropy_lost":"0","parasitized":false}}"#).await;
+        client.handle_message(r#"{"type":"epic_battle","channel":"c","broadcast_channel":"g","data":{
+            "battle_id":"b","attacker_id":"ΩΩΩΩΩ","defender_id":"","outcome":"PARASITIZED","entropy_looted":"9"}}"#).await;
+
+        // Both events were handled in full; a conquest naming no defender adds no parasite
+        let bodies = notifier.0.lock().unwrap().clone();
+        assert_eq!(bodies.len(), 1);
+        assert!(bodies[0].starts_with("Attacker: x1\n"), "{:?}", bodies[0]);
+        assert!(state.get_snapshot().await.parasites.is_empty());
     }
 }